@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use jsonpath_rust::JsonPathInst;
+use serde_json::json;
+use std::str::FromStr;
+
+struct SearchData {
+    json: serde_json::Value,
+    path: JsonPathInst,
+}
+
+const PATH: &str = "$.a.b[0].c";
+
+fn chain_perf_test(cfg: &SearchData) {
+    let _v = cfg.path.find_slice(&cfg.json);
+}
+
+fn chain_perf_test_with_paths(cfg: &SearchData) {
+    let _v = jsonpath_rust::find_slice(&cfg.path, &cfg.json);
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let data = SearchData {
+        json: json!({
+            "a": {"b": [{"c": 1}]},
+        }),
+        path: JsonPathInst::from_str(PATH).unwrap(),
+    };
+    c.bench_function("chain bench", |b| b.iter(|| chain_perf_test(&data)));
+    c.bench_function("chain bench with paths", |b| {
+        b.iter(|| chain_perf_test_with_paths(&data))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
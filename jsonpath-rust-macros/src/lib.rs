@@ -0,0 +1,370 @@
+//! # Json path macros
+//! The crate provides the macro [`json_path!`] that parses the query at compile time
+//! and expands into the prebuilt [`JsonPathInst`](jsonpath_rust::JsonPathInst).
+//! Therefore, the typos in the constant queries turn into the build errors
+//! and there is no parsing cost at runtime.
+//!
+//! ```
+//! use jsonpath_rust::JsonPathInst;
+//! use jsonpath_rust_macros::json_path;
+//! use serde_json::json;
+//!
+//! let path: JsonPathInst = json_path!("$.first.second[?(@.active)]");
+//! let data = json!({"first":{"second":[{"active":1},{"passive":1}]}});
+//!
+//! assert_eq!(jsonpath_rust::find(&path, &data), json!([{"active":1}]));
+//! ```
+//!
+//! The incorrect query fails the build:
+//! ```compile_fail
+//! use jsonpath_rust_macros::json_path;
+//!
+//! let path = json_path!("$.first.[second");
+//! ```
+//!
+//! The derive macro [`JsonPathExtract`] generates the extraction of the struct from the json
+//! where every field is found by its query.
+use jsonpath_rust::parser::model::{
+    Aggregate, FilterExpression, FilterSign, Function, JsonPath, JsonPathIndex, JsonType, Operand,
+};
+use jsonpath_rust::parser::parser::parse_json_path;
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use serde_json::Value;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Parses the query at compile time and expands into [`JsonPathInst`](jsonpath_rust::JsonPathInst).
+#[proc_macro]
+pub fn json_path(input: TokenStream) -> TokenStream {
+    let query = parse_macro_input!(input as LitStr);
+    match parse_json_path(&query.value()) {
+        Ok(path) => {
+            let path = path_tokens(&path);
+            quote!(::jsonpath_rust::JsonPathInst::from(#path)).into()
+        }
+        Err(e) => syn::Error::new(query.span(), e.to_string())
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/// Generates `from_json(&Value) -> Result<Self, JsonPathError>` for the struct
+/// whose every field carries the query `#[jsonpath("...")]` checked at compile time.
+/// The field is deserialized from the elements found by its query
+/// as [`JsonPathInst::find_typed`](jsonpath_rust::JsonPathInst::find_typed) does:
+/// the singular query gives the single element or `null` if it is absent,
+/// the other queries give the array of the found elements.
+///
+/// ```
+/// use jsonpath_rust_macros::JsonPathExtract;
+/// use serde_json::json;
+///
+/// #[derive(JsonPathExtract, Debug, PartialEq)]
+/// struct Book {
+///     #[jsonpath("$.store.book[0].title")]
+///     title: String,
+///     #[jsonpath("$..book[?(@.price < 10)].title")]
+///     cheap: Vec<String>,
+///     #[jsonpath("$.store.owner")]
+///     owner: Option<String>,
+/// }
+///
+/// let data = json!({"store":{"book":[{"title":"a","price":5},{"title":"b","price":15}]}});
+/// assert_eq!(
+///     Book::from_json(&data).unwrap(),
+///     Book { title: "a".to_string(), cheap: vec!["a".to_string()], owner: None }
+/// );
+/// ```
+#[proc_macro_derive(JsonPathExtract, attributes(jsonpath))]
+pub fn json_path_extract(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match extract_tokens(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn extract_tokens(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "JsonPathExtract supports only the structs with the named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "JsonPathExtract supports only the structs",
+            ))
+        }
+    };
+
+    let mut inits = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().expect("the fields are named");
+        let attr = field
+            .attrs
+            .iter()
+            .find(|a| a.path().is_ident("jsonpath"))
+            .ok_or_else(|| {
+                syn::Error::new_spanned(ident, "the field needs `#[jsonpath(\"...\")]`")
+            })?;
+        let query: LitStr = attr.parse_args()?;
+        let path = parse_json_path(&query.value())
+            .map_err(|e| syn::Error::new(query.span(), e.to_string()))?;
+        let path = path_tokens(&path);
+        let name = ident.to_string();
+        inits.push(quote! {
+            #ident: {
+                static PATH: ::std::sync::OnceLock<::jsonpath_rust::JsonPathInst> =
+                    ::std::sync::OnceLock::new();
+                PATH.get_or_init(|| ::jsonpath_rust::JsonPathInst::from(#path))
+                    .find_typed(json)
+                    .map_err(|e| ::jsonpath_rust::JsonPathError::Extraction {
+                        field: ::std::string::String::from(#name),
+                        reason: e.to_string(),
+                    })?
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// extracts the fields from the json by their queries
+            pub fn from_json(
+                json: &::jsonpath_rust::__serde_json::Value,
+            ) -> ::std::result::Result<Self, ::jsonpath_rust::JsonPathError> {
+                ::std::result::Result::Ok(#name { #(#inits),* })
+            }
+        }
+    })
+}
+
+fn path_tokens(path: &JsonPath) -> TokenStream2 {
+    let model = quote!(::jsonpath_rust::parser::model);
+    match path {
+        JsonPath::Root => quote!(#model::JsonPath::Root),
+        JsonPath::Field(key) => quote!(#model::JsonPath::Field(::std::string::String::from(#key))),
+        JsonPath::FieldGlob(pattern) => {
+            quote!(#model::JsonPath::FieldGlob(::std::string::String::from(#pattern)))
+        }
+        JsonPath::Chain(elems) => {
+            let elems = elems.iter().map(path_tokens);
+            quote!(#model::JsonPath::Chain(::std::vec![#(#elems),*]))
+        }
+        JsonPath::Descent(key) => {
+            quote!(#model::JsonPath::Descent(::std::string::String::from(#key)))
+        }
+        JsonPath::DescentGlob(pattern) => {
+            quote!(#model::JsonPath::DescentGlob(::std::string::String::from(#pattern)))
+        }
+        JsonPath::DescentW => quote!(#model::JsonPath::DescentW),
+        JsonPath::DescentIndex(index) => {
+            let index = index_tokens(index);
+            quote!(#model::JsonPath::DescentIndex(#index))
+        }
+        JsonPath::Index(index) => {
+            let index = index_tokens(index);
+            quote!(#model::JsonPath::Index(#index))
+        }
+        JsonPath::Current(tail) => {
+            let tail = path_tokens(tail);
+            quote!(#model::JsonPath::Current(::std::boxed::Box::new(#tail)))
+        }
+        JsonPath::Wildcard => quote!(#model::JsonPath::Wildcard),
+        JsonPath::Empty => quote!(#model::JsonPath::Empty),
+        JsonPath::Fn(Function::Length) => quote!(#model::JsonPath::Fn(#model::Function::Length)),
+        JsonPath::Fn(Function::Aggregate(a)) => {
+            let a = aggregate_tokens(a);
+            quote!(#model::JsonPath::Fn(#model::Function::Aggregate(#a)))
+        }
+        JsonPath::Fn(Function::Keys) => quote!(#model::JsonPath::Fn(#model::Function::Keys)),
+        JsonPath::Fn(Function::Extract(re, group)) => {
+            quote!(#model::JsonPath::Fn(#model::Function::Extract(#re.to_string(), #group)))
+        }
+        JsonPath::Fn(Function::Default(v)) => {
+            let v = value_tokens(v);
+            quote!(#model::JsonPath::Fn(#model::Function::Default(#v)))
+        }
+        JsonPath::Type(tp) => {
+            let tp = type_tokens(tp);
+            quote!(#model::JsonPath::Type(#tp))
+        }
+    }
+}
+
+fn index_tokens(index: &JsonPathIndex) -> TokenStream2 {
+    let model = quote!(::jsonpath_rust::parser::model);
+    match index {
+        JsonPathIndex::Single(v) => {
+            let v = value_tokens(v);
+            quote!(#model::JsonPathIndex::Single(#v))
+        }
+        JsonPathIndex::UnionIndex(elems) => {
+            let elems = elems.iter().map(value_tokens);
+            quote!(#model::JsonPathIndex::UnionIndex(::std::vec![#(#elems),*]))
+        }
+        JsonPathIndex::UnionKeys(keys) => {
+            quote!(#model::JsonPathIndex::UnionKeys(::std::vec![#(::std::string::String::from(#keys)),*]))
+        }
+        JsonPathIndex::Union(elems) => {
+            let elems = elems.iter().map(path_tokens);
+            quote!(#model::JsonPathIndex::Union(::std::vec![#(#elems),*]))
+        }
+        JsonPathIndex::Slice(start, end, step) => {
+            quote!(#model::JsonPathIndex::Slice(#start, #end, #step))
+        }
+        JsonPathIndex::Filter(expr) => {
+            let expr = filter_tokens(expr);
+            quote!(#model::JsonPathIndex::Filter(#expr))
+        }
+    }
+}
+
+fn filter_tokens(expr: &FilterExpression) -> TokenStream2 {
+    let model = quote!(::jsonpath_rust::parser::model);
+    match expr {
+        FilterExpression::Atom(left, sign, right) => {
+            let left = operand_tokens(left);
+            let sign = sign_tokens(sign);
+            let right = operand_tokens(right);
+            quote!(#model::FilterExpression::Atom(#left, #sign, #right))
+        }
+        FilterExpression::And(left, right) => {
+            let left = filter_tokens(left);
+            let right = filter_tokens(right);
+            quote!(#model::FilterExpression::And(
+                ::std::boxed::Box::new(#left),
+                ::std::boxed::Box::new(#right)
+            ))
+        }
+        FilterExpression::Or(left, right) => {
+            let left = filter_tokens(left);
+            let right = filter_tokens(right);
+            quote!(#model::FilterExpression::Or(
+                ::std::boxed::Box::new(#left),
+                ::std::boxed::Box::new(#right)
+            ))
+        }
+        FilterExpression::Xor(left, right) => {
+            let left = filter_tokens(left);
+            let right = filter_tokens(right);
+            quote!(#model::FilterExpression::Xor(
+                ::std::boxed::Box::new(#left),
+                ::std::boxed::Box::new(#right)
+            ))
+        }
+        FilterExpression::Not(exp) => {
+            let exp = filter_tokens(exp);
+            quote!(#model::FilterExpression::Not(::std::boxed::Box::new(#exp)))
+        }
+    }
+}
+
+fn operand_tokens(op: &Operand) -> TokenStream2 {
+    let model = quote!(::jsonpath_rust::parser::model);
+    match op {
+        Operand::Static(v) => {
+            let v = value_tokens(v);
+            quote!(#model::Operand::Static(#v))
+        }
+        Operand::Dynamic(jp) => {
+            let jp = path_tokens(jp);
+            quote!(#model::Operand::Dynamic(::std::boxed::Box::new(#jp)))
+        }
+        Operand::Param(name) => quote!(#model::Operand::Param(#name.to_string())),
+        Operand::Index => quote!(#model::Operand::Index),
+        Operand::Property => quote!(#model::Operand::Property),
+        Operand::Path => quote!(#model::Operand::Path),
+        Operand::Context(jp) => {
+            let jp = path_tokens(jp);
+            quote!(#model::Operand::Context(::std::boxed::Box::new(#jp)))
+        }
+    }
+}
+
+fn sign_tokens(sign: &FilterSign) -> TokenStream2 {
+    let model = quote!(::jsonpath_rust::parser::model);
+    let sign = match sign {
+        FilterSign::Equal => quote!(Equal),
+        FilterSign::Unequal => quote!(Unequal),
+        FilterSign::Less => quote!(Less),
+        FilterSign::Greater => quote!(Greater),
+        FilterSign::LeOrEq => quote!(LeOrEq),
+        FilterSign::GrOrEq => quote!(GrOrEq),
+        FilterSign::Regex => quote!(Regex),
+        FilterSign::In => quote!(In),
+        FilterSign::Nin => quote!(Nin),
+        FilterSign::Size => quote!(Size),
+        FilterSign::NoneOf => quote!(NoneOf),
+        FilterSign::AnyOf => quote!(AnyOf),
+        FilterSign::SubSetOf => quote!(SubSetOf),
+        FilterSign::Exists => quote!(Exists),
+        FilterSign::Schema => quote!(Schema),
+    };
+    quote!(#model::FilterSign::#sign)
+}
+
+fn aggregate_tokens(aggregate: &Aggregate) -> TokenStream2 {
+    let model = quote!(::jsonpath_rust::parser::model);
+    let aggregate = match aggregate {
+        Aggregate::Min => quote!(Min),
+        Aggregate::Max => quote!(Max),
+        Aggregate::Avg => quote!(Avg),
+        Aggregate::Stddev => quote!(Stddev),
+        Aggregate::Sum => quote!(Sum),
+    };
+    quote!(#model::Aggregate::#aggregate)
+}
+
+fn type_tokens(tp: &JsonType) -> TokenStream2 {
+    let model = quote!(::jsonpath_rust::parser::model);
+    let tp = match tp {
+        JsonType::Null => quote!(Null),
+        JsonType::Boolean => quote!(Boolean),
+        JsonType::Number => quote!(Number),
+        JsonType::Integer => quote!(Integer),
+        JsonType::String => quote!(String),
+        JsonType::Array => quote!(Array),
+        JsonType::Object => quote!(Object),
+        JsonType::Scalar => quote!(Scalar),
+    };
+    quote!(#model::JsonType::#tp)
+}
+
+fn value_tokens(v: &Value) -> TokenStream2 {
+    let value = quote!(::jsonpath_rust::__serde_json::Value);
+    match v {
+        Value::Null => quote!(#value::Null),
+        Value::Bool(b) => quote!(#value::Bool(#b)),
+        Value::Number(n) => {
+            if let Some(n) = n.as_u64() {
+                quote!(#value::from(#n))
+            } else if let Some(n) = n.as_i64() {
+                quote!(#value::from(#n))
+            } else {
+                let n = n.as_f64().unwrap_or_default();
+                quote!(#value::from(#n))
+            }
+        }
+        Value::String(s) => quote!(#value::String(::std::string::String::from(#s))),
+        Value::Array(elems) => {
+            let elems = elems.iter().map(value_tokens);
+            quote!(#value::Array(::std::vec![#(#elems),*]))
+        }
+        Value::Object(fields) => {
+            let keys = fields.keys();
+            let values = fields.values().map(value_tokens);
+            quote!(#value::Object(::std::iter::IntoIterator::into_iter([
+                #((::std::string::String::from(#keys), #values)),*
+            ]).collect()))
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use jsonpath_rust::JsonPathError;
+use jsonpath_rust_macros::JsonPathExtract;
+use serde_json::json;
+
+#[derive(JsonPathExtract, Debug, PartialEq)]
+struct Order {
+    #[jsonpath("$.id")]
+    id: u64,
+    #[jsonpath("$.items[*].sku")]
+    skus: Vec<String>,
+    #[jsonpath("$.items[?(@.qty > 1)].qty")]
+    bulk: Vec<u32>,
+    #[jsonpath("$.customer.email")]
+    email: Option<String>,
+}
+
+#[test]
+fn extract_test() {
+    let data = json!({"id":7,"items":[{"sku":"a","qty":1},{"sku":"b","qty":3}],"customer":{}});
+    assert_eq!(
+        Order::from_json(&data).unwrap(),
+        Order {
+            id: 7,
+            skus: vec!["a".to_string(), "b".to_string()],
+            bulk: vec![3],
+            email: None,
+        }
+    );
+}
+
+#[test]
+fn extract_error_test() {
+    match Order::from_json(&json!({"items":[]})) {
+        Err(JsonPathError::Extraction { field, reason }) => {
+            assert_eq!(field, "id");
+            assert_eq!(reason, "nothing is found by the singular query");
+        }
+        res => panic!("unexpected {res:?}"),
+    }
+    assert!(matches!(
+        Order::from_json(&json!({"id":"x","items":[]})),
+        Err(JsonPathError::Extraction { field, .. }) if field == "id"
+    ));
+}
@@ -0,0 +1,45 @@
+use jsonpath_rust::JsonPathInst;
+use jsonpath_rust_macros::json_path;
+use serde_json::json;
+use std::str::FromStr;
+
+fn test(built: JsonPathInst, query: &str) {
+    let parsed = JsonPathInst::from_str(query).expect("the path is correct");
+    assert_eq!(format!("{:?}", built), format!("{:?}", parsed));
+}
+
+#[test]
+fn prebuilt_path_test() {
+    test(json_path!("$.a['b']..c..*[*]"), "$.a['b']..c..*[*]");
+    test(
+        json_path!("$[1][1,2]['a','b'][0,'a',1:3][-1:][::2].length()"),
+        "$[1][1,2]['a','b'][0,'a',1:3][-1:][::2].length()",
+    );
+    test(
+        json_path!("$..[?(@.a)]..*@string()"),
+        "$..[?(@.a)]..*@string()",
+    );
+}
+
+#[test]
+fn prebuilt_filter_test() {
+    test(
+        json_path!("$[?(@.a == 'b' && @.c > 1.5 || !@.d)]"),
+        "$[?(@.a == 'b' && @.c > 1.5 || !@.d)]",
+    );
+    test(
+        json_path!("$[?(@.a in ['x','y'] && @.b nin [1,-2] && @.c == null && @.d == true)]"),
+        "$[?(@.a in ['x','y'] && @.b nin [1,-2] && @.c == null && @.d == true)]",
+    );
+    test(
+        json_path!("$[?(@array(@.items) && @.id == $.id)]"),
+        "$[?(@array(@.items) && @.id == $.id)]",
+    );
+}
+
+#[test]
+fn find_test() {
+    let data = json!({"orders":[{"id":1,"active":true},{"id":2},{"id":3,"active":true}]});
+    let path = json_path!("$.orders[?(@.active)].id");
+    assert_eq!(jsonpath_rust::find(&path, &data), json!([1, 3]));
+}
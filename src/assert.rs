@@ -0,0 +1,224 @@
+//! The checks behind the macro [`assert_jsonpath!`](crate::assert_jsonpath).
+//!
+//! The failed check describes the query, the paths and the values it has matched
+//! and the difference between the expected values and the found ones.
+//!
+//! ```
+//! use jsonpath_rust::assert::{check, Expectation};
+//! use serde_json::json;
+//!
+//! let data = json!({"a":[{"id":1},{"id":2}]});
+//! assert!(check(&data, "$..id", Expectation::Count(2)).is_ok());
+//!
+//! let failure = check(&data, "$..id", Expectation::Contains(json!(3))).unwrap_err();
+//! assert!(failure.contains("$.['a'][1].['id'] = 2"));
+//! ```
+use crate::{JsonPathError, JsonPathInst};
+use serde_json::Value;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// The expectation about the elements found by the query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expectation {
+    /// at least one element is found
+    Exists,
+    /// nothing is found
+    Absent,
+    /// the number of the found elements
+    Count(usize),
+    /// one of the found elements equals the value
+    Contains(Value),
+    /// the found elements equal the values in the same order
+    Equals(Vec<Value>),
+    /// the found elements equal the values in any order
+    MatchesSet(Vec<Value>),
+}
+
+/// checks the expectation about the elements found by the query in the json.
+/// Returns the description of the failure if the expectation is not met or the query is incorrect.
+pub fn check(json: &Value, query: &str, expectation: Expectation) -> Result<(), String> {
+    let path = JsonPathInst::from_str(query)
+        .map_err(|e| format!("the query `{query}` is incorrect: {e}"))?;
+    let found = match path.query(json) {
+        Ok(found) => found,
+        Err(JsonPathError::Inapplicable) => vec![],
+        Err(e) => return Err(format!("the query `{query}` has failed: {e}")),
+    };
+    let values: Vec<&Value> = found.iter().map(|m| &*m.value).collect();
+
+    let (holds, diff) = match &expectation {
+        Expectation::Exists => (!values.is_empty(), vec![]),
+        Expectation::Absent => (values.is_empty(), vec![]),
+        Expectation::Count(count) => (values.len() == *count, vec![]),
+        Expectation::Contains(value) => (values.contains(&value), vec![]),
+        Expectation::Equals(expected) => {
+            let holds =
+                values.len() == expected.len() && values.iter().zip(expected).all(|(v, e)| *v == e);
+            (holds, ordered_diff(&values, expected))
+        }
+        Expectation::MatchesSet(expected) => {
+            let diff = set_diff(&values, expected);
+            (diff.is_empty(), diff)
+        }
+    };
+    if holds {
+        return Ok(());
+    }
+
+    let mut msg = format!(
+        "the query `{}` does not meet the expectation {expectation:?}\n",
+        path.canonicalize()
+    );
+    let _ = writeln!(msg, "found {} element(s):", found.len());
+    for m in found.iter() {
+        let _ = writeln!(
+            msg,
+            "  {} = {}",
+            m.path.as_deref().unwrap_or("<generated>"),
+            *m.value
+        );
+    }
+    if !diff.is_empty() {
+        let _ = writeln!(msg, "diff (- expected, + found):");
+        diff.iter().for_each(|line| {
+            let _ = writeln!(msg, "  {line}");
+        });
+    }
+    Err(msg)
+}
+
+/// the lines of the elements differing at the same positions
+fn ordered_diff(found: &[&Value], expected: &[Value]) -> Vec<String> {
+    let mut diff = vec![];
+    for i in 0..found.len().max(expected.len()) {
+        match (expected.get(i), found.get(i)) {
+            (Some(e), Some(f)) if e == *f => {}
+            (e, f) => {
+                if let Some(e) = e {
+                    diff.push(format!("- [{i}] {e}"));
+                }
+                if let Some(f) = f {
+                    diff.push(format!("+ [{i}] {f}"));
+                }
+            }
+        }
+    }
+    diff
+}
+
+/// the lines of the expected elements that are not found and the found ones that are not expected
+fn set_diff(found: &[&Value], expected: &[Value]) -> Vec<String> {
+    let mut unmatched: Vec<&Value> = found.to_vec();
+    let mut diff = vec![];
+    for e in expected {
+        match unmatched.iter().position(|f| *f == e) {
+            Some(i) => {
+                unmatched.remove(i);
+            }
+            None => diff.push(format!("- {e}")),
+        }
+    }
+    diff.extend(unmatched.iter().map(|f| format!("+ {f}")));
+    diff
+}
+
+/// Asserts the expectation about the elements found by the query in the json.
+/// The failure message lists the query, the paths and the values it has matched
+/// and the difference between the expected values and the found ones.
+///
+/// The expectations are
+/// - `exists` and `absent`
+/// - `count <number>`
+/// - `contains <value>`
+/// - `equals [<values>]` comparing the found elements in their order
+/// - `matches_set [<values>]` comparing the found elements in any order
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::assert_jsonpath;
+/// use serde_json::json;
+///
+/// let data = json!({"a":[{"id":1},{"id":2}]});
+/// assert_jsonpath!(data, "$..id", exists);
+/// assert_jsonpath!(data, "$..name", absent);
+/// assert_jsonpath!(data, "$..id", count 2);
+/// assert_jsonpath!(data, "$..id", contains json!(2));
+/// assert_jsonpath!(data, "$..id", equals [json!(1), json!(2)]);
+/// assert_jsonpath!(data, "$..id", matches_set [json!(2), json!(1)]);
+/// ```
+#[macro_export]
+macro_rules! assert_jsonpath {
+    ($json:expr, $query:expr, exists) => {
+        $crate::assert_jsonpath!(@check $json, $query, $crate::assert::Expectation::Exists)
+    };
+    ($json:expr, $query:expr, absent) => {
+        $crate::assert_jsonpath!(@check $json, $query, $crate::assert::Expectation::Absent)
+    };
+    ($json:expr, $query:expr, count $count:expr) => {
+        $crate::assert_jsonpath!(@check $json, $query, $crate::assert::Expectation::Count($count))
+    };
+    ($json:expr, $query:expr, contains $value:expr) => {
+        $crate::assert_jsonpath!(@check $json, $query, $crate::assert::Expectation::Contains($value))
+    };
+    ($json:expr, $query:expr, equals [$($value:expr),* $(,)?]) => {
+        $crate::assert_jsonpath!(@check $json, $query, $crate::assert::Expectation::Equals(vec![$($value),*]))
+    };
+    ($json:expr, $query:expr, matches_set [$($value:expr),* $(,)?]) => {
+        $crate::assert_jsonpath!(@check $json, $query, $crate::assert::Expectation::MatchesSet(vec![$($value),*]))
+    };
+    (@check $json:expr, $query:expr, $expectation:expr) => {
+        if let Err(msg) = $crate::assert::check(&$json, $query, $expectation) {
+            panic!("{}", msg)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert::{check, Expectation};
+    use serde_json::json;
+
+    #[test]
+    fn check_test() {
+        let data = json!({"a":[{"id":1},{"id":2}]});
+        assert!(check(&data, "$.a.length()", Expectation::Equals(vec![json!(2)])).is_ok());
+        assert!(check(&data, "$.b", Expectation::Absent).is_ok());
+        assert!(check(&data, "$.b", Expectation::Count(0)).is_ok());
+        assert!(check(&data, "$..id", Expectation::Count(1)).is_err());
+        assert!(check(&data, "$.[", Expectation::Exists)
+            .unwrap_err()
+            .starts_with("the query `$.[` is incorrect"));
+    }
+
+    #[test]
+    fn diff_test() {
+        let data = json!({"a":[{"id":1},{"id":2}]});
+        assert_eq!(
+            check(
+                &data,
+                "$..id",
+                Expectation::Equals(vec![json!(2), json!(1)])
+            )
+            .unwrap_err(),
+            "the query `$..['id']` does not meet the expectation Equals([Number(2), Number(1)])\n\
+             found 2 element(s):\n  $.['a'][0].['id'] = 1\n  $.['a'][1].['id'] = 2\n\
+             diff (- expected, + found):\n  - [0] 2\n  + [0] 1\n  - [1] 1\n  + [1] 2\n"
+        );
+        let failure = check(
+            &data,
+            "$..id",
+            Expectation::MatchesSet(vec![json!(2), json!(3)]),
+        )
+        .unwrap_err();
+        assert!(failure.ends_with("diff (- expected, + found):\n  - 3\n  + 1\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "found 2 element(s)")]
+    fn macro_test() {
+        let data = json!({"a":[{"id":1},{"id":2}]});
+        assert_jsonpath!(data, "$..id", matches_set [json!(2), json!(1)]);
+        assert_jsonpath!(data, "$..id", contains json!(3));
+    }
+}
@@ -0,0 +1,229 @@
+//! The command line tool evaluating the query on the json documents.
+//!
+//! ```text
+//! jsonpath '$..book[?(@.price < 10)].title' store.json
+//! cat events.ndjson | jsonpath --ndjson --raw '$.user.name'
+//! jsonpath --repl store.json
+//! ```
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use jsonpath_rust::{to_pointer, JsonPathError, JsonPathInst, MatchRecord};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
+use std::ops::Deref;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+/// The interactive mode
+mod repl;
+
+/// What is printed for every match
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Output {
+    Values,
+    Paths,
+    Both,
+    /// the json objects with the path, the json pointer and the value
+    Records,
+}
+
+/// The options of the printing
+#[derive(Debug, Clone, Copy)]
+struct Format {
+    output: Output,
+    raw: bool,
+}
+
+fn command() -> Command {
+    Command::new("jsonpath")
+        .about("Finds the elements of the json documents by the jsonpath query")
+        .arg(
+            Arg::new("query")
+                .required_unless_present("repl")
+                .help("the jsonpath query"),
+        )
+        .arg(
+            Arg::new("file")
+                .help("the json file, the standard input is read if it is absent or equals `-`"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_parser(["values", "paths", "both", "records"])
+                .default_value("values")
+                .help("prints the found values, their paths, the paths followed by the values or the json records with both"),
+        )
+        .arg(
+            Arg::new("raw")
+                .short('r')
+                .long("raw")
+                .action(ArgAction::SetTrue)
+                .help("prints the found strings without the quotes"),
+        )
+        .arg(
+            Arg::new("ndjson")
+                .long("ndjson")
+                .action(ArgAction::SetTrue)
+                .help("reads every line of the input as a separate document"),
+        )
+        .arg(
+            Arg::new("repl")
+                .short('i')
+                .long("repl")
+                .value_name("FILE")
+                .conflicts_with_all(["query", "file", "ndjson"])
+                .help("loads the json file and evaluates the queries typed one by one"),
+        )
+}
+
+fn main() -> ExitCode {
+    let args = command().get_matches();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("jsonpath: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &ArgMatches) -> Result<(), String> {
+    if let Some(file) = args.get_one::<String>("repl") {
+        let file = File::open(file).map_err(|e| format!("{file}: {e}"))?;
+        let doc = serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())?;
+        return repl::run(&doc, stdin().lock(), &mut stdout().lock());
+    }
+    let query = args
+        .get_one::<String>("query")
+        .expect("the query is required");
+    let path = JsonPathInst::from_str(query)?;
+    let format = Format {
+        output: match args.get_one::<String>("output").map(String::as_str) {
+            Some("paths") => Output::Paths,
+            Some("both") => Output::Both,
+            Some("records") => Output::Records,
+            _ => Output::Values,
+        },
+        raw: args.get_flag("raw"),
+    };
+    let input: Box<dyn Read> = match args.get_one::<String>("file").map(String::as_str) {
+        None | Some("-") => Box::new(stdin().lock()),
+        Some(file) => Box::new(File::open(file).map_err(|e| format!("{file}: {e}"))?),
+    };
+
+    let mut out = BufWriter::new(stdout().lock());
+    if args.get_flag("ndjson") {
+        for (i, line) in BufReader::new(input).lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let doc = serde_json::from_str(&line).map_err(|e| format!("line {}: {e}", i + 1))?;
+            print_matches(&path, &doc, format, &mut out)?;
+        }
+    } else {
+        let doc = serde_json::from_reader(BufReader::new(input)).map_err(|e| e.to_string())?;
+        print_matches(&path, &doc, format, &mut out)?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+/// prints every match on a separate line, the path is separated from the value by the tab.
+/// The documents the path is not applicable to have no matches.
+fn print_matches(
+    path: &JsonPathInst,
+    doc: &Value,
+    format: Format,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    let found = match path.query(doc) {
+        Ok(found) => found,
+        Err(JsonPathError::Inapplicable) => vec![],
+        Err(e) => return Err(e.to_string()),
+    };
+    for m in found {
+        let value = match m.value.as_str() {
+            Some(s) if format.raw => s.to_string(),
+            _ => m.value.to_string(),
+        };
+        let segments = m.to_segments();
+        let path = m.path.unwrap_or_default();
+        match format.output {
+            Output::Values => writeln!(out, "{value}"),
+            Output::Paths => writeln!(out, "{path}"),
+            Output::Both => writeln!(out, "{path}\t{value}"),
+            Output::Records => match segments {
+                Some(segments) => {
+                    let record = MatchRecord {
+                        pointer: to_pointer(&segments),
+                        path,
+                        value: m.value.deref().clone(),
+                    };
+                    writeln!(out, "{}", json!(record))
+                }
+                // the generated elements have neither the path nor the pointer
+                None => Ok(()),
+            },
+        }
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command, print_matches, Format, Output};
+    use jsonpath_rust::JsonPathInst;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn printed(query: &str, output: Output, raw: bool) -> String {
+        let path = JsonPathInst::from_str(query).unwrap();
+        let doc = json!({"a":[{"n":"x","v":1},{"n":"y","v":2}]});
+        let mut out = vec![];
+        print_matches(&path, &doc, Format { output, raw }, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn print_test() {
+        assert_eq!(printed("$.a[*].n", Output::Values, false), "\"x\"\n\"y\"\n");
+        assert_eq!(printed("$.a[*].n", Output::Values, true), "x\ny\n");
+        assert_eq!(
+            printed("$.a[?(@.v > 1)]", Output::Values, false),
+            "{\"n\":\"y\",\"v\":2}\n"
+        );
+        assert_eq!(
+            printed("$.a[*].v", Output::Paths, false),
+            "$.['a'][0].['v']\n$.['a'][1].['v']\n"
+        );
+        assert_eq!(
+            printed("$.a[1].n", Output::Both, true),
+            "$.['a'][1].['n']\ty\n"
+        );
+        assert_eq!(printed("$.b", Output::Values, false), "");
+        assert_eq!(
+            printed("$.a[1].n", Output::Records, true),
+            "{\"path\":\"$.['a'][1].['n']\",\"pointer\":\"/a/1/n\",\"value\":\"y\"}\n"
+        );
+        assert_eq!(printed("$.a.length()", Output::Records, false), "");
+    }
+
+    #[test]
+    fn command_test() {
+        command().debug_assert();
+        let args = command()
+            .try_get_matches_from(["jsonpath", "-r", "-o", "both", "$.a", "f.json"])
+            .unwrap();
+        assert!(args.get_flag("raw"));
+        assert_eq!(args.get_one::<String>("file").unwrap(), "f.json");
+        assert!(command()
+            .try_get_matches_from(["jsonpath", "-o", "keys", "$.a"])
+            .is_err());
+        assert!(command()
+            .try_get_matches_from(["jsonpath", "--repl", "f.json"])
+            .is_ok());
+        assert!(command().try_get_matches_from(["jsonpath"]).is_err());
+    }
+}
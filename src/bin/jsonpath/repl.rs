@@ -0,0 +1,117 @@
+//! The interactive mode evaluating the queries typed one by one against the loaded document.
+use crate::{print_matches, Format, Output};
+use jsonpath_rust::{find_values, JsonPathInst};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+const HELP: &str = "\
+  <query>              prints the number of the matches and the matches with their paths
+  :complete <query>    lists the queries completing the last member name of the query
+  :help                prints this help
+  :quit                exits";
+
+/// reads the queries from the input until it ends or `:quit` is typed
+pub(crate) fn run(doc: &Value, input: impl BufRead, out: &mut impl Write) -> Result<(), String> {
+    let format = Format {
+        output: Output::Both,
+        raw: false,
+    };
+    prompt(out)?;
+    for line in input.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        let res = match line.split_once(' ').unwrap_or((line, "")) {
+            ("", _) => Ok(()),
+            (":quit" | ":q", _) => return Ok(()),
+            (":help", _) => writeln!(out, "{HELP}").map_err(|e| e.to_string()),
+            (":complete", partial) => complete(doc, partial.trim())
+                .into_iter()
+                .try_for_each(|c| writeln!(out, "{c}"))
+                .map_err(|e| e.to_string()),
+            _ => match JsonPathInst::from_str(line) {
+                Ok(path) => {
+                    let count = path.query(doc).map(|found| found.len()).unwrap_or(0);
+                    writeln!(out, "{count} match(es) for {}", path.canonicalize())
+                        .map_err(|e| e.to_string())
+                        .and_then(|_| print_matches(&path, doc, format, out))
+                }
+                Err(e) => writeln!(out, "{e}").map_err(|e| e.to_string()),
+            },
+        };
+        res?;
+        prompt(out)?;
+    }
+    Ok(())
+}
+
+fn prompt(out: &mut impl Write) -> Result<(), String> {
+    write!(out, "> ")
+        .and_then(|_| out.flush())
+        .map_err(|e| e.to_string())
+}
+
+/// lists the queries completing the last member name of the partial query
+/// by the keys of the objects the rest of the query leads to, e.g. `$.store.bo` gives `$.store.book`.
+fn complete(doc: &Value, partial: &str) -> Vec<String> {
+    let Some(dot) = partial.rfind('.') else {
+        return vec![];
+    };
+    let (parent, name) = (&partial[..dot], &partial[dot + 1..]);
+    let parent_query = if parent.ends_with('.') {
+        format!("{parent}.*")
+    } else {
+        parent.to_string()
+    };
+    let Ok(path) = JsonPathInst::from_str(&parent_query) else {
+        return vec![];
+    };
+    find_values(&path, doc)
+        .into_iter()
+        .filter_map(Value::as_object)
+        .flat_map(|fields| fields.keys())
+        .filter(|k| k.starts_with(name))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|k| format!("{parent}.{k}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{complete, run};
+    use serde_json::json;
+
+    #[test]
+    fn complete_test() {
+        let doc = json!({"store":{"book":[{"title":"a"},{"tag":"b"}],"bicycle":{"color":"red"}}});
+        assert_eq!(
+            complete(&doc, "$.store.b"),
+            vec!["$.store.bicycle", "$.store.book"]
+        );
+        assert_eq!(complete(&doc, "$.store.bo"), vec!["$.store.book"]);
+        assert_eq!(
+            complete(&doc, "$.store.book[*].t"),
+            vec!["$.store.book[*].tag", "$.store.book[*].title"]
+        );
+        assert_eq!(complete(&doc, "$..co"), vec!["$..color"]);
+        assert!(complete(&doc, "$.store.x").is_empty());
+        assert!(complete(&doc, "$.store[").is_empty());
+        assert!(complete(&doc, "$").is_empty());
+    }
+
+    #[test]
+    fn repl_test() {
+        let doc = json!({"a":[1,2]});
+        let input = "$.a[*]\n:complete $.\n$.[\n:quit\n$.a\n";
+        let mut out = vec![];
+        run(&doc, input.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.starts_with("> 2 match(es) for $['a'][*]\n$.['a'][0]\t1\n$.['a'][1]\t2\n> $.a\n> ")
+        );
+        assert!(out.contains("Failed to parse rule"));
+        assert!(out.ends_with("> "));
+    }
+}
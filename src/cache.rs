@@ -0,0 +1,204 @@
+//! The cache of the compiled queries.
+//!
+//! The services receiving the queries at runtime usually get the same handful of them over and over again.
+//! The cache keeps the compiled queries by their text and evicts the least recently used one
+//! when the capacity is exceeded.
+//!
+//! ```
+//! use jsonpath_rust::QueryCache;
+//! use serde_json::json;
+//!
+//! let cache = QueryCache::new(16);
+//! let data = json!({"a": [1, 2]});
+//! for _ in 0..3 {
+//!     let path = cache.get("$.a[*]").unwrap();
+//!     assert_eq!(path.find_slice(&data).len(), 2);
+//! }
+//! assert_eq!(cache.metrics().hits, 2);
+//! assert_eq!(cache.metrics().misses, 1);
+//! ```
+use crate::JsonPathInst;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The default capacity of the global cache
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// The counters of the cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// The thread-safe LRU cache of the compiled queries keyed by the text of the query.
+#[derive(Debug)]
+pub struct QueryCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<String, (Arc<JsonPathInst>, u64)>,
+    /// the keys ordered by the last usage
+    usages: BTreeMap<u64, String>,
+    tick: u64,
+    metrics: CacheMetrics,
+}
+
+impl QueryCache {
+    /// creates the cache keeping at most `capacity` queries, the zero capacity disables caching.
+    pub fn new(capacity: usize) -> Self {
+        QueryCache {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// the cache shared by the whole process with the capacity [`DEFAULT_CAPACITY`]
+    pub fn global() -> &'static QueryCache {
+        static GLOBAL: OnceLock<QueryCache> = OnceLock::new();
+        GLOBAL.get_or_init(|| QueryCache::new(DEFAULT_CAPACITY))
+    }
+
+    /// returns the compiled query, parsing it only if it is not in the cache.
+    /// The queries failed to parse are not cached.
+    pub fn get(&self, query: &str) -> Result<Arc<JsonPathInst>, String> {
+        {
+            let mut state = self.lock();
+            if let Some(path) = state.touch(query) {
+                state.metrics.hits += 1;
+                return Ok(path);
+            }
+            state.metrics.misses += 1;
+        }
+
+        // parsing happens outside the lock so the other threads are not blocked by it
+        let path = Arc::new(JsonPathInst::from_str(query)?);
+        if self.capacity > 0 {
+            self.lock().insert(query, path.clone(), self.capacity);
+        }
+        Ok(path)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// the number of the cached queries
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn metrics(&self) -> CacheMetrics {
+        self.lock().metrics
+    }
+
+    /// removes all the queries, the metrics are kept
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.usages.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // the state stays consistent even if a thread panicked while holding the lock
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CacheState {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn touch(&mut self, query: &str) -> Option<Arc<JsonPathInst>> {
+        let tick = self.next_tick();
+        let (path, used) = self.entries.get_mut(query)?;
+        let key = self.usages.remove(used)?;
+        *used = tick;
+        self.usages.insert(tick, key);
+        Some(path.clone())
+    }
+
+    fn insert(&mut self, query: &str, path: Arc<JsonPathInst>, capacity: usize) {
+        // another thread could have inserted the same query in the meantime
+        if self.touch(query).is_some() {
+            return;
+        }
+        while self.entries.len() >= capacity {
+            match self.usages.pop_first() {
+                Some((_, oldest)) => {
+                    self.entries.remove(&oldest);
+                    self.metrics.evictions += 1;
+                }
+                None => break,
+            }
+        }
+        let tick = self.next_tick();
+        self.usages.insert(tick, query.to_string());
+        self.entries.insert(query.to_string(), (path, tick));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::{CacheMetrics, QueryCache};
+    use std::sync::Arc;
+
+    #[test]
+    fn lru_test() {
+        let cache = QueryCache::new(2);
+        let a = cache.get("$.a").unwrap();
+        cache.get("$.b").unwrap();
+        assert!(Arc::ptr_eq(&a, &cache.get("$.a").unwrap()));
+        // $.b is the least recently used one
+        cache.get("$.c").unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.get("$.a").unwrap();
+        cache.get("$.b").unwrap();
+        assert_eq!(
+            cache.metrics(),
+            CacheMetrics {
+                hits: 2,
+                misses: 4,
+                evictions: 2
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_query_test() {
+        let cache = QueryCache::new(2);
+        assert!(cache.get("$.a[").is_err());
+        assert!(cache.get("$.a[").is_err());
+        assert!(cache.is_empty());
+        assert_eq!(cache.metrics().misses, 2);
+    }
+
+    #[test]
+    fn zero_capacity_test() {
+        let cache = QueryCache::new(0);
+        cache.get("$.a").unwrap();
+        cache.get("$.a").unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(cache.metrics().hits, 0);
+    }
+
+    #[test]
+    fn global_test() {
+        let path = QueryCache::global().get("$.global_test").unwrap();
+        assert!(Arc::ptr_eq(
+            &path,
+            &QueryCache::global().get("$.global_test").unwrap()
+        ));
+    }
+}
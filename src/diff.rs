@@ -0,0 +1,129 @@
+//! The difference of two documents in the scope of the query.
+//!
+//! ```
+//! use jsonpath_rust::{diff_at, Change, JsonPathInst};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let before = json!({"services":{"api":{"replicas":2},"db":{"replicas":1}},"version":1});
+//! let after = json!({"services":{"api":{"replicas":3},"web":{"replicas":1}},"version":2});
+//! let path = JsonPathInst::from_str("$.services.*.replicas").unwrap();
+//!
+//! let diff = diff_at(&path, &before, &after);
+//! assert_eq!(diff.len(), 3);
+//! assert_eq!(diff[0].path, "$.['services'].['api'].['replicas']");
+//! assert_eq!(diff[0].change, Change::Changed { before: &json!(2), after: &json!(3) });
+//! assert_eq!(diff[1].change, Change::Removed(&json!(1)));
+//! assert_eq!(diff[2].path, "$.['services'].['web'].['replicas']");
+//! ```
+use crate::{JsonPathInst, JsonPathValue};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The change of the element found by the query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    /// the element is found only in the second document
+    Added(&'a Value),
+    /// the element is found only in the first document
+    Removed(&'a Value),
+    /// the element is found in both documents by the same path and differs
+    Changed { before: &'a Value, after: &'a Value },
+}
+
+/// The change of the element along with its path
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff<'a> {
+    pub path: String,
+    pub change: Change<'a>,
+}
+
+/// evaluates the query on both documents and compares the found elements by their paths.
+/// The removed and the changed elements come in the order of the first document followed by the added ones
+/// in the order of the second document. The generated elements (like the length) have no path and are skipped.
+pub fn diff_at<'a>(path: &'a JsonPathInst, before: &'a Value, after: &'a Value) -> Vec<Diff<'a>> {
+    let before = found_with_paths(path, before);
+    let after = found_with_paths(path, after);
+    let after_by_path: HashMap<&str, &Value> =
+        after.iter().map(|(p, v)| (p.as_str(), *v)).collect();
+
+    let mut diff = vec![];
+    for (p, b) in before.iter() {
+        match after_by_path.get(p.as_str()) {
+            Some(a) if a == b => {}
+            Some(a) => diff.push(Diff {
+                path: p.clone(),
+                change: Change::Changed {
+                    before: b,
+                    after: a,
+                },
+            }),
+            None => diff.push(Diff {
+                path: p.clone(),
+                change: Change::Removed(b),
+            }),
+        }
+    }
+    let before_paths: HashMap<&str, ()> = before.iter().map(|(p, _)| (p.as_str(), ())).collect();
+    for (p, a) in after {
+        if !before_paths.contains_key(p.as_str()) {
+            diff.push(Diff {
+                path: p,
+                change: Change::Added(a),
+            });
+        }
+    }
+    diff
+}
+
+fn found_with_paths<'a>(path: &'a JsonPathInst, json: &'a Value) -> Vec<(String, &'a Value)> {
+    path.evaluate(json, JsonPathValue::from_root(json))
+        .into_iter()
+        .filter_map(|v| match v {
+            JsonPathValue::Slice(v, p) => Some((p, v)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::{diff_at, Change, Diff};
+    use crate::JsonPathInst;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn diff_test() {
+        let path = JsonPathInst::from_str("$.a[*]").unwrap();
+        let before = json!({"a":[1,{"x":1},3],"b":1});
+        let after = json!({"a":[1,{"x":2}],"b":2});
+        assert_eq!(
+            diff_at(&path, &before, &after),
+            vec![
+                Diff {
+                    path: "$.['a'][1]".to_string(),
+                    change: Change::Changed {
+                        before: &json!({"x":1}),
+                        after: &json!({"x":2})
+                    }
+                },
+                Diff {
+                    path: "$.['a'][2]".to_string(),
+                    change: Change::Removed(&json!(3))
+                },
+            ]
+        );
+        assert_eq!(
+            diff_at(&path, &after, &before)[1].change,
+            Change::Added(&json!(3))
+        );
+        assert!(diff_at(&path, &before, &before).is_empty());
+        assert!(diff_at(
+            &JsonPathInst::from_str("$.a.length()").unwrap(),
+            &before,
+            &after
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+//! The evaluation of the query while the input is being deserialized.
+//!
+//! The leading fields and indexes of the query like `$.metadata.id` or `$.items[0]` are followed
+//! by the deserializer itself: the skipped branches are consumed without being stored
+//! and only the subtree they lead to is turned into [`Value`] to evaluate the rest of the query on it.
+//! Therefore the whole document is never materialized.
+//!
+//! ```
+//! use jsonpath_rust::JsonPathInst;
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let input = r#"{"payload":[1,2,3],"metadata":{"id":7,"labels":{"a":"b"}}}"#;
+//! let path = JsonPathInst::from_str("$.metadata.id").unwrap();
+//! assert_eq!(path.extract_reader(input.as_bytes()).unwrap(), vec![json!(7)]);
+//!
+//! let mut de = serde_json::Deserializer::from_str(input);
+//! let ids: Vec<u32> = path.extract_as(&mut de).unwrap();
+//! assert_eq!(ids, vec![7]);
+//! ```
+use crate::parser::model::{JsonPath, JsonPathIndex};
+use crate::parser::visitor::{walk_path, Visitor};
+use crate::JsonPathInst;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Error, IgnoredAny, MapAccess, SeqAccess,
+    Visitor as DeVisitor,
+};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::fmt::Formatter;
+use std::io::Read;
+
+impl JsonPathInst {
+    /// finds the elements while the input is being deserialized.
+    /// The leading fields and indexes of the query are followed by the deserializer skipping the other branches,
+    /// the subtree they lead to is deserialized into [`Value`] and the rest of the query is evaluated on it.
+    /// The queries referring to the root `$` inside the filters after the leading part deserialize the whole input.
+    pub fn extract<'de, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<Vec<Value>, D::Error> {
+        let query = LazyQuery::new(self.path());
+        Select {
+            steps: &query.steps,
+            query: &query,
+        }
+        .deserialize(deserializer)
+    }
+
+    /// finds the elements while the input is being deserialized and converts them into the given type,
+    /// see [`JsonPathInst::extract`].
+    pub fn extract_as<'de, D: Deserializer<'de>, T: DeserializeOwned>(
+        &self,
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error> {
+        self.extract(deserializer)?
+            .into_iter()
+            .map(|v| serde_json::from_value(v).map_err(D::Error::custom))
+            .collect()
+    }
+
+    /// finds the elements while the json is being read from the reader,
+    /// see [`JsonPathInst::extract`].
+    ///
+    /// Returns the error if the input is not a valid json.
+    pub fn extract_reader<R: Read>(&self, reader: R) -> Result<Vec<Value>, serde_json::Error> {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        let found = self.extract(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(found)
+    }
+}
+
+/// The step resolved without materializing the value
+pub(crate) enum LazyStep<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+/// The query split into the leading steps resolved without materializing the values
+/// and the rest evaluated on the subtree they lead to
+pub(crate) struct LazyQuery<'a> {
+    pub(crate) steps: Vec<LazyStep<'a>>,
+    rest: JsonPathInst,
+}
+
+impl<'a> LazyQuery<'a> {
+    pub(crate) fn new(path: &'a JsonPath) -> Self {
+        let elems = match path {
+            JsonPath::Chain(elems) if matches!(elems.first(), Some(JsonPath::Root)) => &elems[1..],
+            _ => &[],
+        };
+        let mut steps: Vec<LazyStep> = elems.iter().map_while(lazy_step).collect();
+        if refers_root(&elems[steps.len()..]) {
+            steps.clear();
+        }
+        let rest = std::iter::once(JsonPath::Root)
+            .chain(elems[steps.len()..].iter().cloned())
+            .collect();
+        LazyQuery {
+            steps,
+            rest: JsonPathInst::from(JsonPath::Chain(rest)),
+        }
+    }
+
+    /// evaluates the rest of the query on the subtree the leading steps lead to
+    pub(crate) fn eval_rest(&self, value: &Value) -> Vec<Value> {
+        self.rest
+            .evaluate_untracked(value)
+            .into_iter()
+            .filter(|v| v.has_value())
+            .map(|v| v.to_data())
+            .collect()
+    }
+}
+
+fn lazy_step(step: &JsonPath) -> Option<LazyStep<'_>> {
+    match step {
+        JsonPath::Field(key) => Some(LazyStep::Field(key)),
+        JsonPath::Index(JsonPathIndex::Single(idx)) => {
+            idx.as_u64().map(|i| LazyStep::Index(i as usize))
+        }
+        _ => None,
+    }
+}
+
+/// checks if the steps refer to the root of the document
+fn refers_root(steps: &[JsonPath]) -> bool {
+    struct Root(bool);
+    impl Visitor for Root {
+        fn visit_path(&mut self, path: &JsonPath) {
+            self.0 |= matches!(path, JsonPath::Root);
+            walk_path(self, path)
+        }
+    }
+    let mut root = Root(false);
+    steps.iter().for_each(|s| root.visit_path(s));
+    root.0
+}
+
+/// The seed following the remaining leading steps of the query
+struct Select<'q> {
+    steps: &'q [LazyStep<'q>],
+    query: &'q LazyQuery<'q>,
+}
+
+impl<'de> DeserializeSeed<'de> for Select<'_> {
+    type Value = Vec<Value>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        match self.steps.first() {
+            None => Ok(self.query.eval_rest(&Value::deserialize(deserializer)?)),
+            Some(_) => deserializer.deserialize_any(self),
+        }
+    }
+}
+
+/// The scalars and the containers of the other type have nothing to select
+macro_rules! nothing_selected {
+    ($($visit:ident($tp:ty)),*) => {
+        $(fn $visit<E: Error>(self, _: $tp) -> Result<Self::Value, E> {
+            Ok(vec![])
+        })*
+    };
+}
+
+impl<'de> DeVisitor<'de> for Select<'_> {
+    type Value = Vec<Value>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a json value")
+    }
+
+    nothing_selected!(
+        visit_bool(bool),
+        visit_i64(i64),
+        visit_u64(u64),
+        visit_f64(f64),
+        visit_str(&str)
+    );
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(vec![])
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut found = vec![];
+        while let Some(key) = map.next_key::<String>()? {
+            match self.steps.first() {
+                Some(LazyStep::Field(field)) if *field == key => {
+                    found = map.next_value_seed(Select {
+                        steps: &self.steps[1..],
+                        query: self.query,
+                    })?
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut found = vec![];
+        let mut i = 0;
+        loop {
+            let selected = match self.steps.first() {
+                Some(LazyStep::Index(idx)) if *idx == i => seq.next_element_seed(Select {
+                    steps: &self.steps[1..],
+                    query: self.query,
+                })?,
+                _ => seq.next_element::<IgnoredAny>()?.map(|_| vec![]),
+            };
+            match selected {
+                Some(selected) => found.extend(selected),
+                None => return Ok(found),
+            }
+            i += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonPathInst;
+    use serde_json::{json, Value};
+    use std::str::FromStr;
+
+    fn extract(path: &str, input: &str) -> Vec<Value> {
+        JsonPathInst::from_str(path)
+            .unwrap()
+            .extract_reader(input.as_bytes())
+            .unwrap()
+    }
+
+    #[test]
+    fn extract_test() {
+        let input = r#"{"meta":{"id":1,"tags":["a","b"]},"items":[{"v":1},{"v":5}]}"#;
+        assert_eq!(extract("$.meta.id", input), vec![json!(1)]);
+        assert_eq!(extract("$.meta.tags[1]", input), vec![json!("b")]);
+        assert_eq!(extract("$.items[?(@.v > 2)].v", input), vec![json!(5)]);
+        assert_eq!(extract("$.meta.tags.length()", input), vec![json!(2)]);
+        assert_eq!(
+            extract("$.items[?(@.v == $.meta.id)]", input),
+            vec![json!({"v":1})]
+        );
+        assert!(extract("$.meta.absent", input).is_empty());
+        assert!(extract("$.items.id", input).is_empty());
+        assert!(extract("$.meta[0]", input).is_empty());
+        assert!(extract("$.meta.id.x", input).is_empty());
+        assert_eq!(extract("$", "[1]"), vec![json!([1])]);
+    }
+
+    #[test]
+    fn extract_invalid_test() {
+        let path = JsonPathInst::from_str("$.a").unwrap();
+        assert!(path.extract_reader(r#"{"a":1,"b":"#.as_bytes()).is_err());
+        assert!(path.extract_reader(r#"{"a":1} 1"#.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn extract_as_test() {
+        let path = JsonPathInst::from_str("$.items[*].v").unwrap();
+        let mut de = serde_json::Deserializer::from_str(r#"{"items":[{"v":1},{"v":5}]}"#);
+        let found: Vec<u8> = path.extract_as(&mut de).unwrap();
+        assert_eq!(found, vec![1, 5]);
+
+        let mut de = serde_json::Deserializer::from_str(r#"{"items":[{"v":"x"}]}"#);
+        assert!(path.extract_as::<_, u8>(&mut de).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+//! The C interface of the library, the header is generated by `cbindgen --lang c`
+//! and the library is built by `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! The query is compiled once by `jsonpath_compile` and evaluated against the utf-8 json texts
+//! by `jsonpath_find` giving the results iterated as the pairs of the path and the json text of the value.
+//! Every object returned by the library is released by its own `*_free` function:
+//!
+//! ```c
+//! char *error = NULL;
+//! JsonPathQuery *query = jsonpath_compile("$..book[?(@.price < 10)].title", &error);
+//! JsonPathResults *found = jsonpath_find(query, (const uint8_t *)text, strlen(text), &error);
+//! const char *path, *value;
+//! while (jsonpath_results_next(found, &path, &value)) {
+//!     printf("%s = %s\n", path, value);
+//! }
+//! jsonpath_results_free(found);
+//! jsonpath_query_free(query);
+//! ```
+//! The failed functions return `NULL` and put the message into `error` unless it is `NULL`,
+//! the message is released by `jsonpath_string_free`.
+//! The panics are caught at the boundary and reported as the failures instead of unwinding into the caller.
+use crate::{JsonPathError, JsonPathInst};
+use serde_json::Value;
+use std::any::Any;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::str::FromStr;
+
+/// The compiled query reused for every document
+pub struct JsonPathQuery {
+    inner: JsonPathInst,
+}
+
+/// The found elements as the pairs of the path and the json text of the value.
+/// The generated elements (like the length) have the empty path,
+/// the nul characters of the keys are written in the path as `\u0000`.
+pub struct JsonPathResults {
+    found: Vec<(CString, CString)>,
+    next: usize,
+}
+
+/// compiles the null-terminated query.
+///
+/// # Safety
+/// `query` is a null-terminated string and `error` is either null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_compile(
+    query: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut JsonPathQuery {
+    if query.is_null() {
+        return fail(error, "the query is null");
+    }
+    guard(error, || {
+        let inner = CStr::from_ptr(query)
+            .to_str()
+            .map_err(|e| e.to_string())
+            .and_then(JsonPathInst::from_str)?;
+        Ok(Box::into_raw(Box::new(JsonPathQuery { inner })))
+    })
+}
+
+/// releases the query compiled by `jsonpath_compile`, the null is ignored.
+///
+/// # Safety
+/// `query` is either null or returned by `jsonpath_compile` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_query_free(query: *mut JsonPathQuery) {
+    if !query.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(query))));
+    }
+}
+
+/// evaluates the query against the utf-8 json text of the given length.
+/// The document the query is not applicable to gives no results rather than the error.
+///
+/// # Safety
+/// `query` is returned by `jsonpath_compile`, `json` points to `len` readable bytes
+/// and `error` is either null or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_find(
+    query: *const JsonPathQuery,
+    json: *const u8,
+    len: usize,
+    error: *mut *mut c_char,
+) -> *mut JsonPathResults {
+    if query.is_null() || json.is_null() {
+        return fail(error, "the query or the json is null");
+    }
+    let text = std::slice::from_raw_parts(json, len);
+    guard(error, || {
+        let found = find(&(*query).inner, text)?;
+        Ok(Box::into_raw(Box::new(JsonPathResults { found, next: 0 })))
+    })
+}
+
+/// the number of the results
+///
+/// # Safety
+/// `results` is returned by `jsonpath_find` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_results_len(results: *const JsonPathResults) -> usize {
+    results.as_ref().map_or(0, |r| r.found.len())
+}
+
+/// moves to the next result putting its path and value, returns false when there are no more results.
+/// The strings belong to the results and live until `jsonpath_results_free`.
+///
+/// # Safety
+/// `results` is returned by `jsonpath_find` and not released yet, `path` and `value` are valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_results_next(
+    results: *mut JsonPathResults,
+    path: *mut *const c_char,
+    value: *mut *const c_char,
+) -> bool {
+    let Some(results) = results.as_mut() else {
+        return false;
+    };
+    let Some((p, v)) = results.found.get(results.next) else {
+        return false;
+    };
+    results.next += 1;
+    if !path.is_null() {
+        *path = p.as_ptr();
+    }
+    if !value.is_null() {
+        *value = v.as_ptr();
+    }
+    true
+}
+
+/// releases the results of `jsonpath_find` along with their strings, the null is ignored.
+///
+/// # Safety
+/// `results` is either null or returned by `jsonpath_find` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_results_free(results: *mut JsonPathResults) {
+    if !results.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(results))));
+    }
+}
+
+/// releases the error message, the null is ignored.
+///
+/// # Safety
+/// `s` is either null or the message put by the library and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(CString::from_raw(s))));
+    }
+}
+
+fn find(path: &JsonPathInst, text: &[u8]) -> Result<Vec<(CString, CString)>, String> {
+    let doc: Value = serde_json::from_slice(text).map_err(|e| e.to_string())?;
+    let found = match path.query(&doc) {
+        Ok(found) => found,
+        Err(JsonPathError::Inapplicable) => vec![],
+        Err(e) => return Err(e.to_string()),
+    };
+    Ok(found
+        .into_iter()
+        .map(|m| {
+            (
+                c_string(m.path.unwrap_or_default()),
+                c_string(m.value.to_string()),
+            )
+        })
+        .collect())
+}
+
+/// the nul characters can only come from the keys of the paths, the json texts escape them
+fn c_string(s: String) -> CString {
+    let escaped = if s.contains('\0') {
+        s.replace('\0', "\\u0000")
+    } else {
+        s
+    };
+    CString::new(escaped).expect("the nul characters are escaped")
+}
+
+/// runs the body reporting its error or its panic by `fail`
+unsafe fn guard<T>(
+    error: *mut *mut c_char,
+    body: impl FnOnce() -> Result<*mut T, String>,
+) -> *mut T {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(ptr)) => ptr,
+        Ok(Err(e)) => fail(error, &e),
+        Err(panic) => fail(
+            error,
+            &format!("the library has panicked: {}", panic_message(&panic)),
+        ),
+    }
+}
+
+fn panic_message(panic: &Box<dyn Any + Send>) -> &str {
+    if let Some(msg) = panic.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = panic.downcast_ref::<String>() {
+        msg
+    } else {
+        "unknown"
+    }
+}
+
+/// puts the message into `error` if it is not null and returns null
+unsafe fn fail<T>(error: *mut *mut c_char, msg: &str) -> *mut T {
+    if !error.is_null() {
+        *error = CString::new(msg.replace('\0', " ")).map_or(ptr::null_mut(), CString::into_raw);
+    }
+    ptr::null_mut()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::*;
+    use std::ffi::{c_char, CStr, CString};
+    use std::ptr;
+
+    unsafe fn results(query: &str, json: &str) -> Result<Vec<(String, String)>, String> {
+        let query = CString::new(query).expect("no nul");
+        let mut error: *mut c_char = ptr::null_mut();
+        let compiled = jsonpath_compile(query.as_ptr(), &mut error);
+        if compiled.is_null() {
+            let msg = CStr::from_ptr(error).to_string_lossy().into_owned();
+            jsonpath_string_free(error);
+            return Err(msg);
+        }
+        let found = jsonpath_find(compiled, json.as_ptr(), json.len(), &mut error);
+        jsonpath_query_free(compiled);
+        if found.is_null() {
+            let msg = CStr::from_ptr(error).to_string_lossy().into_owned();
+            jsonpath_string_free(error);
+            return Err(msg);
+        }
+        let len = jsonpath_results_len(found);
+        let mut pairs = vec![];
+        let (mut path, mut value): (*const c_char, *const c_char) = (ptr::null(), ptr::null());
+        while jsonpath_results_next(found, &mut path, &mut value) {
+            pairs.push((
+                CStr::from_ptr(path).to_string_lossy().into_owned(),
+                CStr::from_ptr(value).to_string_lossy().into_owned(),
+            ));
+        }
+        assert_eq!(len, pairs.len());
+        jsonpath_results_free(found);
+        Ok(pairs)
+    }
+
+    #[test]
+    fn ffi_test() {
+        unsafe {
+            assert_eq!(
+                results("$.a[?(@.b > 1)].b", r#"{"a":[{"b":1},{"b":"x"},{"b":2}]}"#),
+                Ok(vec![("$.['a'][2].['b']".to_string(), "2".to_string())])
+            );
+            assert_eq!(
+                results("$.a.length()", r#"{"a":[1,2]}"#),
+                Ok(vec![(String::new(), "2".to_string())])
+            );
+            assert_eq!(results("$.c", r#"{"a":1}"#), Ok(vec![]));
+            assert!(results("$.a[", "{}").is_err());
+            assert!(results("$.a", "{").is_err());
+
+            assert!(jsonpath_compile(ptr::null(), ptr::null_mut()).is_null());
+            assert!(jsonpath_find(ptr::null(), ptr::null(), 0, ptr::null_mut()).is_null());
+            assert_eq!(jsonpath_results_len(ptr::null()), 0);
+            jsonpath_query_free(ptr::null_mut());
+            jsonpath_results_free(ptr::null_mut());
+            jsonpath_string_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn ffi_nul_and_panic_test() {
+        unsafe {
+            assert_eq!(
+                results("$.*", r#"{"a\u0000b":1}"#),
+                Ok(vec![("$.['a\\u0000b']".to_string(), "1".to_string())])
+            );
+
+            let mut error: *mut c_char = ptr::null_mut();
+            let failed: *mut JsonPathQuery = guard(&mut error, || panic!("boom"));
+            assert!(failed.is_null());
+            assert_eq!(
+                CStr::from_ptr(error).to_str(),
+                Ok("the library has panicked: boom")
+            );
+            jsonpath_string_free(error);
+        }
+    }
+}
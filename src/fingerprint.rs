@@ -0,0 +1,147 @@
+//! The stable hashes of the content of the found elements.
+//!
+//! The hash does not depend on the order of the keys of the objects, on the way the numbers are written
+//! (`1` and `1.0` are the same) or on the version of the compiler, since it is the 64-bit FNV-1a
+//! of the canonical form of the element, therefore the hashes can be stored and compared between the runs.
+//!
+//! ```
+//! use jsonpath_rust::{fingerprint, JsonPathInst};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let before = json!({"services":{"api":{"replicas":2,"image":"a"},"db":{"replicas":1}}});
+//! let after = json!({"services":{"api":{"image":"a","replicas":2},"db":{"replicas":3}}});
+//! let path = JsonPathInst::from_str("$.services.*").unwrap();
+//!
+//! let (before, after) = (fingerprint(&path, &before), fingerprint(&path, &after));
+//! assert_eq!(before[0], after[0]);
+//! assert_eq!(before[1].0, after[1].0);
+//! assert_ne!(before[1].1, after[1].1);
+//! ```
+use crate::{find_map, JsonLike, JsonPathInst};
+use serde_json::Value;
+
+const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The 64-bit FNV-1a hash
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(PRIME);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes())
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_len(s.len());
+        self.write(s.as_bytes())
+    }
+
+    /// writes the canonical form of the element: the tag of the type followed by the content,
+    /// the fields of the objects sorted by the keys
+    fn write_el<T: JsonLike>(&mut self, json: &T) {
+        if let Some(elems) = json.as_array() {
+            self.write(&[5]);
+            self.write_len(elems.len());
+            elems.iter().for_each(|e| self.write_el(e));
+        } else if let Some(entries) = json.entries() {
+            let mut entries: Vec<_> = entries.collect();
+            entries.sort_by_key(|(k, _)| *k);
+            self.write(&[6]);
+            self.write_len(entries.len());
+            for (k, v) in entries {
+                self.write_str(k);
+                self.write_el(v);
+            }
+        } else if let Some(s) = json.as_str() {
+            self.write(&[4]);
+            self.write_str(s)
+        } else if let Some(n) = json.as_f64() {
+            // the negative zero equals the zero
+            let n = if n == 0.0 { 0.0 } else { n };
+            self.write(&[3]);
+            self.write(&n.to_bits().to_le_bytes())
+        } else if let Some(b) = json.as_bool() {
+            self.write(&[if b { 2 } else { 1 }])
+        } else {
+            self.write(&[0])
+        }
+    }
+}
+
+/// the stable hash of the content of the element, see the [module](self) for the details
+pub fn content_hash<T: JsonLike>(json: &T) -> u64 {
+    let mut hash = Fnv(OFFSET);
+    hash.write_el(json);
+    hash.0
+}
+
+/// finds the elements and gives their paths along with the stable hashes of their content,
+/// so the changed elements can be told without keeping the elements themselves.
+/// The generated elements (like the length) have no path and are skipped.
+pub fn fingerprint(path: &JsonPathInst, json: &Value) -> Vec<(String, u64)> {
+    find_map(path, json)
+        .into_iter()
+        .filter_map(|m| Some((m.path?, content_hash(&*m.value))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{content_hash, fingerprint, JsonPathInst};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn content_hash_test() {
+        assert_eq!(
+            content_hash(&json!({"a":1,"b":[true,null,"x"]})),
+            content_hash(&json!({"b":[true,null,"x"],"a":1.0}))
+        );
+        assert_eq!(content_hash(&json!(0.0)), content_hash(&json!(-0.0)));
+        // the hash is stable between the runs
+        assert_eq!(content_hash(&json!(null)), 0xaf63_bd4c_8601_b7df);
+        let distinct = [
+            json!(null),
+            json!(false),
+            json!(true),
+            json!(0),
+            json!(""),
+            json!("0"),
+            json!([]),
+            json!({}),
+            json!([[]]),
+            json!([[], []]),
+            json!(["a", "b"]),
+            json!(["ab"]),
+            json!({"a":"b"}),
+            json!({"ab":""}),
+            json!({"a":{}}),
+        ];
+        for (i, l) in distinct.iter().enumerate() {
+            for r in &distinct[i + 1..] {
+                assert_ne!(content_hash(l), content_hash(r), "{l} {r}");
+            }
+        }
+    }
+
+    #[test]
+    fn fingerprint_test() {
+        let json = json!({"a":[{"b":1},{"b":2}]});
+        let path = JsonPathInst::from_str("$.a[*]").expect("the path is correct");
+        let found = fingerprint(&path, &json);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].0, "$.['a'][0]");
+        assert_eq!(found[0].1, content_hash(&json!({"b":1})));
+        assert_ne!(found[0].1, found[1].1);
+
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        assert!(fingerprint(&length, &json).is_empty());
+    }
+}
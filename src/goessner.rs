@@ -0,0 +1,300 @@
+//! The compatibility with the original javascript implementation of Stefan Goessner.
+//!
+//! The queries are parsed with [`Dialect::Goessner`](crate::Dialect::Goessner) accepting the syntax of the original:
+//! the script expressions like `[(@.length-1)]` picking the element from the end,
+//! the names without the quotes like `[author,title]` and the operators `===` and `!==` in the filters.
+//! The rewritten parts of the query are listed by [`JsonPathInst::explain`].
+//!
+//! The parsed path evaluates like the original (see [`JsonPathInst::with_goessner`]):
+//! the filters are applied to the fields of the objects as well as to the elements of the arrays,
+//! `?(@.isbn)` checks the truthiness of the field (`0`, `""`, `false` and `null` do not pass),
+//! the comparisons convert the types like javascript ([`Coercion::JsLike`](crate::Coercion::JsLike))
+//! and [`find`] gives `false` when nothing is found.
+//! [`divergence`] tells the documents the original and the library give the different results for.
+//!
+//! ```
+//! use jsonpath_rust::goessner::divergence;
+//! use jsonpath_rust::{find, Dialect, JsonPathInst};
+//! use serde_json::json;
+//!
+//! let json = json!({"book":[
+//!     {"title":"Emma","price":0},
+//!     {"title":"Dune","price":"8"},
+//!     {"title":"Ulysses","price":12}
+//! ]});
+//! let path = JsonPathInst::from_str_with_dialect("$.book[(@.length-1)].title", Dialect::Goessner).unwrap();
+//! assert_eq!(find(&path, &json), json!(["Ulysses"]));
+//!
+//! let path = JsonPathInst::from_str_with_dialect("$.book[?(@.price)].title", Dialect::Goessner).unwrap();
+//! assert_eq!(find(&path, &json), json!(["Dune", "Ulysses"]));
+//! let diff = divergence(&path, &json).unwrap();
+//! assert_eq!(diff.standard, json!(["Emma", "Dune", "Ulysses"]));
+//!
+//! let path = JsonPathInst::from_str_with_dialect("$.book[?(@.price > 100)]", Dialect::Goessner).unwrap();
+//! assert_eq!(find(&path, &json), json!(false));
+//! assert_eq!(divergence(&path, &json), None);
+//! ```
+use crate::{find, JsonPathInst};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// The different results of the same query on the same document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// the result of the original javascript implementation
+    pub goessner: Value,
+    /// the result of the library
+    pub standard: Value,
+}
+
+/// evaluates the path both like the original and like the library and gives the results if they differ.
+/// The path finding nothing gives `false` in the original and `null` in the library, that is not a divergence.
+pub fn divergence(path: &JsonPathInst, json: &Value) -> Option<Divergence> {
+    let evaluate = |goessner: bool| {
+        let path = path
+            .clone()
+            .with_options(path.options().clone().with_goessner(goessner));
+        match find(&path, json) {
+            Value::Bool(false) => Value::Null,
+            found => found,
+        }
+    };
+    let (goessner, standard) = (evaluate(true), evaluate(false));
+    if goessner == standard {
+        None
+    } else {
+        Some(Divergence {
+            goessner: if goessner.is_null() {
+                Value::Bool(false)
+            } else {
+                goessner
+            },
+            standard,
+        })
+    }
+}
+
+/// parses the query written in the syntax of the original and turns on its evaluation
+pub(crate) fn goessner_query(query: &str) -> Result<JsonPathInst, String> {
+    let mut rewrites = vec![];
+    let translated = translate(query, &mut rewrites)?;
+    let mut path = JsonPathInst::from_str(&translated)?.with_goessner(true);
+    path.rewrites.extend(rewrites);
+    Ok(path)
+}
+
+/// rewrites the syntax of the original into the syntax of the library outside the quoted strings
+fn translate(query: &str, rewrites: &mut Vec<String>) -> Result<String, String> {
+    let mut out = String::with_capacity(query.len());
+    let mut rest = query;
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' || c == '"' {
+            let end =
+                quote_end(rest, c).ok_or_else(|| format!("the string `{rest}` is not closed"))?;
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if let Some(tail) = rest.strip_prefix("===") {
+            out.push_str("==");
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix("!==") {
+            out.push_str("!=");
+            rest = tail;
+        } else if let Some(index) = bracket(rest).filter(|_| c == '[') {
+            let body = &rest[1..index];
+            match rewrite_index(body.trim())? {
+                Some(rewritten) => {
+                    rewrites.push(format!("[{body}] as [{rewritten}]"));
+                    out.push('[');
+                    out.push_str(&rewritten);
+                    out.push(']');
+                    rest = &rest[index + 1..];
+                }
+                None => {
+                    out.push(c);
+                    rest = &rest[1..];
+                }
+            }
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Ok(out)
+}
+
+/// the position right after the closing quote
+fn quote_end(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+/// the position of the bracket closing the one the text starts with, the nested brackets and the quotes are skipped
+fn bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        match c {
+            '\'' | '"' => {
+                i += quote_end(&text[i..], c)?;
+                continue;
+            }
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    None
+}
+
+/// rewrites the script expression like `(@.length-1)` and the names without the quotes like `author,title`,
+/// the rest of the brackets are kept as they are
+fn rewrite_index(body: &str) -> Result<Option<String>, String> {
+    if let Some(script) = body.strip_prefix('(').and_then(|b| b.strip_suffix(')')) {
+        let from_end = script
+            .replace(char::is_whitespace, "")
+            .strip_prefix("@.length-")
+            .and_then(|n| n.parse::<i32>().ok())
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("the script expression `({script})` is not supported"))?;
+        return Ok(Some(if from_end == 1 {
+            "-1:".to_string()
+        } else {
+            format!("-{from_end}:-{}", from_end - 1)
+        }));
+    }
+    if body.starts_with('?') {
+        return Ok(None);
+    }
+    let elems: Vec<&str> = body.split(',').map(str::trim).collect();
+    let is_name = |e: &str| {
+        e.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && e.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if !elems.iter().copied().any(is_name) {
+        return Ok(None);
+    }
+    let elems: Vec<String> = elems
+        .into_iter()
+        .map(|e| {
+            if is_name(e) {
+                format!("'{e}'")
+            } else {
+                e.to_string()
+            }
+        })
+        .collect();
+    Ok(Some(elems.join(",")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::goessner::divergence;
+    use crate::{find, Dialect, JsonPathInst};
+    use serde_json::{json, Value};
+
+    fn goessner(query: &str) -> JsonPathInst {
+        JsonPathInst::from_str_with_dialect(query, Dialect::Goessner).expect("the path is correct")
+    }
+
+    fn store() -> Value {
+        json!({"store":{
+            "book":[
+                {"category":"reference","author":"Nigel Rees","title":"Sayings","price":8.95},
+                {"category":"fiction","author":"Evelyn Waugh","title":"Sword","price":12.99},
+                {"category":"fiction","author":"Herman Melville","title":"Moby Dick","isbn":"0-553","price":8.99},
+                {"category":"fiction","author":"J. R. R. Tolkien","title":"The Lord","isbn":"0-395","price":22.99}
+            ],
+            "bicycle":{"color":"red","price":19.95}
+        }})
+    }
+
+    #[test]
+    fn goessner_syntax_test() {
+        let parse = |q: &str| {
+            JsonPathInst::from_str_with_dialect(q, Dialect::Goessner).map(|p| p.canonicalize())
+        };
+        assert_eq!(
+            parse("$..book[(@.length-1)]"),
+            Ok("$..['book'][-1:]".to_string())
+        );
+        assert_eq!(
+            parse("$..book[( @.length - 2 )]"),
+            Ok("$..['book'][-2:-1]".to_string())
+        );
+        assert_eq!(
+            parse("$.a[author, title]"),
+            Ok("$['a']['author','title']".to_string())
+        );
+        assert_eq!(parse("$.a[0,1]"), Ok("$['a'][0,1]".to_string()));
+        assert_eq!(
+            parse("$.a[?(@.b === '[x]' && @.c !== 1)]"),
+            Ok("$['a'][?(@['b'] == '[x]' && @['c'] != 1)]".to_string())
+        );
+        assert!(parse("$.a[(@.length/2)]").is_err());
+        assert_eq!(goessner("$.a[(@.length-1)]").explain().rewrites.len(), 1);
+    }
+
+    #[test]
+    fn goessner_eval_test() {
+        let json = store();
+        assert_eq!(
+            find(&goessner("$..book[(@.length-1)].title"), &json),
+            json!(["The Lord"])
+        );
+        assert_eq!(
+            find(&goessner("$.store.book[0][author,title]"), &json),
+            json!(["Nigel Rees", "Sayings"])
+        );
+        assert_eq!(
+            find(&goessner("$..book[?(@.isbn)].price"), &json),
+            json!([8.99, 22.99])
+        );
+        // the filter is applied to the fields of the object
+        assert_eq!(
+            find(&goessner("$.store[?(@.color === 'red')].price"), &json),
+            json!([19.95])
+        );
+        assert_eq!(find(&goessner("$.store.missing"), &json), json!(false));
+        assert_eq!(
+            find(&goessner("$..book[?(@.price > 100)]"), &json),
+            json!(false)
+        );
+
+        let zero = json!([{"n":0},{"n":"5"},{"n":""},{"n":3}]);
+        assert_eq!(find(&goessner("$[?(@.n)].n"), &zero), json!(["5", 3]));
+        assert_eq!(find(&goessner("$[?(@.n == 5)].n"), &zero), json!(["5"]));
+    }
+
+    #[test]
+    fn divergence_test() {
+        let json = store();
+        assert_eq!(
+            divergence(&goessner("$..book[?(@.isbn)].price"), &json),
+            None
+        );
+        assert_eq!(divergence(&goessner("$.store.missing"), &json), None);
+
+        let diff = divergence(&goessner("$.store[?(@.color)]"), &json).expect("diverges");
+        assert_eq!(diff.goessner, json!([{"color":"red","price":19.95}]));
+        assert_eq!(diff.standard, Value::Null);
+
+        let zero = json!([{"n":0},{"n":1}]);
+        let diff = divergence(&goessner("$[?(@.n)].n"), &zero).expect("diverges");
+        assert_eq!(diff.goessner, json!([1]));
+        assert_eq!(diff.standard, json!([0, 1]));
+    }
+}
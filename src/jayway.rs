@@ -0,0 +1,287 @@
+//! The semantics of Jayway JsonPath, the library of the Java world.
+//!
+//! The queries are parsed with [`Dialect::Jayway`](crate::Dialect::Jayway) accepting the syntax of Jayway:
+//! the regexes like `@.name =~ /^ab.*/i` matching the whole string as `String::matches` does,
+//! the operators `subsetof`, `anyof` and `noneof` written in the lower case
+//! and the functions `min()`, `max()`, `avg()`, `stddev()`, `sum()`, `length()` and `keys()` ending the path.
+//!
+//! [`read`] returns what `JsonPath.read` of Jayway returns:
+//! - the definite path (without wildcards, descents, slices, unions or filters) gives the found value
+//!   or [`JsonPathError::Inapplicable`] like the `PathNotFoundException`,
+//! - the indefinite path gives the array of the found values, empty when nothing is found,
+//! - the path ending in a function gives the computed value
+//!   or [`JsonPathError::Evaluation`] when there is nothing to compute, like the aggregates of the empty array.
+//!
+//! ```
+//! use jsonpath_rust::{jayway, Dialect, JsonPathError, JsonPathInst};
+//! use serde_json::json;
+//!
+//! let json = json!({"books":[{"title":"Dune","price":8},{"title":"Emma","price":12}]});
+//! let read = |query: &str| {
+//!     let path = JsonPathInst::from_str_with_dialect(query, Dialect::Jayway).unwrap();
+//!     jayway::read(&path, &json)
+//! };
+//!
+//! assert_eq!(read("$.books[0].title"), Ok(json!("Dune")));
+//! assert_eq!(read("$.books[*].title"), Ok(json!(["Dune", "Emma"])));
+//! # #[cfg(feature = "regex")]
+//! assert_eq!(read("$.books[?(@.title =~ /d.*/i)].price"), Ok(json!([8])));
+//! assert_eq!(read("$.books[*].price.avg()"), Ok(json!(10.0)));
+//! assert_eq!(read("$.books[?(@.price > 20)].title"), Ok(json!([])));
+//! assert_eq!(read("$.books[5].title"), Err(JsonPathError::Inapplicable));
+//! ```
+use crate::parser::model::JsonPath;
+use crate::{JsonPathError, JsonPathInst};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// evaluates the query as `JsonPath.read` of Jayway does, see the [module](self) for the details
+pub fn read(path: &JsonPathInst, json: &Value) -> Result<Value, JsonPathError> {
+    let found = path.query(json);
+    if ends_with_function(path.path()) {
+        return match found {
+            Ok(found) if !found.is_empty() => Ok((*found[0].value).clone()),
+            Ok(_) | Err(JsonPathError::Inapplicable) => Err(JsonPathError::Evaluation(format!(
+                "the function of `{path}` has nothing to compute"
+            ))),
+            Err(e) => Err(e),
+        };
+    }
+    if path.is_singular() {
+        return found?
+            .first()
+            .map(|m| (*m.value).clone())
+            .ok_or(JsonPathError::Inapplicable);
+    }
+    match found {
+        Ok(found) => Ok(Value::Array(
+            found.iter().map(|m| (*m.value).clone()).collect(),
+        )),
+        Err(JsonPathError::Inapplicable) => Ok(Value::Array(vec![])),
+        Err(e) => Err(e),
+    }
+}
+
+fn ends_with_function(path: &JsonPath) -> bool {
+    match path {
+        JsonPath::Chain(elems) => elems.last().is_some_and(ends_with_function),
+        JsonPath::Fn(_) => true,
+        _ => false,
+    }
+}
+
+fn has_inner_function(path: &JsonPath) -> bool {
+    match path {
+        JsonPath::Chain(elems) => elems
+            .split_last()
+            .is_some_and(|(_, init)| init.iter().any(|e| matches!(e, JsonPath::Fn(_)))),
+        _ => false,
+    }
+}
+
+/// parses the query written in the syntax of Jayway
+pub(crate) fn jayway_query(query: &str) -> Result<JsonPathInst, String> {
+    let path = JsonPathInst::from_str(&translate(query)?)?;
+    if has_inner_function(path.path()) {
+        return Err(format!(
+            "the functions can only end the path in the Jayway dialect: `{query}`"
+        ));
+    }
+    Ok(path)
+}
+
+/// rewrites the syntax of Jayway into the syntax of the library outside the quoted strings
+fn translate(query: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(query.len());
+    let mut rest = query;
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' || c == '"' {
+            let end =
+                quote_end(rest, c).ok_or_else(|| format!("the string `{rest}` is not closed"))?;
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if let Some(tail) = rest.strip_prefix("=~") {
+            let (regex, tail) = regex_literal(tail.trim_start())?;
+            out.push_str("~= ");
+            out.push_str(&regex);
+            rest = tail;
+        } else if let Some((sign, tail)) = lowercase_sign(&out, rest) {
+            out.push_str(sign);
+            rest = tail;
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Ok(out)
+}
+
+/// the position right after the closing quote
+fn quote_end(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+/// converts the regex like `/^ab.*/i` into the quoted pattern matching the whole string
+fn regex_literal(text: &str) -> Result<(String, &str), String> {
+    let body = text
+        .strip_prefix('/')
+        .ok_or_else(|| format!("the regex `{text}` does not start with `/`"))?;
+    let mut pattern = String::new();
+    let mut chars = body.char_indices();
+    let end = loop {
+        match chars.next() {
+            Some((_, '\\')) => match chars.next() {
+                Some((_, '/')) => pattern.push('/'),
+                Some((_, c)) => {
+                    pattern.push('\\');
+                    pattern.push(c);
+                }
+                None => break None,
+            },
+            Some((i, '/')) => break Some(i),
+            Some((_, '\'')) => pattern.push_str("\\'"),
+            Some((_, c)) => pattern.push(c),
+            None => break None,
+        }
+    }
+    .ok_or_else(|| format!("the regex `{text}` is not closed"))?;
+    let tail = &body[end + 1..];
+    let flags_len = tail
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(tail.len());
+    let mut flags = String::new();
+    for flag in tail[..flags_len].chars() {
+        match flag {
+            'i' | 'm' | 's' | 'x' => flags.push(flag),
+            // the unicode case folding is the default of the regexes of the library
+            'u' => {}
+            _ => return Err(format!("the flag `{flag}` of the regex is not supported")),
+        }
+    }
+    let flags = if flags.is_empty() {
+        flags
+    } else {
+        format!("(?{flags})")
+    };
+    Ok((format!("'{flags}^(?:{pattern})$'"), &tail[flags_len..]))
+}
+
+/// the operators of Jayway written in the lower case standing between the spaces
+fn lowercase_sign<'a>(out: &str, rest: &'a str) -> Option<(&'static str, &'a str)> {
+    if !out.ends_with(char::is_whitespace) {
+        return None;
+    }
+    [
+        ("subsetof", "subsetOf"),
+        ("anyof", "anyOf"),
+        ("noneof", "noneOf"),
+    ]
+    .into_iter()
+    .find_map(|(jayway, sign)| {
+        rest.strip_prefix(jayway)
+            .filter(|tail| tail.starts_with(char::is_whitespace))
+            .map(|tail| (sign, tail))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jayway::read;
+    use crate::{Dialect, JsonPathError, JsonPathInst};
+    use serde_json::{json, Value};
+
+    fn jayway(query: &str, json: &Value) -> Result<Value, JsonPathError> {
+        let path = JsonPathInst::from_str_with_dialect(query, Dialect::Jayway)
+            .expect("the path is correct");
+        read(&path, json)
+    }
+
+    #[test]
+    fn jayway_syntax_test() {
+        let parse = |q: &str| {
+            JsonPathInst::from_str_with_dialect(q, Dialect::Jayway).map(|p| p.canonicalize())
+        };
+        assert_eq!(
+            parse("$[?(@.a =~ /a\\/b/i)]"),
+            Ok("$[?(@['a'] ~= '(?i)^(?:a/b)$')]".to_string())
+        );
+        assert_eq!(
+            parse("$[?(@.a =~ /it's/)]"),
+            Ok("$[?(@['a'] ~= '^(?:it\\'s)$')]".to_string())
+        );
+        assert_eq!(
+            parse("$[?(@.a subsetof ['x'])]"),
+            parse("$[?(@.a subsetOf ['x'])]")
+        );
+        assert_eq!(
+            parse("$[?(@.a anyof ['x'] && @.b noneof ['y'])]"),
+            parse("$[?(@.a anyOf ['x'] && @.b noneOf ['y'])]")
+        );
+        assert_eq!(
+            parse("$[?(@.a == ' anyof =~ /x/ ')]"),
+            Ok("$[?(@['a'] == ' anyof =~ /x/ ')]".to_string())
+        );
+        assert!(parse("$[?(@.a =~ /x)]").is_err());
+        assert!(parse("$[?(@.a =~ /x/g)]").is_err());
+        assert!(parse("$.a.length().b").is_err());
+    }
+
+    #[test]
+    fn jayway_read_test() {
+        let json = json!({
+            "store":{"book":[
+                {"author":"Nigel Rees","price":8.95},
+                {"author":"Evelyn Waugh","price":12.99,"isbn":"0-553"},
+                {"author":"Herman Melville","price":8.99}
+            ],"bicycle":{"color":"red","price":19.95}},
+            "empty":[]
+        });
+        assert_eq!(jayway("$.store.bicycle.color", &json), Ok(json!("red")));
+        assert_eq!(
+            jayway("$.store.missing", &json),
+            Err(JsonPathError::Inapplicable)
+        );
+        assert_eq!(jayway("$.store.book[*].isbn", &json), Ok(json!(["0-553"])));
+        assert_eq!(jayway("$..missing", &json), Ok(json!([])));
+        assert_eq!(jayway("$.store.book.length()", &json), Ok(json!(3)));
+        assert_eq!(
+            jayway("$.store.book[*].price.min()", &json),
+            Ok(json!(8.95))
+        );
+        assert_eq!(jayway("$..price.max()", &json), Ok(json!(19.95)));
+        assert_eq!(
+            jayway("$.store.bicycle.keys()", &json),
+            Ok(json!(["color", "price"]))
+        );
+        assert!(matches!(
+            jayway("$.empty.avg()", &json),
+            Err(JsonPathError::Evaluation(_))
+        ));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn jayway_regex_test() {
+        let json = json!({"store":{"book":[
+            {"author":"Nigel Rees","price":8.95},
+            {"author":"Evelyn Waugh","price":12.99}
+        ]}});
+        assert_eq!(
+            jayway("$.store.book[?(@.author =~ /.*rees/i)].price", &json),
+            Ok(json!([8.95]))
+        );
+        // the regex matches the whole string
+        assert_eq!(
+            jayway("$.store.book[?(@.author =~ /rees/i)].price", &json),
+            Ok(json!([]))
+        );
+    }
+}
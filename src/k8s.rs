@@ -0,0 +1,238 @@
+//! The templates of `kubectl -o jsonpath=...`.
+//!
+//! The template is the text with the expressions in the braces like `{.items[*].metadata.name}`,
+//! the expressions start without `$` and are evaluated by the engine of the library,
+//! `{range <expr>}...{end}` repeats the text for every found element evaluating the expressions inside
+//! relative to that element, `{"\n"}` and `{'\t'}` are the quoted text with the escapes.
+//! The found strings are printed as they are, the rest of the values as json,
+//! several found elements are separated by the space and the missing ones print nothing like `kubectl` does.
+//!
+//! ```
+//! use jsonpath_rust::K8sTemplate;
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let pods = json!({"items":[
+//!     {"metadata":{"name":"api"},"status":{"phase":"Running"}},
+//!     {"metadata":{"name":"db"},"status":{"phase":"Pending"}}
+//! ]});
+//!
+//! let names = K8sTemplate::from_str("{.items[*].metadata.name}").unwrap();
+//! assert_eq!(names.render(&pods), "api db");
+//!
+//! let table = K8sTemplate::from_str(r#"{range .items[*]}{.metadata.name}={.status.phase}{"\n"}{end}"#).unwrap();
+//! assert_eq!(table.render(&pods), "api=Running\ndb=Pending\n");
+//! ```
+use crate::{JsonPathInst, JsonPtr};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// The parsed template of `kubectl -o jsonpath=...`, see the [module](self) for the syntax
+#[derive(Debug, Clone)]
+pub struct K8sTemplate {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Expr(JsonPathInst),
+    Range(JsonPathInst, Vec<Node>),
+}
+
+impl FromStr for K8sTemplate {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        // the stack of the ranges being parsed along with the nodes of the template itself at the bottom
+        let mut stack: Vec<(Option<JsonPathInst>, Vec<Node>)> = vec![(None, vec![])];
+        let mut rest = template;
+        while !rest.is_empty() {
+            let nodes = &mut stack.last_mut().expect("the template itself").1;
+            let Some(start) = rest.find('{') else {
+                nodes.push(Node::Text(rest.to_string()));
+                break;
+            };
+            if start > 0 {
+                nodes.push(Node::Text(rest[..start].to_string()));
+            }
+            let end = start
+                + block_end(&rest[start..])
+                    .ok_or_else(|| format!("the expression `{}` is not closed", &rest[start..]))?;
+            let expr = rest[start + 1..end].trim();
+            rest = &rest[end + 1..];
+
+            if expr == "end" {
+                let (range, body) = stack.pop().expect("the template itself");
+                let range = range.ok_or("the `{end}` without the `{range}`")?;
+                stack
+                    .last_mut()
+                    .ok_or("the `{end}` without the `{range}`")?
+                    .1
+                    .push(Node::Range(range, body));
+            } else if let Some(range) = expr.strip_prefix("range ") {
+                stack.push((Some(k8s_query(range.trim())?), vec![]));
+            } else if let Some(text) = quoted(expr) {
+                nodes.push(Node::Text(text));
+            } else {
+                nodes.push(Node::Expr(k8s_query(expr)?));
+            }
+        }
+        match stack.pop() {
+            Some((None, nodes)) if stack.is_empty() => Ok(K8sTemplate { nodes }),
+            _ => Err("the `{range}` without the `{end}`".to_string()),
+        }
+    }
+}
+
+impl K8sTemplate {
+    /// prints the template for the document
+    pub fn render(&self, json: &Value) -> String {
+        let mut out = String::new();
+        render(&self.nodes, json, &mut out);
+        out
+    }
+
+    /// the query of the template made of the only expression like `{.items[*].metadata.name}`
+    pub(crate) fn into_query(self) -> Option<JsonPathInst> {
+        match <[Node; 1]>::try_from(self.nodes) {
+            Ok([Node::Expr(path)]) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+fn render(nodes: &[Node], json: &Value, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Expr(path) => {
+                let found: Vec<String> = path.find_slice(json).iter().map(print).collect();
+                out.push_str(&found.join(" "));
+            }
+            Node::Range(path, body) => {
+                for el in path.find_slice(json) {
+                    render(body, &el, out);
+                }
+            }
+        }
+    }
+}
+
+fn print(found: &JsonPtr<Value>) -> String {
+    match &**found {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// the position of the brace closing the expression outside the quotes
+fn block_end(block: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut chars = block.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '}') => return Some(i),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+/// the text of the quoted expression like `{"\n"}` with the escapes replaced
+fn quoted(expr: &str) -> Option<String> {
+    let q = expr.chars().next().filter(|q| *q == '"' || *q == '\'')?;
+    let inner = expr.strip_prefix(q)?.strip_suffix(q)?;
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => text.push('\n'),
+            't' => text.push('\t'),
+            'r' => text.push('\r'),
+            c => text.push(c),
+        }
+    }
+    Some(text)
+}
+
+/// parses the expression of `kubectl` starting without `$` like `.items[*]` or `@.name`
+pub(crate) fn k8s_query(expr: &str) -> Result<JsonPathInst, String> {
+    let query = if expr == "@" {
+        "$".to_string()
+    } else if let Some(rest) = expr.strip_prefix('@') {
+        format!("${rest}")
+    } else if expr.starts_with('$') {
+        expr.to_string()
+    } else if expr.starts_with('.') || expr.starts_with('[') {
+        format!("${expr}")
+    } else {
+        format!("$.{expr}")
+    };
+    JsonPathInst::from_str(&query)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::K8sTemplate;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn render(template: &str, json: &serde_json::Value) -> String {
+        K8sTemplate::from_str(template)
+            .expect("the template is correct")
+            .render(json)
+    }
+
+    #[test]
+    fn k8s_template_test() {
+        let json = json!({"kind":"List","items":[
+            {"metadata":{"name":"a","labels":{"app":"x"}},"spec":{"containers":[{"image":"i1"},{"image":"i2"}]}},
+            {"metadata":{"name":"b"},"spec":{"containers":[{"image":"i3"}]}}
+        ]});
+        assert_eq!(render("{.kind}", &json), "List");
+        assert_eq!(render("kind: {$.kind}!", &json), "kind: List!");
+        assert_eq!(render("{.items[*].metadata.name}", &json), "a b");
+        assert_eq!(render("{..image}", &json), "i1 i2 i3");
+        assert_eq!(
+            render("{.items[0].metadata.labels}", &json),
+            r#"{"app":"x"}"#
+        );
+        assert_eq!(render("{.items[*].metadata.labels.app}", &json), "x");
+        assert_eq!(render("[{.missing}]", &json), "[]");
+        assert_eq!(
+            render(
+                r#"{.items[?(@.metadata.name=="b")].spec.containers[0].image}"#,
+                &json
+            ),
+            "i3"
+        );
+        assert_eq!(
+            render(
+                r#"{range .items[*]}{.metadata.name}:{range .spec.containers[*]} {.image}{end}{"\n"}{end}"#,
+                &json
+            ),
+            "a: i1 i2\nb: i3\n"
+        );
+        assert_eq!(
+            render("{range .items[*]}{@.metadata.name};{end}", &json),
+            "a;b;"
+        );
+        assert_eq!(render(r#"{'}\t'}"#, &json), "}\t");
+
+        assert!(K8sTemplate::from_str("{.items").is_err());
+        assert!(K8sTemplate::from_str("{range .items[*]}{.a}").is_err());
+        assert!(K8sTemplate::from_str("{.a}{end}").is_err());
+        assert!(K8sTemplate::from_str("{.a[}").is_err());
+    }
+}
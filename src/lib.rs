@@ -100,18 +100,95 @@
 
 #![allow(clippy::vec_init_then_push)]
 
-use crate::parser::model::JsonPath;
+use crate::parser::explain::Plan;
+use crate::parser::jq::path_to_jq;
+pub use crate::parser::jq::UnsupportedFeature;
+use crate::parser::lint::lint_path;
+pub use crate::parser::lint::LintWarning;
+use crate::parser::model::{
+    FilterExpression, FilterSign, Function, JsonPath, JsonPathIndex, Operand,
+};
 use crate::parser::parser::parse_json_path;
-use crate::path::json_path_instance;
+use crate::parser::visitor::{
+    walk_filter, walk_operand, walk_operand_mut, walk_path, Visitor, VisitorMut,
+};
+use crate::path::{
+    compiled_instance, exceeds_depth, optimize, pattern_error, with_budget, Budget, Chain,
+    Compiled, Ctx, EvalWarning, EvalWarningKind, PathInstance, Rules, Stop,
+};
+pub use crate::path::{
+    BorrowedValue, CancellationToken, Coercion, Comparator, DocumentIndex, DocumentStats, JsonLike,
+    PathNode, Scalar, StringComparator, StringLength, StringOrder, StringTransform, Track,
+};
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
+use thiserror::Error;
 use JsonPathValue::{NewValue, NoValue, Slice};
 
+pub mod assert;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod diff;
+mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fingerprint;
+pub mod goessner;
+pub mod jayway;
+pub mod k8s;
+pub mod matches;
+pub mod ndjson;
+pub mod options;
 pub mod parser;
 pub mod path;
+pub mod project;
+pub mod prune;
+#[cfg(feature = "raw_value")]
+mod raw;
+#[cfg(feature = "registry")]
+pub mod registry;
+#[cfg(feature = "jsonschema")]
+pub mod schema;
+pub mod snapshot;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod zip;
+
+#[cfg(feature = "cache")]
+pub use crate::cache::{CacheMetrics, QueryCache};
+pub use crate::diff::{diff_at, Change, Diff};
+pub use crate::fingerprint::{content_hash, fingerprint};
+pub use crate::k8s::K8sTemplate;
+pub use crate::matches::{
+    find_grouped, find_grouped_at, find_map, find_map_with_style, find_page, find_records,
+    find_records_with_style, find_with_context, Match, MatchContext, MatchRecord, Page,
+};
+pub(crate) use crate::options::{arrange, nesting, selectors};
+pub use crate::options::{
+    find_with_options, Dialect, EvalLimits, EvalOptions, Limit, ParseLimits, ResultOrder,
+};
+pub use crate::project::Projection;
+pub use crate::prune::{except, partition, select_subtree, select_subtrees};
+#[cfg(feature = "registry")]
+pub use crate::registry::QueryRegistry;
+pub use crate::snapshot::snapshot;
+pub use crate::zip::{zip_queries, Row};
+
+/// The reexport for the code generated by the macros of `jsonpath-rust-macros`.
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub use serde_json as __serde_json;
 
 #[macro_use]
 extern crate pest_derive;
@@ -153,38 +230,1063 @@ pub trait JsonPathQuery {
     fn path(self, query: &str) -> Result<Value, String>;
 }
 
+/// The trait allows to inspect the matches of the query without cloning the found data.
+/// # Examples:
+/// ```
+/// use serde_json::json;
+/// use jsonpath_rust::JsonPathInspect;
+///
+/// let json = json!({"store":{"book":[{"isbn":"0-553-21311-3"},{"title":"Moby Dick"}]}});
+///
+/// assert_eq!(json.exists("$..isbn"), Ok(true));
+/// assert_eq!(json.exists("$..author"), Ok(false));
+/// assert_eq!(json.count("$..book[*]"), Ok(2));
+/// ```
+pub trait JsonPathInspect {
+    /// checks if the query matches at least one element
+    fn exists(&self, query: &str) -> Result<bool, String>;
+    /// counts the elements matching the query
+    fn count(&self, query: &str) -> Result<usize, String>;
+}
+
+impl JsonPathInspect for Value {
+    fn exists(&self, query: &str) -> Result<bool, String> {
+        let p = JsonPathInst::from_str(query)?;
+        Ok(p.find_one(self).is_some())
+    }
+
+    fn count(&self, query: &str) -> Result<usize, String> {
+        let p = JsonPathInst::from_str(query)?;
+        Ok(p.find_slice(self).len())
+    }
+}
+
+/// The parsed path along with its compiled form that is reused by every search.
 #[derive(Clone, Debug)]
 pub struct JsonPathInst {
     inner: JsonPath,
+    compiled: Compiled,
+    /// the rewrites applied by [`JsonPathInst::optimize`]
+    rewrites: Vec<String>,
+    options: EvalOptions,
+}
+
+impl From<JsonPath> for JsonPathInst {
+    fn from(inner: JsonPath) -> Self {
+        JsonPathInst {
+            compiled: Compiled::new(&inner),
+            inner,
+            rewrites: vec![],
+            options: EvalOptions::default(),
+        }
+    }
+}
+
+/// The path is serialized as its query text.
+/// The path with the options differing from the default ones is serialized
+/// as `{"path": <query text>, "options": {..}}` listing the changed options.
+/// The options holding the code, that is the comparator, the cancellation token and the custom string length,
+/// can not be serialized and fail the serialization.
+impl Serialize for JsonPathInst {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.options == EvalOptions::default() {
+            return serializer.collect_str(self);
+        }
+        let options = self.options.to_json().map_err(serde::ser::Error::custom)?;
+        let mut m = serializer.serialize_struct("JsonPathInst", 2)?;
+        m.serialize_field("path", &self.to_string())?;
+        m.serialize_field("options", &options)?;
+        m.end()
+    }
+}
+
+/// The path is deserialized from its query text that is parsed during the deserialization,
+/// or from the query text along with the options as it is serialized.
+/// ```
+/// use jsonpath_rust::JsonPathInst;
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Deserialize)]
+/// struct Rule {
+///     path: JsonPathInst,
+/// }
+///
+/// let rule: Rule = serde_json::from_value(json!({"path": "$.orders[?(@.active)].id"})).unwrap();
+/// let data = json!({"orders":[{"id":1,"active":true},{"id":2}]});
+/// assert_eq!(jsonpath_rust::find(&rule.path, &data), json!([1]));
+/// ```
+impl<'de> Deserialize<'de> for JsonPathInst {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parse = |query: &str| JsonPathInst::from_str(query).map_err(serde::de::Error::custom);
+        match Value::deserialize(deserializer)? {
+            Value::String(query) => parse(&query),
+            Value::Object(mut fields) => {
+                let path = match fields.remove("path") {
+                    Some(Value::String(query)) => parse(&query)?,
+                    _ => return Err(serde::de::Error::missing_field("path")),
+                };
+                let options = match fields.remove("options") {
+                    Some(options) => {
+                        EvalOptions::from_json(&options).map_err(serde::de::Error::custom)?
+                    }
+                    None => EvalOptions::default(),
+                };
+                match fields.keys().next() {
+                    Some(key) => Err(serde::de::Error::unknown_field(key, &["path", "options"])),
+                    None => Ok(path.with_options(options)),
+                }
+            }
+            _ => Err(serde::de::Error::custom(
+                "expected the query text or the query along with its options",
+            )),
+        }
+    }
+}
+
+impl Display for JsonPathInst {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inner)
+    }
+}
+
+/// The paths are equal if they have the same canonical form and the same options.
+/// The rewrites applied by [`JsonPathInst::optimize`] are not a part of the identity
+/// since they do not change what the path finds.
+impl PartialEq for JsonPathInst {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonicalize() == other.canonicalize() && self.options == other.options
+    }
+}
+
+impl Eq for JsonPathInst {}
+
+impl Hash for JsonPathInst {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonicalize().hash(state);
+        self.options.hash(state)
+    }
 }
 
 impl FromStr for JsonPathInst {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(JsonPathInst {
-            inner: s.try_into()?,
-        })
+        let inner: JsonPath = s.try_into()?;
+        Ok(JsonPathInst::from(inner))
     }
 }
 
 impl JsonPathInst {
-    pub fn find_slice<'a>(&'a self, value: &'a Value) -> Vec<JsonPtr<'a, Value>> {
-        json_path_instance(&self.inner, value)
-            .find(JsonPathValue::from_root(value))
-            .into_iter()
-            .filter(|v| v.has_value())
-            .map(|v| match v {
-                JsonPathValue::Slice(v, _) => JsonPtr::Slice(v),
-                JsonPathValue::NewValue(v) => JsonPtr::NewValue(v),
-                JsonPathValue::NoValue => unreachable!("has_value was already checked"),
+    /// builds the evaluator for the given json reusing the compiled parts of the path
+    /// and following the rules of the given options
+    fn instance<'a, T: JsonLike, P: Track + 'a>(
+        &'a self,
+        value: &'a T,
+        options: &EvalOptions,
+    ) -> PathInstance<'a, T, P> {
+        compiled_instance(&self.inner, value, &self.ctx(options))
+    }
+
+    /// the prepared parts of the path along with the rules of the given options
+    pub(crate) fn ctx(&self, options: &EvalOptions) -> Ctx<'_> {
+        Ctx::new(&self.compiled, options.rules())
+    }
+
+    /// evaluates the path starting from the root with the options of the path
+    pub(crate) fn evaluate<'a, T: JsonLike, P: Track + 'a>(
+        &'a self,
+        value: &'a T,
+        root: JsonPathValue<'a, T, P>,
+    ) -> Vec<JsonPathValue<'a, T, P>> {
+        self.evaluate_with(value, root, &self.options)
+    }
+
+    /// evaluates the path starting from the root without tracking the paths of the found elements.
+    /// The paths are tracked anyway if the filters ask for them with `@property` or `@path`.
+    pub(crate) fn evaluate_untracked<'a, T: JsonLike>(
+        &'a self,
+        value: &'a T,
+    ) -> Vec<JsonPathValue<'a, T, ()>> {
+        if self.uses_location() {
+            self.evaluate(value, JsonPathValue::from_root(value))
+                .into_iter()
+                .map(JsonPathValue::untracked)
+                .collect()
+        } else {
+            self.evaluate(value, JsonPathValue::from_root_untracked(value))
+        }
+    }
+
+    /// checks if the filters of the path read the location of the element with `@property` or `@path`
+    fn uses_location(&self) -> bool {
+        struct Uses(bool);
+        impl Visitor for Uses {
+            fn visit_operand(&mut self, op: &Operand) {
+                match op {
+                    Operand::Property | Operand::Path => self.0 = true,
+                    _ => walk_operand(self, op),
+                }
+            }
+        }
+        let mut uses = Uses(false);
+        uses.visit_path(&self.inner);
+        uses.0
+    }
+
+    /// evaluates the path starting from the root ordering and deduplicating the found elements as the options tell
+    fn evaluate_with<'a, T: JsonLike, P: Track + 'a>(
+        &'a self,
+        value: &'a T,
+        root: JsonPathValue<'a, T, P>,
+        options: &EvalOptions,
+    ) -> Vec<JsonPathValue<'a, T, P>> {
+        let found = self.instance(value, options).find(root);
+        arrange(value, found, options)
+    }
+
+    /// evaluates the path like [`JsonPathInst::evaluate_with`] collecting the warnings of the strict mode
+    /// and telling if the elements have run out at the step selecting them, see [`Chain::find_selecting`]
+    fn evaluate_checked<'a, T: JsonLike, P: Track + 'a>(
+        &'a self,
+        value: &'a T,
+        root: JsonPathValue<'a, T, P>,
+        options: &EvalOptions,
+    ) -> Checked<'a, T, P> {
+        let warnings = Rc::new(RefCell::new(vec![]));
+        let rules = Rules {
+            warnings: Some(warnings.clone()),
+            ..options.rules()
+        };
+        let ctx = Ctx::new(&self.compiled, rules);
+        let (found, selecting) = match &self.inner {
+            JsonPath::Chain(chain) => Chain::from(chain, value, &ctx).find_selecting(root),
+            path => (compiled_instance(path, value, &ctx).find(root), false),
+        };
+        Checked {
+            found: arrange(value, found, options),
+            warnings: warnings.take(),
+            selecting,
+        }
+    }
+
+    /// the names of the parameters like `:max_price` in the order of their first occurrence
+    pub fn params(&self) -> Vec<String> {
+        struct Params(Vec<String>);
+        impl Visitor for Params {
+            fn visit_operand(&mut self, op: &Operand) {
+                match op {
+                    Operand::Param(name) if !self.0.contains(name) => self.0.push(name.clone()),
+                    _ => walk_operand(self, op),
+                }
+            }
+        }
+        let mut params = Params(vec![]);
+        params.visit_path(&self.inner);
+        params.0
+    }
+
+    /// replaces the parameters like `:max_price` in the filters with the given values.
+    /// The query is parsed once and bound for every evaluation
+    /// instead of formatting the values into the query text.
+    /// Returns an error naming the parameter that has no value.
+    /// The unbound parameters have no values like the absent fields,
+    /// [`JsonPathInst::query`] fails on them.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{find_values, JsonPathInst};
+    /// use serde_json::json;
+    /// use std::collections::HashMap;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"book":[
+    ///     {"title":"a","price":5,"category":"fiction"},
+    ///     {"title":"b","price":15,"category":"fiction"}
+    /// ]});
+    /// let path = JsonPathInst::from_str("$.book[?(@.price < :max_price && @.category == :cat)].title").unwrap();
+    /// assert_eq!(path.params(), vec!["max_price", "cat"]);
+    ///
+    /// let params = HashMap::from([("max_price", json!(10)), ("cat", json!("fiction"))]);
+    /// let bound = path.bind(&params).unwrap();
+    /// assert_eq!(find_values(&bound, &data), vec![&json!("a")]);
+    /// assert!(path.bind(&HashMap::new()).is_err());
+    /// ```
+    pub fn bind(&self, params: &HashMap<&str, Value>) -> Result<JsonPathInst, String> {
+        struct Bind<'p> {
+            params: &'p HashMap<&'p str, Value>,
+            missing: Option<String>,
+        }
+        impl VisitorMut for Bind<'_> {
+            fn visit_operand_mut(&mut self, op: &mut Operand) {
+                match op {
+                    Operand::Param(name) => match self.params.get(name.as_str()) {
+                        Some(v) => *op = Operand::Static(v.clone()),
+                        None => self.missing = Some(name.clone()),
+                    },
+                    _ => walk_operand_mut(self, op),
+                }
+            }
+        }
+        let mut inner = self.inner.clone();
+        let mut bind = Bind {
+            params,
+            missing: None,
+        };
+        bind.visit_path_mut(&mut inner);
+        if let Some(name) = bind.missing {
+            return Err(format!("the parameter `:{name}` is not bound"));
+        }
+        Ok(JsonPathInst {
+            compiled: Compiled::new(&inner),
+            inner,
+            rewrites: self.rewrites.clone(),
+            options: self.options.clone(),
+        })
+    }
+
+    /// replaces the paths like `$ctx.allowed_regions` in the filters with the values found in the context document,
+    /// the data of the request that does not belong to the queried document.
+    /// The singular path (see [`JsonPathInst::is_singular`]) gives the found value itself,
+    /// the other ones give the array of the found values.
+    /// The paths finding nothing in the context and the paths of the unbound context have no values like the absent fields.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{find_values, JsonPathInst};
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"servers":[{"name":"a","region":"eu"},{"name":"b","region":"us"}]});
+    /// let ctx = json!({"allowed_regions":["eu"]});
+    /// let path = JsonPathInst::from_str("$.servers[?(@.region in $ctx.allowed_regions)].name").unwrap();
+    ///
+    /// let bound = path.bind_context(&ctx);
+    /// assert_eq!(find_values(&bound, &data), vec![&json!("a")]);
+    /// assert!(find_values(&path, &data).is_empty());
+    /// ```
+    pub fn bind_context(&self, ctx: &Value) -> JsonPathInst {
+        struct BindContext<'c>(&'c Value);
+        impl VisitorMut for BindContext<'_> {
+            fn visit_operand_mut(&mut self, op: &mut Operand) {
+                let Operand::Context(jp) = op else {
+                    return walk_operand_mut(self, op);
+                };
+                let path = JsonPathInst::from(jp.as_ref().clone());
+                let mut found = path
+                    .find_slice(self.0)
+                    .into_iter()
+                    .map(|v| v.deref().clone());
+                let value = if path.is_singular() {
+                    found.next()
+                } else {
+                    Some(Value::Array(found.collect()))
+                };
+                if let Some(value) = value {
+                    *op = Operand::Static(value);
+                }
+            }
+        }
+        let mut inner = self.inner.clone();
+        BindContext(ctx).visit_path_mut(&mut inner);
+        JsonPathInst {
+            compiled: Compiled::new(&inner),
+            inner,
+            rewrites: self.rewrites.clone(),
+            options: self.options.clone(),
+        }
+    }
+
+    /// replaces all the options of the evaluation set before, see [`EvalOptions`]
+    pub fn with_options(mut self, options: EvalOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// the options of the evaluation set by the `with_*` methods
+    pub fn options(&self) -> &EvalOptions {
+        &self.options
+    }
+
+    /// parses the untrusted query checking the limits, see [`ParseLimits`].
+    /// The length and the nesting are checked before the parsing
+    /// since the parser goes down the nested brackets recursively.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{JsonPathInst, ParseLimits};
+    ///
+    /// let limits = ParseLimits {
+    ///     max_nesting: Some(4),
+    ///     ..ParseLimits::default()
+    /// };
+    /// assert!(JsonPathInst::from_str_with_limits("$.a[?(@.b[?(@.c)])]", &limits).is_ok());
+    /// let deep = format!("$.a{}", "[?(@.b".repeat(10) + &")]".repeat(10));
+    /// assert_eq!(
+    ///     JsonPathInst::from_str_with_limits(&deep, &limits).unwrap_err(),
+    ///     "the query is nested deeper than 4"
+    /// );
+    /// ```
+    pub fn from_str_with_limits(query: &str, limits: &ParseLimits) -> Result<Self, String> {
+        if let Some(max) = limits.max_length.filter(|max| query.len() > *max) {
+            return Err(format!("the query is longer than {max} bytes"));
+        }
+        if let Some(max) = limits.max_nesting.filter(|max| nesting(query) > *max) {
+            return Err(format!("the query is nested deeper than {max}"));
+        }
+        let path = JsonPathInst::from_str(query)?;
+        if let Some(max) = limits
+            .max_selectors
+            .filter(|max| selectors(&path.inner) > *max)
+        {
+            return Err(format!("the query has more than {max} selectors"));
+        }
+        if let Some(max) = limits.max_regex_len {
+            if let Some(re) = path.regexes().into_iter().find(|re| re.len() > max) {
+                return Err(format!("the regex `{re}` is longer than {max} bytes"));
+            }
+        }
+        Ok(path)
+    }
+
+    /// parses the query written in the given dialect.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{Dialect, JsonPathInst};
+    /// use serde_json::json;
+    ///
+    /// let path = JsonPathInst::from_str_with_dialect("{.items[*].metadata.name}", Dialect::Kubernetes).unwrap();
+    /// assert_eq!(path.canonicalize(), "$['items'][*]['metadata']['name']");
+    /// ```
+    pub fn from_str_with_dialect(query: &str, dialect: Dialect) -> Result<Self, String> {
+        match dialect {
+            Dialect::Standard => JsonPathInst::from_str(query),
+            Dialect::Kubernetes => {
+                let query = query.trim();
+                if query.starts_with('{') {
+                    K8sTemplate::from_str(query)?.into_query().ok_or_else(|| {
+                        format!(
+                            "the template `{query}` is not a single expression, see `K8sTemplate`"
+                        )
+                    })
+                } else {
+                    crate::k8s::k8s_query(query)
+                }
+            }
+            Dialect::Jayway => crate::jayway::jayway_query(query),
+            Dialect::Goessner => crate::goessner::goessner_query(query),
+        }
+    }
+
+    /// the constant patterns of `~=` in the filters and the patterns of `extract`
+    fn regexes(&self) -> Vec<String> {
+        struct Regexes(Vec<String>);
+        impl Visitor for Regexes {
+            fn visit_path(&mut self, path: &JsonPath) {
+                if let JsonPath::Fn(Function::Extract(re, _)) = path {
+                    self.0.push(re.clone());
+                }
+                walk_path(self, path)
+            }
+            fn visit_filter(&mut self, expr: &FilterExpression) {
+                if let FilterExpression::Atom(
+                    _,
+                    FilterSign::Regex,
+                    Operand::Static(Value::String(re)),
+                ) = expr
+                {
+                    self.0.push(re.clone());
+                }
+                walk_filter(self, expr)
+            }
+        }
+        let mut regexes = Regexes(vec![]);
+        regexes.visit_path(&self.inner);
+        regexes.0
+    }
+
+    /// builds the definite query from the json pointer like `/store/book/0/title` taking the segments as they are,
+    /// see [`JsonPathInst::from_pointer_escaped`] for the pointers escaping `~` and `/`.
+    /// The segments made of digits without the leading zeros are the indexes of the arrays,
+    /// the rest are the keys of the objects taken literally even if they contain `*`.
+    /// The empty pointer is the root.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    ///
+    /// let path = JsonPathInst::from_pointer("/store/book/0/title").unwrap();
+    /// assert_eq!(path.canonicalize(), "$['store']['book'][0]['title']");
+    /// ```
+    pub fn from_pointer(pointer: &str) -> Result<Self, String> {
+        Ok(JsonPathInst::from(
+            pointer_segments(pointer)?.fold(JsonPath::root(), pointer_step),
+        ))
+    }
+
+    /// builds the definite query from the [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) json pointer
+    /// unescaping `~1` and `~0` into `/` and `~` respectively, like the pointers given by [`JsonPathValue::to_pointer`].
+    /// The other `~` sequences are rejected.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    ///
+    /// let path = JsonPathInst::from_pointer_escaped("/a~1b/c~0d").unwrap();
+    /// assert_eq!(path.canonicalize(), "$['a/b']['c~d']");
+    /// assert!(JsonPathInst::from_pointer_escaped("/a~2").is_err());
+    /// ```
+    pub fn from_pointer_escaped(pointer: &str) -> Result<Self, String> {
+        pointer_segments(pointer)?
+            .try_fold(JsonPath::root(), |path, s| {
+                unescape_pointer(s).map(|s| pointer_step(path, &s))
             })
+            .map(JsonPathInst::from)
+    }
+
+    /// sets the maximum depth of the documents the descent `..` is allowed to go through.
+    /// [`JsonPathInst::query`] and [`find_with_options`] return [`JsonPathError::DepthLimitExceeded`]
+    /// for the deeper documents, the functions returning no error like [`find`] and [`find_slice`] do not check it.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.options.max_depth = Some(max_depth);
+        self
+    }
+
+    /// rewrites the expensive patterns of the query before the evaluation.
+    /// The comparisons of the constants in the filters are evaluated beforehand
+    /// and the wildcard descent followed by the filter `..*[?(...)]` turns into the descent filter `..[?(...)]`
+    /// visiting the elements of the arrays once instead of twice.
+    /// The latter yields every matched element once, ordered by their parents,
+    /// and applies the filter to the arrays nested in the objects as well.
+    /// The applied rewrites are listed by [`JsonPathInst::explain`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    /// # use std::str::FromStr;
+    ///
+    /// let path = JsonPathInst::from_str("$..*[?(@.isbn && 1 < 2)].title").unwrap().optimize();
+    /// assert_eq!(path.canonicalize(), "$..[?(@['isbn'])]['title']");
+    /// assert_eq!(path.explain().rewrites.len(), 2);
+    /// ```
+    pub fn optimize(self) -> Self {
+        let mut inner = self.inner;
+        let mut rewrites = self.rewrites;
+        rewrites.extend(optimize(&mut inner));
+        JsonPathInst {
+            compiled: Compiled::new(&inner),
+            inner,
+            rewrites,
+            options: self.options.clone(),
+        }
+    }
+
+    /// sets the limits of the evaluation.
+    /// [`JsonPathInst::query`] aborts the evaluation and returns [`JsonPathError::LimitExceeded`]
+    /// as soon as one of them is exceeded.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{EvalLimits, JsonPathError, JsonPathInst, Limit};
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"a":[[1,2,3],[4,5,6]]});
+    /// let path = JsonPathInst::from_str("$..*").unwrap().with_limits(EvalLimits {
+    ///     max_nodes: Some(5),
+    ///     ..EvalLimits::default()
+    /// });
+    /// assert_eq!(
+    ///     path.query(&data),
+    ///     Err(JsonPathError::LimitExceeded { limit: Limit::Nodes, max: 5 })
+    /// );
+    /// ```
+    pub fn with_limits(mut self, limits: EvalLimits) -> Self {
+        self.options.limits = limits;
+        self
+    }
+
+    /// yields every element of the json at most once, at the position of its first occurrence.
+    /// The combinations of the descent and the wildcard like `$..*.[?(@.isbn)]` reach the same elements
+    /// several times, since the filter is applied to the arrays and to their elements as well.
+    /// The elements are compared by their identity in the json, therefore the equal values
+    /// at the different positions are kept. The generated values (like length) are kept as they are.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{find_values, JsonPathInst};
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"books":[{"isbn":"1","title":"a"},{"title":"b"}],"other":{"isbn":"2","title":"a"}});
+    /// let path = JsonPathInst::from_str("$..*.[?(@.isbn)].title").unwrap();
+    /// assert_eq!(find_values(&path, &data).len(), 3);
+    ///
+    /// let path = path.with_distinct_nodes(true);
+    /// assert_eq!(find_values(&path, &data), vec![&json!("a"), &json!("a")]);
+    /// ```
+    pub fn with_distinct_nodes(mut self, distinct_nodes: bool) -> Self {
+        self.options.distinct_nodes = distinct_nodes;
+        self
+    }
+
+    /// sets the order of the found elements, see [`ResultOrder`].
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{find_values, JsonPathInst, ResultOrder};
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!([10, 11, 12, 13]);
+    /// let path = JsonPathInst::from_str("$[3,1]").unwrap();
+    /// assert_eq!(find_values(&path, &data), vec![&json!(13), &json!(11)]);
+    ///
+    /// let path = path.with_order(ResultOrder::Document);
+    /// assert_eq!(find_values(&path, &data), vec![&json!(11), &json!(13)]);
+    /// ```
+    pub fn with_order(mut self, order: ResultOrder) -> Self {
+        self.options.order = order;
+        self
+    }
+
+    /// evaluates the path like the original javascript implementation of Goessner:
+    /// the filters are applied to the fields of the objects as well as to the elements of the arrays,
+    /// the filters like `?(@.isbn)` check the truthiness of the field,
+    /// the comparisons convert the types like [`Coercion::JsLike`] and [`find`] gives `false` when nothing is found.
+    /// [`goessner::divergence`] tells if the results differ from the results of the library.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{find, JsonPathInst};
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"a":{"x":{"on":1},"y":{"on":0}}});
+    /// let path = JsonPathInst::from_str("$.a[?(@.on)]").unwrap();
+    /// assert_eq!(find(&path, &data), json!(null));
+    ///
+    /// let path = path.with_goessner(true);
+    /// assert_eq!(find(&path, &data), json!([{"on":1}]));
+    /// assert_eq!(find(&path, &json!({})), json!(false));
+    /// ```
+    pub fn with_goessner(mut self, goessner: bool) -> Self {
+        self.options.goessner = goessner;
+        self
+    }
+
+    pub fn find_slice<'a, T: JsonLike>(&'a self, value: &'a T) -> Vec<JsonPtr<'a, T>> {
+        self.evaluate_untracked(value)
+            .into_iter()
+            .filter_map(JsonPathValue::into_ptr)
+            .collect()
+    }
+
+    /// finds the first element matching the path.
+    /// The element is either a reference to the initial json or a new generated value (like length).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"store":{"book":[{"title":"Moby Dick"},{"title":"Sword of Honour"}]}});
+    /// let path = JsonPathInst::from_str("$.store.book[*].title").unwrap();
+    ///
+    /// assert_eq!(path.find_one(&data).as_deref(), Some(&json!("Moby Dick")));
+    /// ```
+    pub fn find_one<'a>(&'a self, value: &'a Value) -> Option<JsonPtr<'a, Value>> {
+        self.find_slice(value).into_iter().next()
+    }
+
+    /// finds the only element matching the path.
+    /// Returns an error if the path matches more than one element.
+    pub fn find_single<'a>(
+        &'a self,
+        value: &'a Value,
+    ) -> Result<Option<JsonPtr<'a, Value>>, String> {
+        let mut found = self.find_slice(value).into_iter();
+        match (found.next(), found.next()) {
+            (el, None) => Ok(el),
+            (Some(_), Some(_)) => Err(format!(
+                "the path {} matches {} elements instead of one",
+                self,
+                found.count() + 2
+            )),
+            (None, Some(_)) => unreachable!("the iterator is fused"),
+        }
+    }
+
+    /// finds the elements and reports the reason when there is nothing to return.
+    ///
+    /// - `Ok` with the empty vector means the path is applicable to the data but nothing matches,
+    ///   that is a filter or a slice of the path has selected nothing, whatever steps follow it.
+    /// - [`JsonPathError::Inapplicable`] means the path does not fit the structure of the data (like absent fields).
+    /// - [`JsonPathError::Evaluation`] means the path can not be evaluated (like an incorrect regex).
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::{JsonPathError, JsonPathInst, JsonPtr};
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"orders":[{"id":1,"active":true},{"id":2}]});
+    /// let path = JsonPathInst::from_str("$.orders[?(@.active)].id").unwrap();
+    /// let found = path.query(&data).unwrap();
+    /// assert_eq!(found[0].value, JsonPtr::Slice(&json!(1)));
+    /// assert_eq!(found[0].path.as_deref(), Some("$.['orders'][0].['id']"));
+    ///
+    /// let path = JsonPathInst::from_str("$.orders[?(@.id > 10)]").unwrap();
+    /// assert_eq!(path.query(&data), Ok(vec![]));
+    ///
+    /// let path = JsonPathInst::from_str("$.customers").unwrap();
+    /// assert_eq!(path.query(&data), Err(JsonPathError::Inapplicable));
+    /// ```
+    pub fn query<'a>(&'a self, value: &'a Value) -> Result<Vec<Match<'a>>, JsonPathError> {
+        self.query_with(value, &self.options)
+    }
+
+    fn query_with<'a>(
+        &'a self,
+        value: &'a Value,
+        options: &EvalOptions,
+    ) -> Result<Vec<Match<'a>>, JsonPathError> {
+        if let Some(err) = invalid_regex(&self.inner) {
+            return Err(JsonPathError::Evaluation(err));
+        }
+        if let Some(name) = self.params().first() {
+            return Err(JsonPathError::Evaluation(format!(
+                "the parameter `:{name}` is not bound"
+            )));
+        }
+        if let Some(max_depth) = options.max_depth {
+            if has_descent(&self.inner) && exceeds_depth(value, max_depth) {
+                return Err(JsonPathError::DepthLimitExceeded(max_depth));
+            }
+        }
+        let budget = Budget {
+            max_nodes: options.limits.max_nodes,
+            deadline: options.limits.timeout.map(|t| Instant::now() + t),
+            token: options.cancellation.clone(),
+        };
+        let (checked, stopped) = with_budget(budget, || {
+            self.evaluate_checked(value, Slice(value, PathNode::root()), options)
+        });
+        match (stopped, options.limits) {
+            (
+                Some(Stop::Nodes),
+                EvalLimits {
+                    max_nodes: Some(max),
+                    ..
+                },
+            ) => {
+                return Err(JsonPathError::LimitExceeded {
+                    limit: Limit::Nodes,
+                    max,
+                })
+            }
+            (
+                Some(Stop::Deadline),
+                EvalLimits {
+                    timeout: Some(t), ..
+                },
+            ) => return Err(JsonPathError::TimedOut(t)),
+            (Some(Stop::Cancelled), _) => return Err(JsonPathError::Cancelled),
+            _ => {}
+        }
+        if options.strict && !checked.warnings.is_empty() {
+            return Err(JsonPathError::Strict(checked.warnings));
+        }
+        let found = checked.found;
+        options.limits.check_output(&found)?;
+        if JsonPathValue::only_no_value(&found) {
+            return if checked.selecting {
+                Ok(vec![])
+            } else {
+                Err(JsonPathError::Inapplicable)
+            };
+        }
+        Ok(found.into_iter().filter_map(Match::from_found).collect())
+    }
+
+    /// returns the parsed path
+    pub fn path(&self) -> &JsonPath {
+        &self.inner
+    }
+
+    /// checks if the query yields at most one element,
+    /// that is it has no wildcards, descents, slices, unions or filters.
+    pub fn is_singular(&self) -> bool {
+        self.inner.is_singular()
+    }
+
+    /// returns the normalized text of the query.
+    /// The fields are written in the bracket notation with the single quotes escaping the quotes and `\` of the keys
+    /// and the filters have the same spacing, therefore the queries differing only in the writing
+    /// like `$.a.*[?(@.b>1)]` and `$['a'][*][?(@['b'] > 1)]` have the same canonical form.
+    /// The equality and the hash of [`JsonPathInst`] are based on the canonical form.
+    pub fn canonicalize(&self) -> String {
+        self.inner.to_string()
+    }
+
+    /// returns the evaluation plan listing the steps of the path
+    /// and marking the steps visiting all the children or descendants.
+    pub fn explain(&self) -> Plan {
+        Plan {
+            rewrites: self.rewrites.clone(),
+            ..Plan::new(&self.inner)
+        }
+    }
+
+    /// finds the elements in the strict mode.
+    /// Comparing a string to a number, indexing a scalar or matching a regex against a non-string
+    /// turn into the list of warnings instead of silently yielding no match.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"orders":[{"id":"1"},{"id":2}]});
+    /// let path = JsonPathInst::from_str("$.orders[?(@.id == 1)]").unwrap();
+    /// let warnings = path.find_strict(&data).unwrap_err();
+    /// assert_eq!(
+    ///     warnings[0].to_string(),
+    ///     "$.['orders'][0]: the operator `==` compares string with number"
+    /// );
+    /// ```
+    pub fn find_strict<'a>(
+        &'a self,
+        value: &'a Value,
+    ) -> Result<Vec<JsonPtr<'a, Value>>, Vec<EvalWarning>> {
+        let checked = self.evaluate_checked(value, JsonPathValue::from_root(value), &self.options);
+        if checked.warnings.is_empty() {
+            Ok(checked
+                .found
+                .into_iter()
+                .filter_map(JsonPathValue::into_ptr)
+                .collect())
+        } else {
+            Err(checked.warnings)
+        }
+    }
+
+    /// reports the type mismatches of the strict mode without failing the evaluation.
+    /// The mismatches are the ones the evaluation meets, so the right side of `&&` is not checked
+    /// when the left side is false, and the comparisons decided by the comparator or the coercion
+    /// of the options are not reported. The descent with the brackets and the operands of the filters
+    /// are not checked since they visit every element of the document.
+    pub fn warnings(&self, value: &Value) -> Vec<EvalWarning> {
+        self.evaluate_checked(value, JsonPathValue::from_root(value), &self.options)
+            .warnings
+    }
+
+    /// finds the elements and deserializes every one of them into the given type.
+    /// The elements are deserialized right from the references, so the data is not cloned.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct Order {
+    ///     id: u64,
+    /// }
+    ///
+    /// let data = json!({"orders":[{"id":1,"active":true},{"id":2},{"id":3,"active":true}]});
+    /// let path = JsonPathInst::from_str("$.orders[?(@.active)]").unwrap();
+    /// let orders: Vec<Order> = path.find_as(&data).unwrap();
+    ///
+    /// assert_eq!(orders, vec![Order { id: 1 }, Order { id: 3 }]);
+    /// ```
+    pub fn find_as<T: DeserializeOwned>(&self, value: &Value) -> Result<Vec<T>, serde_json::Error> {
+        self.find_slice(value)
+            .iter()
+            .map(|v| T::deserialize(v.deref()))
             .collect()
     }
+
+    /// finds the elements and deserializes them into the given type as a whole.
+    /// The single element found by the singular query (see [`JsonPathInst::is_singular`]) is deserialized itself
+    /// and the absent one is deserialized from `null`, therefore it suits [`Option`].
+    /// The elements found by the other queries are deserialized as an array.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"name":"a","tags":[{"v":"x"},{"v":"y"}]});
+    /// let name: String = JsonPathInst::from_str("$.name").unwrap().find_typed(&data).unwrap();
+    /// let tags: Vec<String> = JsonPathInst::from_str("$.tags[*].v").unwrap().find_typed(&data).unwrap();
+    /// let age: Option<u8> = JsonPathInst::from_str("$.age").unwrap().find_typed(&data).unwrap();
+    ///
+    /// assert_eq!((name.as_str(), tags, age), ("a", vec!["x".to_string(), "y".to_string()], None));
+    /// ```
+    pub fn find_typed<T: DeserializeOwned>(&self, value: &Value) -> Result<T, serde_json::Error> {
+        let found = self.find_slice(value);
+        if !self.is_singular() {
+            let found = found.into_iter().map(|v| v.deref().clone()).collect();
+            return T::deserialize(Value::Array(found));
+        }
+        match found.first() {
+            Some(v) => T::deserialize(v.deref()),
+            None => T::deserialize(&Value::Null)
+                .map_err(|_| serde::de::Error::custom("nothing is found by the singular query")),
+        }
+    }
+}
+
+/// translates the path into the equivalent jq filter,
+/// see [`parser::jq`] for the details of the translation.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{to_jq, JsonPathInst};
+/// # use std::str::FromStr;
+///
+/// let path = JsonPathInst::from_str("$..book[0].title").unwrap();
+/// assert_eq!(to_jq(&path).unwrap(), r#".. | objects | select(has("book")) | .book[0].title"#);
+/// ```
+pub fn to_jq(path: &JsonPathInst) -> Result<String, UnsupportedFeature> {
+    path_to_jq(&path.inner)
+}
+
+/// checks the path for the parts that can never match anything or are degenerate,
+/// like the empty slices `[1:1]`, the contradictions `[?(@.x > 1 && @.x < 0)]`
+/// or the filters comparing a path with itself.
+pub fn lint(path: &JsonPathInst) -> Vec<LintWarning> {
+    lint_path(&path.inner)
+}
+
+/// checks the path like [`lint`] and additionally flags the indexes
+/// applied to the elements of the sample that are not arrays.
+pub fn lint_with_sample(path: &JsonPathInst, sample: &Value) -> Vec<LintWarning> {
+    let mut warnings = lint(path);
+    warnings.extend(
+        path.warnings(sample)
+            .into_iter()
+            .filter_map(|w| match w.kind {
+                EvalWarningKind::IndexNonArray { index, .. } => Some(LintWarning::NotAnArray {
+                    path: w.path,
+                    index,
+                }),
+                _ => None,
+            }),
+    );
+    warnings
+}
+
+/// The reasons why [`JsonPathInst::query`] has nothing to return
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum JsonPathError {
+    #[error("the path is not applicable to the structure of the data")]
+    Inapplicable,
+    #[error("the path can not be evaluated: {0}")]
+    Evaluation(String),
+    #[error("the document is deeper than the limit {0}")]
+    DepthLimitExceeded(usize),
+    #[error("the evaluation exceeded the limit of {limit}: {max}")]
+    LimitExceeded { limit: Limit, max: usize },
+    #[error("the field `{field}` can not be extracted: {reason}")]
+    Extraction { field: String, reason: String },
+    #[error("the strict evaluation has found the problems: {}", join_warnings(.0))]
+    Strict(Vec<EvalWarning>),
+    #[error("the evaluation has not finished in {0:?}")]
+    TimedOut(Duration),
+    #[error("the evaluation has been cancelled")]
+    Cancelled,
+}
+
+fn join_warnings(warnings: &[EvalWarning]) -> String {
+    warnings
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// The elements found by the evaluation along with what it has reported
+struct Checked<'a, T, P = String> {
+    found: Vec<JsonPathValue<'a, T, P>>,
+    /// the type mismatches of the strict mode
+    warnings: Vec<EvalWarning>,
+    /// nothing is found since the step selecting the elements, like the filter, has selected none of them
+    selecting: bool,
+}
+
+/// checks if the path or the paths inside its filters contain the descent `..`
+fn has_descent(path: &JsonPath) -> bool {
+    struct Descent(bool);
+    impl Visitor for Descent {
+        fn visit_path(&mut self, path: &JsonPath) {
+            match path {
+                JsonPath::Descent(_) | JsonPath::DescentW | JsonPath::DescentIndex(_) => {
+                    self.0 = true
+                }
+                _ => walk_path(self, path),
+            }
+        }
+    }
+    let mut descent = Descent(false);
+    descent.visit_path(path);
+    descent.0
+}
+
+/// finds the first regex in the filters that can not be compiled
+fn invalid_regex(path: &JsonPath) -> Option<String> {
+    fn in_filter(expr: &FilterExpression) -> Option<String> {
+        match expr {
+            FilterExpression::Atom(left, sign, right) => {
+                if let (FilterSign::Regex, Operand::Static(Value::String(re))) = (sign, right) {
+                    if let Some(e) = pattern_error(re) {
+                        return Some(e);
+                    }
+                }
+                in_operand(left).or_else(|| in_operand(right))
+            }
+            FilterExpression::And(l, r)
+            | FilterExpression::Or(l, r)
+            | FilterExpression::Xor(l, r) => in_filter(l).or_else(|| in_filter(r)),
+            FilterExpression::Not(exp) => in_filter(exp),
+        }
+    }
+    fn in_operand(op: &Operand) -> Option<String> {
+        match op {
+            Operand::Static(_)
+            | Operand::Param(_)
+            | Operand::Index
+            | Operand::Property
+            | Operand::Path => None,
+            Operand::Dynamic(jp) | Operand::Context(jp) => invalid_regex(jp),
+        }
+    }
+    fn in_index(index: &JsonPathIndex) -> Option<String> {
+        match index {
+            JsonPathIndex::Filter(expr) => in_filter(expr),
+            JsonPathIndex::Union(elems) => elems.iter().find_map(invalid_regex),
+            _ => None,
+        }
+    }
+    match path {
+        JsonPath::Chain(elems) => elems.iter().find_map(invalid_regex),
+        JsonPath::Current(tail) => invalid_regex(tail),
+        JsonPath::Index(index) | JsonPath::DescentIndex(index) => in_index(index),
+        JsonPath::Fn(Function::Extract(re, _)) => pattern_error(re),
+        _ => None,
+    }
 }
 
 /// Json paths may return either pointers to the original json or new data. This custom pointer type allows us to handle both cases.
 /// Unlike JsonPathValue, this type does not represent NoValue to allow the implementation of Deref.
+#[derive(Debug, PartialEq, Clone)]
 pub enum JsonPtr<'a, Data> {
     /// The slice of the initial json data
     Slice(&'a Data),
@@ -211,6 +1313,25 @@ impl JsonPathQuery for Value {
     }
 }
 
+/// Borrows the document, so it does not need to be cloned for every query.
+/// The found elements are still cloned into the resulting array.
+/// ```
+/// use serde_json::json;
+/// use jsonpath_rust::JsonPathQuery;
+///
+/// let json = json!({"first":{"second":[{"active":1},{"passive":1}]}});
+/// let v = (&json).path("$.first.second[?(@.active)]").unwrap();
+/// assert_eq!(v, json!([{"active":1}]));
+/// // the document is still available
+/// assert_eq!((&json).path("$.first.second[1]").unwrap(), json!([{"passive":1}]));
+/// ```
+impl JsonPathQuery for &Value {
+    fn path(self, query: &str) -> Result<Value, String> {
+        let p = JsonPathInst::from_str(query)?;
+        Ok(find(&p, self))
+    }
+}
+
 /*
 impl<T> JsonPathQuery for T
     where T: Deref<Target=Value> {
@@ -282,65 +1403,486 @@ macro_rules! jp_v {
 /// Represents the path of the found json data
 type JsPathStr = String;
 
-pub(crate) fn jsp_idx(prefix: &str, idx: usize) -> String {
-    format!("{}[{}]", prefix, idx)
-}
-pub(crate) fn jsp_obj(prefix: &str, key: &str) -> String {
-    format!("{}.['{}']", prefix, key)
+/// The typed element of the path to the found json data
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathSegment {
+    /// The field of an object
+    Key(String),
+    /// The element of an array
+    Index(usize),
 }
 
-/// A result of json path
-/// Can be either a slice of initial data or a new generated value(like length of array)
-#[derive(Debug, PartialEq, Clone)]
-pub enum JsonPathValue<'a, Data> {
-    /// The slice of the initial json data
-    Slice(&'a Data, JsPathStr),
-    /// The new data that was generated from the input data (like length operator)
-    NewValue(Data),
-    /// The absent value that indicates the input data is not matched to the given json path (like the absent fields)
-    NoValue,
+/// The lowest common ancestor of the elements at the paths, that is the longest path all of them start with.
+/// The path of the only element is the element itself, nothing is common to no paths at all.
+///
+/// ```
+/// use jsonpath_rust::{common_ancestor, PathSegment};
+///
+/// let key = |k: &str| PathSegment::Key(k.to_string());
+/// let paths = vec![
+///     vec![key("a.b"), PathSegment::Index(0), key("c")],
+///     vec![key("a.b"), PathSegment::Index(0)],
+///     vec![key("a.b"), PathSegment::Index(0), key("d"), PathSegment::Index(1)],
+/// ];
+/// assert_eq!(common_ancestor(&paths), Some(&paths[1][..]));
+/// assert_eq!(common_ancestor(&[]), None);
+/// ```
+pub fn common_ancestor(paths: &[Vec<PathSegment>]) -> Option<&[PathSegment]> {
+    let (first, rest) = paths.split_first()?;
+    let len = rest.iter().fold(first.len(), |len, path| {
+        first[..len]
+            .iter()
+            .zip(path)
+            .take_while(|(l, r)| l == r)
+            .count()
+    });
+    Some(&first[..len])
 }
 
-impl<'a, Data: Clone + Debug + Default> JsonPathValue<'a, Data> {
-    /// Transforms given value into data either by moving value out or by cloning
-    pub fn to_data(self) -> Data {
-        match self {
-            Slice(r, _) => r.clone(),
-            NewValue(val) => val,
-            NoValue => Data::default(),
-        }
-    }
-
-    /// Transforms given value into path
-    pub fn to_path(self) -> Option<JsPathStr> {
-        match self {
-            Slice(_, path) => Some(path),
-            _ => None,
+/// Groups the paths by their ancestors at the given depth, where the root is at the depth 0
+/// and its children are at the depth 1, giving the positions of the paths in every group.
+/// The groups come in the order of their first paths and the paths not deeper than the depth are skipped,
+/// like [`find_grouped_at`] does with the found elements.
+///
+/// ```
+/// use jsonpath_rust::{group_at_depth, PathSegment};
+///
+/// let key = |k: &str| PathSegment::Key(k.to_string());
+/// let paths = vec![
+///     vec![key("a"), PathSegment::Index(0), key("x")],
+///     vec![key("b")],
+///     vec![key("a"), PathSegment::Index(1), key("z")],
+///     vec![key("a"), PathSegment::Index(0), key("y")],
+/// ];
+/// let groups = group_at_depth(&paths, 2);
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].0, [PathSegment::Key("a".to_string()), PathSegment::Index(0)]);
+/// assert_eq!(groups[0].1, vec![0, 3]);
+/// assert_eq!(groups[1].1, vec![2]);
+/// ```
+pub fn group_at_depth(
+    paths: &[Vec<PathSegment>],
+    depth: usize,
+) -> Vec<(&[PathSegment], Vec<usize>)> {
+    let mut groups: Vec<(&[PathSegment], Vec<usize>)> = vec![];
+    let mut positions: HashMap<&[PathSegment], usize> = HashMap::new();
+    for (i, path) in paths.iter().enumerate().filter(|(_, p)| p.len() > depth) {
+        let ancestor = &path[..depth];
+        match positions.get(ancestor) {
+            Some(g) => groups[*g].1.push(i),
+            None => {
+                positions.insert(ancestor, groups.len());
+                groups.push((ancestor, vec![i]));
+            }
         }
     }
-
-    pub fn from_root(data: &'a Data) -> Self {
-        Slice(data, String::from("$"))
-    }
-    pub fn new_slice(data: &'a Data, path: String) -> Self {
-        Slice(data, path.to_string())
-    }
+    groups
 }
 
-impl<'a, Data> JsonPathValue<'a, Data> {
-    fn only_no_value(input: &[JsonPathValue<'a, Data>]) -> bool {
-        !input.is_empty() && input.iter().filter(|v| v.has_value()).count() == 0
-    }
+/// The path from the ancestor to the element, `None` if the ancestor is not the ancestor of the element.
+/// The relative path can be formatted by [`PathStyle::format`] as the path starting from the ancestor.
+///
+/// ```
+/// use jsonpath_rust::{relative_path, PathSegment, PathStyle};
+///
+/// let ancestor = vec![PathSegment::Key("a.b".to_string())];
+/// let path = vec![
+///     PathSegment::Key("a.b".to_string()),
+///     PathSegment::Index(0),
+///     PathSegment::Key("c".to_string()),
+/// ];
+/// let relative = relative_path(&ancestor, &path).unwrap();
+/// assert_eq!(PathStyle::Normalized.format(relative), "$[0]['c']");
+/// assert_eq!(relative_path(&path, &ancestor), None);
+/// ```
+pub fn relative_path<'a>(
+    ancestor: &[PathSegment],
+    path: &'a [PathSegment],
+) -> Option<&'a [PathSegment]> {
+    path.strip_prefix(ancestor)
+}
 
-    fn map_vec(data: Vec<(&'a Data, JsPathStr)>) -> Vec<JsonPathValue<'a, Data>> {
-        data.into_iter()
-            .map(|(data, pref)| Slice(data, pref))
-            .collect()
+/// Walks to the element at the path found by the library like `$.['store'].['book'][0]`
+/// without parsing and evaluating the path as a query.
+/// The paths in [`PathStyle::Normalized`] and [`PathStyle::Bracket`] are accepted as well.
+///
+/// The key of the default style ends at any `']` that leads to the existing element,
+/// therefore the keys containing `']` are resolved too.
+/// ```
+/// use jsonpath_rust::{find_as_path, resolve, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"store":{"book":[{"title":"Moby Dick"}]}});
+/// let path = JsonPathInst::from_str("$..title").unwrap();
+/// let found = find_as_path(&path, &data);
+///
+/// assert_eq!(resolve(&data, found[0].as_str().unwrap()), Some(&json!("Moby Dick")));
+/// assert_eq!(resolve(&data, "$['store']['book'][0]"), Some(&json!({"title":"Moby Dick"})));
+/// assert_eq!(resolve(&data, "$.['store'].['book'][1]"), None);
+/// ```
+pub fn resolve<'a, T: JsonLike>(json: &'a T, path: &str) -> Option<&'a T> {
+    resolve_steps(json, path.strip_prefix('$')?)
+}
+
+/// walks the steps of the path trying every possible end of the key of the default style
+fn resolve_steps<'a, T: JsonLike>(json: &'a T, path: &str) -> Option<&'a T> {
+    let step = |json: &'a T, segment: PathSegment, rest: &str| {
+        let next = match &segment {
+            PathSegment::Key(k) => json.get(k)?,
+            PathSegment::Index(i) => json.as_array()?.get(*i)?,
+        };
+        resolve_steps(next, rest)
+    };
+    if path.is_empty() {
+        Some(json)
+    } else if let Some(tail) = path.strip_prefix(".['") {
+        tail.match_indices("']").find_map(|(end, _)| {
+            step(
+                json,
+                PathSegment::Key(tail[..end].to_string()),
+                &tail[end + 2..],
+            )
+        })
+    } else if let Some(tail) = path.strip_prefix("['") {
+        let (key, rest) = normalized_key_end(tail)?;
+        step(json, PathSegment::Key(key), rest.strip_prefix(']')?)
+    } else if let Some(tail) = path.strip_prefix('[').filter(|t| t.starts_with('"')) {
+        let mut keys = serde_json::Deserializer::from_str(tail).into_iter::<String>();
+        let key = keys.next()?.ok()?;
+        step(
+            json,
+            PathSegment::Key(key),
+            tail[keys.byte_offset()..].strip_prefix(']')?,
+        )
+    } else if let Some(tail) = path.strip_prefix('[') {
+        let end = tail.find(']')?;
+        let idx = tail[..end].parse().ok()?;
+        step(json, PathSegment::Index(idx), &tail[end + 1..])
+    } else {
+        None
+    }
+}
+
+/// unescapes the key of the normalized path up to the closing `'` returning the rest of the path
+fn normalized_key_end(path: &str) -> Option<(String, &str)> {
+    let mut key = String::new();
+    let mut chars = path.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' => return Some((key, &path[i + 1..])),
+            '\\' => match chars.next()?.1 {
+                'b' => key.push('\u{8}'),
+                'f' => key.push('\u{c}'),
+                'n' => key.push('\n'),
+                'r' => key.push('\r'),
+                't' => key.push('\t'),
+                'u' => {
+                    let code: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                    key.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+                }
+                c => key.push(c),
+            },
+            c => key.push(c),
+        }
+    }
+    None
+}
+
+/// Transforms the segments into the [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) json pointer
+/// escaping `~` and `/` as `~0` and `~1` respectively.
+pub fn to_pointer(segments: &[PathSegment]) -> String {
+    segments
+        .iter()
+        .map(|s| match s {
+            PathSegment::Key(k) => format!("/{}", k.replace('~', "~0").replace('/', "~1")),
+            PathSegment::Index(i) => format!("/{}", i),
+        })
+        .collect()
+}
+
+/// The format of the paths of the found elements,
+/// see [`find_as_path_with_style`], [`find_map_with_style`] and [`find_records_with_style`].
+///
+/// ```
+/// use jsonpath_rust::{PathSegment, PathStyle};
+///
+/// let segments = [PathSegment::Key("it's".to_string()), PathSegment::Index(0)];
+/// assert_eq!(PathStyle::DottedLegacy.format(&segments), "$.['it's'][0]");
+/// assert_eq!(PathStyle::Normalized.format(&segments), r"$['it\'s'][0]");
+/// assert_eq!(PathStyle::Bracket.format(&segments), r#"$["it's"][0]"#);
+/// assert_eq!(PathStyle::JsonPointer.format(&segments), "/it's/0");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PathStyle {
+    /// the normalized path of [RFC 9535](https://datatracker.ietf.org/doc/html/rfc9535#name-normalized-paths)
+    /// like `$['store']['book'][0]` escaping `'` and `\` in the keys
+    Normalized,
+    /// the path the library has always returned like `$.['store'].['book'][0]` leaving the keys as they are
+    #[default]
+    DottedLegacy,
+    /// the path with the keys as json strings like `$["store"]["book"][0]`
+    Bracket,
+    /// the [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) json pointer like `/store/book/0`
+    JsonPointer,
+}
+
+impl PathStyle {
+    /// formats the segments of the path in the style
+    pub fn format(&self, segments: &[PathSegment]) -> String {
+        if *self == PathStyle::JsonPointer {
+            return to_pointer(segments);
+        }
+        segments.iter().fold(String::from("$"), |mut path, s| {
+            match (self, s) {
+                (_, PathSegment::Index(i)) => path.push_str(&format!("[{i}]")),
+                (PathStyle::Normalized, PathSegment::Key(k)) => {
+                    path.push_str(&format!("['{}']", normalized_key(k)))
+                }
+                (PathStyle::Bracket, PathSegment::Key(k)) => {
+                    path.push_str(&format!("[{}]", Value::from(k.as_str())))
+                }
+                (_, PathSegment::Key(k)) => path.push_str(&format!(".['{k}']")),
+            }
+            path
+        })
+    }
+}
+
+/// escapes the key of the normalized path
+fn normalized_key(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for c in key.chars() {
+        match c {
+            '\'' => escaped.push_str("\\'"),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c < ' ' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn pointer_segments(pointer: &str) -> Result<impl Iterator<Item = &str>, String> {
+    if pointer.is_empty() || pointer.starts_with('/') {
+        Ok(pointer.split('/').skip(1))
+    } else {
+        Err(format!(
+            "the json pointer `{pointer}` does not start with `/`"
+        ))
+    }
+}
+
+/// appends the segment of the json pointer to the path as the index or the key
+fn pointer_step(path: JsonPath, segment: &str) -> JsonPath {
+    let is_index = segment == "0"
+        || (!segment.is_empty()
+            && !segment.starts_with('0')
+            && segment.bytes().all(|b| b.is_ascii_digit()));
+    match segment.parse() {
+        Ok(idx) if is_index => path.index(idx),
+        _ => path.field(segment),
+    }
+}
+
+fn unescape_pointer(segment: &str) -> Result<String, String> {
+    let mut key = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some('0') => key.push('~'),
+                Some('1') => key.push('/'),
+                _ => {
+                    return Err(format!(
+                        "the segment `{segment}` has the incorrect escape `~`"
+                    ))
+                }
+            },
+            _ => key.push(c),
+        }
+    }
+    Ok(key)
+}
+
+/// sorts the elements of the json by their position in the depth-first walk of the document
+pub(crate) fn document_order<'a, T: JsonLike, P>(
+    root: &'a T,
+    found: Vec<JsonPathValue<'a, T, P>>,
+) -> Vec<JsonPathValue<'a, T, P>> {
+    // the explicit stack keeps the deep documents from overflowing the call stack
+    let mut positions = HashMap::new();
+    let mut stack = vec![root];
+    while let Some(v) = stack.pop() {
+        positions.insert(v as *const T, positions.len());
+        if let Some(elems) = v.as_array() {
+            stack.extend(elems.iter().rev());
+        } else if let Some(fields) = v.entries() {
+            stack.extend(fields.rev().map(|(_, el)| el));
+        }
+    }
+
+    let mut last = 0;
+    let mut keyed: Vec<(usize, JsonPathValue<'a, T, P>)> = found
+        .into_iter()
+        .map(|v| {
+            if let Slice(el, _) = &v {
+                last = positions.get(&(*el as *const T)).copied().unwrap_or(last);
+            }
+            (last, v)
+        })
+        .collect();
+    keyed.sort_by_key(|(pos, _)| *pos);
+    keyed.into_iter().map(|(_, v)| v).collect()
+}
+
+/// keeps the first occurrence of every element of the json, the generated values are kept as they are
+pub(crate) fn distinct_nodes<T, P>(
+    found: Vec<JsonPathValue<'_, T, P>>,
+) -> Vec<JsonPathValue<'_, T, P>> {
+    let mut seen = HashSet::new();
+    found
+        .into_iter()
+        .filter(|v| match v {
+            Slice(v, _) => seen.insert(*v as *const T),
+            NewValue(_) | NoValue => true,
+        })
+        .collect()
+}
+
+/// Transforms the segments into the path of the found data like `$.['store'].['book'][0]`
+pub(crate) fn to_path(segments: &[PathSegment]) -> String {
+    segments.iter().fold("$".to_string(), |p, s| match s {
+        PathSegment::Key(k) => jsp_obj(&p, k),
+        PathSegment::Index(i) => jsp_idx(&p, *i),
+    })
+}
+
+pub(crate) fn jsp_idx(prefix: &str, idx: usize) -> String {
+    format!("{}[{}]", prefix, idx)
+}
+pub(crate) fn jsp_obj(prefix: &str, key: &str) -> String {
+    format!("{}.['{}']", prefix, key)
+}
+
+/// A result of json path
+/// Can be either a slice of initial data or a new generated value(like length of array)
+///
+/// The slice keeps its location as the [`Track`] tells, the rendered path by default.
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonPathValue<'a, Data, P = JsPathStr> {
+    /// The slice of the initial json data
+    Slice(&'a Data, P),
+    /// The new data that was generated from the input data (like length operator)
+    NewValue(Data),
+    /// The absent value that indicates the input data is not matched to the given json path (like the absent fields)
+    NoValue,
+}
+
+impl<'a, Data: Clone + Debug + Default, P> JsonPathValue<'a, Data, P> {
+    /// Transforms given value into data either by moving value out or by cloning
+    pub fn to_data(self) -> Data {
+        match self {
+            Slice(r, _) => r.clone(),
+            NewValue(val) => val,
+            NoValue => Data::default(),
+        }
+    }
+
+    /// Transforms given value into path
+    pub fn to_path(self) -> Option<P> {
+        match self {
+            Slice(_, path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, Data: Clone + Debug + Default> JsonPathValue<'a, Data> {
+    pub fn from_root(data: &'a Data) -> Self {
+        Slice(data, String::from("$"))
+    }
+    pub fn new_slice(data: &'a Data, path: String) -> Self {
+        Slice(data, path.to_string())
+    }
+}
+
+impl<'a, Data, P: Track> JsonPathValue<'a, Data, P> {
+    /// Transforms the path of the slice into the json pointer like `/store/book/0/title`.
+    /// Returns `None` if the value has been generated or is absent.
+    ///
+    /// The pointer can be resolved back with [`Value::pointer`].
+    /// ```
+    /// use jsonpath_rust::{find_slice, JsonPathInst};
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"store":{"book":[{"title":"Moby Dick"}]}});
+    /// let path = JsonPathInst::from_str("$.store.book[0].title").unwrap();
+    /// let found = find_slice(&path, &data).remove(0);
+    /// let pointer = found.to_pointer().unwrap();
+    ///
+    /// assert_eq!(pointer, "/store/book/0/title");
+    /// assert_eq!(data.pointer(&pointer), Some(&json!("Moby Dick")));
+    /// ```
+    pub fn to_pointer(&self) -> Option<String> {
+        self.to_segments().map(|s| to_pointer(&s))
+    }
+
+    /// The typed segments of the path of the slice, see [`Track::to_segments`].
+    /// Returns `None` if the value has been generated or is absent or its path is not tracked.
+    pub fn to_segments(&self) -> Option<Vec<PathSegment>> {
+        match self {
+            Slice(_, path) => path.to_segments(),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, Data> JsonPathValue<'a, Data, ()> {
+    /// the root without the path, the paths of the found elements are not tracked at all
+    pub fn from_root_untracked(data: &'a Data) -> Self {
+        Slice(data, ())
+    }
+}
+
+impl<'a, Data, P> JsonPathValue<'a, Data, P> {
+    fn only_no_value(input: &[JsonPathValue<'a, Data, P>]) -> bool {
+        !input.is_empty() && input.iter().filter(|v| v.has_value()).count() == 0
+    }
+
+    /// the same element without its location
+    fn untracked(self) -> JsonPathValue<'a, Data, ()> {
+        match self {
+            Slice(v, _) => Slice(v, ()),
+            NewValue(v) => NewValue(v),
+            NoValue => NoValue,
+        }
+    }
+
+    /// the found element without its path, `None` if it is absent
+    fn into_ptr(self) -> Option<JsonPtr<'a, Data>> {
+        match self {
+            Slice(v, _) => Some(JsonPtr::Slice(v)),
+            NewValue(v) => Some(JsonPtr::NewValue(v)),
+            NoValue => None,
+        }
+    }
+
+    fn map_vec(data: Vec<(&'a Data, P)>) -> Vec<JsonPathValue<'a, Data, P>> {
+        data.into_iter()
+            .map(|(data, pref)| Slice(data, pref))
+            .collect()
     }
 
-    fn map_slice<F>(self, mapper: F) -> Vec<JsonPathValue<'a, Data>>
+    fn map_slice<F>(self, mapper: F) -> Vec<JsonPathValue<'a, Data, P>>
     where
-        F: FnOnce(&'a Data, JsPathStr) -> Vec<(&'a Data, JsPathStr)>,
+        F: FnOnce(&'a Data, P) -> Vec<(&'a Data, P)>,
     {
         match self {
             Slice(r, pref) => mapper(r, pref)
@@ -353,9 +1895,9 @@ impl<'a, Data> JsonPathValue<'a, Data> {
         }
     }
 
-    fn flat_map_slice<F>(self, mapper: F) -> Vec<JsonPathValue<'a, Data>>
+    fn flat_map_slice<F>(self, mapper: F) -> Vec<JsonPathValue<'a, Data, P>>
     where
-        F: FnOnce(&'a Data, JsPathStr) -> Vec<JsonPathValue<'a, Data>>,
+        F: FnOnce(&'a Data, P) -> Vec<JsonPathValue<'a, Data, P>>,
     {
         match self {
             Slice(r, pref) => mapper(r, pref),
@@ -367,7 +1909,7 @@ impl<'a, Data> JsonPathValue<'a, Data> {
         !matches!(self, NoValue)
     }
 
-    pub fn vec_as_data(input: Vec<JsonPathValue<'a, Data>>) -> Vec<&'a Data> {
+    pub fn vec_as_data(input: Vec<JsonPathValue<'a, Data, P>>) -> Vec<&'a Data> {
         input
             .into_iter()
             .filter_map(|v| match v {
@@ -376,7 +1918,7 @@ impl<'a, Data> JsonPathValue<'a, Data> {
             })
             .collect()
     }
-    pub fn vec_as_pair(input: Vec<JsonPathValue<'a, Data>>) -> Vec<(&'a Data, JsPathStr)> {
+    pub fn vec_as_pair(input: Vec<JsonPathValue<'a, Data, P>>) -> Vec<(&'a Data, P)> {
         input
             .into_iter()
             .filter_map(|v| match v {
@@ -419,8 +1961,7 @@ impl<'a, Data> JsonPathValue<'a, Data> {
 /// );
 /// ```
 pub fn find_slice<'a>(path: &'a JsonPathInst, json: &'a Value) -> Vec<JsonPathValue<'a, Value>> {
-    let instance = json_path_instance(&path.inner, json);
-    let res = instance.find(JsonPathValue::from_root(json));
+    let res = path.evaluate(json, JsonPathValue::from_root(json));
     let has_v: Vec<JsonPathValue<'_, Value>> = res.into_iter().filter(|v| v.has_value()).collect();
 
     if has_v.is_empty() {
@@ -430,10 +1971,112 @@ pub fn find_slice<'a>(path: &'a JsonPathInst, json: &'a Value) -> Vec<JsonPathVa
     }
 }
 
+/// finds the elements like [`find_slice`] evaluating the path relative to the element found earlier,
+/// the paths of the found elements start with the path of that element.
+/// The path starts from the element either with `@` or with `$`, so `$` in the filters refers to the element as well.
+/// The generated element (like length) has no path to start with, therefore nothing is found in it.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_from, find_slice, JsonPathInst, JsonPathValue};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"orders":[{"id":1,"lines":[{"sku":"a"}]},{"id":2,"lines":[{"sku":"b"},{"sku":"c"}]}]});
+/// let records = JsonPathInst::from_str("$.orders[?(@.id > 1)]").unwrap();
+/// let fields = JsonPathInst::from_str("$.lines[*].sku").unwrap();
+///
+/// let paths: Vec<String> = find_slice(&records, &data)
+///     .into_iter()
+///     .flat_map(|record| find_from(&fields, record))
+///     .filter_map(JsonPathValue::to_path)
+///     .collect();
+///
+/// assert_eq!(
+///     paths,
+///     vec![
+///         "$.['orders'][1].['lines'][0].['sku']",
+///         "$.['orders'][1].['lines'][1].['sku']"
+///     ]
+/// );
+/// ```
+pub fn find_from<'a>(
+    path: &'a JsonPathInst,
+    node: JsonPathValue<'a, Value>,
+) -> Vec<JsonPathValue<'a, Value>> {
+    match node {
+        Slice(data, prefix) => find_slice(path, data)
+            .into_iter()
+            .map(|v| match v {
+                Slice(v, p) => Slice(v, format!("{prefix}{}", p.strip_prefix('$').unwrap_or(&p))),
+                other => other,
+            })
+            .collect(),
+        NewValue(_) | NoValue => vec![NoValue],
+    }
+}
+
+/// finds the references to the elements of the initial json without tracking their paths.
+/// It is faster than [`find_slice`] since the paths are not built at all.
+/// The values generated by the path (like length) are not references, therefore they are skipped,
+/// [`JsonPathInst::find_slice`] returns them as well.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_values, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"first":{"second":[{"active":1},{"passive":1}]}});
+/// let path = JsonPathInst::from_str("$.first.second[?(@.active)]").unwrap();
+///
+/// assert_eq!(find_values(&path, &data), vec![&json!({"active":1})]);
+/// ```
+pub fn find_values<'a, T: JsonLike>(path: &'a JsonPathInst, json: &'a T) -> Vec<&'a T> {
+    path.evaluate_untracked(json)
+        .into_iter()
+        .filter_map(|v| match v {
+            Slice(v, _) => Some(v),
+            NewValue(_) | NoValue => None,
+        })
+        .collect()
+}
+
+/// finds the references to the elements of the initial json along with their locations.
+/// The found elements share the locations of their common ancestors
+/// instead of copying the whole path for every one of them, like [`find_slice`] does,
+/// and the paths are rendered only when they are asked for.
+/// The values generated by the path (like length) have no location, therefore they are skipped.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_nodes, JsonPathInst, PathSegment};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"items":[{"id":1},{"id":2}]});
+/// let path = JsonPathInst::from_str("$.items[*].id").unwrap();
+/// let found = find_nodes(&path, &data);
+///
+/// assert_eq!(found[1].0, &json!(2));
+/// assert_eq!(found[1].1.to_string(), "$.['items'][1].['id']");
+/// assert_eq!(found[1].1.segment(), Some(&PathSegment::Key("id".to_string())));
+/// ```
+pub fn find_nodes<'a, T: JsonLike>(path: &'a JsonPathInst, json: &'a T) -> Vec<(&'a T, PathNode)> {
+    path.evaluate(json, Slice(json, PathNode::root()))
+        .into_iter()
+        .filter_map(|v| match v {
+            Slice(v, node) => Some((v, node)),
+            NewValue(_) | NoValue => None,
+        })
+        .collect()
+}
+
 /// finds a slice of data and wrap it with Value::Array by cloning the data.
 /// Returns either an array of elements or Json::Null if the match is incorrect.
 ///
-/// In case, if there is no match `find` will return `json!(null)`.
+/// In case, if there is no match `find` will return `json!(null)`,
+/// or `json!(false)` for the path following Goessner, see [`JsonPathInst::with_goessner`].
 ///
 /// ## Example
 /// ```rust
@@ -451,7 +2094,11 @@ pub fn find(path: &JsonPathInst, json: &Value) -> Value {
     let slice = find_slice(path, json);
     if !slice.is_empty() {
         if JsonPathValue::only_no_value(&slice) {
-            Value::Null
+            if path.options.goessner {
+                Value::Bool(false)
+            } else {
+                Value::Null
+            }
         } else {
             Value::Array(
                 slice
@@ -466,6 +2113,24 @@ pub fn find(path: &JsonPathInst, json: &Value) -> Value {
     }
 }
 
+/// finds the elements like [`find`] with the paths like `$ctx.allowed_regions` in the filters
+/// referring to the context document, see [`JsonPathInst::bind_context`].
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_ctx, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"docs":[{"id":1,"owner":"ann"},{"id":2,"owner":"bob"}]});
+/// let path = JsonPathInst::from_str("$.docs[?(@.owner == $ctx.user)].id").unwrap();
+///
+/// assert_eq!(find_with_ctx(&path, &data, &json!({"user":"bob"})), json!([2]));
+/// ```
+pub fn find_with_ctx(path: &JsonPathInst, json: &Value, ctx: &Value) -> Value {
+    find(&path.bind_context(ctx), json)
+}
+
 /// finds a path describing the value, instead of the value itself.
 /// If the values has been obtained by moving the data out of the initial json the path is absent.
 ///
@@ -486,23 +2151,122 @@ pub fn find(path: &JsonPathInst, json: &Value) -> Value {
 /// assert_eq!(slice_of_data, Value::Array(vec![Value::String(expected_path)]));
 /// ```
 pub fn find_as_path(path: &JsonPathInst, json: &Value) -> Value {
+    find_as_path_with_style(path, json, PathStyle::DottedLegacy)
+}
+
+/// finds the paths like [`find_as_path`] in the given style.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_as_path_with_style, JsonPathInst, PathStyle};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"first":{"second":[{"active":1},{"passive":1}]}});
+/// let path = JsonPathInst::from_str("$.first.second[?(@.active)]").unwrap();
+///
+/// assert_eq!(
+///     find_as_path_with_style(&path, &data, PathStyle::Normalized),
+///     json!(["$['first']['second'][0]"])
+/// );
+/// assert_eq!(
+///     find_as_path_with_style(&path, &data, PathStyle::JsonPointer),
+///     json!(["/first/second/0"])
+/// );
+/// ```
+pub fn find_as_path_with_style(path: &JsonPathInst, json: &Value, style: PathStyle) -> Value {
     Value::Array(
-        find_slice(path, json)
+        find_nodes(path, json)
             .into_iter()
-            .flat_map(|v| v.to_path())
-            .map(|v| v.into())
+            .map(|(_, node)| style.format(&node.segments()).into())
             .collect(),
     )
 }
 
+/// The comparison of the elements of the json with the needle in [`locate_with`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Lookup {
+    /// the arrays and the objects equal to the needle as a whole are found as well as the scalars
+    #[default]
+    Deep,
+    /// only the strings, the numbers, the booleans and the nulls are compared with the needle
+    ScalarOnly,
+}
+
+/// finds the paths of all the elements of the json equal to the needle in the depth-first order,
+/// the parent before its children.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::locate;
+/// use serde_json::json;
+///
+/// let data = json!({"users":[{"id":"u1"},{"id":"u2","friends":["u1"]}]});
+///
+/// assert_eq!(
+///     locate(&data, &json!("u1")),
+///     vec!["$.['users'][0].['id']", "$.['users'][1].['friends'][0]"]
+/// );
+/// assert_eq!(locate(&data, &json!({"id":"u1"})), vec!["$.['users'][0]"]);
+/// ```
+pub fn locate(json: &Value, needle: &Value) -> Vec<JsPathStr> {
+    locate_with(json, needle, Lookup::Deep)
+}
+
+/// finds the paths of all the elements of the json equal to the needle like [`locate`]
+/// comparing the elements as the lookup tells.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{locate_with, Lookup};
+/// use serde_json::json;
+///
+/// let data = json!({"a":[1],"b":[1]});
+/// assert_eq!(locate_with(&data, &json!([1]), Lookup::Deep).len(), 2);
+/// assert!(locate_with(&data, &json!([1]), Lookup::ScalarOnly).is_empty());
+/// ```
+pub fn locate_with(json: &Value, needle: &Value, lookup: Lookup) -> Vec<JsPathStr> {
+    // the explicit stack keeps the deep documents from overflowing the call stack,
+    // the paths share their prefixes and are rendered only for the found elements
+    let mut out = vec![];
+    let mut stack = vec![(json, PathNode::root())];
+    while let Some((v, path)) = stack.pop() {
+        let comparable = lookup == Lookup::Deep || !(v.is_array() || v.is_object());
+        if comparable && v == needle {
+            out.push(path.to_string());
+        }
+        match v {
+            Value::Array(elems) => stack.extend(
+                elems
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(i, el)| (el, path.index(i))),
+            ),
+            Value::Object(fields) => {
+                stack.extend(fields.iter().rev().map(|(k, el)| (el, path.key(k))))
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::JsonPathQuery;
+    use crate::parser::model::JsonPath;
     use crate::JsonPathValue::{NoValue, Slice};
-    use crate::{jp_v, JsonPathInst, JsonPathValue};
+    use crate::{
+        find, find_nodes, find_slice, find_values, lint_with_sample, Coercion, EvalLimits,
+        EvalOptions, JsonPathError, JsonPathInst, JsonPathValue, JsonPtr, Limit, LintWarning,
+        Match, PathNode, PathSegment, PathStyle, ResultOrder, StringComparator, StringLength,
+    };
+    use crate::{JsonPathInspect, JsonPathQuery, Track};
     use serde_json::{json, Value};
     use std::ops::Deref;
     use std::str::FromStr;
+    use std::sync::Arc;
+    use std::time::Duration;
 
     fn test(json: &str, path: &str, expected: Vec<JsonPathValue<Value>>) {
         let json: Value = match serde_json::from_str(json) {
@@ -517,6 +2281,31 @@ mod tests {
         assert_eq!(super::find_slice(&path, &json), expected)
     }
 
+    /// the leaf nested into `depth` arrays, serde_json drops the deep values recursively
+    /// so the nesting is taken apart one level at a time
+    pub(crate) struct Nested(pub(crate) Value);
+
+    impl Nested {
+        pub(crate) fn new(leaf: Value, depth: usize) -> Self {
+            Nested((0..depth).fold(leaf, |value, _| Value::Array(vec![value])))
+        }
+    }
+
+    impl Drop for Nested {
+        fn drop(&mut self) {
+            let mut stack = vec![self.0.take()];
+            while let Some(mut value) = stack.pop() {
+                match &mut value {
+                    Value::Array(elems) => stack.append(elems),
+                    Value::Object(fields) => {
+                        stack.extend(std::mem::take(fields).into_iter().map(|(_, v)| v))
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
     fn template_json<'a>() -> &'a str {
         r#" {"store": { "book": [
              {
@@ -671,6 +2460,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn distinct_nodes_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let path = JsonPathInst::from_str("$..*.[?(@.isbn)].title")
+            .expect("the path is correct")
+            .with_distinct_nodes(true);
+        let found = crate::find_map(&path, &json);
+        let paths: Vec<_> = found.iter().map(|m| m.path.as_deref()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Some("$.['store'].['book'][2].['title']"),
+                Some("$.['store'].['book'][3].['title']")
+            ]
+        );
+        assert_eq!(path.find_slice(&json).len(), 2);
+        assert_eq!(path.query(&json).map(|m| m.len()), Ok(2));
+    }
+
+    #[test]
+    fn descent_filter_test() {
+        let bicycle = json!({"color": "red", "price": 19.95});
+        let book1 = json!({"category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99});
+        let book3 = json!({"category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99});
+        test(
+            template_json(),
+            "$..[?(@.price > 10)]",
+            jp_v![
+                &bicycle;"$.['store'].['bicycle']",
+                &book1;"$.['store'].['book'][1]",
+                &book3;"$.['store'].['book'][3]",],
+        );
+        let order = json!({"ref":[1,2,3],"id":1,"filled":true});
+        let ref0 = json!(1);
+        let ref1 = json!(4);
+        let ref2 = json!(7);
+        test(
+            template_json(),
+            "$.orders..[0]",
+            jp_v![
+                &order;"$.['orders'][0]",
+                &ref0;"$.['orders'][0].['ref'][0]",
+                &ref1;"$.['orders'][1].['ref'][0]",
+                &ref2;"$.['orders'][2].['ref'][0]",],
+        );
+    }
+
     #[test]
     fn field_test() {
         let value = json!({"active":1});
@@ -682,15 +2518,109 @@ mod tests {
     }
 
     #[test]
-    fn index_index_test() {
-        let value = json!("0-553-21311-3");
+    fn glob_field_test() {
+        let json = r#"{"metrics":{"cpu_user":1,"cpu_sys":2,"mem_used":3},"error_a":"a","errors":"b","ok":"c"}"#;
+        let sys = json!(2);
+        let user = json!(1);
         test(
-            template_json(),
-            "$..book[2].isbn",
-            jp_v![&value;"$.['store'].['book'][2].['isbn']",],
+            json,
+            "$.metrics.cpu_*",
+            jp_v![
+                &sys;"$.['metrics'].['cpu_sys']",
+                &user;"$.['metrics'].['cpu_user']",],
         );
-    }
-
+        let a = json!("a");
+        let b = json!("b");
+        test(
+            json,
+            "$.error*",
+            jp_v![&a;"$.['error_a']", &b;"$.['errors']",],
+        );
+        let used = json!(3);
+        test(
+            json,
+            "$..*_used",
+            jp_v![&used;"$.['metrics'].['mem_used']",],
+        );
+        test(json, "$.metrics.disk_*", vec![NoValue]);
+
+        // the quoted keys are the globs as well, `\*` stands for the star itself
+        let json = r#"{"a*b":1,"axb":2,"c":{"a*b":3},"error 1":4,"error_2":5}"#;
+        let (one, two, three) = (json!(1), json!(2), json!(3));
+        let (four, five) = (json!(4), json!(5));
+        test(
+            json,
+            "$['error*']",
+            jp_v![&four;"$.['error 1']", &five;"$.['error_2']",],
+        );
+        test(json, r"$['a\*b']", jp_v![&one;"$.['a*b']",]);
+        test(json, "$['a*b']", jp_v![&one;"$.['a*b']", &two;"$.['axb']",]);
+        test(json, "$.a*b", jp_v![&one;"$.['a*b']", &two;"$.['axb']",]);
+        test(
+            json,
+            r"$..['a\*b']",
+            jp_v![&one;"$.['a*b']", &three;"$.['c'].['a*b']",],
+        );
+        test(json, "$..['error *']", jp_v![&four;"$.['error 1']",]);
+        test(
+            json,
+            "$['axb','a*b']",
+            jp_v![&two;"$.['axb']", &one;"$.['a*b']",],
+        );
+        assert!(JsonPathInst::from_str(r"$['a\*b']").unwrap().is_singular());
+        assert!(!JsonPathInst::from_str("$['a*b']").unwrap().is_singular());
+        let canonical = |q: &str| JsonPathInst::from_str(q).unwrap().canonicalize();
+        assert_eq!(canonical("$.a*b"), "$.a*b");
+        assert_eq!(canonical("$['a*b']"), "$.a*b");
+        assert_eq!(canonical(r"$['a\*b']"), r"$['a\*b']");
+        assert_eq!(canonical("$..['error *']"), "$..['error *']");
+        assert_eq!(canonical(r"$['it\'s\**']"), r"$['it\'s\**']");
+        for query in [
+            r"$['a\*b']",
+            "$['error *']",
+            r"$..['\\*\**']",
+            r"$['a\*','b*']",
+        ] {
+            let path = JsonPathInst::from_str(query).unwrap();
+            assert_eq!(
+                JsonPathInst::from_str(&path.to_string()),
+                Ok(path),
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn slice_str_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let v = json
+            .clone()
+            .path("$.store.book[2].isbn[0:5]")
+            .expect("the path is correct");
+        assert_eq!(v, json!(["0-553"]));
+
+        let v = json
+            .path("$.store.book[*].title[:4]")
+            .expect("the path is correct");
+        assert_eq!(v, json!(["Sayi", "Swor", "Moby", "The "]));
+
+        let json = json!({"s":"abc"});
+        let v = json.clone().path("$.s[5:]").expect("the path is correct");
+        assert_eq!(v, json!([""]));
+        let v = json.path("$.s[1:1]").expect("the path is correct");
+        assert_eq!(v, json!([""]));
+    }
+
+    #[test]
+    fn index_index_test() {
+        let value = json!("0-553-21311-3");
+        test(
+            template_json(),
+            "$..book[2].isbn",
+            jp_v![&value;"$.['store'].['book'][2].['isbn']",],
+        );
+    }
+
     #[test]
     fn index_unit_index_test() {
         let value = json!("0-553-21311-3");
@@ -724,6 +2654,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn index_union_mixed_test() {
+        let js0 = json!(0);
+        let js2 = json!(2);
+        let js3 = json!(3);
+        test(
+            r#"{"array":[0,1,2,3,4]}"#,
+            "$.array[0, 'abc', 2:4]",
+            jp_v![
+                &js0;"$.['array'][0]",
+                &js2;"$.['array'][2]",
+                &js3;"$.['array'][3]",],
+        );
+        let js1 = json!("Sword of Honour");
+        let js2 = json!(12.99);
+        test(
+            template_json(),
+            "$.store.book[1]['title', 0, 'price']",
+            jp_v![
+                &js1;"$.['store'].['book'][1].['title']",
+                &js2;"$.['store'].['book'][1].['price']",],
+        );
+        let js1 = json!(1);
+        let js4 = json!(4);
+        test(
+            r#"{"array":[0,1,2,3,4]}"#,
+            "$.array[-1,'a']",
+            jp_v![&js4;"$.['array'][4]",],
+        );
+        test(
+            r#"{"array":[0,1,2,3,4]}"#,
+            "$.array[-2,-6,1]",
+            jp_v![&js3;"$.['array'][3]", &js1;"$.['array'][1]",],
+        );
+        test(r#"{"a":1}"#, "$[-1,'a']", jp_v![&js1;"$.['a']",]);
+    }
+
+    #[test]
+    fn type_selector_test() {
+        let js1 = json!("red");
+        test(
+            template_json(),
+            "$.store.bicycle.*@string()",
+            jp_v![&js1;"$.['store'].['bicycle'].['color']",],
+        );
+        let js1 = json!(10);
+        test(
+            template_json(),
+            "$.*@integer()",
+            jp_v![&js1;"$.['expensive']",],
+        );
+        let js1 = json!({"ref":[1,2,3], "id":1, "filled": true});
+        let js2 = json!({"ref":[4,5,6], "id":2, "filled": false});
+        test(
+            template_json(),
+            "$.orders[?(@boolean(@.filled))]",
+            jp_v![
+                &js1;"$.['orders'][0]",
+                &js2;"$.['orders'][1]",],
+        );
+        test(template_json(), "$.array@object()", vec![NoValue]);
+    }
+
     #[test]
     fn index_slice_test() {
         let i0 = "$.['array'][0]";
@@ -772,6 +2765,7 @@ mod tests {
         test(template_json(), "$.array[-2:-1]", jp_v![&j8;&i8,]);
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn index_filter_test() {
         let moby = json!("Moby Dick");
@@ -906,6 +2900,309 @@ mod tests {
         );
     }
 
+    #[test]
+    fn path_style_test() {
+        let json = json!({"a/b":[1,{"c'\\\n":2}]});
+        let path = JsonPathInst::from_str("$..*").expect("the path is correct");
+        let paths = |style| crate::find_as_path_with_style(&path, &json, style);
+        assert_eq!(
+            paths(PathStyle::DottedLegacy),
+            crate::find_as_path(&path, &json)
+        );
+        assert_eq!(
+            paths(PathStyle::Normalized),
+            json!([
+                "$['a/b']",
+                "$['a/b'][0]",
+                "$['a/b'][1]",
+                r"$['a/b'][1]['c\'\\\n']"
+            ])
+        );
+        assert_eq!(
+            paths(PathStyle::Bracket),
+            json!([
+                r#"$["a/b"]"#,
+                r#"$["a/b"][0]"#,
+                r#"$["a/b"][1]"#,
+                r#"$["a/b"][1]["c'\\\n"]"#
+            ])
+        );
+        assert_eq!(
+            paths(PathStyle::JsonPointer),
+            json!(["/a~1b", "/a~1b/0", "/a~1b/1", "/a~1b/1/c'\\\n"])
+        );
+
+        let found = crate::find_map_with_style(&path, &json, PathStyle::JsonPointer);
+        assert_eq!(found[3].path.as_deref(), Some("/a~1b/1/c'\\\n"));
+        let records = crate::find_records_with_style(&path, &json, PathStyle::Normalized);
+        assert_eq!(records[1].path, "$['a/b'][0]");
+        assert_eq!(records[1].pointer, "/a~1b/0");
+
+        let length = JsonPathInst::from_str("$.['a/b'].length()").expect("the path is correct");
+        let found = crate::find_map_with_style(&length, &json, PathStyle::Normalized);
+        assert_eq!(found[0].path, None);
+    }
+
+    #[test]
+    fn resolve_test() {
+        let json = json!({"a/b":[1,{"c'\\\n":2}], "x'].['y": {"z": 3}});
+        let path = JsonPathInst::from_str("$..*").expect("the path is correct");
+        let values = crate::find_values(&path, &json);
+        for style in [
+            PathStyle::DottedLegacy,
+            PathStyle::Normalized,
+            PathStyle::Bracket,
+        ] {
+            let paths = crate::find_as_path_with_style(&path, &json, style);
+            let resolved: Vec<&Value> = paths
+                .as_array()
+                .expect("the paths")
+                .iter()
+                .filter_map(|p| crate::resolve(&json, p.as_str()?))
+                .collect();
+            assert_eq!(resolved, values, "{style:?}");
+        }
+        assert_eq!(
+            crate::resolve(&json, r"$['x\'].[\'y']['z']"),
+            Some(&json!(3))
+        );
+        assert_eq!(
+            crate::resolve(&json, "$.['x'].['y'].['z']"),
+            Some(&json!(3))
+        );
+        assert_eq!(crate::resolve(&json, "$"), Some(&json));
+        assert_eq!(crate::resolve(&json, "$['a/b'][2]"), None);
+        assert_eq!(crate::resolve(&json, "$['a/b'][-1]"), None);
+        assert_eq!(crate::resolve(&json, "$['a/b'"), None);
+        assert_eq!(crate::resolve(&json, "$.a"), None);
+        assert_eq!(crate::resolve(&json, "/a~1b"), None);
+    }
+
+    #[test]
+    fn find_from_test() {
+        let json = json!({"a":[{"b":{"c":1}},{"b":{"c":2,"d":[3,4]}}],"c":0});
+        let records = JsonPathInst::from_str("$.a[*].b").expect("the path is correct");
+        let from = |q: &str| -> Vec<(Value, Option<String>)> {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find_slice(&records, &json)
+                .into_iter()
+                .flat_map(|r| crate::find_from(&path, r))
+                .filter(|v| v.has_value())
+                .map(|v| match v {
+                    Slice(v, p) => (v.clone(), Some(p)),
+                    other => (other.to_data(), None),
+                })
+                .collect()
+        };
+        let found = |v: Value, p: &str| (v, Some(p.to_string()));
+        assert_eq!(
+            from("$.c"),
+            vec![
+                found(json!(1), "$.['a'][0].['b'].['c']"),
+                found(json!(2), "$.['a'][1].['b'].['c']")
+            ]
+        );
+        assert_eq!(
+            from("@.d[?(@ > $.c)]"),
+            vec![
+                found(json!(3), "$.['a'][1].['b'].['d'][0]"),
+                found(json!(4), "$.['a'][1].['b'].['d'][1]")
+            ]
+        );
+        assert_eq!(
+            from("$"),
+            vec![
+                found(json!({"c":1}), "$.['a'][0].['b']"),
+                found(json!({"c":2,"d":[3,4]}), "$.['a'][1].['b']")
+            ]
+        );
+        assert_eq!(from("$.d.length()"), vec![(json!(2), None)]);
+
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        let path = JsonPathInst::from_str("$").expect("the path is correct");
+        for found in find_slice(&length, &json) {
+            assert_eq!(crate::find_from(&path, found), vec![NoValue]);
+        }
+    }
+
+    #[test]
+    fn ancestor_test() {
+        let json = json!({"a.b":[{"c":{"d":1}},{"c":{"d":2,"e":3}}],"c":{"d":4}});
+        let path = JsonPathInst::from_str("$..d").expect("the path is correct");
+        let paths: Vec<Vec<PathSegment>> = crate::find_map(&path, &json)
+            .iter()
+            .filter_map(Match::to_segments)
+            .collect();
+        let key = |k: &str| PathSegment::Key(k.to_string());
+        assert_eq!(crate::common_ancestor(&paths), Some(&[][..]));
+        assert_eq!(crate::common_ancestor(&paths[..2]), Some(&[key("a.b")][..]));
+        assert_eq!(crate::common_ancestor(&paths[1..2]), Some(&paths[1][..]));
+
+        let groups = crate::group_at_depth(&paths, 2);
+        let ancestors: Vec<String> = groups
+            .iter()
+            .map(|(a, _)| PathStyle::Normalized.format(a))
+            .collect();
+        // the path `$['c']['d']` is not deeper than the ancestors
+        assert_eq!(ancestors, vec!["$['a.b'][0]", "$['a.b'][1]"]);
+        assert_eq!(groups[0].1, vec![0]);
+        assert_eq!(crate::group_at_depth(&paths, 4), vec![]);
+        assert_eq!(crate::group_at_depth(&paths, 0).len(), 1);
+
+        let relative = crate::relative_path(&paths[0][..1], &paths[0]);
+        assert_eq!(
+            relative.map(|r| PathStyle::Normalized.format(r)),
+            Some("$[0]['c']['d']".to_string())
+        );
+        assert_eq!(crate::relative_path(&paths[0], &paths[0]), Some(&[][..]));
+        assert_eq!(crate::relative_path(&paths[0], &paths[1]), None);
+    }
+
+    #[test]
+    fn dialect_test() {
+        let json = json!({"items":[{"metadata":{"name":"a"}},{"metadata":{"name":"b"}}]});
+        let k8s =
+            |q: &str| crate::JsonPathInst::from_str_with_dialect(q, crate::Dialect::Kubernetes);
+        for q in [
+            "{.items[*].metadata.name}",
+            " { .items[*].metadata.name } ",
+            ".items[*].metadata.name",
+            "items[*].metadata.name",
+            "$.items[*].metadata.name",
+        ] {
+            let path = k8s(q).expect("the path is correct");
+            assert_eq!(find(&path, &json), json!(["a", "b"]), "{q}");
+        }
+        assert!(k8s("{.items}{.kind}").is_err());
+        assert!(k8s("{range .items[*]}{.a}{end}").is_err());
+        assert!(k8s("name: {.kind}").is_err());
+        assert!(
+            crate::JsonPathInst::from_str_with_dialect("{.items}", crate::Dialect::Standard)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn locate_test() {
+        let json = json!({"a":[1,{"b":1}],"c":{"b":1},"d":1.0});
+        assert_eq!(
+            crate::locate(&json, &json!(1)),
+            vec!["$.['a'][0]", "$.['a'][1].['b']", "$.['c'].['b']"]
+        );
+        assert_eq!(
+            crate::locate(&json, &json!({"b":1})),
+            vec!["$.['a'][1]", "$.['c']"]
+        );
+        assert_eq!(crate::locate(&json, &json), vec!["$"]);
+        assert!(crate::locate_with(&json, &json, crate::Lookup::ScalarOnly).is_empty());
+        assert!(crate::locate(&json, &json!("x")).is_empty());
+    }
+
+    #[test]
+    fn deep_walk_test() {
+        let nested = Nested::new(json!({"x": 1}), 50_000);
+        let json = &nested.0;
+        assert_eq!(
+            crate::locate(json, &json!(1)),
+            vec![format!("${}.['x']", "[0]".repeat(50_000))]
+        );
+        let path = JsonPathInst::from_str("$..x")
+            .expect("the path is correct")
+            .with_order(crate::ResultOrder::Document);
+        assert_eq!(find_values(&path, json), vec![&json!(1)]);
+    }
+
+    #[test]
+    fn path_set_operand_test() {
+        let json = json!({
+            "valid": [{"id":"a"},{"id":"b"}],
+            "items": [{"refs":["a"],"ref":"b"},{"refs":["a","x"],"ref":"x"}]
+        });
+        let found = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find_values(&path, &json)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            found("$.items[?(@.refs subsetOf $.valid[*].id)].ref"),
+            vec![json!("b")]
+        );
+        assert_eq!(
+            found("$.items[?(@.refs noneOf $..id)].ref"),
+            Vec::<Value>::new()
+        );
+        assert_eq!(
+            found("$.items[?(@.ref in $.valid[*].id)].ref"),
+            vec![json!("b")]
+        );
+        assert_eq!(found("$.items[?(@.ref nin $..id)].ref"), vec![json!("x")]);
+        assert_eq!(found("$.items[?(@.ref in @.refs)].ref"), vec![json!("x")]);
+    }
+
+    #[test]
+    fn bind_test() {
+        let json = json!({"a":[{"r":"eu","n":1},{"r":"us","n":2},{"r":"eu","n":3}]});
+        let path = JsonPathInst::from_str("$.a[?(@.r == :region && @.n > :min || @.n == :min)].n")
+            .expect("the path is correct");
+        assert_eq!(path.params(), vec!["region", "min"]);
+        assert_eq!(
+            path.query(&json),
+            Err(JsonPathError::Evaluation(
+                "the parameter `:region` is not bound".to_string()
+            ))
+        );
+        assert!(find_values(&path, &json).is_empty());
+
+        let params = std::collections::HashMap::from([("region", json!("eu")), ("min", json!(1))]);
+        let bound = path.bind(&params).expect("all the parameters are bound");
+        assert!(bound.params().is_empty());
+        assert_eq!(find_values(&bound, &json), vec![&json!(1), &json!(3)]);
+        assert_eq!(
+            bound.canonicalize(),
+            "$['a'][?(@['r'] == 'eu' && @['n'] > 1 || @['n'] == 1)]['n']"
+        );
+        assert_eq!(
+            path.canonicalize(),
+            "$['a'][?(@['r'] == :region && @['n'] > :min || @['n'] == :min)]['n']"
+        );
+        assert_eq!(
+            path.bind(&std::collections::HashMap::from([("min", json!(1))])),
+            Err("the parameter `:region` is not bound".to_string())
+        );
+    }
+
+    #[test]
+    fn bind_context_test() {
+        let json = json!({"a":[{"r":"eu","n":1},{"r":"us","n":2}]});
+        let ctx = json!({"user":{"regions":["eu","asia"],"min":2},"all":[{"r":"us"}]});
+        let find = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            crate::find_with_ctx(&path, &json, &ctx)
+        };
+        assert_eq!(find("$.a[?(@.r in $ctx.user.regions)].n"), json!([1]));
+        assert_eq!(find("$.a[?(@.n >= $ctx.user.min)].n"), json!([2]));
+        assert_eq!(find("$.a[?(@.r in $ctx.all[*].r)].n"), json!([2]));
+        assert_eq!(
+            find("$.a[?(@.r == $ctx.absent)].n"),
+            find("$.a[?(@.r == 'absent')].n")
+        );
+        assert_eq!(find("$.a[?($ctx.user.min)].n"), json!([1, 2]));
+
+        let path =
+            JsonPathInst::from_str("$.a[?(@.n >= $ctx.user.min)]").expect("the path is correct");
+        assert_eq!(
+            path.canonicalize(),
+            "$['a'][?(@['n'] >= $ctx['user']['min'])]"
+        );
+        assert_eq!(
+            path.bind_context(&ctx).canonicalize(),
+            "$['a'][?(@['n'] >= 2)]"
+        );
+        assert!(JsonPathInst::from_str("$.a[?(@.n >= $ctxuser)]").is_err());
+    }
+
     #[test]
     fn query_test() {
         let json: Box<Value> = serde_json::from_str(template_json()).expect("to get json");
@@ -922,6 +3219,542 @@ mod tests {
         assert_eq!(path, &json!(["Sayings of the Century"]));
     }
 
+    #[test]
+    fn query_by_ref_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let authors = (&json)
+            .path("$.store.book[0,1].author")
+            .expect("the path is correct");
+        assert_eq!(authors, json!(["Nigel Rees", "Evelyn Waugh"]));
+
+        let bicycle = json
+            .path("$.store.bicycle.color")
+            .expect("the path is correct");
+        assert_eq!(bicycle, json!(["red"]));
+    }
+
+    #[test]
+    fn find_as_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+
+        let path = JsonPathInst::from_str("$.store.book[*].price").expect("the path is correct");
+        let prices: Vec<f64> = path.find_as(&json).expect("the prices are numbers");
+        assert_eq!(prices, vec![8.95, 12.99, 8.99, 22.99]);
+
+        let path = JsonPathInst::from_str("$.orders[*].ref").expect("the path is correct");
+        let refs: Vec<Vec<u8>> = path.find_as(&json).expect("the refs are arrays");
+        assert_eq!(refs, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
+
+        let path = JsonPathInst::from_str("$.store.book[*].title").expect("the path is correct");
+        assert!(path.find_as::<u64>(&json).is_err());
+
+        let path = JsonPathInst::from_str("$.absent").expect("the path is correct");
+        assert!(path
+            .find_as::<u64>(&json)
+            .expect("nothing to convert")
+            .is_empty());
+    }
+
+    #[test]
+    fn serde_test() {
+        let path = JsonPathInst::from_str("$.store.book[?(@.price < 10)].title")
+            .expect("the path is correct");
+        let serialized = serde_json::to_value(&path).expect("to serialize");
+        assert_eq!(
+            serialized,
+            json!("$['store']['book'][?(@['price'] < 10)]['title']")
+        );
+
+        let deserialized: JsonPathInst =
+            serde_json::from_value(serialized).expect("to deserialize");
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        assert_eq!(
+            super::find(&deserialized, &json),
+            json!(["Sayings of the Century", "Moby Dick"])
+        );
+
+        let err = serde_json::from_value::<JsonPathInst>(json!("$.[abc")).err();
+        assert!(err.is_some());
+        let err = serde_json::from_value::<JsonPathInst>(json!(1)).err();
+        assert!(err.is_some());
+
+        let json = json!({"it's": 1, "x']..['y": 2, "x": {"y": 3}});
+        for pointer in ["/it's", "/x']..['y"] {
+            let path = JsonPathInst::from_pointer(pointer).unwrap();
+            let serialized = serde_json::to_string(&path).expect("to serialize");
+            let deserialized: JsonPathInst =
+                serde_json::from_str(&serialized).expect("to deserialize");
+            assert_eq!(deserialized, path);
+            assert_eq!(super::find(&deserialized, &json), super::find(&path, &json));
+        }
+    }
+
+    #[test]
+    fn serde_options_test() {
+        let path = JsonPathInst::from_str("$..book[?(@.price > '10')].title")
+            .expect("the path is correct")
+            .with_options(
+                EvalOptions::default()
+                    .with_coercion(Coercion::Lenient)
+                    .with_order(ResultOrder::Document)
+                    .with_string_length(StringLength::Bytes)
+                    .with_max_depth(8)
+                    .with_limits(EvalLimits {
+                        max_nodes: Some(100),
+                        timeout: Some(Duration::from_millis(250)),
+                        ..EvalLimits::default()
+                    }),
+            );
+        let serialized = serde_json::to_value(&path).expect("to serialize");
+        assert_eq!(
+            serialized,
+            json!({
+                "path": "$..['book'][?(@['price'] > '10')]['title']",
+                "options": {
+                    "order": "document",
+                    "limits": {"max_nodes": 100, "timeout_ms": 250},
+                    "max_depth": 8,
+                    "coercion": "lenient",
+                    "string_length": "bytes"
+                }
+            })
+        );
+        let deserialized: JsonPathInst =
+            serde_json::from_value(serialized).expect("to deserialize");
+        assert_eq!(deserialized, path);
+        assert_eq!(deserialized.options(), path.options());
+
+        let goessner = JsonPathInst::from_str("$.a").unwrap().with_goessner(true);
+        let serialized = serde_json::to_value(&goessner).expect("to serialize");
+        assert_eq!(
+            serialized,
+            json!({"path": "$['a']", "options": {"goessner": true}})
+        );
+
+        let compared = JsonPathInst::from_str("$.a").unwrap().with_options(
+            EvalOptions::default().with_comparator(Arc::new(StringComparator::new())),
+        );
+        assert!(serde_json::to_value(&compared).is_err());
+
+        for wrong in [
+            json!({"options": {}}),
+            json!({"path": "$.a", "options": {"order": "random"}}),
+            json!({"path": "$.a", "options": {"strict": 1}}),
+            json!({"path": "$.a", "other": true}),
+        ] {
+            assert!(serde_json::from_value::<JsonPathInst>(wrong).is_err());
+        }
+    }
+
+    #[test]
+    fn to_segments_test() {
+        let json: Value = serde_json::from_str(
+            r#"{"store":{"book":[{"it's":1,"a']b":[0,{"']":2}],"a'][0]":3}]}}"#,
+        )
+        .expect("to get json");
+        let segments = |query: &str| {
+            let path = JsonPathInst::from_str(query).expect("the path is correct");
+            super::find_slice(&path, &json)
+                .into_iter()
+                .map(|v| v.to_segments())
+                .collect::<Vec<_>>()
+        };
+        let key = |k: &str| PathSegment::Key(k.to_string());
+
+        assert_eq!(
+            segments("$..[?(@ == 1)]"),
+            vec![Some(vec![
+                key("store"),
+                key("book"),
+                PathSegment::Index(0),
+                key("it's")
+            ])]
+        );
+        assert_eq!(
+            segments("$..[?(@ == 2)]"),
+            vec![Some(vec![
+                key("store"),
+                key("book"),
+                PathSegment::Index(0),
+                key("a']b"),
+                PathSegment::Index(1),
+                key("']")
+            ])]
+        );
+        assert_eq!(
+            segments("$..[?(@ == 3)]"),
+            vec![Some(vec![
+                key("store"),
+                key("book"),
+                PathSegment::Index(0),
+                key("a'][0]")
+            ])]
+        );
+        assert_eq!(segments("$"), vec![Some(vec![])]);
+        assert_eq!(segments("$.store.book.length()"), vec![None]);
+    }
+
+    #[test]
+    fn to_pointer_test() {
+        let json = json!({"a/b":{"c~d":[0,{"":1}]}});
+        let path = JsonPathInst::from_str("$..[?(@ == 1)]").expect("the path is correct");
+        let found = super::find_slice(&path, &json).remove(0);
+        let pointer = found.to_pointer().expect("the value is a slice");
+
+        assert_eq!(pointer, "/a~1b/c~0d/1/");
+        assert_eq!(json.pointer(&pointer), Some(&json!(1)));
+
+        let root = JsonPathInst::from_str("$").expect("the path is correct");
+        let found = super::find_slice(&root, &json).remove(0);
+        assert_eq!(found.to_pointer(), Some(String::new()));
+        assert_eq!(json.pointer(""), Some(&json));
+
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        let found = super::find_slice(&length, &json).remove(0);
+        assert_eq!(found.to_pointer(), None);
+    }
+
+    #[test]
+    fn from_pointer_test() {
+        let json = json!({"a/b":{"c~d":[0,{"":1,"01":2,"*":3}]}});
+        let find = |path: JsonPathInst| super::find(&path, &json);
+
+        let path =
+            JsonPathInst::from_pointer_escaped("/a~1b/c~0d/1/").expect("the pointer is correct");
+        assert!(path.is_singular());
+        assert_eq!(find(path), json!([1]));
+        let path = JsonPathInst::from_pointer_escaped("/a~1b/c~0d/1/01").unwrap();
+        assert_eq!(find(path), json!([2]));
+        let path = JsonPathInst::from_pointer_escaped("/a~1b/c~0d/1/*").unwrap();
+        assert!(path.is_singular());
+        assert_eq!(find(path), json!([3]));
+        assert_eq!(find(JsonPathInst::from_pointer("").unwrap()), json!([json]));
+
+        assert_eq!(
+            JsonPathInst::from_pointer("/a~1b").unwrap().canonicalize(),
+            "$['a~1b']"
+        );
+        assert!(JsonPathInst::from_pointer("a").is_err());
+        assert!(JsonPathInst::from_pointer_escaped("/a~").is_err());
+
+        let json = json!({"x']..['y": 1, "x": {"y": 2}, "it's": 3, "a\\b": 4, "a*": 5, "ab": 6});
+        for (pointer, found) in [("/x']..['y", 1), ("/it's", 3), ("/a\\b", 4), ("/a*", 5)] {
+            let path = JsonPathInst::from_pointer(pointer).unwrap();
+            let printed =
+                JsonPathInst::from_str(&path.to_string()).expect("the printed path is correct");
+            assert_eq!(super::find(&path, &json), json!([found]));
+            assert_eq!(super::find(&printed, &json), json!([found]));
+        }
+        assert_eq!(
+            JsonPathInst::from_pointer("/it's").unwrap().to_string(),
+            "$['it\\'s']"
+        );
+        assert_eq!(
+            JsonPathInst::from_pointer("/a*").unwrap().to_string(),
+            r"$['a\*']"
+        );
+    }
+
+    #[test]
+    fn find_one_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let path = |q: &str| JsonPathInst::from_str(q).expect("the path is correct");
+        let title = json!("Sayings of the Century");
+
+        assert_eq!(
+            path("$..title").find_one(&json),
+            Some(JsonPtr::Slice(&title))
+        );
+        assert_eq!(path("$.absent").find_one(&json), None);
+        assert_eq!(
+            path("$.array.length()").find_one(&json),
+            Some(JsonPtr::NewValue(json!(10)))
+        );
+
+        assert_eq!(
+            path("$.store.book[0].title").find_single(&json),
+            Ok(Some(JsonPtr::Slice(&title)))
+        );
+        assert_eq!(path("$.absent").find_single(&json), Ok(None));
+        assert_eq!(
+            path("$..title").find_single(&json),
+            Err("the path $..['title'] matches 4 elements instead of one".to_string())
+        );
+    }
+
+    #[test]
+    fn inspect_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+
+        assert_eq!(json.exists("$..isbn"), Ok(true));
+        assert_eq!(json.exists("$..book[?(@.price > 100)]"), Ok(false));
+        assert_eq!(json.exists("$.array.length()"), Ok(true));
+        assert!(json.exists("$.[abc").is_err());
+
+        assert_eq!(json.count("$..book[*]"), Ok(4));
+        assert_eq!(json.count("$..isbn"), Ok(2));
+        assert_eq!(json.count("$.absent"), Ok(0));
+        assert!(json.count("$.[abc").is_err());
+    }
+
+    #[test]
+    fn query_result_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let path = |q: &str| JsonPathInst::from_str(q).expect("the path is correct");
+        let v1 = json!(8.95);
+        let v2 = json!(8.99);
+        let book = PathNode::root().key("store").key("book");
+
+        assert_eq!(
+            path("$.store.book[?(@.price < 10)].price").query(&json),
+            Ok(vec![
+                Match {
+                    value: JsonPtr::Slice(&v1),
+                    path: Some("$.['store'].['book'][0].['price']".to_string()),
+                    node: Some(book.index(0).key("price"))
+                },
+                Match {
+                    value: JsonPtr::Slice(&v2),
+                    path: Some("$.['store'].['book'][2].['price']".to_string()),
+                    node: Some(book.index(2).key("price"))
+                },
+            ])
+        );
+        assert_eq!(
+            path("$.array.length()").query(&json),
+            Ok(vec![Match {
+                value: JsonPtr::NewValue(json!(10)),
+                path: None,
+                node: None
+            }])
+        );
+        assert_eq!(path("$.array[?(@ > 100)]").query(&json), Ok(vec![]));
+        assert_eq!(path("$.array[5:1]").query(&json), Ok(vec![]));
+        // the steps following the empty selection do not make the path inapplicable
+        assert_eq!(
+            path("$..book[?(@.price > 100)].title").query(&json),
+            Ok(vec![])
+        );
+        assert_eq!(
+            path("$.store.book[?(@.price > 100)].author.length()").query(&json),
+            Ok(vec![])
+        );
+        assert_eq!(path("$.array[5:1][0]").query(&json), Ok(vec![]));
+        assert_eq!(
+            path("$.store.book[?(@.price < 10)].absent").query(&json),
+            Err(JsonPathError::Inapplicable)
+        );
+        assert_eq!(
+            path("$.store[1:]").query(&json),
+            Err(JsonPathError::Inapplicable)
+        );
+        assert_eq!(
+            path("$.store.absent").query(&json),
+            Err(JsonPathError::Inapplicable)
+        );
+        assert_eq!(
+            path("$.expensive[0]").query(&json),
+            Err(JsonPathError::Inapplicable)
+        );
+        #[cfg(feature = "regex")]
+        assert!(matches!(
+            path("$..book[?(@.author ~= '(unclosed')]").query(&json),
+            Err(JsonPathError::Evaluation(_))
+        ));
+    }
+
+    #[test]
+    fn optimize_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let path = |q: &str| JsonPathInst::from_str(q).expect("the path is correct");
+
+        let original = path("$..*[?(@.isbn)].title");
+        let optimized = path("$..*[?(@.isbn)].title").optimize();
+        assert_eq!(original.find_slice(&json).len(), 4);
+        assert_eq!(
+            optimized.find_slice(&json),
+            original.find_slice(&json)[..2].to_vec()
+        );
+
+        let original = path("$.store.book[?(@.price < 10 && 'a' == 'a' || 1 > 2)].title");
+        let optimized =
+            path("$.store.book[?(@.price < 10 && 'a' == 'a' || 1 > 2)].title").optimize();
+        assert_eq!(optimized.explain().rewrites.len(), 2);
+        assert_eq!(optimized.find_slice(&json), original.find_slice(&json));
+    }
+
+    #[test]
+    fn limits_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let path = |q: &str, limits: EvalLimits| {
+            JsonPathInst::from_str(q)
+                .expect("the path is correct")
+                .with_limits(limits)
+        };
+        let limited = |q: &str, limits: EvalLimits| match path(q, limits).query(&json) {
+            Err(JsonPathError::LimitExceeded { limit, .. }) => Some(limit),
+            _ => None,
+        };
+        let nodes = |max| EvalLimits {
+            max_nodes: Some(max),
+            ..EvalLimits::default()
+        };
+        let matches = |max| EvalLimits {
+            max_matches: Some(max),
+            ..EvalLimits::default()
+        };
+        let bytes = |max| EvalLimits {
+            max_output_bytes: Some(max),
+            ..EvalLimits::default()
+        };
+
+        assert_eq!(limited("$..*", nodes(10)), Some(Limit::Nodes));
+        assert_eq!(limited("$..[?(@.price)]", nodes(10)), Some(Limit::Nodes));
+        assert_eq!(limited("$.store.bicycle.color", nodes(10)), None);
+        assert_eq!(limited("$..author", matches(3)), Some(Limit::Matches));
+        assert_eq!(limited("$..author", matches(4)), None);
+        assert_eq!(
+            limited("$.store.bicycle", bytes(20)),
+            Some(Limit::OutputBytes)
+        );
+        assert_eq!(limited("$.store.bicycle.color", bytes(20)), None);
+        assert_eq!(
+            path("$.store.bicycle.color", nodes(10)).query(&json),
+            path("$.store.bicycle.color", EvalLimits::default()).query(&json)
+        );
+        // the budget of the evaluation does not leak into the next one
+        assert_eq!(
+            path("$..*", EvalLimits::default()).find_slice(&json).len(),
+            61
+        );
+    }
+
+    #[test]
+    fn find_values_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let path = |q: &str| JsonPathInst::from_str(q).expect("the path is correct");
+
+        assert_eq!(
+            find_values(&path("$..book[?(@.price < 10)].price"), &json),
+            vec![&json!(8.95), &json!(8.99)]
+        );
+        assert_eq!(
+            find_values(&path("$.store.bicycle[?(@.color == 'red')].price"), &json),
+            vec![&json!(19.95)]
+        );
+        assert!(find_values(&path("$.absent"), &json).is_empty());
+        assert!(find_values(&path("$.array.length()"), &json).is_empty());
+        assert_eq!(
+            path("$.store..price").find_slice(&json).len(),
+            find_slice(&path("$.store..price"), &json).len()
+        );
+        // the filters reading the location of the element still see it
+        assert_eq!(
+            find_values(&path("$.store.*[?(@property == 'bicycle')].color"), &json),
+            vec![&json!("red")]
+        );
+    }
+
+    #[test]
+    fn find_nodes_test() {
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        let path = JsonPathInst::from_str("$..*").expect("the path is correct");
+        let nodes: Vec<_> = find_nodes(&path, &json)
+            .into_iter()
+            .map(|(v, node)| (v, node.to_string()))
+            .collect();
+        let slices: Vec<_> = find_slice(&path, &json)
+            .into_iter()
+            .map(|v| (v.clone().slice_or(&Value::Null), v.to_path().unwrap()))
+            .collect();
+        assert_eq!(nodes, slices);
+
+        let path = JsonPathInst::from_str("$.store.book[*].author").expect("the path is correct");
+        let found = find_nodes(&path, &json);
+        assert_eq!(found.len(), 4);
+        assert_eq!(
+            found[0].1.parent().and_then(|p| p.parent()),
+            found[3].1.parent().and_then(|p| p.parent())
+        );
+        assert!(find_nodes(&JsonPathInst::from_str("$.array.length()").unwrap(), &json).is_empty());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn compiled_regex_test() {
+        let path =
+            JsonPathInst::from_str("$..book[?(@.author ~= '(?i)REES' || @.title ~= 'Moby')].price")
+                .expect("the path is correct");
+        assert!(format!("{path:?}").contains(r#"regexes: ["(?i)REES", "Moby"]"#));
+
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        for _ in 0..3 {
+            assert_eq!(
+                path.find_slice(&json),
+                vec![JsonPtr::Slice(&json!(8.95)), JsonPtr::Slice(&json!(8.99))]
+            );
+        }
+    }
+
+    #[test]
+    fn canonicalize_test() {
+        let path = |q: &str| JsonPathInst::from_str(q).expect("the path is correct");
+        assert_eq!(
+            path("$.a.*[?(@.b>1&&@.c=='x')]").canonicalize(),
+            "$['a'][*][?(@['b'] > 1 && @['c'] == 'x')]"
+        );
+        assert_eq!(path("$['it\\'s']").canonicalize(), "$['it\\'s']");
+        assert_eq!(path("$.a[0,1]"), path("$['a'][0, 1]"));
+        assert_ne!(path("$.a[0,1]"), path("$.a[1,0]"));
+
+        let quoted = JsonPathInst::from(JsonPath::root().field("a']..['b"));
+        let descent = JsonPathInst::from(JsonPath::root().field("a").descent("b"));
+        assert_ne!(quoted.canonicalize(), descent.canonicalize());
+        assert_ne!(quoted, descent);
+        let unique: std::collections::HashSet<JsonPathInst> = [quoted, descent].into();
+        assert_eq!(unique.len(), 2);
+
+        let unique: std::collections::HashSet<JsonPathInst> =
+            ["$.a.b", "$['a']['b']", "$.a[\"b\"]", "$.a.c"]
+                .into_iter()
+                .map(path)
+                .collect();
+        assert_eq!(unique.len(), 2);
+
+        let strict = path("$.a.b").with_options(EvalOptions::default().with_strict(true));
+        assert_ne!(path("$.a.b"), strict);
+        assert_eq!(
+            path("$['a']['b']").with_options(strict.options().clone()),
+            strict
+        );
+        assert_ne!(path("$.a").with_goessner(true), path("$.a"));
+        assert_eq!(path("$.a").with_goessner(false), path("$.a"));
+        let unique: std::collections::HashSet<JsonPathInst> = [
+            path("$.a"),
+            path("$.a").with_distinct_nodes(true),
+            path("$['a']"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn lint_with_sample_test() {
+        let path = JsonPathInst::from_str("$.store.bicycle[0:1:0]").unwrap();
+        let json: Value = serde_json::from_str(template_json()).expect("to get json");
+        assert_eq!(
+            lint_with_sample(&path, &json),
+            vec![
+                LintWarning::ZeroStep("[:1:0]".to_string()),
+                LintWarning::NotAnArray {
+                    path: "$.['store'].['bicycle']".to_string(),
+                    index: "[:1:0]".to_string()
+                }
+            ]
+        );
+    }
+
     #[test]
     fn find_slice_test() {
         let json: Box<Value> = serde_json::from_str(template_json()).expect("to get json");
@@ -972,11 +3805,11 @@ mod tests {
         );
         assert_eq!(super::find(&path, &json), json!([3]));
 
-        // length of object returns 0
+        // length of object returns the number of its fields
         let json: Box<Value> = Box::new(json!({"verb": "TEST"}));
         let path: Box<JsonPathInst> =
             Box::from(JsonPathInst::from_str("$.length()").expect("the path is correct"));
-        assert_eq!(super::find(&path, &json), Value::Null);
+        assert_eq!(super::find(&path, &json), json!([1]));
 
         // length of integer returns null
         let json: Box<Value> = Box::new(json!(1));
@@ -1020,14 +3853,14 @@ mod tests {
         let js = json!(null);
         assert_eq!(v, js);
 
-        // fetching first object returns length null
+        // fetching first object returns the number of its fields
         let json: Box<Value> =
             Box::new(json!([{"verb": "TEST"},{"verb": "TEST"}, {"verb": "RUN"}]));
         let path: Box<JsonPathInst> =
             Box::from(JsonPathInst::from_str("$.[0].length()").expect("the path is correct"));
 
         let v = super::find(&path, &json);
-        let js = Value::Null;
+        let js = json!([1]);
         assert_eq!(v, js);
 
         // length on fetching the index after search gives length of the object (array)
@@ -1047,10 +3880,254 @@ mod tests {
         );
 
         let v = super::find(&path, &json);
-        let js = Value::Null;
+        let js = json!([1]);
         assert_eq!(v, js);
     }
 
+    #[test]
+    fn length_operand_test() {
+        let json = json!([
+            {"tags":["a","b","c"],"attrs":{"x":1,"y":2},"name":"héllo"},
+            {"tags":["a"],"attrs":{},"name":"ab"}
+        ]);
+        let count = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find_values(&path, &json).len()
+        };
+        assert_eq!(count("$[?(@.tags.length() > 2)]"), 1);
+        assert_eq!(count("$[?(@.attrs.length() == 0)]"), 1);
+        assert_eq!(count("$[?(@.name.length() == 5)]"), 1);
+        assert_eq!(count("$[?(@.name size 5)]"), 1);
+        assert_eq!(count("$[?(@.tags size @.attrs.length())]"), 0);
+        assert_eq!(count("$[?(@.tags.length() > @.attrs.length())]"), 2);
+        assert_eq!(count("$[?(@.absent.length() >= 0)]"), 0);
+
+        let bytes = crate::EvalOptions::default().with_string_length(crate::StringLength::Bytes);
+        let path = JsonPathInst::from_str("$[?(@.name size 6)]").expect("the path is correct");
+        assert_eq!(
+            crate::find_with_options(&path, &json, &bytes).map(|m| m.len()),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn position_test() {
+        let json = json!({"items":[
+            {"id":1,"active":false},
+            {"id":2,"active":true},
+            {"id":3,"active":true},
+            {"id":4,"active":true}
+        ]});
+        let ids = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find_values(&path, &json)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids("$.items[?(@index < 3 && @.active == true)].id"),
+            [json!(2), json!(3)]
+        );
+        assert_eq!(ids("$.items[?(position() >= 2)].id"), [json!(3), json!(4)]);
+        assert!(ids("$.items[?(@index == @.id)]").is_empty());
+        assert_eq!(ids("$..[?(@index == 1)].id"), [json!(2)]);
+        // the object filtered as a whole has no position
+        assert!(ids("$.items[0][?(@index >= 0)]").is_empty());
+    }
+
+    #[test]
+    fn metadata_test() {
+        let json = json!({
+            "error_rate": 0.1,
+            "errors": {"timeout": 3, "refused": 1},
+            "warnings": [{"code": "w1"}, {"code": "w2"}]
+        });
+        let found = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find_values(&path, &json)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(found("$..[?(@property == 'refused')]"), [json!(1)]);
+        assert_eq!(found("$.warnings[?(@property == 1)].code"), [json!("w2")]);
+        assert_eq!(found("$.*[?(@property == 'error_rate')]"), [json!(0.1)]);
+        // the object filtered as a whole takes the key it is found by
+        assert_eq!(
+            found("$.errors[?(@property == 'errors')].timeout"),
+            [json!(3)]
+        );
+        #[cfg(feature = "regex")]
+        {
+            assert_eq!(
+                found("$..*[?(@property ~= '^err')]"),
+                [json!(0.1), json!({"timeout": 3, "refused": 1})]
+            );
+            assert_eq!(found("$.warnings[?(@path ~= '1]$')].code"), [json!("w2")]);
+            assert_eq!(found("$..[?(@path ~= 'refused')]"), [json!(1)]);
+        }
+        assert!(found("$.warnings[?(@.code[?(@property == 'code')])]").is_empty());
+    }
+
+    #[test]
+    fn default_test() {
+        let json = json!({
+            "config": {"retries": 0},
+            "items": [{"qty": 7}, {"qty": 2}, {"name": "x"}]
+        });
+        let found = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find(&path, &json)
+        };
+        assert_eq!(found("$.config.timeout ?? 30"), json!([30]));
+        assert_eq!(found("$.config.retries ?? 3"), json!([0]));
+        assert_eq!(found("$.config.mode ?? 'fast'"), json!(["fast"]));
+        assert_eq!(found("$..absent ?? null"), json!([null]));
+        assert_eq!(found("$.items[*].qty ?? 0"), json!([7, 2]));
+        assert_eq!(found("$.items[?(@.qty > 10)] ?? false"), json!([false]));
+        assert_eq!(found("$.items[*].length() ?? 0"), json!([3]));
+        assert_eq!(
+            found("$.items[?(@.qty ?? 0 < 5)]"),
+            json!([{"qty": 2}, {"name": "x"}])
+        );
+        assert!(JsonPathInst::from_str("$.a ?? @.b").is_err());
+    }
+
+    #[test]
+    fn xor_not_in_test() {
+        let json = json!([
+            {"id": 1, "admin": true, "owner": true, "kind": "a"},
+            {"id": 2, "admin": true, "kind": "b"},
+            {"id": 3, "owner": true, "kind": "c"},
+            {"id": 4, "kind": "a"}
+        ]);
+        let ids = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find(&path, &json)
+        };
+        assert_eq!(ids("$[?(@.admin xor @.owner)].id"), json!([2, 3]));
+        assert_eq!(
+            ids("$[?(@.admin xor @.owner || @.id == 4)].id"),
+            json!([2, 3, 4])
+        );
+        assert_eq!(ids("$[?(@.admin xor @.owner && @.id == 1)].id"), json!([2]));
+        assert_eq!(ids("$[?(@.kind not in ['a','c'])].id"), json!([2]));
+        assert_eq!(ids("$[?(1 == 1 xor @.admin)].id"), json!([3, 4]));
+    }
+
+    #[test]
+    fn between_test() {
+        let json = json!({"limit": 8, "items": [
+            {"id": 1, "price": 4.99},
+            {"id": 2, "price": 5},
+            {"id": 3, "price": 10},
+            {"id": 4, "price": "7"},
+            {"id": 5, "code": "b1"}
+        ]});
+        let ids = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find(&path, &json)
+        };
+        assert_eq!(
+            ids("$.items[?(@.price between 5 and 10)].id"),
+            json!([2, 3])
+        );
+        assert_eq!(
+            ids("$.items[?(@.price between 0 and $.limit)].id"),
+            json!([1, 2])
+        );
+        // the strings are ordered only by the comparison rules ordering them
+        assert_eq!(
+            ids("$.items[?(@.code between 'a' and 'c')].id"),
+            Value::Null
+        );
+        let js = crate::EvalOptions::default().with_coercion(crate::Coercion::JsLike);
+        let path = JsonPathInst::from_str("$.items[?(@.code between 'a' and 'c')].id")
+            .expect("the path is correct");
+        assert_eq!(
+            crate::find_with_options(&path, &json, &js).map(|m| m.len()),
+            Ok(1)
+        );
+        assert_eq!(
+            ids("$.items[?(!(@.price between 5 and 10) && @.price)].id"),
+            json!([1, 4])
+        );
+    }
+
+    #[test]
+    fn deep_equality_test() {
+        let json = json!({"people": [
+            {"id": 1, "address": {"zip": "0150", "city": "Oslo"}, "tags": ["a", "b"]},
+            {"id": 2, "address": {"city": "Oslo"}, "tags": ["b", "a"]},
+            {"id": 3, "address": {"city": "Oslo", "zip": 150}, "tags": [["a"], "b"]},
+            {"id": 4, "address": {"city": "Oslo", "zip": "0150", "floor": 2}, "tags": []}
+        ]});
+        let ids = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find(&path, &json)
+        };
+        assert_eq!(
+            ids(r#"$.people[?(@.address == {"city": "Oslo", "zip": "0150"})].id"#),
+            json!([1])
+        );
+        assert_eq!(ids("$.people[?(@.tags == ['a','b'])].id"), json!([1]));
+        assert_eq!(ids("$.people[?(@.tags == [['a'],'b'])].id"), json!([3]));
+        assert_eq!(ids("$.people[?(@.tags == [])].id"), json!([4]));
+        assert_eq!(
+            ids("$.people[?(@.address != {'city': 'Oslo'})].id"),
+            json!([1, 3, 4])
+        );
+        assert_eq!(
+            ids("$.people[?(@.address in [{'city': 'Oslo'}, {'zip': 150, 'city': 'Oslo'}])].id"),
+            json!([2, 3])
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn extract_test() {
+        let json = json!({"log": [
+            {"msg": "order=12 shipped"},
+            {"msg": "heartbeat"},
+            {"msg": "order=7 paid order=8"},
+            {"msg": 3}
+        ]});
+        let found = |q: &str| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            find(&path, &json)
+        };
+        assert_eq!(
+            found(r"$..log[*].msg.extract('order=(\d+)', 1)"),
+            json!(["12", "7"])
+        );
+        assert_eq!(
+            found(r"$.log[*].msg.extract('order=\d+')"),
+            json!(["order=12", "order=7"])
+        );
+        assert_eq!(
+            found(r"$.log[*].msg.extract('order=(\d+)', 2)"),
+            Value::Null
+        );
+        assert_eq!(
+            found(r"$.log[?(@.msg.extract('order=(\d+)', 1) == '7')].msg"),
+            json!(["order=7 paid order=8"])
+        );
+
+        let path = JsonPathInst::from_str("$.log[*].msg.extract('(', 1)").unwrap();
+        assert!(matches!(
+            path.query(&json),
+            Err(JsonPathError::Evaluation(_))
+        ));
+    }
+
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn extract_test() {
+        let err = JsonPathInst::from_str("$.msg.extract('id=(.*)', 1)").unwrap_err();
+        assert!(err.contains("the feature `regex`"));
+    }
+
     #[test]
     fn no_value_index_from_not_arr_filter_test() {
         let json: Box<Value> = Box::new(json!({
@@ -1204,6 +4281,7 @@ mod tests {
         assert_eq!(v, vec![NoValue]);
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn regex_filter_test() {
         let json: Box<Value> = Box::new(json!({
@@ -0,0 +1,628 @@
+//! The found elements along with their paths.
+//!
+//! The elements are found in one traversal along with the locations the evaluation records,
+//! so the paths can be rendered in any [`PathStyle`], split into the segments or grouped by the ancestors
+//! without parsing them back.
+//!
+//! ```
+//! use jsonpath_rust::{find_map, JsonPathInst, PathSegment};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let data = json!({"a.b":[{"c":1}]});
+//! let path = JsonPathInst::from_str("$..c").unwrap();
+//! let found = find_map(&path, &data);
+//!
+//! assert_eq!(found[0].path.as_deref(), Some("$.['a.b'][0].['c']"));
+//! assert_eq!(found[0].to_segments().unwrap()[0], PathSegment::Key("a.b".to_string()));
+//! ```
+use crate::path::{JsonLike, PathNode, Track};
+use crate::JsonPathValue::{NewValue, NoValue, Slice};
+use crate::{
+    find_nodes, jsp_idx, jsp_obj, to_path, to_pointer, JsonPathInst, JsonPathValue, JsonPtr,
+    PathSegment, PathStyle,
+};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// The element found by [`JsonPathInst::query`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Match<'a> {
+    /// The found element
+    pub value: JsonPtr<'a, Value>,
+    /// The path of the element, absent if the element has been generated (like length)
+    pub path: Option<String>,
+    /// the location of the element recorded by the evaluation
+    pub(crate) node: Option<PathNode>,
+}
+
+impl<'a> Match<'a> {
+    pub(crate) fn from_found(found: JsonPathValue<'a, Value, PathNode>) -> Option<Self> {
+        match found {
+            Slice(v, node) => Some(Match {
+                value: JsonPtr::Slice(v),
+                path: Some(node.to_string()),
+                node: Some(node),
+            }),
+            NewValue(v) => Some(Match {
+                value: JsonPtr::NewValue(v),
+                path: None,
+                node: None,
+            }),
+            NoValue => None,
+        }
+    }
+
+    /// finds the elements like [`find_map`] evaluating the path relative to this element, see [`find_from`](crate::find_from).
+    /// The paths of the found elements start with the path of this element,
+    /// the generated element (like length) has no path to start with, therefore nothing is found in it.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use jsonpath_rust::JsonPathInst;
+    /// use serde_json::json;
+    /// # use std::str::FromStr;
+    ///
+    /// let data = json!({"users":[{"name":"ann","address":{"city":"Oslo"}}]});
+    /// let users = JsonPathInst::from_str("$.users[*]").unwrap();
+    /// let city = JsonPathInst::from_str("@.address.city").unwrap();
+    ///
+    /// let user = users.query(&data).unwrap().remove(0);
+    /// let found = user.find_within(&city);
+    /// assert_eq!(found[0].path.as_deref(), Some("$.['users'][0].['address'].['city']"));
+    /// assert_eq!(*found[0].value, json!("Oslo"));
+    /// ```
+    pub fn find_within(&self, path: &'a JsonPathInst) -> Vec<Match<'a>> {
+        match (&self.value, &self.node) {
+            (JsonPtr::Slice(v), Some(prefix)) => path
+                .evaluate(*v, Slice(*v, PathNode::root()))
+                .into_iter()
+                .map(|found| match found {
+                    Slice(v, node) => Slice(v, prefix.join(&node)),
+                    other => other,
+                })
+                .filter_map(Match::from_found)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The typed segments of the path of the element.
+    /// Returns `None` if the element has been generated.
+    pub fn to_segments(&self) -> Option<Vec<PathSegment>> {
+        self.node.as_ref().map(PathNode::segments)
+    }
+
+    /// converts the path of the element into the given style
+    pub fn with_path_style(self, style: PathStyle) -> Self {
+        Match {
+            path: self.to_segments().map(|s| style.format(&s)),
+            ..self
+        }
+    }
+}
+
+/// Serialized as `{"path": ..., "value": ...}` with the `null` path for the generated elements.
+impl Serialize for Match<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut m = serializer.serialize_struct("Match", 2)?;
+        m.serialize_field("path", &self.path)?;
+        m.serialize_field("value", &*self.value)?;
+        m.end()
+    }
+}
+
+/// finds the elements along with their paths in one traversal,
+/// unlike [`find_slice`](crate::find_slice) and [`find_as_path`](crate::find_as_path) called one after another.
+/// The generated elements (like length) have no path.
+/// Unlike [`JsonPathInst::query`] it does not tell why nothing is found.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_map, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"first":{"second":[{"active":1},{"passive":1}]}});
+/// let path = JsonPathInst::from_str("$.first.second[?(@.active)]").unwrap();
+/// let found = find_map(&path, &data);
+///
+/// assert_eq!(found[0].path.as_deref(), Some("$.['first'].['second'][0]"));
+/// assert_eq!(*found[0].value, json!({"active":1}));
+/// assert_eq!(
+///     serde_json::to_value(&found).unwrap(),
+///     json!([{"path":"$.['first'].['second'][0]","value":{"active":1}}])
+/// );
+/// ```
+pub fn find_map<'a>(path: &'a JsonPathInst, json: &'a Value) -> Vec<Match<'a>> {
+    path.evaluate(json, Slice(json, PathNode::root()))
+        .into_iter()
+        .filter_map(Match::from_found)
+        .collect()
+}
+
+/// finds the elements along with their paths like [`find_map`] with the paths in the given style.
+/// The grouping functions like [`find_grouped`] rely on the default style.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_map_with_style, JsonPathInst, PathStyle};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"first":{"second":[{"active":1},{"passive":1}]}});
+/// let path = JsonPathInst::from_str("$.first.second[?(@.active)]").unwrap();
+/// let found = find_map_with_style(&path, &data, PathStyle::Bracket);
+///
+/// assert_eq!(found[0].path.as_deref(), Some(r#"$["first"]["second"][0]"#));
+/// ```
+pub fn find_map_with_style<'a>(
+    path: &'a JsonPathInst,
+    json: &'a Value,
+    style: PathStyle,
+) -> Vec<Match<'a>> {
+    find_map(path, json)
+        .into_iter()
+        .map(|m| m.with_path_style(style))
+        .collect()
+}
+
+/// finds the elements along with their paths like [`find_map`] and groups them by the paths of their parents.
+/// The groups come in the order of their first elements, the generated elements (like length) are skipped.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_grouped, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"orders":[{"sku":["a","b"]},{"sku":["c"]}]});
+/// let path = JsonPathInst::from_str("$..sku[*]").unwrap();
+/// let groups: Vec<(String, usize)> = find_grouped(&path, &data)
+///     .into_iter()
+///     .map(|(parent, found)| (parent, found.len()))
+///     .collect();
+///
+/// assert_eq!(
+///     groups,
+///     vec![
+///         ("$.['orders'][0].['sku']".to_string(), 2),
+///         ("$.['orders'][1].['sku']".to_string(), 1)
+///     ]
+/// );
+/// ```
+pub fn find_grouped<'a>(path: &'a JsonPathInst, json: &'a Value) -> Vec<(String, Vec<Match<'a>>)> {
+    group_by_ancestor(find_map(path, json), |segments| {
+        segments.len().checked_sub(1)
+    })
+}
+
+/// finds the elements along with their paths like [`find_map`] and groups them by the paths of their ancestors
+/// at the given depth, where the root is at the depth 0 and its children are at the depth 1.
+/// The groups come in the order of their first elements,
+/// the generated elements (like length) and the elements not deeper than the depth are skipped.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_grouped_at, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"orders":[{"items":[{"sku":"a"},{"sku":"b"}]},{"items":[{"sku":"c"}]}]});
+/// let path = JsonPathInst::from_str("$..items[*].sku").unwrap();
+/// let groups = find_grouped_at(&path, &data, 2);
+///
+/// assert_eq!(groups[0].0, "$.['orders'][0]");
+/// assert_eq!(groups[0].1.len(), 2);
+/// assert_eq!(groups[1].0, "$.['orders'][1]");
+/// ```
+pub fn find_grouped_at<'a>(
+    path: &'a JsonPathInst,
+    json: &'a Value,
+    depth: usize,
+) -> Vec<(String, Vec<Match<'a>>)> {
+    group_by_ancestor(find_map(path, json), |segments| {
+        (segments.len() > depth).then_some(depth)
+    })
+}
+
+/// The element found by [`find_records`] in the form suitable for the responses of the services
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchRecord {
+    /// the path of the element like `$.['store'].['book'][0]`
+    pub path: String,
+    /// the json pointer of the element like `/store/book/0`
+    pub pointer: String,
+    /// the element itself
+    pub value: Value,
+}
+
+impl Serialize for MatchRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut m = serializer.serialize_struct("MatchRecord", 3)?;
+        m.serialize_field("path", &self.path)?;
+        m.serialize_field("pointer", &self.pointer)?;
+        m.serialize_field("value", &self.value)?;
+        m.end()
+    }
+}
+
+/// finds the elements and clones them into the records along with their paths and json pointers.
+/// The generated elements (like length) have neither the path nor the pointer, therefore they are skipped.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_records, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"store":{"book":[{"title":"Moby Dick"}]}});
+/// let path = JsonPathInst::from_str("$..title").unwrap();
+///
+/// assert_eq!(
+///     serde_json::to_value(find_records(&path, &data)).unwrap(),
+///     json!([{
+///         "path": "$.['store'].['book'][0].['title']",
+///         "pointer": "/store/book/0/title",
+///         "value": "Moby Dick"
+///     }])
+/// );
+/// ```
+pub fn find_records(path: &JsonPathInst, json: &Value) -> Vec<MatchRecord> {
+    find_records_with_style(path, json, PathStyle::DottedLegacy)
+}
+
+/// finds the records like [`find_records`] with the paths in the given style, the pointers stay as they are.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_records_with_style, JsonPathInst, PathStyle};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"store":{"book":[{"title":"Moby Dick"}]}});
+/// let path = JsonPathInst::from_str("$..title").unwrap();
+/// let records = find_records_with_style(&path, &data, PathStyle::Normalized);
+///
+/// assert_eq!(records[0].path, "$['store']['book'][0]['title']");
+/// assert_eq!(records[0].pointer, "/store/book/0/title");
+/// ```
+pub fn find_records_with_style(
+    path: &JsonPathInst,
+    json: &Value,
+    style: PathStyle,
+) -> Vec<MatchRecord> {
+    find_nodes(path, json)
+        .into_iter()
+        .map(|(v, node)| {
+            let segments = node.segments();
+            MatchRecord {
+                path: style.format(&segments),
+                pointer: to_pointer(&segments),
+                value: v.clone(),
+            }
+        })
+        .collect()
+}
+
+/// The element found by [`find_with_context`] along with its ancestors
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchContext<'a> {
+    /// the found element
+    pub found: Match<'a>,
+    /// the paths and the references of the ancestors starting from the parent
+    pub ancestors: Vec<(String, &'a Value)>,
+}
+
+/// finds the elements along with their paths like [`find_map`] and the given number of their ancestors,
+/// the parent, the grandparent and so on up to the root.
+/// The generated elements (like length) have no ancestors.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_context, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"orders":[{"id":1,"items":[{"sku":"a","qty":0}]}]});
+/// let path = JsonPathInst::from_str("$..items[?(@.qty == 0)].sku").unwrap();
+/// let found = find_with_context(&path, &data, 3);
+///
+/// let (parent, item) = &found[0].ancestors[0];
+/// assert_eq!((parent.as_str(), item["qty"].clone()), ("$.['orders'][0].['items'][0]", json!(0)));
+/// assert_eq!(found[0].ancestors[2].1["id"], json!(1));
+/// ```
+pub fn find_with_context<'a>(
+    path: &'a JsonPathInst,
+    json: &'a Value,
+    depth: usize,
+) -> Vec<MatchContext<'a>> {
+    find_map(path, json)
+        .into_iter()
+        .map(|found| {
+            let segments = found.to_segments().unwrap_or_default();
+            let mut ancestors = vec![];
+            let mut current = Some(("$".to_string(), json));
+            for s in segments.iter() {
+                let Some((p, v)) = current.take() else {
+                    break;
+                };
+                current = match s {
+                    PathSegment::Key(k) => v.get(k).map(|next| (jsp_obj(&p, k), next)),
+                    PathSegment::Index(i) => v.get(i).map(|next| (jsp_idx(&p, *i), next)),
+                };
+                ancestors.push((p, v));
+            }
+            ancestors.reverse();
+            ancestors.truncate(depth);
+            MatchContext { found, ancestors }
+        })
+        .collect()
+}
+
+/// The page of the elements found by [`find_page`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Page<'a> {
+    /// the found elements of the page along with their paths
+    pub items: Vec<Match<'a>>,
+    /// the number of all the found elements
+    pub total_hint: usize,
+}
+
+/// finds the page of the elements skipping the first `offset` ones and taking at most `limit` ones.
+/// The paths are built only for the elements of the page and the skipped elements are not cloned.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_page, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"items":[1,2,3,4,5]});
+/// let path = JsonPathInst::from_str("$.items[*]").unwrap();
+/// let page = find_page(&path, &data, 2, 2);
+///
+/// assert_eq!(page.total_hint, 5);
+/// assert_eq!(page.items[0].path.as_deref(), Some("$.['items'][2]"));
+/// assert_eq!(*page.items[1].value, json!(4));
+/// ```
+pub fn find_page<'a>(
+    path: &'a JsonPathInst,
+    json: &'a Value,
+    offset: usize,
+    limit: usize,
+) -> Page<'a> {
+    let found: Vec<JsonPathValue<'a, Value, ()>> = path
+        .evaluate_untracked(json)
+        .into_iter()
+        .filter(|v| v.has_value())
+        .collect();
+    let total_hint = found.len();
+    let page: Vec<JsonPathValue<'a, Value, ()>> =
+        found.into_iter().skip(offset).take(limit).collect();
+
+    let wanted: HashSet<*const Value> = page
+        .iter()
+        .filter_map(|v| match v {
+            Slice(v, _) => Some(*v as *const Value),
+            NewValue(_) | NoValue => None,
+        })
+        .collect();
+    let nodes = locate_nodes(json, wanted);
+    let items = page
+        .into_iter()
+        .filter_map(|v| match v {
+            Slice(v, _) => Match::from_found(Slice(v, nodes.get(&(v as *const Value))?.clone())),
+            NewValue(v) => Match::from_found(NewValue(v)),
+            NoValue => None,
+        })
+        .collect();
+    Page { items, total_hint }
+}
+
+/// builds the locations of the given elements of the json walking it until all of them are found
+pub(crate) fn locate_nodes<T: JsonLike>(
+    json: &T,
+    mut wanted: HashSet<*const T>,
+) -> HashMap<*const T, PathNode> {
+    fn walk<T: JsonLike>(
+        v: &T,
+        node: &PathNode,
+        wanted: &mut HashSet<*const T>,
+        nodes: &mut HashMap<*const T, PathNode>,
+    ) {
+        if wanted.remove(&(v as *const T)) {
+            nodes.insert(v as *const T, node.clone());
+        }
+        if let Some(elems) = v.as_array() {
+            for (i, el) in elems.iter().enumerate() {
+                if wanted.is_empty() {
+                    return;
+                }
+                walk(el, &node.index(i), wanted, nodes);
+            }
+        } else if let Some(fields) = v.entries() {
+            for (k, el) in fields {
+                if wanted.is_empty() {
+                    return;
+                }
+                walk(el, &node.key(k), wanted, nodes);
+            }
+        }
+    }
+    let mut nodes = HashMap::new();
+    walk(json, &PathNode::root(), &mut wanted, &mut nodes);
+    nodes
+}
+
+/// groups the elements by the path made of the first segments of their paths,
+/// the number of the segments is given by `prefix` or the element is skipped
+fn group_by_ancestor<'a>(
+    found: Vec<Match<'a>>,
+    prefix: impl Fn(&[PathSegment]) -> Option<usize>,
+) -> Vec<(String, Vec<Match<'a>>)> {
+    let mut groups: Vec<(String, Vec<Match<'a>>)> = vec![];
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for m in found {
+        let Some(segments) = m.to_segments() else {
+            continue;
+        };
+        let Some(len) = prefix(&segments) else {
+            continue;
+        };
+        let ancestor = to_path(&segments[..len]);
+        match positions.get(&ancestor) {
+            Some(i) => groups[*i].1.push(m),
+            None => {
+                positions.insert(ancestor.clone(), groups.len());
+                groups.push((ancestor, vec![m]));
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonPathInst;
+    use serde_json::{json, Value};
+    use std::ops::Deref;
+    use std::str::FromStr;
+
+    #[test]
+    fn find_map_test() {
+        let json = json!({"a":[1,2],"b":{"c":3}});
+        let path = JsonPathInst::from_str("$..[?(@ > 1)]").expect("the path is correct");
+        let found = crate::find_map(&path, &json);
+        let paths: Vec<_> = found.iter().map(|m| m.path.as_deref()).collect();
+        assert_eq!(paths, vec![Some("$.['a'][1]"), Some("$.['b'].['c']")]);
+
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        assert_eq!(
+            serde_json::to_value(crate::find_map(&length, &json)).unwrap(),
+            json!([{"path":null,"value":2}])
+        );
+        let absent = JsonPathInst::from_str("$.x").expect("the path is correct");
+        assert!(crate::find_map(&absent, &json).is_empty());
+    }
+
+    #[test]
+    fn find_grouped_test() {
+        let json = json!({"a":[1,{"b":[2,3]}],"c":4});
+        let path = JsonPathInst::from_str("$..*").expect("the path is correct");
+        let groups: Vec<(String, usize)> = crate::find_grouped(&path, &json)
+            .into_iter()
+            .map(|(p, found)| (p, found.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("$".to_string(), 2),
+                ("$.['a']".to_string(), 2),
+                ("$.['a'][1]".to_string(), 1),
+                ("$.['a'][1].['b']".to_string(), 2),
+            ]
+        );
+        let groups = crate::find_grouped_at(&path, &json, 1);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, "$.['a']");
+        assert_eq!(groups[0].1.len(), 5);
+
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        assert!(crate::find_grouped(&length, &json).is_empty());
+        let root = JsonPathInst::from_str("$").expect("the path is correct");
+        assert!(crate::find_grouped(&root, &json).is_empty());
+    }
+
+    #[test]
+    fn find_with_context_test() {
+        let json = json!({"a":[{"b":1}]});
+        let path = JsonPathInst::from_str("$..b").expect("the path is correct");
+        let found = crate::find_with_context(&path, &json, 5);
+        let ancestors: Vec<&str> = found[0].ancestors.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(ancestors, vec!["$.['a'][0]", "$.['a']", "$"]);
+        assert_eq!(found[0].ancestors[0].1, &json!({"b":1}));
+        assert_eq!(
+            crate::find_with_context(&path, &json, 1)[0].ancestors.len(),
+            1
+        );
+
+        let root = JsonPathInst::from_str("$").expect("the path is correct");
+        assert!(crate::find_with_context(&root, &json, 2)[0]
+            .ancestors
+            .is_empty());
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        assert!(crate::find_with_context(&length, &json, 2)[0]
+            .ancestors
+            .is_empty());
+    }
+
+    #[test]
+    fn find_page_test() {
+        let json = json!({"a":[{"b":1},{"b":2}],"c":{"b":3}});
+        let path = JsonPathInst::from_str("$..b").expect("the path is correct");
+        let page = crate::find_page(&path, &json, 1, 5);
+        assert_eq!(page.total_hint, 3);
+        let paths: Vec<_> = page.items.iter().map(|m| m.path.as_deref()).collect();
+        assert_eq!(paths, vec![Some("$.['a'][1].['b']"), Some("$.['c'].['b']")]);
+        assert!(crate::find_page(&path, &json, 3, 5).items.is_empty());
+        assert!(crate::find_page(&path, &json, 0, 0).items.is_empty());
+
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        let page = crate::find_page(&length, &json, 0, 1);
+        assert_eq!(page.items[0].path, None);
+        assert_eq!(*page.items[0].value, json!(2));
+    }
+
+    #[test]
+    fn find_records_test() {
+        let json = json!({"a/b":[1,{"c":2}]});
+        let path = JsonPathInst::from_str("$..*").expect("the path is correct");
+        let records = crate::find_records(&path, &json);
+        let pointers: Vec<&str> = records.iter().map(|r| r.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["/a~1b", "/a~1b/0", "/a~1b/1", "/a~1b/1/c"]);
+        assert_eq!(records[3].path, "$.['a/b'][1].['c']");
+        assert_eq!(records[3].value, json!(2));
+
+        let length = JsonPathInst::from_str("$.['a/b'].length()").expect("the path is correct");
+        assert!(crate::find_records(&length, &json).is_empty());
+        let absent = JsonPathInst::from_str("$.x").expect("the path is correct");
+        assert!(crate::find_records(&absent, &json).is_empty());
+    }
+
+    #[test]
+    fn find_within_test() {
+        let json = json!({"a":[{"b":[1,2]},{"b":[3]}]});
+        let records = JsonPathInst::from_str("$.a[*]").expect("the path is correct");
+        let elems = JsonPathInst::from_str("@.b[?(@ > 1)]").expect("the path is correct");
+        let found: Vec<(String, Value)> = crate::find_map(&records, &json)
+            .iter()
+            .flat_map(|m| m.find_within(&elems))
+            .map(|m| (m.path.expect("the path"), m.value.deref().clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("$.['a'][0].['b'][1]".to_string(), json!(2)),
+                ("$.['a'][1].['b'][0]".to_string(), json!(3))
+            ]
+        );
+        // the paths of the nested queries keep the whole prefix
+        let first = JsonPathInst::from_str("$[0]").expect("the path is correct");
+        let b = JsonPathInst::from_str("$.b").expect("the path is correct");
+        let nested = records.query(&json).expect("found")[1].find_within(&b)[0].find_within(&first);
+        assert_eq!(nested[0].path.as_deref(), Some("$.['a'][1].['b'][0]"));
+
+        let length = JsonPathInst::from_str("$.a.length()").expect("the path is correct");
+        let generated = length.query(&json).expect("found");
+        assert_eq!(generated[0].find_within(&b), vec![]);
+    }
+}
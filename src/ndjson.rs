@@ -0,0 +1,257 @@
+//! The aggregations over the streams of the json documents, one document per line (NDJSON, JSON Lines).
+//!
+//! The lines are read and evaluated one by one and the found numbers are folded into the [`Summary`]
+//! right away, so neither the documents nor the found elements are kept in the memory.
+//! The query ends with the aggregate like `$.metrics.latency_ms.avg()` that is computed over all the documents,
+//! the numbers of every document are found like the aggregate of the single document finds them:
+//! the only found array gives its elements and the values other than the numbers are skipped.
+//! The empty lines and the documents the path is not applicable to are skipped.
+//!
+//! ```
+//! use jsonpath_rust::ndjson::{aggregate, summarize};
+//! use jsonpath_rust::JsonPathInst;
+//! use std::str::FromStr;
+//!
+//! let lines = r#"{"metrics":{"latency_ms":12}}
+//! {"metrics":{"latency_ms":30}}
+//!
+//! {"metrics":{}}
+//! {"metrics":{"latency_ms":18}}"#;
+//!
+//! let path = JsonPathInst::from_str("$.metrics.latency_ms.avg()").unwrap();
+//! assert_eq!(aggregate(&path, lines.as_bytes()).unwrap(), Some(20.0));
+//!
+//! let path = JsonPathInst::from_str("$.metrics.latency_ms").unwrap();
+//! let summary = summarize(&path, lines.as_bytes()).unwrap();
+//! assert_eq!((summary.count(), summary.min(), summary.max()), (3, Some(12.0), Some(30.0)));
+//! ```
+use crate::parser::model::{Aggregate, Function, JsonPath};
+use crate::{JsonLike, JsonPathError, JsonPathInst};
+use serde_json::Value;
+use std::io::BufRead;
+use thiserror::Error;
+
+/// The reasons why the stream can not be aggregated
+#[derive(Error, Debug)]
+pub enum NdjsonError {
+    #[error("the input can not be read: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the line {line} is not a json document: {reason}")]
+    Parse { line: usize, reason: String },
+    #[error("the line {line} can not be evaluated: {error}")]
+    Evaluation { line: usize, error: JsonPathError },
+    #[error("the query `{0}` does not end with the aggregate like `.sum()`")]
+    NoAggregate(String),
+}
+
+/// The running statistics of the numbers, computed in one pass without keeping the numbers
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Summary {
+    count: usize,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+    mean: f64,
+    /// the sum of the squared differences from the mean
+    m2: f64,
+}
+
+impl Summary {
+    /// adds the number to the statistics
+    pub fn push(&mut self, n: f64) {
+        self.count += 1;
+        self.sum += n;
+        self.min = Some(self.min.map_or(n, |m| m.min(n)));
+        self.max = Some(self.max.map_or(n, |m| m.max(n)));
+        let delta = n - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (n - self.mean);
+    }
+
+    /// combines the statistics of the two parts of the stream, like the ones computed in parallel
+    pub fn merge(&mut self, other: &Summary) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = (self.count + other.count) as f64;
+        let delta = other.mean - self.mean;
+        self.m2 += other.m2 + delta * delta * self.count as f64 * other.count as f64 / count;
+        self.mean += delta * other.count as f64 / count;
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.zip(other.min).map(|(a, b)| a.min(b));
+        self.max = self.max.zip(other.max).map(|(a, b)| a.max(b));
+    }
+
+    /// the number of the numbers
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+
+    pub fn avg(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// the standard deviation of the population
+    pub fn stddev(&self) -> Option<f64> {
+        (self.count > 0).then(|| (self.m2 / self.count as f64).sqrt())
+    }
+
+    /// the value of the aggregate, nothing for no numbers like the aggregate of the single document
+    pub fn get(&self, aggregate: Aggregate) -> Option<f64> {
+        match aggregate {
+            Aggregate::Min => self.min(),
+            Aggregate::Max => self.max(),
+            Aggregate::Avg => self.avg(),
+            Aggregate::Stddev => self.stddev(),
+            Aggregate::Sum => (self.count > 0).then_some(self.sum),
+        }
+    }
+}
+
+/// computes the aggregate ending the query like `$.metrics.latency_ms.sum()` over all the lines,
+/// see the [module](self) for the details.
+/// Gives nothing if no numbers are found and [`NdjsonError::NoAggregate`] if the query has no aggregate at the end.
+pub fn aggregate<R: BufRead>(path: &JsonPathInst, reader: R) -> Result<Option<f64>, NdjsonError> {
+    let (prefix, aggregate) = split_aggregate(path);
+    let aggregate = aggregate.ok_or_else(|| NdjsonError::NoAggregate(path.to_string()))?;
+    Ok(summarize(&prefix, reader)?.get(aggregate))
+}
+
+/// collects the statistics of the numbers found by the query in all the lines,
+/// the aggregate ending the query is ignored
+pub fn summarize<R: BufRead>(path: &JsonPathInst, reader: R) -> Result<Summary, NdjsonError> {
+    let (path, _) = split_aggregate(path);
+    let mut summary = Summary::default();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let doc: Value = serde_json::from_str(&line).map_err(|e| NdjsonError::Parse {
+            line: i + 1,
+            reason: e.to_string(),
+        })?;
+        let found = match path.query(&doc) {
+            Ok(found) => found,
+            Err(JsonPathError::Inapplicable) => continue,
+            Err(error) => return Err(NdjsonError::Evaluation { line: i + 1, error }),
+        };
+        match found.as_slice() {
+            [single] => match single.value.as_array() {
+                Some(elems) => elems
+                    .iter()
+                    .filter_map(JsonLike::as_f64)
+                    .for_each(|n| summary.push(n)),
+                None => single
+                    .value
+                    .as_f64()
+                    .into_iter()
+                    .for_each(|n| summary.push(n)),
+            },
+            found => found
+                .iter()
+                .filter_map(|m| m.value.as_f64())
+                .for_each(|n| summary.push(n)),
+        }
+    }
+    Ok(summary)
+}
+
+/// the query without the aggregate at the end along with the aggregate
+fn split_aggregate(path: &JsonPathInst) -> (JsonPathInst, Option<Aggregate>) {
+    if let JsonPath::Chain(elems) = path.path() {
+        if let Some((JsonPath::Fn(Function::Aggregate(a)), init)) = elems.split_last() {
+            let prefix = JsonPathInst::from(JsonPath::Chain(init.to_vec()))
+                .with_options(path.options().clone());
+            return (prefix, Some(*a));
+        }
+    }
+    (path.clone(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ndjson::{aggregate, summarize, NdjsonError, Summary};
+    use crate::parser::model::Aggregate;
+    use crate::JsonPathInst;
+    use std::str::FromStr;
+
+    fn path(query: &str) -> JsonPathInst {
+        JsonPathInst::from_str(query).expect("the path is correct")
+    }
+
+    #[test]
+    fn summary_test() {
+        let mut all = Summary::default();
+        assert_eq!(all.get(Aggregate::Sum), None);
+        assert_eq!(all.get(Aggregate::Stddev), None);
+        let (mut left, mut right) = (Summary::default(), Summary::default());
+        for n in [2.0, 4.0, 4.0, 4.0] {
+            all.push(n);
+            left.push(n);
+        }
+        for n in [5.0, 5.0, 7.0, 9.0] {
+            all.push(n);
+            right.push(n);
+        }
+        assert_eq!(all.get(Aggregate::Avg), Some(5.0));
+        assert_eq!(all.get(Aggregate::Stddev), Some(2.0));
+        assert_eq!(all.get(Aggregate::Sum), Some(40.0));
+        left.merge(&right);
+        assert_eq!(left.count(), 8);
+        assert_eq!(left.min(), Some(2.0));
+        assert_eq!(left.max(), Some(9.0));
+        assert_eq!(left.avg(), Some(5.0));
+        assert_eq!(left.stddev(), Some(2.0));
+        let mut empty = Summary::default();
+        empty.merge(&all);
+        assert_eq!(empty, all);
+    }
+
+    #[test]
+    fn aggregate_test() {
+        let lines = "{\"a\":[1,2]}\n{\"a\":3}\n\n{\"b\":4}\n{\"a\":\"x\"}\n{\"a\":[10]}\n";
+        let agg = |q: &str| aggregate(&path(q), lines.as_bytes()).expect("the lines are correct");
+        assert_eq!(agg("$.a.sum()"), Some(16.0));
+        assert_eq!(agg("$.a.max()"), Some(10.0));
+        assert_eq!(agg("$.a.min()"), Some(1.0));
+        assert_eq!(agg("$..*.avg()"), Some(4.0));
+        assert_eq!(agg("$.c.sum()"), None);
+        assert_eq!(
+            summarize(&path("$.a"), lines.as_bytes())
+                .expect("the lines are correct")
+                .count(),
+            4
+        );
+
+        assert!(matches!(
+            aggregate(&path("$.a"), lines.as_bytes()),
+            Err(NdjsonError::NoAggregate(_))
+        ));
+        assert!(matches!(
+            aggregate(&path("$.a.sum()"), "{\"a\":1}\n{\"a\":\n".as_bytes()),
+            Err(NdjsonError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            aggregate(&path("$.a[?(@ == :x)].sum()"), "{\"a\":[1]}".as_bytes()),
+            Err(NdjsonError::Evaluation { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,683 @@
+//! The options of the evaluation and the limits protecting the parser and the evaluation.
+//!
+//! The options are set either one by one by the `with_*` methods of [`JsonPathInst`]
+//! or at once by [`JsonPathInst::with_options`] and [`find_with_options`].
+//!
+//! ```
+//! use jsonpath_rust::{find_with_options, EvalOptions, JsonPathInst, ResultOrder};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let data = json!({"b":[1],"a":[2]});
+//! let path = JsonPathInst::from_str("$['b','a'][*]").unwrap();
+//! let options = EvalOptions::default().with_order(ResultOrder::Document);
+//!
+//! let found = find_with_options(&path, &data, &options).unwrap();
+//! assert_eq!(*found[0].value, json!(2));
+//! ```
+use crate::parser::model::JsonPath;
+use crate::parser::visitor::{walk_path, Visitor};
+use crate::path::{CancellationToken, Coercion, Comparator, JsonLike, Rules, StringLength};
+use crate::JsonPathValue::{NewValue, NoValue, Slice};
+use crate::{distinct_nodes, document_order, JsonPathError, JsonPathInst, JsonPathValue, Match};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The options of the evaluation gathered in one place,
+/// set either by the `with_*` methods of [`JsonPathInst`] or at once by [`JsonPathInst::with_options`]
+/// and [`find_with_options`].
+/// The new options may be added later, therefore it is built from the default one.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{EvalOptions, ResultOrder};
+///
+/// let options = EvalOptions::default()
+///     .with_strict(true)
+///     .with_order(ResultOrder::Document)
+///     .with_max_depth(64);
+/// assert!(options.strict);
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Clone, Default)]
+pub struct EvalOptions {
+    /// fails with [`JsonPathError::Strict`] on the type mismatches, see [`JsonPathInst::find_strict`]
+    pub strict: bool,
+    /// the order of the found elements, see [`JsonPathInst::with_order`]
+    pub order: ResultOrder,
+    /// yields every element once, see [`JsonPathInst::with_distinct_nodes`]
+    pub distinct_nodes: bool,
+    /// see [`JsonPathInst::with_limits`]
+    pub limits: EvalLimits,
+    /// checked by [`find_with_options`] only, see [`JsonPathInst::with_max_depth`]
+    pub max_depth: Option<usize>,
+    /// decides how the filters compare the elements, see [`Comparator`]
+    pub comparator: Option<Arc<dyn Comparator>>,
+    /// converts the scalars of the different types before comparing them, see [`Coercion`]
+    pub coercion: Coercion,
+    /// stops the evaluation from another thread, see [`CancellationToken`]
+    pub cancellation: Option<CancellationToken>,
+    /// counts the strings for `length()` and `size`, see [`StringLength`]
+    pub string_length: StringLength,
+    /// follows the original javascript implementation, see [`JsonPathInst::with_goessner`]
+    pub goessner: bool,
+}
+
+/// The comparators are equal only if they are the same one.
+impl PartialEq for EvalOptions {
+    fn eq(&self, other: &Self) -> bool {
+        let same_comparator = match (&self.comparator, &other.comparator) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        self.strict == other.strict
+            && self.order == other.order
+            && self.distinct_nodes == other.distinct_nodes
+            && self.limits == other.limits
+            && self.max_depth == other.max_depth
+            && self.coercion == other.coercion
+            && self.cancellation == other.cancellation
+            && self.string_length == other.string_length
+            && self.goessner == other.goessner
+            && same_comparator
+    }
+}
+
+impl Eq for EvalOptions {}
+
+/// The comparator, the cancellation token and the custom string length are not hashed,
+/// the options differing only by them fall into the same bucket.
+impl Hash for EvalOptions {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.strict.hash(state);
+        self.order.hash(state);
+        self.distinct_nodes.hash(state);
+        self.limits.hash(state);
+        self.max_depth.hash(state);
+        self.coercion.hash(state);
+        std::mem::discriminant(&self.string_length).hash(state);
+        self.goessner.hash(state);
+    }
+}
+
+impl EvalOptions {
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn with_order(mut self, order: ResultOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_distinct_nodes(mut self, distinct_nodes: bool) -> Self {
+        self.distinct_nodes = distinct_nodes;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: EvalLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn with_comparator(mut self, comparator: Arc<dyn Comparator>) -> Self {
+        self.comparator = Some(comparator);
+        self
+    }
+
+    pub fn with_coercion(mut self, coercion: Coercion) -> Self {
+        self.coercion = coercion;
+        self
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn with_string_length(mut self, string_length: StringLength) -> Self {
+        self.string_length = string_length;
+        self
+    }
+
+    pub fn with_goessner(mut self, goessner: bool) -> Self {
+        self.goessner = goessner;
+        self
+    }
+
+    /// the options differing from the default ones as json, see the serialization of [`JsonPathInst`]
+    pub(crate) fn to_json(&self) -> Result<Value, String> {
+        let default = EvalOptions::default();
+        if self.comparator.is_some() {
+            return Err("the comparator can not be serialized".to_string());
+        }
+        if self.cancellation.is_some() {
+            return Err("the cancellation token can not be serialized".to_string());
+        }
+        let mut options = serde_json::Map::new();
+        let mut put = |key: &str, value: Value| {
+            options.insert(key.to_string(), value);
+        };
+        if self.strict {
+            put("strict", Value::Bool(true));
+        }
+        if self.order == ResultOrder::Document {
+            put("order", Value::from("document"));
+        }
+        if self.distinct_nodes {
+            put("distinct_nodes", Value::Bool(true));
+        }
+        if self.limits != default.limits {
+            let limits = &self.limits;
+            let mut fields = serde_json::Map::new();
+            for (key, limit) in [
+                ("max_nodes", limits.max_nodes),
+                ("max_matches", limits.max_matches),
+                ("max_output_bytes", limits.max_output_bytes),
+                ("timeout_ms", limits.timeout.map(|t| t.as_millis() as usize)),
+            ] {
+                if let Some(limit) = limit {
+                    fields.insert(key.to_string(), Value::from(limit));
+                }
+            }
+            put("limits", Value::Object(fields));
+        }
+        if let Some(depth) = self.max_depth {
+            put("max_depth", Value::from(depth));
+        }
+        match self.coercion {
+            Coercion::Strict => {}
+            Coercion::Lenient => put("coercion", Value::from("lenient")),
+            Coercion::JsLike => put("coercion", Value::from("js_like")),
+        }
+        match self.string_length {
+            StringLength::Chars => {}
+            StringLength::Bytes => put("string_length", Value::from("bytes")),
+            StringLength::Custom(_) => {
+                return Err("the custom string length can not be serialized".to_string())
+            }
+        }
+        if self.goessner {
+            put("goessner", Value::Bool(true));
+        }
+        Ok(Value::Object(options))
+    }
+
+    /// reads the options written by [`EvalOptions::to_json`], the absent options are the default ones
+    pub(crate) fn from_json(json: &Value) -> Result<Self, String> {
+        let fields = json.as_object().ok_or("the options are not an object")?;
+        let mut options = EvalOptions::default();
+        let flag = |v: &Value, key: &str| v.as_bool().ok_or(format!("`{key}` is not a boolean"));
+        let number = |v: &Value, key: &str| {
+            v.as_u64()
+                .map(|n| n as usize)
+                .ok_or(format!("`{key}` is not a number"))
+        };
+        for (key, v) in fields {
+            match (key.as_str(), v.as_str()) {
+                ("strict", _) => options.strict = flag(v, key)?,
+                ("order", Some("selector")) => options.order = ResultOrder::Selector,
+                ("order", Some("document")) => options.order = ResultOrder::Document,
+                ("distinct_nodes", _) => options.distinct_nodes = flag(v, key)?,
+                ("limits", _) => {
+                    let limits = v.as_object().ok_or("`limits` is not an object")?;
+                    for (key, v) in limits {
+                        let limit = Some(number(v, key)?);
+                        match key.as_str() {
+                            "max_nodes" => options.limits.max_nodes = limit,
+                            "max_matches" => options.limits.max_matches = limit,
+                            "max_output_bytes" => options.limits.max_output_bytes = limit,
+                            "timeout_ms" => {
+                                options.limits.timeout =
+                                    limit.map(|ms| Duration::from_millis(ms as u64))
+                            }
+                            _ => return Err(format!("unknown limit `{key}`")),
+                        }
+                    }
+                }
+                ("max_depth", _) => options.max_depth = Some(number(v, key)?),
+                ("coercion", Some("strict")) => options.coercion = Coercion::Strict,
+                ("coercion", Some("lenient")) => options.coercion = Coercion::Lenient,
+                ("coercion", Some("js_like")) => options.coercion = Coercion::JsLike,
+                ("string_length", Some("chars")) => options.string_length = StringLength::Chars,
+                ("string_length", Some("bytes")) => options.string_length = StringLength::Bytes,
+                ("goessner", _) => options.goessner = flag(v, key)?,
+                _ => return Err(format!("unknown option `{key}`: {v}")),
+            }
+        }
+        Ok(options)
+    }
+
+    /// the rules the nodes of the path follow, Goessner compares the elements like javascript
+    pub(crate) fn rules(&self) -> Rules {
+        Rules {
+            comparator: self.comparator.clone(),
+            coercion: if self.goessner {
+                Coercion::JsLike
+            } else {
+                self.coercion
+            },
+            string_length: self.string_length.clone(),
+            goessner: self.goessner,
+            warnings: None,
+        }
+    }
+}
+
+/// orders and deduplicates the found elements as the options tell
+pub(crate) fn arrange<'a, T: JsonLike, P>(
+    value: &'a T,
+    found: Vec<JsonPathValue<'a, T, P>>,
+    options: &EvalOptions,
+) -> Vec<JsonPathValue<'a, T, P>> {
+    let found = match options.order {
+        ResultOrder::Selector => found,
+        ResultOrder::Document => document_order(value, found),
+    };
+    if options.distinct_nodes {
+        distinct_nodes(found)
+    } else {
+        found
+    }
+}
+
+/// finds the elements like [`JsonPathInst::query`] does with the given options instead of the options of the path.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_options, EvalOptions, JsonPathError, JsonPathInst};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"orders":[{"id":"1"},{"id":2}]});
+/// let path = JsonPathInst::from_str("$.orders[?(@.id == 2)].id").unwrap();
+///
+/// let found = find_with_options(&path, &data, &EvalOptions::default()).unwrap();
+/// assert_eq!(*found[0].value, json!(2));
+///
+/// let strict = EvalOptions::default().with_strict(true);
+/// assert!(matches!(
+///     find_with_options(&path, &data, &strict),
+///     Err(JsonPathError::Strict(_))
+/// ));
+/// ```
+pub fn find_with_options<'a>(
+    path: &'a JsonPathInst,
+    json: &'a Value,
+    options: &EvalOptions,
+) -> Result<Vec<Match<'a>>, JsonPathError> {
+    path.query_with(json, options)
+}
+
+/// The limit of the evaluation set by [`EvalLimits`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Limit {
+    Nodes,
+    Matches,
+    OutputBytes,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Nodes => write!(f, "the visited nodes"),
+            Limit::Matches => write!(f, "the matches"),
+            Limit::OutputBytes => write!(f, "the output bytes"),
+        }
+    }
+}
+
+/// The order of the found elements.
+///
+/// In the order of the selectors the elements come as every step of the path yields them:
+/// - the wildcards, the slices, the filters and the descent `..key` follow the document,
+///   the fields are visited in the order of [`JsonLike::entries`] (sorted by the key for [`Value`]
+///   unless `serde_json/preserve_order` is enabled),
+/// - the unions like `[3,1]` or `['b','a']` follow the query,
+/// - the descent filter `..[?(...)]` yields the children of every descendant grouped by their parents,
+///   therefore the children of an object come before the elements nested deeper in its preceding fields,
+/// - the combinations like `$..*[?(...)]` may yield the same element several times,
+///   see [`JsonPathInst::with_distinct_nodes`].
+///
+/// In the document order the elements of the json are sorted by their position in the depth-first walk
+/// (the parent before its children, the children in the order of [`JsonLike::entries`]).
+/// The repeated elements stay next to each other and the generated values (like length)
+/// stay after the element preceding them. Sorting walks the whole document once.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum ResultOrder {
+    /// the order the steps of the path yield the elements
+    #[default]
+    Selector,
+    /// the order of the elements in the document
+    Document,
+}
+
+/// The limits protecting the evaluation of the untrusted queries against the untrusted documents.
+/// The absent limit is not checked.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct EvalLimits {
+    /// the number of the elements passed between the steps of the path and gone through by the descent `..`
+    pub max_nodes: Option<usize>,
+    /// the number of the found elements
+    pub max_matches: Option<usize>,
+    /// the total size of the found elements serialized into json
+    pub max_output_bytes: Option<usize>,
+    /// the time the evaluation may take, it is checked while the nodes are visited
+    /// and stops the evaluation with [`JsonPathError::TimedOut`]
+    pub timeout: Option<Duration>,
+}
+
+impl EvalLimits {
+    pub(crate) fn check_output<P>(
+        &self,
+        found: &[JsonPathValue<Value, P>],
+    ) -> Result<(), JsonPathError> {
+        if let Some(max) = self.max_matches {
+            if found.iter().filter(|v| v.has_value()).count() > max {
+                return Err(JsonPathError::LimitExceeded {
+                    limit: Limit::Matches,
+                    max,
+                });
+            }
+        }
+        if let Some(max) = self.max_output_bytes {
+            let mut writer = LimitedWriter { left: max };
+            let fits = found.iter().all(|v| match v {
+                Slice(v, _) => serde_json::to_writer(&mut writer, v).is_ok(),
+                NewValue(v) => serde_json::to_writer(&mut writer, v).is_ok(),
+                NoValue => true,
+            });
+            if !fits {
+                return Err(JsonPathError::LimitExceeded {
+                    limit: Limit::OutputBytes,
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The syntax of the query text, see [`JsonPathInst::from_str_with_dialect`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Dialect {
+    /// the syntax of the library
+    #[default]
+    Standard,
+    /// the expression of `kubectl -o jsonpath=...` like `{.items[*].metadata.name}` or `.items[0]`
+    /// starting without `$`, the templates with the text and `{range}` are rendered by [`K8sTemplate`](crate::k8s::K8sTemplate)
+    Kubernetes,
+    /// the syntax of Jayway JsonPath with the regexes like `/^ab.*/i` and the operators like `anyof`,
+    /// the results of Jayway are given by [`jayway::read`](crate::jayway::read)
+    Jayway,
+    /// the syntax of the original javascript implementation of Goessner with the script expressions like `[(@.length-1)]`,
+    /// the parsed path evaluates like the original, see [`goessner`](crate::goessner)
+    Goessner,
+}
+
+/// The limits protecting the parser against the untrusted queries, see [`JsonPathInst::from_str_with_limits`].
+/// The absent limit is not checked.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ParseLimits {
+    /// the length of the query in bytes
+    pub max_length: Option<usize>,
+    /// the depth of the nested brackets and parentheses, like the filters inside the filters
+    pub max_nesting: Option<usize>,
+    /// the number of the steps like `.key`, `[0]` or `[?(...)]` including the steps of the paths inside the filters
+    pub max_selectors: Option<usize>,
+    /// the length of every regex of `~=` in bytes
+    pub max_regex_len: Option<usize>,
+}
+
+/// the depth of the nested brackets and parentheses outside the string literals
+pub(crate) fn nesting(query: &str) -> usize {
+    let (mut depth, mut max) = (0usize, 0);
+    let mut quote: Option<char> = None;
+    let mut chars = query.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(') => {
+                depth += 1;
+                max = max.max(depth);
+            }
+            (None, ']' | ')') => depth = depth.saturating_sub(1),
+            (None, _) => {}
+        }
+    }
+    max
+}
+
+/// the number of the steps of the path and of the paths inside its filters
+pub(crate) fn selectors(path: &JsonPath) -> usize {
+    struct Selectors(usize);
+    impl Visitor for Selectors {
+        fn visit_path(&mut self, path: &JsonPath) {
+            if !matches!(
+                path,
+                JsonPath::Chain(_) | JsonPath::Root | JsonPath::Current(_) | JsonPath::Empty
+            ) {
+                self.0 += 1;
+            }
+            walk_path(self, path)
+        }
+    }
+    let mut selectors = Selectors(0);
+    selectors.visit_path(path);
+    selectors.0
+}
+
+/// The writer counting the bytes and failing as soon as there are more of them than allowed.
+struct LimitedWriter {
+    left: usize,
+}
+
+impl std::io::Write for LimitedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.left = self
+            .left
+            .checked_sub(buf.len())
+            .ok_or_else(|| std::io::Error::other("the output limit is exceeded"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::Nested;
+    use crate::{
+        find_values, find_with_options, EvalLimits, EvalOptions, JsonPathError, JsonPathInst,
+    };
+    use serde_json::{json, Value};
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    #[test]
+    fn find_with_options_test() {
+        let json = json!({"a":[[1,2],[2,"x"]]});
+        let path = JsonPathInst::from_str("$..[?(@ > 1)]").expect("the path is correct");
+        let options = crate::EvalOptions::default()
+            .with_distinct_nodes(true)
+            .with_order(crate::ResultOrder::Document);
+        let found = crate::find_with_options(&path, &json, &options).unwrap();
+        let paths: Vec<_> = found.iter().map(|m| m.path.as_deref()).collect();
+        assert_eq!(paths, vec![Some("$.['a'][0][1]"), Some("$.['a'][1][0]")]);
+
+        let strict = JsonPathInst::from_str("$.a[1][?(@ > 1)]")
+            .expect("the path is correct")
+            .with_options(options.clone().with_strict(true));
+        assert_eq!(strict.options(), &options.clone().with_strict(true));
+        assert_eq!(
+            strict.query(&json).unwrap_err().to_string(),
+            "the strict evaluation has found the problems: \
+             $.['a'][1][1]: the operator `>` compares string with number"
+        );
+        let shallow = options.with_max_depth(1);
+        assert_eq!(
+            crate::find_with_options(&path, &json, &shallow),
+            Err(JsonPathError::DepthLimitExceeded(1))
+        );
+        assert_eq!(path.query(&json).map(|m| m.len()), Ok(2));
+    }
+
+    #[test]
+    fn comparator_test() {
+        #[derive(Debug)]
+        struct CaseInsensitive;
+        impl crate::Comparator for CaseInsensitive {
+            fn eq(&self, left: crate::Scalar, right: crate::Scalar) -> Option<bool> {
+                match (left, right) {
+                    (crate::Scalar::String(l), crate::Scalar::String(r)) => {
+                        Some(l.eq_ignore_ascii_case(r))
+                    }
+                    _ => None,
+                }
+            }
+        }
+
+        let json = json!([{"c":"EU"},{"c":"us"},{"c":1}]);
+        let options = crate::EvalOptions::default().with_comparator(Arc::new(CaseInsensitive));
+        let count = |q: &str, options: &crate::EvalOptions| {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            crate::find_with_options(&path, &json, options)
+                .unwrap()
+                .len()
+        };
+        assert_eq!(count("$[?(@.c == 'eu')]", &options), 1);
+        assert_eq!(count("$[?(@.c != 'eu')]", &options), 2);
+        assert_eq!(count("$[?(@.c in ['eu','US'])]", &options), 2);
+        assert_eq!(count("$[?(@.c == 1)]", &options), 1);
+        assert_eq!(
+            count("$[?(@.c == 'eu')]", &crate::EvalOptions::default()),
+            0
+        );
+        assert_ne!(
+            options,
+            options.clone().with_comparator(Arc::new(CaseInsensitive))
+        );
+        assert_eq!(options, options.clone());
+    }
+
+    #[test]
+    fn interrupt_test() {
+        let json = Value::Array((0..5000).map(|i| json!({ "i": i })).collect());
+        let path = JsonPathInst::from_str("$..*").expect("the path is correct");
+        let limits = EvalLimits {
+            timeout: Some(std::time::Duration::ZERO),
+            ..EvalLimits::default()
+        };
+        let timed = crate::EvalOptions::default().with_limits(limits);
+        assert_eq!(
+            crate::find_with_options(&path, &json, &timed),
+            Err(JsonPathError::TimedOut(std::time::Duration::ZERO))
+        );
+        assert_eq!(
+            JsonPathError::TimedOut(std::time::Duration::from_millis(5)).to_string(),
+            "the evaluation has not finished in 5ms"
+        );
+
+        let token = crate::CancellationToken::new();
+        let cancellable = crate::EvalOptions::default().with_cancellation(token.clone());
+        assert_eq!(
+            crate::find_with_options(&path, &json, &cancellable).map(|m| m.len()),
+            Ok(10000)
+        );
+        std::thread::spawn(move || token.cancel()).join().unwrap();
+        assert_eq!(
+            crate::find_with_options(&path, &json, &cancellable),
+            Err(JsonPathError::Cancelled)
+        );
+        assert_ne!(
+            cancellable,
+            crate::EvalOptions::default().with_cancellation(crate::CancellationToken::new())
+        );
+
+        // the strict check is the part of the evaluation and stops along with it
+        let strict = cancellable.with_strict(true);
+        let lenient = JsonPathInst::from_str("$[*].i[0]").expect("the path is correct");
+        assert_eq!(
+            crate::find_with_options(&lenient, &json, &strict),
+            Err(JsonPathError::Cancelled)
+        );
+    }
+
+    #[test]
+    fn parse_limits_test() {
+        let parse =
+            |q: &str, limits: crate::ParseLimits| JsonPathInst::from_str_with_limits(q, &limits);
+        let limits = crate::ParseLimits {
+            max_length: Some(40),
+            max_nesting: Some(3),
+            max_selectors: Some(5),
+            max_regex_len: Some(4),
+        };
+        assert!(parse("$.a[?(@.b ~= 'x[(]')].c", limits).is_ok());
+        assert!(parse("$.a[?(@.b == '((((((')]", limits).is_ok());
+        assert_eq!(
+            parse(&format!("$.{}", "a".repeat(40)), limits).unwrap_err(),
+            "the query is longer than 40 bytes"
+        );
+        assert_eq!(
+            parse("$[[[[0]]]]", limits).unwrap_err(),
+            "the query is nested deeper than 3"
+        );
+        assert_eq!(
+            parse("$.a.b.c[?(@.d.e)]", limits).unwrap_err(),
+            "the query has more than 5 selectors"
+        );
+        assert_eq!(
+            parse("$[?(@ ~= 'abcde')]", limits).unwrap_err(),
+            "the regex `abcde` is longer than 4 bytes"
+        );
+        assert!(parse("$.a[", crate::ParseLimits::default()).is_err());
+        let deep = format!("$.a{}", "[?(@.b".repeat(100_000) + &")]".repeat(100_000));
+        assert!(parse(&deep, limits).is_err());
+    }
+
+    #[test]
+    fn max_depth_test() {
+        let nested = Nested::new(json!({"x": 1}), 50_000);
+        let json = &nested.0;
+        let path = JsonPathInst::from_str("$..x").expect("the path is correct");
+        assert_eq!(find_values(&path, json), vec![&json!(1)]);
+
+        let path = path.with_max_depth(100);
+        assert_eq!(
+            path.query(json),
+            Err(JsonPathError::DepthLimitExceeded(100))
+        );
+        let options = EvalOptions::default().with_max_depth(100);
+        let unlimited = JsonPathInst::from_str("$..x").expect("the path is correct");
+        assert_eq!(
+            find_with_options(&unlimited, json, &options).map(|v| v.len()),
+            Err(JsonPathError::DepthLimitExceeded(100))
+        );
+        // the functions returning no error do not check the depth
+        assert_eq!(find_values(&path, json), vec![&json!(1)]);
+        let shallow = json!([[{"x": 2}]]);
+        assert_eq!(path.query(&shallow).map(|v| v.len()), Ok(1));
+
+        let path = JsonPathInst::from_str("$[0][?(@[0])]")
+            .expect("the path is correct")
+            .with_max_depth(100);
+        assert_eq!(path.query(json).map(|v| v.len()), Ok(1));
+    }
+}
@@ -0,0 +1,334 @@
+//! The builder to construct the [JsonPath] programmatically, avoiding the string formatting.
+//!
+//! ```
+//! use jsonpath_rust::parser::builder::{field, Cmp};
+//! use jsonpath_rust::parser::model::JsonPath;
+//! use jsonpath_rust::parser::parser::parse_json_path;
+//!
+//! let path = JsonPath::root()
+//!     .field("store")
+//!     .descent("book")
+//!     .filter(Cmp::lt(field("price"), 10));
+//!
+//! assert_eq!(path, parse_json_path("$.store..book[?(@.price < 10)]").unwrap());
+//! ```
+use crate::parser::model::{
+    Aggregate, FilterExpression, FilterSign, Function, JsonPath, JsonPathIndex, Operand,
+};
+use serde_json::Value;
+use std::ops::Not;
+
+impl JsonPath {
+    /// starts the path from the root `$`
+    pub fn root() -> Self {
+        JsonPath::Chain(vec![JsonPath::Root])
+    }
+
+    /// starts the path from the current element `@`. It is used inside the filters.
+    pub fn at() -> Self {
+        JsonPath::Current(Box::new(JsonPath::Empty))
+    }
+
+    /// appends `['key']` matching the key literally even if it contains `*`
+    pub fn field(self, key: &str) -> Self {
+        self.push(JsonPath::Field(key.to_string()))
+    }
+
+    /// appends `..key`
+    pub fn descent(self, key: &str) -> Self {
+        self.push(JsonPath::Descent(key.to_string()))
+    }
+
+    /// appends `..*`
+    pub fn descent_all(self) -> Self {
+        self.push(JsonPath::DescentW)
+    }
+
+    /// appends `[*]`
+    pub fn wildcard(self) -> Self {
+        self.push(JsonPath::Wildcard)
+    }
+
+    /// appends `[index]`
+    pub fn index(self, index: usize) -> Self {
+        self.push(JsonPath::Index(JsonPathIndex::Single(Value::from(index))))
+    }
+
+    /// appends `[index (,index)]`
+    pub fn indexes(self, indexes: &[usize]) -> Self {
+        self.push(JsonPath::Index(JsonPathIndex::UnionIndex(
+            indexes.iter().map(|i| Value::from(*i)).collect(),
+        )))
+    }
+
+    /// appends `['key' (,'key')]`
+    pub fn keys(self, keys: &[&str]) -> Self {
+        self.push(JsonPath::Index(JsonPathIndex::UnionKeys(
+            keys.iter().map(|k| k.to_string()).collect(),
+        )))
+    }
+
+    /// appends `[start:end:step]`
+    pub fn slice(self, start: i32, end: i32, step: usize) -> Self {
+        self.push(JsonPath::Index(JsonPathIndex::Slice(start, end, step)))
+    }
+
+    /// appends `[?(expression)]`
+    pub fn filter(self, expression: FilterExpression) -> Self {
+        self.push(JsonPath::Index(JsonPathIndex::Filter(expression)))
+    }
+
+    /// appends `.length()`
+    pub fn length(self) -> Self {
+        self.push(JsonPath::Fn(Function::Length))
+    }
+
+    /// appends the aggregate like `.min()` or `.sum()`
+    pub fn aggregate(self, aggregate: Aggregate) -> Self {
+        self.push(JsonPath::Fn(Function::Aggregate(aggregate)))
+    }
+
+    /// appends `.keys()`
+    pub fn key_names(self) -> Self {
+        self.push(JsonPath::Fn(Function::Keys))
+    }
+
+    fn push(self, next: JsonPath) -> Self {
+        match self {
+            JsonPath::Chain(mut elems) => {
+                elems.push(next);
+                JsonPath::Chain(elems)
+            }
+            JsonPath::Current(tail) => match *tail {
+                JsonPath::Empty => JsonPath::current(JsonPath::Chain(vec![next])),
+                tail => JsonPath::current(tail.push(next)),
+            },
+            JsonPath::Empty => JsonPath::Chain(vec![next]),
+            other => JsonPath::Chain(vec![other, next]),
+        }
+    }
+}
+
+/// the field of the current element like `@.key`
+pub fn field(key: &str) -> Operand {
+    JsonPath::at().field(key).into()
+}
+
+/// the current element `@`
+pub fn current() -> Operand {
+    JsonPath::at().into()
+}
+
+/// the position of the current element in the filtered array `@index`
+pub fn position() -> Operand {
+    Operand::Index
+}
+
+/// the key of the current element `@property`
+pub fn property() -> Operand {
+    Operand::Property
+}
+
+/// the normalized path of the current element `@path`
+pub fn node_path() -> Operand {
+    Operand::Path
+}
+
+/// The constructors of the filter expressions like `@.price < 10`
+pub struct Cmp;
+
+impl Cmp {
+    pub fn atom(
+        left: impl Into<Operand>,
+        sign: FilterSign,
+        right: impl Into<Operand>,
+    ) -> FilterExpression {
+        FilterExpression::Atom(left.into(), sign, right.into())
+    }
+    pub fn eq(left: impl Into<Operand>, right: impl Into<Operand>) -> FilterExpression {
+        Cmp::atom(left, FilterSign::Equal, right)
+    }
+    pub fn ne(left: impl Into<Operand>, right: impl Into<Operand>) -> FilterExpression {
+        Cmp::atom(left, FilterSign::Unequal, right)
+    }
+    pub fn lt(left: impl Into<Operand>, right: impl Into<Operand>) -> FilterExpression {
+        Cmp::atom(left, FilterSign::Less, right)
+    }
+    pub fn le(left: impl Into<Operand>, right: impl Into<Operand>) -> FilterExpression {
+        Cmp::atom(left, FilterSign::LeOrEq, right)
+    }
+    pub fn gt(left: impl Into<Operand>, right: impl Into<Operand>) -> FilterExpression {
+        Cmp::atom(left, FilterSign::Greater, right)
+    }
+    pub fn ge(left: impl Into<Operand>, right: impl Into<Operand>) -> FilterExpression {
+        Cmp::atom(left, FilterSign::GrOrEq, right)
+    }
+    pub fn regex(left: impl Into<Operand>, right: &str) -> FilterExpression {
+        Cmp::atom(left, FilterSign::Regex, right)
+    }
+    pub fn inside(left: impl Into<Operand>, right: impl Into<Operand>) -> FilterExpression {
+        Cmp::atom(left, FilterSign::In, right)
+    }
+    pub fn exists(op: impl Into<Operand>) -> FilterExpression {
+        FilterExpression::exists(op.into())
+    }
+}
+
+impl FilterExpression {
+    /// combines the expressions with `&&`
+    pub fn and(self, other: FilterExpression) -> FilterExpression {
+        FilterExpression::And(Box::new(self), Box::new(other))
+    }
+    /// combines the expressions with `||`
+    pub fn or(self, other: FilterExpression) -> FilterExpression {
+        FilterExpression::Or(Box::new(self), Box::new(other))
+    }
+    /// combines the expressions with `xor`
+    pub fn xor(self, other: FilterExpression) -> FilterExpression {
+        FilterExpression::Xor(Box::new(self), Box::new(other))
+    }
+}
+
+impl Not for FilterExpression {
+    type Output = FilterExpression;
+
+    fn not(self) -> Self::Output {
+        FilterExpression::Not(Box::new(self))
+    }
+}
+
+impl From<JsonPath> for Operand {
+    fn from(value: JsonPath) -> Self {
+        match value {
+            JsonPath::Chain(_) => Operand::Dynamic(Box::new(value)),
+            other => Operand::Dynamic(Box::new(JsonPath::Chain(vec![other]))),
+        }
+    }
+}
+
+impl From<Value> for Operand {
+    fn from(value: Value) -> Self {
+        Operand::Static(value)
+    }
+}
+
+impl From<&str> for Operand {
+    fn from(value: &str) -> Self {
+        Operand::Static(Value::from(value))
+    }
+}
+
+impl From<String> for Operand {
+    fn from(value: String) -> Self {
+        Operand::Static(Value::from(value))
+    }
+}
+
+impl From<i64> for Operand {
+    fn from(value: i64) -> Self {
+        Operand::Static(Value::from(value))
+    }
+}
+
+impl From<i32> for Operand {
+    fn from(value: i32) -> Self {
+        Operand::Static(Value::from(value))
+    }
+}
+
+impl From<f64> for Operand {
+    fn from(value: f64) -> Self {
+        Operand::Static(Value::from(value))
+    }
+}
+
+impl From<bool> for Operand {
+    fn from(value: bool) -> Self {
+        Operand::Static(Value::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::builder::{current, field, Cmp};
+    use crate::parser::model::{Aggregate, JsonPath};
+    use crate::parser::parser::parse_json_path;
+    use serde_json::json;
+
+    fn test(built: JsonPath, expected: &str) {
+        assert_eq!(
+            built,
+            parse_json_path(expected).expect("the path is correct")
+        );
+    }
+
+    #[test]
+    fn chain_test() {
+        test(
+            JsonPath::root()
+                .field("store")
+                .descent("book")
+                .wildcard()
+                .index(1)
+                .indexes(&[1, 2])
+                .keys(&["a", "b"])
+                .slice(1, -1, 2)
+                .descent_all()
+                .length(),
+            "$.store..book[*][1][1,2]['a','b'][1:-1:2]..*.length()",
+        );
+        test(
+            JsonPath::root().field("prices").aggregate(Aggregate::Avg),
+            "$.prices.avg()",
+        );
+        test(JsonPath::root().field("a").key_names(), "$.a.keys()");
+        // the keys are taken literally, the star of the quoted key is escaped to be the star itself
+        test(
+            JsonPath::root().field("a*").descent("b*"),
+            r"$['a\*']..['b\*']",
+        );
+    }
+
+    #[test]
+    fn filter_test() {
+        test(
+            JsonPath::root().filter(Cmp::lt(field("price"), 10)),
+            "$[?(@.price < 10)]",
+        );
+        test(
+            JsonPath::root().filter(
+                Cmp::eq(field("verb"), "T")
+                    .or(Cmp::gt(field("size"), 0).and(Cmp::le(field("size"), 10.5))),
+            ),
+            "$[?(@.verb == 'T' || @.size > 0 && @.size <= 10.5)]",
+        );
+        test(
+            JsonPath::root().filter(!Cmp::exists(JsonPath::at().field("a").index(0))),
+            "$[?(!@.a[0])]",
+        );
+        test(
+            JsonPath::root().filter(Cmp::inside(current(), json!(["a", "b"]))),
+            "$[?(@ in ['a','b'])]",
+        );
+        test(
+            JsonPath::root().filter(Cmp::eq(field("id"), JsonPath::root().field("id"))),
+            "$[?(@.id == $.id)]",
+        );
+    }
+
+    #[test]
+    fn quoted_key_test() {
+        let key = "x']..['y";
+        let built = JsonPath::root()
+            .field(key)
+            .descent("it's")
+            .keys(&[key, "a\\b"])
+            .filter(Cmp::eq(field(key), "it's"));
+        let printed = built.to_string();
+        assert_eq!(
+            printed,
+            r"$['x\']..[\'y']..['it\'s']['x\']..[\'y','a\\b'][?(@['x\']..[\'y'] == 'it\'s')]"
+        );
+        test(built, &printed);
+    }
+}
@@ -1,3 +1,4 @@
+use pest::error::{ErrorVariant, InputLocation, LineColLocation};
 use pest::iterators::Pairs;
 use thiserror::Error;
 
@@ -6,7 +7,7 @@ use super::parser::Rule;
 #[derive(Error, Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum JsonPathParserError<'a> {
-    #[error("Failed to parse rule: {0}")]
+    #[error("Failed to parse rule: {}", render_pest_error(.0))]
     PestError(#[from] pest::error::Error<Rule>),
     #[error("Failed to parse JSON: {0}")]
     JsonParsingError(#[from] serde_json::Error),
@@ -21,3 +22,226 @@ pub enum JsonPathParserError<'a> {
 pub fn parser_err(cause: &str) -> JsonPathParserError<'_> {
     JsonPathParserError::ParserError(format!("Failed to parse JSONPath: {cause}"))
 }
+
+impl<'a> JsonPathParserError<'a> {
+    /// The byte offset in the query where the parsing failed
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            JsonPathParserError::PestError(e) => match e.location {
+                InputLocation::Pos(pos) => Some(pos),
+                InputLocation::Span((start, _)) => Some(start),
+            },
+            _ => None,
+        }
+    }
+
+    /// The line and the column (starting from 1) in the query where the parsing failed
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        match self {
+            JsonPathParserError::PestError(e) => match e.line_col {
+                LineColLocation::Pos(pos) => Some(pos),
+                LineColLocation::Span(start, _) => Some(start),
+            },
+            _ => None,
+        }
+    }
+
+    /// The human-readable tokens that would have been valid at the failure position
+    pub fn expected(&self) -> Vec<&'static str> {
+        match self {
+            JsonPathParserError::PestError(e) => pest_expected(e),
+            _ => vec![],
+        }
+    }
+
+    /// The hint for the common mistakes like `=` instead of `==` or unbalanced brackets
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            JsonPathParserError::PestError(e) => pest_suggestion(e),
+            _ => None,
+        }
+    }
+
+    /// The token of the query that could not be parsed.
+    /// Returns `None` if the query ended unexpectedly.
+    pub fn token(&self) -> Option<String> {
+        match self {
+            JsonPathParserError::PestError(e) => pest_token(e),
+            _ => None,
+        }
+    }
+}
+
+fn pest_token(e: &pest::error::Error<Rule>) -> Option<String> {
+    let col = match e.line_col {
+        LineColLocation::Pos((_, col)) | LineColLocation::Span((_, col), _) => col,
+    };
+    let token: String = e
+        .line()
+        .chars()
+        .skip(col - 1)
+        .take_while(|c| !c.is_whitespace())
+        .collect();
+    if token.is_empty() {
+        None
+    } else {
+        Some(token)
+    }
+}
+
+fn pest_expected(e: &pest::error::Error<Rule>) -> Vec<&'static str> {
+    let mut expected: Vec<&'static str> = vec![];
+    if let ErrorVariant::ParsingError { positives, .. } = &e.variant {
+        for token in positives.iter().map(rule_to_token) {
+            if !expected.contains(&token) {
+                expected.push(token)
+            }
+        }
+    }
+    expected
+}
+
+fn rule_to_token(rule: &Rule) -> &'static str {
+    match rule {
+        Rule::EOI => "the end of the query",
+        Rule::WHITESPACE => "a whitespace",
+        Rule::root => "`$`",
+        Rule::current => "`@`",
+        Rule::boolean => "`true` or `false`",
+        Rule::null => "`null`",
+        Rule::number => "a number",
+        Rule::string_qt | Rule::inner | Rule::char => "a quoted string",
+        Rule::sign | Rule::not_in => "a comparison like `==`",
+        Rule::between => "a range like `between 5 and 10`",
+        Rule::not => "`!`",
+        Rule::key_lim | Rule::key | Rule::field | Rule::key_char | Rule::word | Rule::specs => {
+            "a key like `.key`"
+        }
+        Rule::key_glob => "a key pattern like `.key*`",
+        Rule::key_unlim => "a key like `['key']`",
+        Rule::descent | Rule::descent_w | Rule::descent_idx => "a descent like `..key`",
+        Rule::wildcard => "`*` or `[*]`",
+        Rule::function | Rule::function_name => "a function like `.length()` or `.min()`",
+        Rule::extract => "a function like `.extract('id=(\\d+)', 1)`",
+        Rule::coalesce => "a fallback like `?? 30`",
+        Rule::type_name => "a type like `string`",
+        Rule::type_sel | Rule::type_check => "a type selector like `@string()`",
+        Rule::schema_check => "a schema predicate like `schema(@, 'name')`",
+        Rule::unsigned | Rule::signed | Rule::start_slice | Rule::end_slice => "an index",
+        Rule::min => "`-`",
+        Rule::step_slice | Rule::slice | Rule::col => "a slice like `1:2`",
+        Rule::unit_keys | Rule::unit_indexes | Rule::unit_mixed | Rule::union_elem => {
+            "a list like `0,'key',1:2`"
+        }
+        Rule::filter => "a filter like `?(...)`",
+        Rule::logic_or
+        | Rule::logic_xor
+        | Rule::logic_and
+        | Rule::logic_not
+        | Rule::logic_atom
+        | Rule::atom => "an expression",
+        Rule::index | Rule::index_body => "brackets like `[0]`",
+        Rule::dot => "`.`",
+        Rule::position => "a position like `@index`",
+        Rule::property => "`@property`",
+        Rule::node_path => "`@path`",
+        Rule::param | Rule::param_name => "a parameter like `:name`",
+        Rule::context => "a context like `$ctx.key`",
+        Rule::literal | Rule::literal_member | Rule::literal_obj | Rule::literal_arr => {
+            "a literal like `{'key': [1, 2]}`"
+        }
+        Rule::chain | Rule::path => "a path",
+    }
+}
+
+fn pest_suggestion(e: &pest::error::Error<Rule>) -> Option<&'static str> {
+    let query = e.line();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut parentheses = 0;
+    let mut brackets = 0;
+    let mut single_eq = false;
+    let chars: Vec<char> = query.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if q == *c => quote = None,
+            (Some(_), _) => (),
+            (None, '\'' | '"') => quote = Some(*c),
+            (None, '(') => parentheses += 1,
+            (None, ')') => parentheses -= 1,
+            (None, '[') => brackets += 1,
+            (None, ']') => brackets -= 1,
+            (None, '=') => {
+                let prev = if i > 0 { chars[i - 1] } else { ' ' };
+                let next = chars.get(i + 1).copied().unwrap_or(' ');
+                if !"=!<>~".contains(prev) && next != '=' {
+                    single_eq = true
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let unquoted_key = pest_token(e)
+        .map(|t| t.starts_with(|c: char| c.is_alphabetic()))
+        .unwrap_or(false)
+        && query[..offset_in_line(e)].ends_with('[');
+
+    if single_eq {
+        Some("use `==` to compare the values")
+    } else if quote.is_some() {
+        Some("the quoted string is not closed")
+    } else if unquoted_key {
+        Some("the keys in brackets should be quoted like `['key']`")
+    } else if parentheses != 0 {
+        Some("the parentheses `(` and `)` are unbalanced")
+    } else if brackets != 0 {
+        Some("the brackets `[` and `]` are unbalanced")
+    } else {
+        None
+    }
+}
+
+/// the byte offset of the failure position in its line
+fn offset_in_line(e: &pest::error::Error<Rule>) -> usize {
+    let col = match e.line_col {
+        LineColLocation::Pos((_, col)) | LineColLocation::Span((_, col), _) => col,
+    };
+    e.line()
+        .char_indices()
+        .nth(col - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(e.line().len())
+}
+
+/// renders the message with the line of the query where the failed token is underlined
+fn render_pest_error(e: &pest::error::Error<Rule>) -> String {
+    let (line, col) = match e.line_col {
+        LineColLocation::Pos(pos) | LineColLocation::Span(pos, _) => pos,
+    };
+    let token = pest_token(e);
+    let found = match &token {
+        Some(token) => format!("unexpected `{token}`"),
+        None => "unexpected end of the query".to_string(),
+    };
+    let underline = "^".repeat(token.map(|t| t.chars().count()).unwrap_or(1));
+    let margin = " ".repeat(line.to_string().len());
+    let mut rendered = format!(
+        "{found} at line {line}, column {col}\n{margin} |\n{line} | {}\n{margin} | {}{underline}",
+        e.line().trim_end(),
+        " ".repeat(col - 1),
+    );
+    let expected = pest_expected(e);
+    if !expected.is_empty() {
+        rendered.push_str(&format!(
+            "\n{margin} = expected one of: {}",
+            expected.join(", ")
+        ));
+    }
+    if let Some(suggestion) = pest_suggestion(e) {
+        rendered.push_str(&format!("\n{margin} = help: {suggestion}"));
+    }
+    rendered
+}
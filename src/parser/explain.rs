@@ -0,0 +1,186 @@
+//! The evaluation plan of the path.
+//! The plan lists the steps of the chain and marks the steps visiting a lot of elements.
+//!
+//! ```
+//! use jsonpath_rust::JsonPathInst;
+//! use std::str::FromStr;
+//!
+//! let path = JsonPathInst::from_str("$..book[?(@.price < 10)].title").unwrap();
+//! let plan = path.explain();
+//! assert_eq!(plan.fan_out_steps(), 2);
+//! println!("{plan}");
+//! ```
+use crate::parser::model::{FilterExpression, JsonPath, JsonPathIndex};
+use std::fmt::{Display, Formatter};
+
+/// The kind of the step of the evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepKind {
+    Root,
+    Current,
+    Field,
+    Wildcard,
+    Descent,
+    DescentWildcard,
+    DescentIndex,
+    Index,
+    Slice,
+    Union,
+    /// the filter with the list of its predicates
+    Filter(Vec<String>),
+    Function,
+    Type,
+}
+
+/// The single step of the plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanStep {
+    /// the selector as it is written in the query
+    pub selector: String,
+    pub kind: StepKind,
+    /// the step visits every child (wildcard) or every descendant (descent) of the incoming element
+    pub fan_out: bool,
+}
+
+/// The evaluation plan of the path
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub steps: Vec<PlanStep>,
+    /// the rewrites applied to the query before the evaluation
+    pub rewrites: Vec<String>,
+}
+
+impl Plan {
+    pub fn new(path: &JsonPath) -> Self {
+        let steps = match path {
+            JsonPath::Chain(elems) => elems.iter().filter_map(PlanStep::new).collect(),
+            step => PlanStep::new(step).into_iter().collect(),
+        };
+        Plan {
+            steps,
+            rewrites: vec![],
+        }
+    }
+
+    /// the number of the steps visiting all the children or descendants
+    pub fn fan_out_steps(&self) -> usize {
+        self.steps.iter().filter(|s| s.fan_out).count()
+    }
+}
+
+impl PlanStep {
+    fn new(path: &JsonPath) -> Option<Self> {
+        let (kind, fan_out) = match path {
+            JsonPath::Root => (StepKind::Root, false),
+            JsonPath::Current(_) => (StepKind::Current, false),
+            JsonPath::Field(_) => (StepKind::Field, false),
+            JsonPath::FieldGlob(_) => (StepKind::Field, true),
+            JsonPath::Wildcard => (StepKind::Wildcard, true),
+            JsonPath::Descent(_) | JsonPath::DescentGlob(_) => (StepKind::Descent, true),
+            JsonPath::DescentW => (StepKind::DescentWildcard, true),
+            JsonPath::DescentIndex(_) => (StepKind::DescentIndex, true),
+            JsonPath::Index(JsonPathIndex::Single(_)) => (StepKind::Index, false),
+            JsonPath::Index(JsonPathIndex::Slice(..)) => (StepKind::Slice, false),
+            JsonPath::Index(
+                JsonPathIndex::UnionIndex(_)
+                | JsonPathIndex::UnionKeys(_)
+                | JsonPathIndex::Union(_),
+            ) => (StepKind::Union, false),
+            JsonPath::Index(JsonPathIndex::Filter(expr)) => {
+                let mut predicates = vec![];
+                predicates_of(expr, &mut predicates);
+                (StepKind::Filter(predicates), true)
+            }
+            JsonPath::Fn(_) => (StepKind::Function, false),
+            JsonPath::Type(_) => (StepKind::Type, false),
+            JsonPath::Chain(_) | JsonPath::Empty => return None,
+        };
+        Some(PlanStep {
+            selector: path.to_string(),
+            kind,
+            fan_out,
+        })
+    }
+}
+
+/// collects the atoms of the filter expression
+fn predicates_of(expr: &FilterExpression, acc: &mut Vec<String>) {
+    match expr {
+        FilterExpression::And(left, right)
+        | FilterExpression::Or(left, right)
+        | FilterExpression::Xor(left, right) => {
+            predicates_of(left, acc);
+            predicates_of(right, acc);
+        }
+        FilterExpression::Not(_) | FilterExpression::Atom(..) => acc.push(expr.to_string()),
+    }
+}
+
+impl Display for StepKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepKind::Root => write!(f, "root"),
+            StepKind::Current => write!(f, "current"),
+            StepKind::Field => write!(f, "field"),
+            StepKind::Wildcard => write!(f, "wildcard"),
+            StepKind::Descent => write!(f, "descent"),
+            StepKind::DescentWildcard => write!(f, "descent wildcard"),
+            StepKind::DescentIndex => write!(f, "descent index"),
+            StepKind::Index => write!(f, "index"),
+            StepKind::Slice => write!(f, "slice"),
+            StepKind::Union => write!(f, "union"),
+            StepKind::Filter(predicates) => write!(f, "filter: {}", predicates.join(", ")),
+            StepKind::Function => write!(f, "function"),
+            StepKind::Type => write!(f, "type"),
+        }
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            let fan_out = if step.fan_out { " (fan-out)" } else { "" };
+            writeln!(f, "{i}: {} {}{fan_out}", step.selector, step.kind)?;
+        }
+        if self.rewrites.is_empty() {
+            write!(f, "rewrites: none")
+        } else {
+            write!(f, "rewrites: {}", self.rewrites.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::explain::{Plan, StepKind};
+    use crate::parser::parser::parse_json_path;
+
+    #[test]
+    fn explain_test() {
+        let path = parse_json_path("$.store..book[?(@.price < 10 && @.isbn)].title").unwrap();
+        let plan = Plan::new(&path);
+        assert_eq!(
+            plan.steps
+                .iter()
+                .map(|s| s.kind.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                StepKind::Root,
+                StepKind::Field,
+                StepKind::Descent,
+                StepKind::Filter(vec!["@['price'] < 10".to_string(), "@['isbn']".to_string()]),
+                StepKind::Field,
+            ]
+        );
+        assert_eq!(plan.fan_out_steps(), 2);
+        assert_eq!(
+            plan.to_string(),
+            "0: $ root\n\
+             1: ['store'] field\n\
+             2: ..['book'] descent (fan-out)\n\
+             3: [?(@['price'] < 10 && @['isbn'])] filter: @['price'] < 10, @['isbn'] (fan-out)\n\
+             4: ['title'] field\n\
+             rewrites: none"
+        );
+    }
+}
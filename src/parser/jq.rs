@@ -0,0 +1,521 @@
+//! The translation of the path into the equivalent jq filter.
+//!
+//! The selectors and the filters are translated to keep the semantics of the query,
+//! e.g. the filter applied to an object checks the object itself like the query does.
+//! The only difference is that jq yields `null` for the absent fields and indexes
+//! where the query finds nothing.
+//!
+//! ```
+//! use jsonpath_rust::{to_jq, JsonPathInst};
+//! use std::str::FromStr;
+//!
+//! let path = JsonPathInst::from_str("$.store.book[?(@.price < 10)].title").unwrap();
+//! assert_eq!(
+//!     to_jq(&path).unwrap(),
+//!     r#".store.book | (if type == "array" then .[] else . end) | select(.price < 10) | .title"#
+//! );
+//! ```
+use crate::parser::model::{
+    Aggregate, FilterExpression, FilterSign, Function, JsonPath, JsonPathIndex, JsonType, Operand,
+};
+use crate::path::{glob_parts, Chain};
+use serde_json::Value;
+use thiserror::Error;
+
+/// The part of the query that has no jq equivalent
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("the query uses {0} that has no jq equivalent")]
+pub struct UnsupportedFeature(pub String);
+
+/// translates the path into the jq filter
+pub fn path_to_jq(path: &JsonPath) -> Result<String, UnsupportedFeature> {
+    let mut jq = Jq::default();
+    let mut stages = vec![];
+    jq.path(path, &mut stages)?;
+    let body = join(stages);
+    Ok(if jq.root_used {
+        format!(". as $root | {body}")
+    } else {
+        body
+    })
+}
+
+/// The stage of the jq pipeline
+struct Stage {
+    text: String,
+    /// the stage is a path like `.a[0]` that can be extended by the next selectors
+    is_path: bool,
+}
+
+fn join(stages: Vec<Stage>) -> String {
+    if stages.is_empty() {
+        ".".to_string()
+    } else {
+        stages
+            .into_iter()
+            .map(|s| s.text)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// appends the postfix selector like `.a` or `[0]` to the path stage or starts the new one
+fn postfix(stages: &mut Vec<Stage>, selector: &str) {
+    match stages.last_mut() {
+        Some(stage) if stage.is_path => stage.text.push_str(selector),
+        _ => stages.push(Stage {
+            text: if selector.starts_with('[') {
+                format!(".{selector}")
+            } else {
+                selector.to_string()
+            },
+            is_path: true,
+        }),
+    }
+}
+
+fn stage(stages: &mut Vec<Stage>, text: impl Into<String>) {
+    stages.push(Stage {
+        text: text.into(),
+        is_path: false,
+    })
+}
+
+#[derive(Default)]
+struct Jq {
+    root_used: bool,
+}
+
+impl Jq {
+    fn path(&mut self, path: &JsonPath, stages: &mut Vec<Stage>) -> Result<(), UnsupportedFeature> {
+        match path {
+            JsonPath::Root | JsonPath::Empty => {}
+            JsonPath::Chain(elems) => match elems.split_last() {
+                Some((JsonPath::Fn(Function::Default(v)), init)) => {
+                    let mut inner = vec![];
+                    self.path(&JsonPath::Chain(init.to_vec()), &mut inner)?;
+                    stage(
+                        stages,
+                        format!("[{}] | if length > 0 then .[] else {v} end", join(inner)),
+                    );
+                }
+                Some((JsonPath::Fn(Function::Aggregate(a)), init)) => {
+                    let mut inner = vec![];
+                    for el in init {
+                        self.path(el, &mut inner)?;
+                    }
+                    let numbers =
+                        "if length == 1 and (.[0] | type) == \"array\" then .[0] else . end \
+                                   | map(numbers) | select(length > 0)";
+                    let aggregate = match a {
+                        Aggregate::Min => "min",
+                        Aggregate::Max => "max",
+                        Aggregate::Sum => "add",
+                        Aggregate::Avg => "add / length",
+                        Aggregate::Stddev => {
+                            "(add / length) as $avg | map((. - $avg) * (. - $avg)) | add / length | sqrt"
+                        }
+                    };
+                    stage(
+                        stages,
+                        format!("[{}] | {numbers} | {aggregate}", join(inner)),
+                    );
+                }
+                Some((JsonPath::Fn(Function::Length), init)) if Chain::is_search_length(elems) => {
+                    let mut inner = vec![];
+                    for el in init {
+                        self.path(el, &mut inner)?;
+                    }
+                    stage(stages, format!("[{}] | length", join(inner)));
+                }
+                _ => {
+                    for el in elems {
+                        self.path(el, stages)?;
+                    }
+                }
+            },
+            JsonPath::FieldGlob(pattern) => {
+                stage(stages, "to_entries[]?");
+                stage(
+                    stages,
+                    format!("select(.key | test({}))", glob_regex(pattern)),
+                );
+                stage(stages, ".value");
+            }
+            JsonPath::Field(key) => postfix(stages, &field(key)),
+            JsonPath::Wildcard => postfix(stages, "[]?"),
+            JsonPath::Descent(key) => {
+                stage(stages, "..");
+                stage(stages, "objects");
+                stage(stages, format!("select(has({}))", string(key)));
+                postfix(stages, &field(key));
+            }
+            JsonPath::DescentGlob(pattern) => {
+                stage(stages, "..");
+                stage(stages, "objects");
+                stage(stages, "to_entries[]");
+                stage(
+                    stages,
+                    format!("select(.key | test({}))", glob_regex(pattern)),
+                );
+                stage(stages, ".value");
+            }
+            JsonPath::DescentW => {
+                postfix(stages, "[]?");
+                stage(stages, "..");
+            }
+            JsonPath::DescentIndex(JsonPathIndex::Filter(expr)) => {
+                stage(stages, "..");
+                postfix(stages, "[]?");
+                let cond = self.filter(expr)?;
+                stage(stages, format!("select({cond})"));
+            }
+            JsonPath::DescentIndex(index) => {
+                stage(stages, "..");
+                self.index(index, stages)?;
+                if let Some(last) = stages.last_mut().filter(|s| !s.text.ends_with('?')) {
+                    last.text.push('?')
+                }
+            }
+            JsonPath::Index(index) => self.index(index, stages)?,
+            JsonPath::Current(tail) => self.path(tail, stages)?,
+            JsonPath::Fn(Function::Length) => {
+                stage(stages, "arrays");
+                stage(stages, "length");
+            }
+            JsonPath::Fn(Function::Keys) => {
+                stage(stages, "objects");
+                stage(stages, "keys_unsorted");
+            }
+            // the aggregation ending the chain is handled above
+            JsonPath::Fn(Function::Aggregate(a)) => {
+                return Err(UnsupportedFeature(format!(
+                    "the function `{}()` in the middle of the path",
+                    a.name()
+                )))
+            }
+            JsonPath::Fn(Function::Extract(re, group)) => {
+                stage(stages, "strings");
+                stage(stages, format!("match({})", pattern_regex(re)));
+                match group {
+                    0 => stage(stages, ".string"),
+                    g => stage(stages, format!(".captures[{}].string // empty", g - 1)),
+                }
+            }
+            // the fallback of the whole chain is handled above
+            JsonPath::Fn(Function::Default(_)) => {}
+            JsonPath::Type(tp) => stage(stages, type_filter(tp)),
+        }
+        Ok(())
+    }
+
+    fn index(
+        &mut self,
+        index: &JsonPathIndex,
+        stages: &mut Vec<Stage>,
+    ) -> Result<(), UnsupportedFeature> {
+        match index {
+            JsonPathIndex::Single(idx) => postfix(stages, &format!("[{idx}]")),
+            JsonPathIndex::UnionIndex(elems) => {
+                let elems: Vec<String> = elems.iter().map(Value::to_string).collect();
+                postfix(stages, &format!("[{}]", elems.join(",")))
+            }
+            JsonPathIndex::UnionKeys(keys) => {
+                let keys: Vec<String> = keys.iter().map(|k| string(k)).collect();
+                postfix(stages, &format!("[{}]", keys.join(",")))
+            }
+            JsonPathIndex::Slice(start, end, 1) => {
+                let start = if *start == 0 {
+                    String::new()
+                } else {
+                    start.to_string()
+                };
+                let end = if *end == 0 {
+                    String::new()
+                } else {
+                    end.to_string()
+                };
+                postfix(stages, &format!("[{start}:{end}][]?"))
+            }
+            JsonPathIndex::Slice(..) => {
+                return Err(UnsupportedFeature(format!(
+                    "the slice with the step `{index}`"
+                )))
+            }
+            JsonPathIndex::Union(_) => {
+                return Err(UnsupportedFeature(format!("the mixed union `{index}`")))
+            }
+            JsonPathIndex::Filter(expr) => {
+                stage(stages, r#"(if type == "array" then .[] else . end)"#);
+                let cond = self.filter(expr)?;
+                stage(stages, format!("select({cond})"));
+            }
+        }
+        Ok(())
+    }
+
+    fn filter(&mut self, expr: &FilterExpression) -> Result<String, UnsupportedFeature> {
+        Ok(match expr {
+            FilterExpression::And(left, right) => {
+                format!("({} and {})", self.filter(left)?, self.filter(right)?)
+            }
+            FilterExpression::Or(left, right) => {
+                format!("({} or {})", self.filter(left)?, self.filter(right)?)
+            }
+            FilterExpression::Xor(left, right) => {
+                format!("({} != {})", self.filter(left)?, self.filter(right)?)
+            }
+            FilterExpression::Not(exp) => format!("({} | not)", self.filter(exp)?),
+            FilterExpression::Atom(left, sign, right) => {
+                let l = self.operand(left)?;
+                let r = self.operand(right)?;
+                match sign {
+                    FilterSign::Equal => format!("{l} == {r}"),
+                    FilterSign::Unequal => format!("{l} != {r}"),
+                    FilterSign::Less => format!("{l} < {r}"),
+                    FilterSign::Greater => format!("{l} > {r}"),
+                    FilterSign::LeOrEq => format!("{l} <= {r}"),
+                    FilterSign::GrOrEq => format!("{l} >= {r}"),
+                    FilterSign::Regex => match right {
+                        Operand::Static(Value::String(p)) => {
+                            format!("({l} | test({}))", pattern_regex(p))
+                        }
+                        _ => {
+                            return Err(UnsupportedFeature(format!(
+                                "the regex that is not a constant string `{expr}`"
+                            )))
+                        }
+                    },
+                    FilterSign::In => format!("({l}) as $l | ({r}) | any(.[]; . == $l)"),
+                    FilterSign::Nin => format!("({l}) as $l | ({r}) | all(.[]; . != $l)"),
+                    FilterSign::AnyOf => {
+                        format!("({r}) as $r | ({l}) | any(.[]; . as $x | $r | any(.[]; . == $x))")
+                    }
+                    FilterSign::NoneOf => {
+                        format!("({r}) as $r | ({l}) | all(.[]; . as $x | $r | all(.[]; . != $x))")
+                    }
+                    FilterSign::SubSetOf => {
+                        format!("({r}) as $r | ({l}) | all(.[]; . as $x | $r | any(.[]; . == $x))")
+                    }
+                    FilterSign::Size => format!("({l} | length) == {r}"),
+                    FilterSign::Exists => format!("any({l}; . != null)"),
+                    FilterSign::Schema => {
+                        return Err(UnsupportedFeature(format!("the schema predicate `{expr}`")))
+                    }
+                }
+            }
+        })
+    }
+
+    fn operand(&mut self, op: &Operand) -> Result<String, UnsupportedFeature> {
+        match op {
+            Operand::Static(v) => Ok(v.to_string()),
+            // the parameters turn into the variables passed by `jq --argjson`
+            Operand::Param(name) => Ok(format!("${name}")),
+            // the context turns into the variable `$ctx` passed by `jq --argjson ctx`
+            Operand::Context(path) => {
+                let mut stages = vec![Stage {
+                    text: "$ctx".to_string(),
+                    is_path: true,
+                }];
+                self.path(path, &mut stages)?;
+                let text = join(stages);
+                Ok(if text.contains(" | ") {
+                    format!("({text})")
+                } else {
+                    text
+                })
+            }
+            Operand::Index | Operand::Property | Operand::Path => Err(UnsupportedFeature(format!(
+                "the metadata of the element `{op}`"
+            ))),
+            Operand::Dynamic(path) => {
+                let mut stages = vec![];
+                if starts_with_root(path) {
+                    self.root_used = true;
+                    stages.push(Stage {
+                        text: "$root".to_string(),
+                        is_path: true,
+                    });
+                }
+                self.path(path, &mut stages)?;
+                let text = join(stages);
+                Ok(if text.contains(" | ") {
+                    format!("({text})")
+                } else {
+                    text
+                })
+            }
+        }
+    }
+}
+
+fn starts_with_root(path: &JsonPath) -> bool {
+    match path {
+        JsonPath::Root => true,
+        JsonPath::Chain(elems) => matches!(elems.first(), Some(JsonPath::Root)),
+        _ => false,
+    }
+}
+
+/// the field selector like `.key` or `."the key"`
+fn field(key: &str) -> String {
+    let is_ident = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_ident {
+        format!(".{key}")
+    } else {
+        format!(".{}", string(key))
+    }
+}
+
+fn string(s: &str) -> String {
+    Value::String(s.to_string()).to_string()
+}
+
+/// the anchored regex matching the same keys as the pattern with `*`
+fn glob_regex(pattern: &str) -> String {
+    let regex: String = glob_parts(pattern)
+        .into_iter()
+        .map(|part| part.map_or(".*".to_string(), |c| escape(&c.to_string())))
+        .collect();
+    string(&format!("^{regex}$"))
+}
+
+/// escapes the meta characters of the regex
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// the regex of jq matching the same strings as the pattern of `~=`
+fn pattern_regex(pattern: &str) -> String {
+    if cfg!(feature = "regex") {
+        string(pattern)
+    } else {
+        glob_regex(pattern)
+    }
+}
+
+fn type_filter(tp: &JsonType) -> &'static str {
+    match tp {
+        JsonType::Null => "nulls",
+        JsonType::Boolean => "booleans",
+        JsonType::Number => "numbers",
+        JsonType::Integer => "numbers | select(. == floor)",
+        JsonType::String => "strings",
+        JsonType::Array => "arrays",
+        JsonType::Object => "objects",
+        JsonType::Scalar => "scalars",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::jq::{path_to_jq, UnsupportedFeature};
+    use crate::parser::parser::parse_json_path;
+
+    fn jq(path: &str) -> Result<String, UnsupportedFeature> {
+        path_to_jq(&parse_json_path(path).unwrap())
+    }
+
+    #[test]
+    fn selectors_test() {
+        assert_eq!(jq("$").unwrap(), ".");
+        assert_eq!(jq("$.a['b c'][0]").unwrap(), r#".a."b c"[0]"#);
+        assert_eq!(jq("$[1].a").unwrap(), ".[1].a");
+        assert_eq!(jq("$.a.*.b").unwrap(), ".a[]?.b");
+        assert_eq!(jq("$.a[1:3]").unwrap(), ".a[1:3][]?");
+        assert_eq!(jq("$.a[-1:]").unwrap(), ".a[-1:][]?");
+        assert_eq!(jq("$.a[0,2]['x','y']").unwrap(), r#".a[0,2]["x","y"]"#);
+        assert_eq!(
+            jq("$..book.title").unwrap(),
+            r#".. | objects | select(has("book")) | .book.title"#
+        );
+        assert_eq!(jq("$.a..*").unwrap(), ".a[]? | ..");
+        assert_eq!(jq("$..[0]").unwrap(), ".. | .[0]?");
+        assert_eq!(
+            jq("$.m.cpu_*").unwrap(),
+            r#".m | to_entries[]? | select(.key | test("^cpu_.*$")) | .value"#
+        );
+        assert_eq!(
+            jq(r"$.m['a\*b*']").unwrap(),
+            r#".m | to_entries[]? | select(.key | test("^a\\*b.*$")) | .value"#
+        );
+        assert_eq!(jq("$.a.length()").unwrap(), ".a | arrays | length");
+        assert_eq!(jq("$.a[*].length()").unwrap(), "[.a[]?] | length");
+        assert_eq!(
+            jq("$.a[*].b.max()").unwrap(),
+            r#"[.a[]?.b] | if length == 1 and (.[0] | type) == "array" then .[0] else . end | map(numbers) | select(length > 0) | max"#
+        );
+        assert_eq!(jq("$.a.keys()").unwrap(), ".a | objects | keys_unsorted");
+        assert!(jq("$.a.sum().b").is_err());
+        assert_eq!(jq("$.a[*]@string()").unwrap(), ".a[]? | strings");
+        assert_eq!(
+            jq("$.a.b ?? 'x'").unwrap(),
+            r#"[.a.b] | if length > 0 then .[] else "x" end"#
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn extract_test() {
+        assert_eq!(
+            jq("$.a.extract('id=([0-9]+)', 1)").unwrap(),
+            r#".a | strings | match("id=([0-9]+)") | .captures[0].string // empty"#
+        );
+        assert_eq!(
+            jq("$.a.extract('id')").unwrap(),
+            r#".a | strings | match("id") | .string"#
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn filters_test() {
+        let each = r#"(if type == "array" then .[] else . end)"#;
+        assert_eq!(
+            jq("$.a[?(@.b == 'x' && !(@.c > 1) || @.d)]").unwrap(),
+            format!(
+                r#".a | {each} | select(((.b == "x" and (.c > 1 | not)) or any(.d; . != null)))"#
+            )
+        );
+        assert_eq!(
+            jq("$.a[?(@.b < :max)]").unwrap(),
+            format!(r#".a | {each} | select(.b < $max)"#)
+        );
+        assert_eq!(
+            jq("$.a[?(@.b ~= '^x.*')]").unwrap(),
+            format!(r#".a | {each} | select((.b | test("^x.*")))"#)
+        );
+        assert_eq!(
+            jq("$.a[?(@.b in [1,2])]").unwrap(),
+            format!(r#".a | {each} | select((.b) as $l | ([1,2]) | any(.[]; . == $l))"#)
+        );
+        assert_eq!(
+            jq("$.a[?(@.b < $.max)]").unwrap(),
+            format!(r#". as $root | .a | {each} | select(.b < $root.max)"#)
+        );
+        assert_eq!(
+            jq("$..[?(@.price)]").unwrap(),
+            r#".. | .[]? | select(any(.price; . != null))"#
+        );
+        assert_eq!(
+            jq("$.a[?(@ > 1)]").unwrap(),
+            format!(r#".a | {each} | select(. > 1)"#)
+        );
+    }
+
+    #[test]
+    fn unsupported_test() {
+        assert!(jq("$.a[0:4:2]").is_err());
+        assert!(jq("$.a[0,'b']").is_err());
+        assert!(jq("$.a[?(@.b ~= @.c)]").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! The linter detecting the queries that can never match anything or are degenerate.
+//!
+//! ```
+//! use jsonpath_rust::{lint, JsonPathInst, LintWarning};
+//! use std::str::FromStr;
+//!
+//! let path = JsonPathInst::from_str("$.items[?(@.x > 1 && @.x < 0)]").unwrap();
+//! assert!(matches!(lint(&path)[0], LintWarning::Contradiction(_)));
+//! ```
+use crate::parser::model::{FilterExpression, FilterSign, JsonPath, JsonPathIndex, Operand};
+use crate::path::{eq_el, Rules};
+use serde_json::Value;
+use thiserror::Error;
+
+/// The problem found by the linter.
+/// Every warning carries the text of the offending part of the query.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum LintWarning {
+    #[error("the slice `{0}` is always empty")]
+    EmptySlice(String),
+    #[error("the slice `{0}` has the zero step")]
+    ZeroStep(String),
+    #[error("the filter `{0}` can never be true")]
+    Contradiction(String),
+    #[error("the filter `{0}` compares the path with itself")]
+    SelfComparison(String),
+    #[error("the index `{index}` is applied to `{path}` that is not an array in the sample")]
+    NotAnArray { path: String, index: String },
+}
+
+/// checks the path for the unsatisfiable or degenerate parts.
+pub fn lint_path(path: &JsonPath) -> Vec<LintWarning> {
+    let mut warnings = vec![];
+    lint_step(path, &mut warnings);
+    warnings
+}
+
+fn lint_step(path: &JsonPath, warnings: &mut Vec<LintWarning>) {
+    match path {
+        JsonPath::Chain(elems) => elems.iter().for_each(|e| lint_step(e, warnings)),
+        JsonPath::Current(tail) => lint_step(tail, warnings),
+        JsonPath::Index(index) | JsonPath::DescentIndex(index) => lint_index(index, warnings),
+        _ => (),
+    }
+}
+
+fn lint_index(index: &JsonPathIndex, warnings: &mut Vec<LintWarning>) {
+    match index {
+        JsonPathIndex::Slice(start, end, step) => {
+            if *step == 0 {
+                warnings.push(LintWarning::ZeroStep(index.to_string()))
+            } else if is_empty_slice(*start, *end) {
+                warnings.push(LintWarning::EmptySlice(index.to_string()))
+            }
+        }
+        JsonPathIndex::Union(elems) => elems.iter().for_each(|e| lint_step(e, warnings)),
+        JsonPathIndex::Filter(expr) => lint_filter(expr, warnings),
+        _ => (),
+    }
+}
+
+/// the end equal to zero stands for the length of the array
+fn is_empty_slice(start: i32, end: i32) -> bool {
+    end != 0 && (start >= 0) == (end >= 0) && end <= start
+}
+
+fn lint_filter(expr: &FilterExpression, warnings: &mut Vec<LintWarning>) {
+    match expr {
+        FilterExpression::Atom(left, sign, right) => {
+            if let (Operand::Dynamic(l), Operand::Dynamic(r)) = (left, right) {
+                if l == r && *sign != FilterSign::Exists {
+                    warnings.push(LintWarning::SelfComparison(expr.to_string()))
+                }
+            }
+            for op in [left, right] {
+                if let Operand::Dynamic(jp) = op {
+                    lint_step(jp, warnings)
+                }
+            }
+        }
+        FilterExpression::And(..) => {
+            let mut atoms = vec![];
+            conjunction(expr, &mut atoms);
+            if is_contradiction(&atoms) {
+                warnings.push(LintWarning::Contradiction(expr.to_string()))
+            }
+            atoms.into_iter().for_each(|a| lint_filter(a, warnings));
+        }
+        FilterExpression::Or(left, right) | FilterExpression::Xor(left, right) => {
+            lint_filter(left, warnings);
+            lint_filter(right, warnings);
+        }
+        FilterExpression::Not(exp) => lint_filter(exp, warnings),
+    }
+}
+
+/// flattens the nested `&&` into the list of the operands
+fn conjunction<'a>(expr: &'a FilterExpression, acc: &mut Vec<&'a FilterExpression>) {
+    match expr {
+        FilterExpression::And(left, right) => {
+            conjunction(left, acc);
+            conjunction(right, acc);
+        }
+        other => acc.push(other),
+    }
+}
+
+/// checks if the numeric bounds or the equalities of the same path exclude each other
+fn is_contradiction(atoms: &[&FilterExpression]) -> bool {
+    let bounds: Vec<(&JsonPath, &FilterSign, &Value)> = atoms
+        .iter()
+        .filter_map(|a| match a {
+            FilterExpression::Atom(Operand::Dynamic(jp), sign, Operand::Static(v)) => {
+                Some((jp.as_ref(), sign, v))
+            }
+            _ => None,
+        })
+        .collect();
+
+    bounds.iter().enumerate().any(|(i, (p1, s1, v1))| {
+        bounds
+            .iter()
+            .skip(i + 1)
+            .filter(|(p2, ..)| p1 == p2)
+            .any(|(_, s2, v2)| excludes(s1, v1, s2, v2) || excludes(s2, v2, s1, v1))
+    })
+}
+
+fn excludes(s1: &FilterSign, v1: &Value, s2: &FilterSign, v2: &Value) -> bool {
+    if let (FilterSign::Equal, FilterSign::Equal) = (s1, s2) {
+        return !eq_el(v1, v2, &Rules::default());
+    }
+    let (Some(a), Some(b)) = (v1.as_f64(), v2.as_f64()) else {
+        return false;
+    };
+    match (s1, s2) {
+        // x > a && x < b
+        (FilterSign::Greater, FilterSign::Less)
+        | (FilterSign::Greater, FilterSign::LeOrEq)
+        | (FilterSign::GrOrEq, FilterSign::Less) => a >= b,
+        (FilterSign::GrOrEq, FilterSign::LeOrEq) => a > b,
+        // x == a && x < b
+        (FilterSign::Equal, FilterSign::Less) => a >= b,
+        (FilterSign::Equal, FilterSign::LeOrEq) => a > b,
+        (FilterSign::Equal, FilterSign::Greater) => a <= b,
+        (FilterSign::Equal, FilterSign::GrOrEq) => a < b,
+        (FilterSign::Equal, FilterSign::Unequal) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::lint::{lint_path, LintWarning};
+    use crate::parser::parser::parse_json_path;
+
+    fn lint(path: &str) -> Vec<LintWarning> {
+        lint_path(&parse_json_path(path).expect("the path is correct"))
+    }
+
+    #[test]
+    fn slice_lint_test() {
+        assert_eq!(
+            lint("$.a[1:1]"),
+            vec![LintWarning::EmptySlice("[1:1]".to_string())]
+        );
+        assert_eq!(
+            lint("$.a[-1:-3]"),
+            vec![LintWarning::EmptySlice("[-1:-3]".to_string())]
+        );
+        assert_eq!(
+            lint("$.a[0:2:0]"),
+            vec![LintWarning::ZeroStep("[:2:0]".to_string())]
+        );
+        assert!(lint("$.a[1:]").is_empty());
+        assert!(lint("$.a[-2:1]").is_empty());
+        assert!(lint("$.a[0, 2:2]").contains(&LintWarning::EmptySlice("[2:2]".to_string())));
+    }
+
+    #[test]
+    fn filter_lint_test() {
+        assert_eq!(
+            lint("$.a[?(@.x > 1 && @.x < 0)]"),
+            vec![LintWarning::Contradiction(
+                "@['x'] > 1 && @['x'] < 0".to_string()
+            )]
+        );
+        assert_eq!(lint("$.a[?(@.x == 'a' && @.y && @.x == 'b')]").len(), 1);
+        assert!(lint("$.a[?(@.x >= 1 && @.x <= 1)]").is_empty());
+        assert!(lint("$.a[?(@.x == 1 && @.x == 1.0)]").is_empty());
+        assert!(lint("$.a[?(@.x > 1 && @.y < 0)]").is_empty());
+        assert!(lint("$.a[?(@.x > 1 || @.x < 0)]").is_empty());
+        assert_eq!(
+            lint("$.a[?(@.x == @.x)]"),
+            vec![LintWarning::SelfComparison("@['x'] == @['x']".to_string())]
+        );
+        assert_eq!(lint("$.a[?(@.b[?(@.c < 1 && @.c > 2)])]").len(), 1);
+    }
+}
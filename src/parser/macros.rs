@@ -6,6 +6,7 @@ macro_rules! filter {
    };
    ( $left:expr,||, $right:expr) => {FilterExpression::Or(Box::new($left),Box::new($right)) };
    ( $left:expr,&&, $right:expr) => {FilterExpression::And(Box::new($left),Box::new($right)) };
+   ( $left:expr,xor, $right:expr) => {FilterExpression::Xor(Box::new($left),Box::new($right)) };
 }
 #[macro_export]
 macro_rules! op {
@@ -26,6 +27,11 @@ macro_rules! op {
 #[macro_export]
 macro_rules! idx {
    ( $s:literal) => {JsonPathIndex::Single(json!($s))};
+   ( union $($ss:expr),+) => {{
+        let mut ss_vec = Vec::new();
+        $( ss_vec.push($ss) ; )+
+        JsonPathIndex::Union(ss_vec)
+   }};
    ( idx $($ss:literal),+) => {{
         let mut ss_vec = Vec::new();
         $( ss_vec.push(json!($ss)) ; )+
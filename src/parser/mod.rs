@@ -1,9 +1,14 @@
 //! The parser for the jsonpath.
 //! The module grammar denotes the structure of the parsing grammar
 
+pub mod builder;
 pub mod errors;
+pub mod explain;
+pub mod jq;
+pub mod lint;
 mod macros;
 pub mod model;
 #[allow(clippy::module_inception)]
 #[allow(clippy::result_large_err)]
 pub mod parser;
+pub mod visitor;
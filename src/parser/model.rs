@@ -1,21 +1,30 @@
 use crate::parse_json_path;
 use serde_json::Value;
 use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
 
 /// The basic structures for parsing json paths.
 /// The common logic of the structures pursues to correspond the internal parsing structure.
+/// The parsed path can be traversed with [`crate::parser::visitor::Visitor`].
 #[derive(Debug, Clone)]
 pub enum JsonPath {
     /// The $ operator
     Root,
     /// Field represents key
     Field(String),
+    /// The key containing `*` like `.cpu_*` or `['error*']` matching the fields by the glob pattern,
+    /// `\*` and `\\` of the pattern stand for the star and the backslash themselves
+    FieldGlob(String),
     /// The whole chain of the path.
     Chain(Vec<JsonPath>),
     /// The .. operator
     Descent(String),
+    /// The .. operator with the key containing `*` like `..cpu_*` or `..['error*']`
+    DescentGlob(String),
     /// The ..* operator
     DescentW,
+    /// The ..[] operator applying the index or the filter to the children of every descendant
+    DescentIndex(JsonPathIndex),
     /// The indexes for array
     Index(JsonPathIndex),
     /// The @ operator
@@ -26,12 +35,36 @@ pub enum JsonPath {
     Empty,
     /// Functions that can calculate some expressions
     Fn(Function),
+    /// The @type() selector keeping only the elements of the given json type
+    Type(JsonType),
 }
 
 impl JsonPath {
     pub fn current(jp: JsonPath) -> Self {
         JsonPath::Current(Box::new(jp))
     }
+
+    /// checks if the path yields at most one element,
+    /// that is it consists only of the fields, the single indexes and the functions.
+    pub fn is_singular(&self) -> bool {
+        match self {
+            JsonPath::Root
+            | JsonPath::Empty
+            | JsonPath::Field(_)
+            | JsonPath::Fn(_)
+            | JsonPath::Type(_) => true,
+            JsonPath::Index(JsonPathIndex::Single(_)) => true,
+            JsonPath::Chain(elems) => elems.iter().all(JsonPath::is_singular),
+            JsonPath::Current(tail) => tail.is_singular(),
+            JsonPath::Index(_)
+            | JsonPath::FieldGlob(_)
+            | JsonPath::Descent(_)
+            | JsonPath::DescentGlob(_)
+            | JsonPath::DescentW
+            | JsonPath::DescentIndex(_)
+            | JsonPath::Wildcard => false,
+        }
+    }
 }
 
 impl TryFrom<&str> for JsonPath {
@@ -42,11 +75,92 @@ impl TryFrom<&str> for JsonPath {
     }
 }
 
+/// The json types that can be used in the selectors like @string()
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonType {
+    Null,
+    Boolean,
+    Number,
+    Integer,
+    String,
+    Array,
+    Object,
+    /// any value that is neither an array nor an object
+    Scalar,
+}
+
+impl JsonType {
+    pub fn new(key: &str) -> Self {
+        match key {
+            "null" => JsonType::Null,
+            "boolean" => JsonType::Boolean,
+            "number" => JsonType::Number,
+            "integer" => JsonType::Integer,
+            "string" => JsonType::String,
+            "array" => JsonType::Array,
+            "object" => JsonType::Object,
+            _ => JsonType::Scalar,
+        }
+    }
+}
+
+/// The functions applied to the result of the preceding path
 #[derive(Debug, PartialEq, Clone)]
 pub enum Function {
     /// length()
     Length,
+    /// the aggregation of the numbers like `min()`, see [`Aggregate`]
+    Aggregate(Aggregate),
+    /// keys() giving the keys of the object
+    Keys,
+    /// extract('regex', group) giving the group captured by the regex from the strings,
+    /// the group 0 is the whole match
+    Extract(String, usize),
+    /// the fallback like `?? 30` taking the place of the result if the preceding path finds nothing
+    Default(Value),
+}
+
+/// The aggregations of the numbers found by the preceding path like in Jayway JsonPath.
+/// If the path finds the only array the numbers are its elements, the values other than the numbers are skipped.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Aggregate {
+    /// min()
+    Min,
+    /// max()
+    Max,
+    /// avg()
+    Avg,
+    /// stddev(), the standard deviation of the population
+    Stddev,
+    /// sum()
+    Sum,
 }
+
+impl Aggregate {
+    pub fn new(name: &str) -> Option<Self> {
+        match name {
+            "min" => Some(Aggregate::Min),
+            "max" => Some(Aggregate::Max),
+            "avg" => Some(Aggregate::Avg),
+            "stddev" => Some(Aggregate::Stddev),
+            "sum" => Some(Aggregate::Sum),
+            _ => None,
+        }
+    }
+
+    /// the name of the function
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Avg => "avg",
+            Aggregate::Stddev => "stddev",
+            Aggregate::Sum => "sum",
+        }
+    }
+}
+
+/// The content of the brackets `[]`
 #[derive(Debug, Clone)]
 pub enum JsonPathIndex {
     /// A single element in array
@@ -55,12 +169,15 @@ pub enum JsonPathIndex {
     UnionIndex(Vec<Value>),
     /// Union represents a several keys
     UnionKeys(Vec<String>),
+    /// Union mixing indexes, keys and slices like [0,'key',2:5]
+    Union(Vec<JsonPath>),
     /// DEfault slice where the items are start/end/step respectively
     Slice(i32, i32, usize),
     /// Filter ?()
     Filter(FilterExpression),
 }
 
+/// The logical expression of the filter `[?(...)]`
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilterExpression {
     /// a single expression like a > 2
@@ -69,6 +186,8 @@ pub enum FilterExpression {
     And(Box<FilterExpression>, Box<FilterExpression>),
     /// or with ||
     Or(Box<FilterExpression>, Box<FilterExpression>),
+    /// exclusive or with `xor` binding tighter than `||` and looser than `&&`
+    Xor(Box<FilterExpression>, Box<FilterExpression>),
     /// not with !
     Not(Box<FilterExpression>),
 }
@@ -88,6 +207,18 @@ impl FilterExpression {
 pub enum Operand {
     Static(Value),
     Dynamic(Box<JsonPath>),
+    /// the placeholder like `:max_price` replaced by the value bound with [`crate::JsonPathInst::bind`]
+    Param(String),
+    /// the path like `$ctx.allowed` in the context document bound with [`crate::JsonPathInst::bind_context`],
+    /// starting from the root of the context
+    Context(Box<JsonPath>),
+    /// the position of the current element in the array being filtered like `@index` or `position()`,
+    /// absent for the objects filtered as a whole
+    Index,
+    /// the key of the current element like `@property`, the position for the elements of the arrays
+    Property,
+    /// the normalized path of the current element like `@path`
+    Path,
 }
 
 #[allow(dead_code)]
@@ -114,6 +245,8 @@ pub enum FilterSign {
     AnyOf,
     SubSetOf,
     Exists,
+    /// the predicate like `schema(@, 'name')` with the name of the registered schema on the right
+    Schema,
 }
 
 impl FilterSign {
@@ -127,7 +260,7 @@ impl FilterSign {
             ">=" => FilterSign::GrOrEq,
             "~=" => FilterSign::Regex,
             "in" => FilterSign::In,
-            "nin" => FilterSign::Nin,
+            "nin" | "not in" => FilterSign::Nin,
             "size" => FilterSign::Size,
             "noneOf" => FilterSign::NoneOf,
             "anyOf" => FilterSign::AnyOf,
@@ -142,14 +275,18 @@ impl PartialEq for JsonPath {
         match (self, other) {
             (JsonPath::Root, JsonPath::Root) => true,
             (JsonPath::Descent(k1), JsonPath::Descent(k2)) => k1 == k2,
+            (JsonPath::DescentGlob(k1), JsonPath::DescentGlob(k2)) => k1 == k2,
             (JsonPath::DescentW, JsonPath::DescentW) => true,
+            (JsonPath::DescentIndex(idx1), JsonPath::DescentIndex(idx2)) => idx1 == idx2,
             (JsonPath::Field(k1), JsonPath::Field(k2)) => k1 == k2,
+            (JsonPath::FieldGlob(k1), JsonPath::FieldGlob(k2)) => k1 == k2,
             (JsonPath::Wildcard, JsonPath::Wildcard) => true,
             (JsonPath::Empty, JsonPath::Empty) => true,
             (JsonPath::Current(jp1), JsonPath::Current(jp2)) => jp1 == jp2,
             (JsonPath::Chain(ch1), JsonPath::Chain(ch2)) => ch1 == ch2,
             (JsonPath::Index(idx1), JsonPath::Index(idx2)) => idx1 == idx2,
             (JsonPath::Fn(fn1), JsonPath::Fn(fn2)) => fn2 == fn1,
+            (JsonPath::Type(t1), JsonPath::Type(t2)) => t1 == t2,
             (_, _) => false,
         }
     }
@@ -168,6 +305,7 @@ impl PartialEq for JsonPathIndex {
             (JsonPathIndex::UnionKeys(elems1), JsonPathIndex::UnionKeys(elems2)) => {
                 elems1 == elems2
             }
+            (JsonPathIndex::Union(elems1), JsonPathIndex::Union(elems2)) => elems1 == elems2,
             (JsonPathIndex::Filter(left), JsonPathIndex::Filter(right)) => left.eq(right),
             (_, _) => false,
         }
@@ -179,7 +317,409 @@ impl PartialEq for Operand {
         match (self, other) {
             (Operand::Static(v1), Operand::Static(v2)) => v1 == v2,
             (Operand::Dynamic(jp1), Operand::Dynamic(jp2)) => jp1 == jp2,
+            (Operand::Param(n1), Operand::Param(n2)) => n1 == n2,
+            (Operand::Context(jp1), Operand::Context(jp2)) => jp1 == jp2,
+            (Operand::Index, Operand::Index) => true,
+            (Operand::Property, Operand::Property) => true,
+            (Operand::Path, Operand::Path) => true,
             (_, _) => false,
         }
     }
 }
+
+/// writes the string in the single quotes escaping the quotes and `\` so the parser reads it back as it is
+pub(crate) fn quote(text: &str) -> String {
+    quote_escaping(text, &['\'', '"', '\\'])
+}
+
+/// writes the key like [`quote`] escaping `*` as well, since the quoted key with `*` is the glob
+pub(crate) fn quote_key(key: &str) -> String {
+    quote_escaping(key, &['\'', '"', '\\', '*'])
+}
+
+/// checks if the glob pattern reads back unquoted like `cpu_*`
+fn is_plain_glob(pattern: &str) -> bool {
+    pattern.chars().any(|c| c != '*')
+        && pattern
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '*' | '_' | '-' | '/' | '#'))
+}
+
+/// writes the glob pattern in the quotes escaping only the quotes,
+/// since the pattern keeps its own escapes of `*` and `\`
+fn quote_glob(pattern: &str) -> String {
+    quote_escaping(pattern, &['\'', '"'])
+}
+
+fn quote_escaping(text: &str, escaped: &[char]) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        if escaped.contains(&c) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Writes the path back into the query text that can be parsed again.
+/// The fields are always written in the bracket notation like `$['key']` escaping the quotes, `\` and `*` of the keys,
+/// the globs stay unquoted like `$.cpu_*` unless they have the chars the unquoted keys can not have.
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonPath::Root => write!(f, "$"),
+            JsonPath::Field(key) => write!(f, "[{}]", quote_key(key)),
+            JsonPath::FieldGlob(pattern) if is_plain_glob(pattern) => write!(f, ".{pattern}"),
+            JsonPath::FieldGlob(pattern) => write!(f, "[{}]", quote_glob(pattern)),
+            JsonPath::Chain(elems) => elems.iter().try_for_each(|e| write!(f, "{e}")),
+            JsonPath::Descent(key) => write!(f, "..[{}]", quote_key(key)),
+            JsonPath::DescentGlob(pattern) if is_plain_glob(pattern) => write!(f, "..{pattern}"),
+            JsonPath::DescentGlob(pattern) => write!(f, "..[{}]", quote_glob(pattern)),
+            JsonPath::DescentW => write!(f, "..*"),
+            JsonPath::DescentIndex(index) => write!(f, "..{index}"),
+            JsonPath::Index(index) => write!(f, "{index}"),
+            JsonPath::Current(tail) => write!(f, "@{tail}"),
+            JsonPath::Wildcard => write!(f, "[*]"),
+            JsonPath::Empty => Ok(()),
+            JsonPath::Fn(Function::Length) => write!(f, ".length()"),
+            JsonPath::Fn(Function::Aggregate(a)) => write!(f, ".{}()", a.name()),
+            JsonPath::Fn(Function::Keys) => write!(f, ".keys()"),
+            JsonPath::Fn(Function::Extract(re, group)) => {
+                write!(f, ".extract({}, {group})", quote(re))
+            }
+            JsonPath::Fn(Function::Default(v)) => write!(f, " ?? {}", Operand::Static(v.clone())),
+            JsonPath::Type(tp) => write!(f, "@{tp}()"),
+        }
+    }
+}
+
+impl Display for JsonType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            JsonType::Null => "null",
+            JsonType::Boolean => "boolean",
+            JsonType::Number => "number",
+            JsonType::Integer => "integer",
+            JsonType::String => "string",
+            JsonType::Array => "array",
+            JsonType::Object => "object",
+            JsonType::Scalar => "scalar",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Display for JsonPathIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fmt_slice = |s: &i32, e: &i32, st: &usize| {
+            let start = if *s == 0 {
+                String::new()
+            } else {
+                s.to_string()
+            };
+            let end = if *e == 0 {
+                String::new()
+            } else {
+                e.to_string()
+            };
+            if *st == 1 {
+                format!("{start}:{end}")
+            } else {
+                format!("{start}:{end}:{st}")
+            }
+        };
+        let join = |elems: Vec<String>| elems.join(",");
+        match self {
+            JsonPathIndex::Single(idx) => write!(f, "[{idx}]"),
+            JsonPathIndex::UnionIndex(elems) => {
+                write!(
+                    f,
+                    "[{}]",
+                    join(elems.iter().map(|e| e.to_string()).collect())
+                )
+            }
+            JsonPathIndex::UnionKeys(elems) => {
+                write!(
+                    f,
+                    "[{}]",
+                    join(elems.iter().map(|e| quote_key(e)).collect())
+                )
+            }
+            JsonPathIndex::Union(elems) => write!(
+                f,
+                "[{}]",
+                join(
+                    elems
+                        .iter()
+                        .map(|e| match e {
+                            JsonPath::Field(key) => quote_key(key),
+                            JsonPath::Index(JsonPathIndex::Single(idx)) => idx.to_string(),
+                            JsonPath::Index(JsonPathIndex::Slice(s, e, st)) => fmt_slice(s, e, st),
+                            other => other.to_string(),
+                        })
+                        .collect()
+                )
+            ),
+            JsonPathIndex::Slice(s, e, st) => write!(f, "[{}]", fmt_slice(s, e, st)),
+            JsonPathIndex::Filter(expr) => write!(f, "[?({expr})]"),
+        }
+    }
+}
+
+impl Display for FilterExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterExpression::Atom(Operand::Dynamic(jp), FilterSign::Exists, _) => {
+                match jp.as_ref() {
+                    // the type predicate like @array(@.items)
+                    JsonPath::Chain(elems) if elems.len() > 1 => match elems.split_last() {
+                        Some((JsonPath::Type(tp), rest)) => {
+                            write!(f, "@{tp}({})", JsonPath::Chain(rest.to_vec()))
+                        }
+                        _ => write!(f, "{jp}"),
+                    },
+                    _ => write!(f, "{jp}"),
+                }
+            }
+            FilterExpression::Atom(left, FilterSign::Exists, _) => write!(f, "{left}"),
+            FilterExpression::Atom(left, FilterSign::Schema, right) => {
+                write!(f, "schema({left}, {right})")
+            }
+            FilterExpression::Atom(left, sign, right) => write!(f, "{left} {sign} {right}"),
+            FilterExpression::And(left, right) => {
+                let left = match left.as_ref() {
+                    FilterExpression::Or(..) | FilterExpression::Xor(..) => format!("({left})"),
+                    _ => left.to_string(),
+                };
+                let right = match right.as_ref() {
+                    FilterExpression::Or(..)
+                    | FilterExpression::Xor(..)
+                    | FilterExpression::And(..) => format!("({right})"),
+                    _ => right.to_string(),
+                };
+                write!(f, "{left} && {right}")
+            }
+            FilterExpression::Xor(left, right) => {
+                let left = match left.as_ref() {
+                    FilterExpression::Or(..) => format!("({left})"),
+                    _ => left.to_string(),
+                };
+                let right = match right.as_ref() {
+                    FilterExpression::Or(..) | FilterExpression::Xor(..) => format!("({right})"),
+                    _ => right.to_string(),
+                };
+                write!(f, "{left} xor {right}")
+            }
+            FilterExpression::Or(left, right) => match right.as_ref() {
+                FilterExpression::Or(..) => write!(f, "{left} || ({right})"),
+                _ => write!(f, "{left} || {right}"),
+            },
+            FilterExpression::Not(exp) => match exp.as_ref() {
+                FilterExpression::Atom(..) => write!(f, "!{exp}"),
+                _ => write!(f, "!({exp})"),
+            },
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn fmt_static(v: &Value) -> String {
+            match v {
+                Value::String(s) => quote(s),
+                Value::Array(elems) => format!(
+                    "[{}]",
+                    elems.iter().map(fmt_static).collect::<Vec<_>>().join(",")
+                ),
+                Value::Object(members) => format!(
+                    "{{{}}}",
+                    members
+                        .iter()
+                        .map(|(k, v)| format!("{}:{}", quote(k), fmt_static(v)))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ),
+                other => other.to_string(),
+            }
+        }
+        match self {
+            Operand::Static(v) => write!(f, "{}", fmt_static(v)),
+            Operand::Dynamic(jp) => write!(f, "{jp}"),
+            Operand::Param(name) => write!(f, ":{name}"),
+            Operand::Index => write!(f, "@index"),
+            Operand::Property => write!(f, "@property"),
+            Operand::Path => write!(f, "@path"),
+            Operand::Context(jp) => write!(f, "$ctx{}", &jp.to_string()[1..]),
+        }
+    }
+}
+
+impl Display for FilterSign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = match self {
+            FilterSign::Equal => "==",
+            FilterSign::Unequal => "!=",
+            FilterSign::Less => "<",
+            FilterSign::Greater => ">",
+            FilterSign::LeOrEq => "<=",
+            FilterSign::GrOrEq => ">=",
+            FilterSign::Regex => "~=",
+            FilterSign::In => "in",
+            FilterSign::Nin => "nin",
+            FilterSign::Size => "size",
+            FilterSign::NoneOf => "noneOf",
+            FilterSign::AnyOf => "anyOf",
+            FilterSign::SubSetOf => "subsetOf",
+            FilterSign::Exists => "exists",
+            FilterSign::Schema => "schema",
+        };
+        write!(f, "{sign}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::builder::field;
+    use crate::parser::model::{FilterExpression, FilterSign, JsonPath, JsonPathIndex, Operand};
+    use crate::parser::parser::parse_json_path;
+    use serde_json::json;
+
+    fn test(input: &str, expected: &str) {
+        let path = parse_json_path(input).expect("the path is correct");
+        assert_eq!(path.to_string(), expected);
+        let reparsed = parse_json_path(&path.to_string()).expect("the output is correct");
+        assert_eq!(reparsed, path);
+    }
+
+    #[test]
+    fn is_singular_test() {
+        let singular = |q: &str| parse_json_path(q).unwrap().is_singular();
+        assert!(singular("$"));
+        assert!(singular("$.a['b'][0].length()"));
+        assert!(singular("$.a[1]@string()"));
+        assert!(!singular("$.a[*]"));
+        assert!(!singular("$..a"));
+        assert!(!singular("$.a[0:1]"));
+        assert!(!singular("$.a[0,1]"));
+        assert!(!singular("$.a[?(@.b)]"));
+        assert!(!singular("$.a_*"));
+    }
+
+    #[test]
+    fn display_chain_test() {
+        test(
+            "$.a['b']..c..*.*[*].length()",
+            "$['a']['b']..['c']..*[*][*].length()",
+        );
+        test("$.a.stddev()", "$['a'].stddev()");
+        test("$.a[*].keys()", "$['a'][*].keys()");
+        test(
+            "$[1][1,2]['a','b'][0,'a',1:3]",
+            "$[1][1,2]['a','b'][0,'a',1:3]",
+        );
+        test("$[:][1:][:-1][::2][1:5:2]", "$[:][1:][:-1][::2][1:5:2]");
+        test("$..[?(@.a)]..[0]", "$..[?(@['a'])]..[0]");
+        test("$..*@string()", "$..*@string()");
+    }
+
+    #[test]
+    fn display_escape_test() {
+        let key = "x']..['y\\";
+        let path = JsonPath::Chain(vec![
+            JsonPath::Root,
+            JsonPath::Field(key.to_string()),
+            JsonPath::Descent("it's".to_string()),
+            JsonPath::Index(JsonPathIndex::UnionKeys(vec![
+                key.to_string(),
+                "b".to_string(),
+            ])),
+            JsonPath::Index(JsonPathIndex::Union(vec![
+                JsonPath::Field(key.to_string()),
+                JsonPath::Index(JsonPathIndex::Single(json!(0))),
+            ])),
+            JsonPath::Index(JsonPathIndex::Filter(FilterExpression::Atom(
+                field(key),
+                FilterSign::In,
+                Operand::Static(json!([key, {key: "it's"}])),
+            ))),
+        ]);
+        let text = path.to_string();
+        assert!(text.starts_with(r"$['x\']..[\'y\\']..['it\'s']"));
+        assert_eq!(parse_json_path(&text).expect("the output is correct"), path);
+        test(r"$['a\'b\\']", r"$['a\'b\\']");
+        test(r#"$["a\'b\"c"]"#, r#"$['a\'b\"c']"#);
+    }
+
+    #[test]
+    fn display_filter_test() {
+        test(
+            "$[?(@.a == 'b' && @.c > 1.5 || !@.d)]",
+            "$[?(@['a'] == 'b' && @['c'] > 1.5 || !@['d'])]",
+        );
+        test(
+            "$[?((@.a || @.b) && (@.c && @.d))]",
+            "$[?((@['a'] || @['b']) && (@['c'] && @['d']))]",
+        );
+        test("$[?(!(@.a || @.b))]", "$[?(!(@['a'] || @['b']))]");
+        test(
+            "$[?(@.a xor @.b && @.c || @.d)]",
+            "$[?(@['a'] xor @['b'] && @['c'] || @['d'])]",
+        );
+        test(
+            "$[?((@.a || @.b) xor (@.c xor @.d) && (@.e xor @.f))]",
+            "$[?((@['a'] || @['b']) xor (@['c'] xor @['d']) && (@['e'] xor @['f']))]",
+        );
+        test("$[?(@.a not in [1])]", "$[?(@['a'] nin [1])]");
+        test(
+            "$[?(@.a in ['x','y'] && @ nin [1,2] && @.b == null && @.c == true)]",
+            "$[?(@['a'] in ['x','y'] && @ nin [1,2] && @['b'] == null && @['c'] == true)]",
+        );
+        test("$[?(@.id == $.id)]", "$[?(@['id'] == $['id'])]");
+        test("$[?(@array(@.items))]", "$[?(@array(@['items']))]");
+        test(
+            "$[?(position() < 2 || @index>@.a)]",
+            "$[?(@index < 2 || @index > @['a'])]",
+        );
+        #[cfg(feature = "jsonschema")]
+        test("$[?(!schema(@.a, 'x'))]", "$[?(!schema(@['a'], 'x'))]");
+    }
+
+    #[test]
+    fn display_default_test() {
+        test("$.a.b ?? 30", "$['a']['b'] ?? 30");
+        test("$.a[*].length()??'none'", "$['a'][*].length() ?? 'none'");
+        test("$[?(@.qty ?? 0 > 5)]", "$[?(@['qty'] ?? 0 > 5)]");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn display_extract_test() {
+        test(
+            r"$..msg.extract( 'id=(\d+)' ,1)",
+            r"$..['msg'].extract('id=(\\d+)', 1)",
+        );
+        test(
+            r#"$.msg.extract('it\'s (\\w+) \"(\d)\"')"#,
+            r#"$['msg'].extract('it\'s (\\w+) \"(\\d)\"', 0)"#,
+        );
+        test("$.msg.extract('a')", "$['msg'].extract('a', 0)");
+        test("$.extract", "$['extract']");
+    }
+
+    #[test]
+    fn display_literal_test() {
+        test(
+            r#"$[?(@.a == {"city": "Oslo", "tags": [['x'], 1]})]"#,
+            "$[?(@['a'] == {'city':'Oslo','tags':[['x'],1]})]",
+        );
+    }
+
+    #[test]
+    fn display_built_test() {
+        let path = JsonPath::root().field("store").index(0);
+        assert_eq!(path.to_string(), "$['store'][0]");
+    }
+}
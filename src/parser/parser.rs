@@ -2,9 +2,9 @@
 
 use crate::parser::errors::JsonPathParserError::ParserError;
 use crate::parser::errors::{parser_err, JsonPathParserError};
-use crate::parser::model::FilterExpression::{And, Not, Or};
+use crate::parser::model::FilterExpression::{And, Not, Or, Xor};
 use crate::parser::model::{
-    FilterExpression, FilterSign, Function, JsonPath, JsonPathIndex, Operand,
+    Aggregate, FilterExpression, FilterSign, Function, JsonPath, JsonPathIndex, JsonType, Operand,
 };
 use pest::iterators::{Pair, Pairs};
 use pest::Parser;
@@ -19,7 +19,7 @@ struct JsonPathParser;
 /// # Errors
 ///
 /// Returns a variant of [JsonPathParserError] if the parsing operation failed.
-pub fn parse_json_path(jp_str: &str) -> Result<JsonPath, JsonPathParserError> {
+pub fn parse_json_path(jp_str: &str) -> Result<JsonPath, JsonPathParserError<'_>> {
     JsonPathParser::parse(Rule::path, jp_str)?
         .next()
         .ok_or(parser_err(jp_str))
@@ -51,29 +51,125 @@ fn parse_internal(rule: Pair<Rule>) -> Result<JsonPath, JsonPathParserError> {
             .map(JsonPath::Chain),
         Rule::root => Ok(JsonPath::Root),
         Rule::wildcard => Ok(JsonPath::Wildcard),
-        Rule::descent => parse_key(down(rule)?)?
-            .map(JsonPath::Descent)
-            .ok_or(parser_err("expected a JsonPath::Descent but found nothing")),
+        Rule::descent => {
+            let key = down(rule)?;
+            if let Some(pattern) = glob(&key) {
+                Ok(JsonPath::DescentGlob(pattern))
+            } else {
+                parse_key(key)?
+                    .map(JsonPath::Descent)
+                    .ok_or(parser_err("expected a JsonPath::Descent but found nothing"))
+            }
+        }
         Rule::descent_w => Ok(JsonPath::DescentW),
-        Rule::function => Ok(JsonPath::Fn(Function::Length)),
-        Rule::field => parse_key(down(rule)?)?
-            .map(JsonPath::Field)
-            .ok_or(parser_err("expected a JsonPath::Field but found nothing")),
+        Rule::descent_idx => parse_index(rule).map(JsonPath::DescentIndex),
+        Rule::function => {
+            let name = down(rule)?;
+            Ok(JsonPath::Fn(match name.as_str() {
+                "keys" => Function::Keys,
+                other => Aggregate::new(other).map_or(Function::Length, Function::Aggregate),
+            }))
+        }
+        Rule::extract => parse_extract(rule.into_inner()),
+        Rule::coalesce => {
+            let fallback = down(rule)?;
+            let value = match fallback.as_rule() {
+                Rule::string_qt => Value::from(unquote(fallback)?),
+                Rule::number => number_to_value(fallback.as_str())?,
+                _ => fallback.as_str().parse::<Value>()?,
+            };
+            Ok(JsonPath::Fn(Function::Default(value)))
+        }
+        Rule::type_sel => Ok(JsonPath::Type(JsonType::new(down(rule)?.as_str()))),
+        Rule::field => {
+            let key = down(rule)?;
+            if let Some(pattern) = glob(&key) {
+                Ok(JsonPath::FieldGlob(pattern))
+            } else {
+                parse_key(key)?
+                    .map(JsonPath::Field)
+                    .ok_or(parser_err("expected a JsonPath::Field but found nothing"))
+            }
+        }
         Rule::index => parse_index(rule).map(JsonPath::Index),
         _ => Err(ParserError(format!("{rule} did not match any 'Rule' "))),
     }
 }
 
+/// the pattern of the key containing `*` like `.cpu_*` or `['error*']`,
+/// the pattern keeps `\*` and `\\` standing for the star and the backslash themselves
+fn glob(rule: &Pair<Rule>) -> Option<String> {
+    match rule.as_rule() {
+        Rule::key_glob => Some(rule.as_str().to_string()),
+        Rule::inner if has_wildcard(rule.as_str()) => Some(unescape(rule.as_str(), &QUOTES)),
+        Rule::key | Rule::key_unlim | Rule::string_qt => {
+            rule.clone().into_inner().next().and_then(|k| glob(&k))
+        }
+        _ => None,
+    }
+}
+
+/// checks if the quoted text has the star that is not escaped like `\*`
+fn has_wildcard(text: &str) -> bool {
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
 /// parsing the rule 'key' with the structures either .key or .\['key'\]
 fn parse_key(rule: Pair<Rule>) -> Result<Option<String>, JsonPathParserError> {
     let parsed_key = match rule.as_rule() {
         Rule::key | Rule::key_unlim | Rule::string_qt => parse_key(down(rule)?),
-        Rule::key_lim | Rule::inner => Ok(Some(String::from(rule.as_str()))),
+        Rule::key_lim => Ok(Some(String::from(rule.as_str()))),
+        Rule::inner => Ok(Some(unescape(rule.as_str(), &KEY_ESCAPES))),
         _ => Ok(None),
     };
     parsed_key
 }
 
+/// the quotes escaped in the globs, the rest of the escapes are kept by the pattern
+const QUOTES: [char; 2] = ['\'', '"'];
+/// the characters escaped in the quoted strings
+const STRING_ESCAPES: [char; 3] = ['\\', '\'', '"'];
+/// the keys escape the star as well since the key with the unescaped one is the glob
+const KEY_ESCAPES: [char; 4] = ['\\', '\'', '"', '*'];
+
+/// the text of the quoted string with the escaped characters unescaped
+fn unquote(rule: Pair<Rule>) -> Result<String, JsonPathParserError> {
+    Ok(unescape(down(rule)?.as_str(), &STRING_ESCAPES))
+}
+
+/// the quoted key with the escaped characters and the star unescaped
+fn unquote_key(rule: Pair<Rule>) -> Result<String, JsonPathParserError> {
+    Ok(unescape(down(rule)?.as_str(), &KEY_ESCAPES))
+}
+
+/// unescapes the given characters like `\'` and `\\`,
+/// the other escapes like `\d` of the regexes stay as they are
+fn unescape(text: &str, escapes: &[char]) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(n) if escapes.contains(&n) => unescaped.push(n),
+                Some(n) => unescaped.extend([c, n]),
+                None => unescaped.push(c),
+            },
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
 fn parse_slice(pairs: Pairs<Rule>) -> Result<JsonPathIndex, JsonPathParserError> {
     let mut start = 0;
     let mut end = 0;
@@ -93,12 +189,27 @@ fn parse_unit_keys(pairs: Pairs<Rule>) -> Result<JsonPathIndex, JsonPathParserEr
     let mut keys = vec![];
 
     for pair in pairs {
-        keys.push(String::from(down(pair)?.as_str()));
+        keys.push(unquote_key(pair)?);
     }
     Ok(JsonPathIndex::UnionKeys(keys))
 }
 
-fn number_to_value(number: &str) -> Result<Value, JsonPathParserError> {
+fn parse_unit_mixed(pairs: Pairs<Rule>) -> Result<JsonPathIndex, JsonPathParserError> {
+    let mut elems = vec![];
+
+    for pair in pairs {
+        let elem = down(pair)?;
+        let parsed_elem = match elem.as_rule() {
+            Rule::slice => JsonPath::Index(parse_slice(elem.into_inner())?),
+            Rule::string_qt => JsonPath::Field(unquote_key(elem)?),
+            _ => JsonPath::Index(JsonPathIndex::Single(number_to_value(elem.as_str())?)),
+        };
+        elems.push(parsed_elem);
+    }
+    Ok(JsonPathIndex::Union(elems))
+}
+
+fn number_to_value(number: &str) -> Result<Value, JsonPathParserError<'_>> {
     match number
         .parse::<i64>()
         .ok()
@@ -154,7 +265,7 @@ fn parse_logic_or(pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPathParser
     let mut expr: Option<FilterExpression> = None;
     let error_message = format!("Failed to parse logical expression: {:?}", pairs);
     for pair in pairs {
-        let next_expr = parse_logic_and(pair.into_inner())?;
+        let next_expr = parse_logic_xor(pair.into_inner())?;
         match expr {
             None => expr = Some(next_expr),
             Some(e) => expr = Some(Or(Box::new(e), Box::new(next_expr))),
@@ -166,6 +277,22 @@ fn parse_logic_or(pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPathParser
     }
 }
 
+fn parse_logic_xor(pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPathParserError> {
+    let mut expr: Option<FilterExpression> = None;
+    let error_message = format!("Failed to parse logical `xor` expression: {:?}", pairs);
+    for pair in pairs {
+        let next_expr = parse_logic_and(pair.into_inner())?;
+        match expr {
+            None => expr = Some(next_expr),
+            Some(e) => expr = Some(Xor(Box::new(e), Box::new(next_expr))),
+        }
+    }
+    match expr {
+        Some(expr) => Ok(expr),
+        None => Err(JsonPathParserError::ParserError(error_message)),
+    }
+}
+
 fn parse_logic_and(pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPathParserError> {
     let mut expr: Option<FilterExpression> = None;
     let error_message = format!("Failed to parse logical `and` expression: {:?}", pairs,);
@@ -202,12 +329,18 @@ fn parse_logic_atom(mut pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPath
     if let Some(rule) = pairs.peek().map(|x| x.as_rule()) {
         match rule {
             Rule::logic_or => parse_logic_or(pairs.next().expect("unreachable in arithmetic: should have a value as pairs.peek() was Some(_)").into_inner()),
+            Rule::type_check => parse_type_check(pairs.next().expect("unreachable in arithmetic: should have a value as pairs.peek() was Some(_)").into_inner()),
+            Rule::schema_check => parse_schema_check(pairs.next().expect("unreachable in arithmetic: should have a value as pairs.peek() was Some(_)").into_inner()),
             Rule::atom => {
                 let left: Operand = parse_atom(pairs.next().unwrap())?;
                 if pairs.peek().is_none() {
                     Ok(FilterExpression::exists(left))
+                } else if let Some(Rule::between) = pairs.peek().map(|p| p.as_rule()) {
+                    parse_between(left, pairs.next().expect("unreachable in arithmetic: should have a value as pairs.peek() was Some(_)").into_inner())
                 } else {
-                    let sign: FilterSign = FilterSign::new(pairs.next().expect("unreachable in arithmetic: should have a value as pairs.peek() was Some(_)").as_str());
+                    let sign = pairs.next().expect("unreachable in arithmetic: should have a value as pairs.peek() was Some(_)");
+                    // the words of `not in` may be separated by any whitespace
+                    let sign: FilterSign = FilterSign::new(&sign.as_str().split_whitespace().collect::<Vec<_>>().join(" "));
                     let right: Operand =
                         parse_atom(pairs.next().expect("unreachable in arithemetic: should have a right side operand"))?;
                     Ok(FilterExpression::Atom(left, sign, right))
@@ -220,18 +353,164 @@ fn parse_logic_atom(mut pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPath
     }
 }
 
+/// parsing the predicate like @array(@.items) into the existence check of @.items@array()
+fn parse_type_check(mut pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPathParserError> {
+    let tp = pairs
+        .next()
+        .map(|p| JsonType::new(p.as_str()))
+        .ok_or(parser_err("expected a type name in the type predicate"))?;
+    let mut elems = match pairs.next().map(parse_internal).transpose()? {
+        Some(JsonPath::Chain(elems)) => elems,
+        Some(jp) => vec![jp],
+        None => return Err(parser_err("expected a path in the type predicate")),
+    };
+    elems.push(JsonPath::Type(tp));
+    Ok(FilterExpression::exists(Operand::Dynamic(Box::new(
+        JsonPath::Chain(elems),
+    ))))
+}
+
+/// parsing the function like extract('order=(\d+)', 1)
+#[cfg(feature = "regex")]
+fn parse_extract(mut pairs: Pairs<Rule>) -> Result<JsonPath, JsonPathParserError> {
+    let regex = pairs
+        .next()
+        .map(unquote)
+        .ok_or(parser_err("expected a regex in the function extract"))??;
+    let group = match pairs.next() {
+        Some(group) => group
+            .as_str()
+            .parse::<usize>()
+            .map_err(|e| ParserError(format!("the group of the function extract: {e}")))?,
+        None => 0,
+    };
+    Ok(JsonPath::Fn(Function::Extract(regex, group)))
+}
+
+#[cfg(not(feature = "regex"))]
+fn parse_extract(_: Pairs<Rule>) -> Result<JsonPath, JsonPathParserError> {
+    Err(parser_err(
+        "the function extract requires the feature `regex`",
+    ))
+}
+
+/// parsing the range like @.price between 5 and 10 into @.price >= 5 && @.price <= 10.
+/// The constant bounds are either both numbers or both strings.
+fn parse_between(
+    value: Operand,
+    mut pairs: Pairs<Rule>,
+) -> Result<FilterExpression, JsonPathParserError> {
+    let mut bound = || {
+        pairs
+            .next()
+            .map(parse_atom)
+            .ok_or(parser_err("expected the bounds of the range"))?
+    };
+    let (lower, upper) = (bound()?, bound()?);
+    let mismatched = match (&lower, &upper) {
+        (Operand::Static(l), Operand::Static(u)) => l.is_number() != u.is_number(),
+        _ => false,
+    };
+    let unordered = [&lower, &upper]
+        .into_iter()
+        .any(|b| matches!(b, Operand::Static(v) if !v.is_number() && !v.is_string()));
+    if mismatched || unordered {
+        return Err(parser_err(
+            "the bounds of the range should be both numbers or both strings",
+        ));
+    }
+    Ok(And(
+        Box::new(FilterExpression::Atom(
+            value.clone(),
+            FilterSign::GrOrEq,
+            lower,
+        )),
+        Box::new(FilterExpression::Atom(value, FilterSign::LeOrEq, upper)),
+    ))
+}
+
+/// parsing the predicate like schema(@, 'name') checking the elements against the registered schema
+#[cfg(feature = "jsonschema")]
+fn parse_schema_check(mut pairs: Pairs<Rule>) -> Result<FilterExpression, JsonPathParserError> {
+    let value = pairs
+        .next()
+        .map(parse_chain_in_operand)
+        .ok_or(parser_err("expected a path in the schema predicate"))??;
+    let name = pairs.next().map(unquote).ok_or(parser_err(
+        "expected a name of the schema in the schema predicate",
+    ))??;
+    Ok(FilterExpression::Atom(
+        value,
+        FilterSign::Schema,
+        Operand::Static(Value::from(name)),
+    ))
+}
+
+#[cfg(not(feature = "jsonschema"))]
+fn parse_schema_check(_: Pairs<Rule>) -> Result<FilterExpression, JsonPathParserError> {
+    Err(parser_err(
+        "the schema predicate requires the feature `jsonschema`",
+    ))
+}
+
 fn parse_atom(rule: Pair<Rule>) -> Result<Operand, JsonPathParserError> {
     let atom = down(rule.clone())?;
     let parsed_atom = match atom.as_rule() {
         Rule::number => Operand::Static(number_to_value(rule.as_str())?),
-        Rule::string_qt => Operand::Static(Value::from(down(atom)?.as_str())),
+        Rule::string_qt => Operand::Static(Value::from(unquote(atom)?)),
         Rule::chain => parse_chain_in_operand(down(rule)?)?,
         Rule::boolean => Operand::Static(rule.as_str().parse::<Value>()?),
+        Rule::literal_obj | Rule::literal_arr => Operand::Static(parse_literal(atom)?),
+        Rule::param => Operand::Param(down(atom)?.as_str().to_string()),
+        Rule::position => Operand::Index,
+        Rule::property => Operand::Property,
+        Rule::node_path => Operand::Path,
+        Rule::context => {
+            let mut elems = vec![JsonPath::Root];
+            if let Some(chain) = atom.into_inner().next() {
+                match parse_internal(chain)? {
+                    JsonPath::Chain(tail) => elems.extend(tail),
+                    other => elems.push(other),
+                }
+            }
+            Operand::Context(Box::new(JsonPath::Chain(elems)))
+        }
         _ => Operand::Static(Value::Null),
     };
     Ok(parsed_atom)
 }
 
+/// parses the object or the array literal of the filter into the json value
+fn parse_literal(rule: Pair<Rule>) -> Result<Value, JsonPathParserError> {
+    let value = match rule.as_rule() {
+        Rule::literal => parse_literal(down(rule)?)?,
+        Rule::literal_obj => {
+            let mut members = serde_json::Map::new();
+            for member in rule.into_inner() {
+                let mut pairs = member.into_inner();
+                let key = pairs
+                    .next()
+                    .ok_or(parser_err("expected a key of the object"))?;
+                let value = pairs
+                    .next()
+                    .ok_or(parser_err("expected a value of the object"))?;
+                members.insert(unquote(key)?, parse_literal(value)?);
+            }
+            Value::Object(members)
+        }
+        Rule::literal_arr => Value::Array(
+            rule.into_inner()
+                .map(parse_literal)
+                .collect::<Result<_, _>>()?,
+        ),
+        Rule::string_qt => Value::from(unquote(rule)?),
+        Rule::number => number_to_value(rule.as_str())?,
+        Rule::boolean => rule.as_str().parse::<Value>()?,
+        _ => Value::Null,
+    };
+    Ok(value)
+}
+
 fn parse_index(rule: Pair<Rule>) -> Result<JsonPathIndex, JsonPathParserError> {
     let next = down(rule)?;
     let parsed_index = match next.as_rule() {
@@ -239,6 +518,7 @@ fn parse_index(rule: Pair<Rule>) -> Result<JsonPathIndex, JsonPathParserError> {
         Rule::slice => parse_slice(next.into_inner())?,
         Rule::unit_indexes => parse_unit_indexes(next.into_inner())?,
         Rule::unit_keys => parse_unit_keys(next.into_inner())?,
+        Rule::unit_mixed => parse_unit_mixed(next.into_inner())?,
         Rule::filter => parse_filter_index(down(next)?)?,
         _ => JsonPathIndex::Single(number_to_value(next.as_str())?),
     };
@@ -316,16 +596,54 @@ mod tests {
     fn descent_test() {
         test("..abc", vec![path!(.."abc")]);
         test("..['abc']", vec![path!(.."abc")]);
+        test("..abc*", vec![JsonPath::DescentGlob("abc*".to_string())]);
+        test(
+            "..['abc*']",
+            vec![JsonPath::DescentGlob("abc*".to_string())],
+        );
+        test("..['abc\\*']", vec![path!(.."abc*")]);
         test_failed("...['abc']");
         test_failed("...abc");
     }
 
+    #[test]
+    fn descent_index_test() {
+        test(
+            "$..[?(@.price > 10)]",
+            vec![
+                path!($),
+                JsonPath::DescentIndex(
+                    idx!(?filter!(op!(chain!(path!(@,path!("price")))), ">", op!(10))),
+                ),
+            ],
+        );
+        test("..[1]", vec![JsonPath::DescentIndex(idx!(1))]);
+        test("..[1,2]", vec![JsonPath::DescentIndex(idx!(idx 1,2))]);
+        test("..['abc']", vec![path!(.."abc")]);
+        test_failed("...[1]");
+    }
+
     #[test]
     fn field_test() {
         test(".abc", vec![path!("abc")]);
         test(".['abc']", vec![path!("abc")]);
         test("['abc']", vec![path!("abc")]);
-        test(".['abc\\\"abc']", vec![path!("abc\\\"abc")]);
+        test(".['abc\\\"abc']", vec![path!("abc\"abc")]);
+        test("['it\\'s \\\\d']", vec![path!("it's \\d")]);
+        test("['\\d']", vec![path!("\\d")]);
+        test(".abc_*", vec![JsonPath::FieldGlob("abc_*".to_string())]);
+        test(".*_abc", vec![JsonPath::FieldGlob("*_abc".to_string())]);
+        test(".['abc*']", vec![JsonPath::FieldGlob("abc*".to_string())]);
+        test("['abc*']", vec![JsonPath::FieldGlob("abc*".to_string())]);
+        test("['a b*']", vec![JsonPath::FieldGlob("a b*".to_string())]);
+        // the escaped star is the star itself, the glob keeps its escapes
+        test("['abc\\*']", vec![path!("abc*")]);
+        test("['\\*\\\\']", vec![path!("*\\")]);
+        test(
+            "['it\\'s\\**']",
+            vec![JsonPath::FieldGlob("it's\\**".to_string())],
+        );
+        test("['a\\*','b*']", vec![path!(idx!("a*", "b*"))]);
         test_failed(".abc()abc");
         test_failed("..[abc]");
         test_failed(".'abc'");
@@ -370,6 +688,30 @@ mod tests {
         test("[\"abc\",\"bcd\"]", vec![path!(idx!("abc", "bcd"))]);
     }
 
+    #[test]
+    fn index_union_mixed_test() {
+        test(
+            "[0,'abc',2:5]",
+            vec![path!(
+                idx!(union path!(idx!(0)), path!("abc"), path!(idx!([2;5;])))
+            )],
+        );
+        test(
+            "['abc', 1]",
+            vec![path!(idx!(union path!("abc"), path!(idx!(1))))],
+        );
+        test(
+            "[:2,'abc']",
+            vec![path!(idx!(union path!(idx!([;2;])), path!("abc")))],
+        );
+        test(
+            "[-1,'a']",
+            vec![path!(idx!(union path!(idx!(-1)), path!("a")))],
+        );
+        test_failed("[0,abc]");
+        test_failed("[0,'abc',]");
+    }
+
     #[test]
     fn array_start_test() {
         test(
@@ -437,6 +779,130 @@ mod tests {
         );
     }
 
+    #[test]
+    fn xor_filter_test() {
+        let verb = || filter!(op!(chain!(path!(@,path!("verb")))), "==", op!("T"));
+        let small = || filter!(op!(chain!(path!(@,path!("size")))), "<", op!(10));
+        let elem = || filter!(op!(chain!(path!(@,path!("elem")))), "==", op!(0));
+        test(
+            "$[?(@.verb == 'T' || @.size < 10 xor @.elem == 0 && @.verb == 'T')]",
+            vec![
+                path!($),
+                path!(idx!(?filter!(
+                    verb(),
+                    ||,
+                    filter!(small(), xor, filter!(elem(), &&, verb()))
+                ))),
+            ],
+        );
+        test(
+            "$[?(@.verb == 'T' xor @.size < 10 xor @.elem == 0)]",
+            vec![
+                path!($),
+                path!(idx!(?filter!(filter!(verb(), xor, small()), xor, elem()))),
+            ],
+        );
+        test(
+            "$[?((@.verb == 'T' || @.size < 10) xor !(@.elem == 0))]",
+            vec![
+                path!($),
+                path!(idx!(?filter!(
+                    filter!(verb(), ||, small()),
+                    xor,
+                    FilterExpression::Not(Box::new(elem()))
+                ))),
+            ],
+        );
+        test_failed("$[?(@.verb xor)]");
+    }
+
+    #[test]
+    fn between_test() {
+        let price = || op!(chain!(path!(@,path!("price"))));
+        test(
+            "$[?(@.price between 5 and 10.5)]",
+            vec![
+                path!($),
+                path!(idx!(?filter!(
+                    filter!(price(), ">=", op!(5)),
+                    &&,
+                    filter!(price(), "<=", op!(10.5))
+                ))),
+            ],
+        );
+        test(
+            "$[?(@.price between :min and $.max || @.free)]",
+            vec![
+                path!($),
+                path!(idx!(?filter!(
+                    filter!(
+                        filter!(price(), ">=", Operand::Param("min".to_string())),
+                        &&,
+                        filter!(price(), "<=", op!(chain!(path!($), path!("max"))))
+                    ),
+                    ||,
+                    FilterExpression::exists(op!(chain!(path!(@,path!("free")))))
+                ))),
+            ],
+        );
+        test_failed("$[?(@.price between 5 and 'x')]");
+        test_failed("$[?(@.price between true and false)]");
+        test_failed("$[?(@.price between 5)]");
+    }
+
+    #[test]
+    fn literal_test() {
+        let address = || op!(chain!(path!(@,path!("address"))));
+        test(
+            r#"$[?(@.address == {"city":"Oslo", 'zip': '0150'})]"#,
+            vec![
+                path!($),
+                path!(idx!(?filter!(
+                    address(),
+                    "==",
+                    op!(s json!({"city": "Oslo", "zip": "0150"}))
+                ))),
+            ],
+        );
+        test(
+            "$[?(@.address in [{'tags': [['a'], []]}, null, true])]",
+            vec![
+                path!($),
+                path!(idx!(?filter!(
+                    address(),
+                    "in",
+                    op!(s json!([{"tags": [["a"], []]}, null, true]))
+                ))),
+            ],
+        );
+        test(
+            "$[?(@.address == {})]",
+            vec![
+                path!($),
+                path!(idx!(?filter!(address(), "==", op!(s json!({}))))),
+            ],
+        );
+        test_failed("$[?(@.address == {'city'})]");
+        test_failed("$[?(@.address == {city: 'Oslo'})]");
+        test_failed("$[?(@.address == [1,])]");
+    }
+
+    #[test]
+    fn not_in_test() {
+        let expected = vec![
+            path!($),
+            path!(idx!(?filter!(
+                op!(chain!(path!(@,path!("verb")))),
+                "nin",
+                op!(s json!(["T", "U"]))
+            ))),
+        ];
+        test("$[?(@.verb nin ['T','U'])]", expected.clone());
+        test("$[?(@.verb not in ['T','U'])]", expected.clone());
+        test("$[?(@.verb not\n  in ['T','U'])]", expected);
+        test_failed("$[?(@.verb notin ['T','U'])]");
+    }
+
     #[test]
     fn index_filter_test() {
         test(
@@ -519,7 +985,12 @@ mod tests {
 
         test_failed("[?(@[1] subsetof ['abc','abc'])]");
         test_failed("[?(@ >< ['abc','abc'])]");
-        test_failed("[?(@ in {\"abc\":1})]");
+        test(
+            "[?(@ in {\"abc\":1})]",
+            vec![path!(idx!(?filter!(
+                op!(chain!(path!(@path!()))),"in",op!(s json!({"abc":1}))
+            )))],
+        );
     }
 
     #[test]
@@ -529,12 +1000,106 @@ mod tests {
             vec![path!($), path!("k"), function!(length)],
         );
 
+        test(
+            "$.k.min()",
+            vec![
+                path!($),
+                path!("k"),
+                JsonPath::Fn(Function::Aggregate(Aggregate::Min)),
+            ],
+        );
+        test(
+            "$.k.keys.field",
+            vec![path!($), path!("k"), path!("keys"), path!("field")],
+        );
         test(
             "$.k.length.field",
             vec![path!($), path!("k"), path!("length"), path!("field")],
         )
     }
 
+    #[test]
+    #[cfg(feature = "jsonschema")]
+    fn schema_predicate_test() {
+        test(
+            "$.events[?(schema(@, 'event_v2') && @.id)]",
+            vec![
+                path!($),
+                path!("events"),
+                path!(idx!(?filter!(
+                    FilterExpression::Atom(
+                        op!(chain!(path!(@path!()))),
+                        FilterSign::Schema,
+                        op!("event_v2")
+                    ),
+                    &&,
+                    FilterExpression::exists(op!(chain!(path!(@,path!("id")))))
+                ))),
+            ],
+        );
+        test_failed("$[?(schema(@))]");
+        test_failed("$[?(schema(@, 1))]");
+    }
+
+    #[test]
+    #[cfg(not(feature = "jsonschema"))]
+    fn schema_predicate_test() {
+        let err = parse_json_path("$[?(schema(@, 'event_v2'))]").unwrap_err();
+        assert!(err.to_string().contains("the feature `jsonschema`"));
+    }
+
+    #[test]
+    fn type_selector_test() {
+        test(
+            "$..*@string()",
+            vec![path!($), path!(..*), JsonPath::Type(JsonType::String)],
+        );
+        test(
+            "$.data[?(@array(@.items))]",
+            vec![
+                path!($),
+                path!("data"),
+                path!(idx!(
+                    ?FilterExpression::exists(
+                        op!(chain!(
+                            path!(@,path!("items")),
+                            JsonPath::Type(JsonType::Array)
+                        )),
+                    )
+                )),
+            ],
+        );
+        test(
+            "$.data[?(@array())]",
+            vec![
+                path!($),
+                path!("data"),
+                path!(idx!(
+                    ?FilterExpression::exists(op!(chain!(JsonPath::Type(JsonType::Array))))
+                )),
+            ],
+        );
+        test_failed("$..*@text()");
+    }
+
+    #[test]
+    fn param_test() {
+        test(
+            "$.data[?(@.price < :max_price)]",
+            vec![
+                path!($),
+                path!("data"),
+                path!(idx!(?filter!(
+                    op!(chain!(path!(@,path!("price")))),
+                    "<",
+                    Operand::Param("max_price".to_string())
+                ))),
+            ],
+        );
+        test_failed("$.data[?(@.price < :1)]");
+        test_failed("$.data[?(@.price < : a)]");
+    }
+
     #[test]
     fn parser_error_test_invalid_rule() {
         let result = parse_json_path("notapath");
@@ -547,6 +1112,82 @@ mod tests {
             .starts_with("Failed to parse rule"));
     }
 
+    #[test]
+    fn parser_error_test_position() {
+        let err = parse_json_path("$.store[abc]").expect_err("the path is incorrect");
+        assert_eq!(err.offset(), Some(8));
+        assert_eq!(err.line_col(), Some((1, 9)));
+        assert_eq!(err.token(), Some("abc]".to_string()));
+        assert!(err.to_string().contains(concat!(
+            "\n  |\n",
+            "1 | $.store[abc]\n",
+            "  |         ^^^^"
+        )));
+        assert!(err
+            .to_string()
+            .starts_with("Failed to parse rule: unexpected `abc]` at line 1, column 9"));
+
+        let err = parse_json_path("$.store[").expect_err("the path is incorrect");
+        assert_eq!(err.offset(), Some(8));
+        assert_eq!(err.token(), None);
+        assert!(err
+            .to_string()
+            .starts_with("Failed to parse rule: unexpected end of the query at line 1, column 9"));
+        assert!(err.to_string().contains("1 | $.store[\n  |         ^\n"));
+
+        let err = parse_json_path("$.a\n .b c").expect_err("the path is incorrect");
+        assert_eq!(err.line_col(), Some((2, 5)));
+        assert_eq!(err.token(), Some("c".to_string()));
+    }
+
+    #[test]
+    fn parser_error_test_hints() {
+        let err = parse_json_path("$.store[abc]").expect_err("the path is incorrect");
+        assert!(err.expected().contains(&"a quoted string"));
+        assert!(err.expected().contains(&"a filter like `?(...)`"));
+        assert_eq!(
+            err.suggestion(),
+            Some("the keys in brackets should be quoted like `['key']`")
+        );
+        assert!(err
+            .to_string()
+            .contains("\n  = help: the keys in brackets should be quoted like `['key']`"));
+
+        let err = parse_json_path("$.a b").expect_err("the path is incorrect");
+        assert!(err.expected().contains(&"the end of the query"));
+        assert_eq!(err.suggestion(), None);
+
+        let err = parse_json_path("$[?(@.a == :)]").expect_err("the path is incorrect");
+        assert!(err.expected().contains(&"a parameter like `:name`"));
+        assert!(!err.expected().contains(&"a whitespace"));
+
+        let suggestion = |q: &str| {
+            parse_json_path(q)
+                .expect_err("the path is incorrect")
+                .suggestion()
+        };
+        assert_eq!(
+            suggestion("$[?(@.a = 1)]"),
+            Some("use `==` to compare the values")
+        );
+        assert_eq!(
+            suggestion("$[?(@.a == 'a)]"),
+            Some("the quoted string is not closed")
+        );
+        assert_eq!(
+            suggestion("$[?(@.a == 1]"),
+            Some("the parentheses `(` and `)` are unbalanced")
+        );
+        assert_eq!(
+            suggestion("$.a[0"),
+            Some("the brackets `[` and `]` are unbalanced")
+        );
+        assert_eq!(
+            suggestion("$[?(@.a == '=' ]"),
+            Some("the parentheses `(` and `)` are unbalanced")
+        );
+    }
+
     #[test]
     fn parser_error_test_empty_rule() {
         let result = parse_json_path("");
@@ -0,0 +1,236 @@
+//! The traversal of the parsed path.
+//!
+//! The trait [`Visitor`] goes over the elements of the path by reference
+//! and the trait [`VisitorMut`] allows changing them in place.
+//! Every method by default descends into the nested elements by calling the corresponding `walk` function,
+//! therefore the implementation overrides only the methods it is interested in
+//! and calls the `walk` function to keep descending.
+//!
+//! ```
+//! use jsonpath_rust::parser::model::JsonPath;
+//! use jsonpath_rust::parser::visitor::{walk_path, Visitor};
+//! use jsonpath_rust::JsonPathInst;
+//! use std::str::FromStr;
+//!
+//! #[derive(Default)]
+//! struct Fields(Vec<String>);
+//!
+//! impl Visitor for Fields {
+//!     fn visit_path(&mut self, path: &JsonPath) {
+//!         if let JsonPath::Field(key) | JsonPath::Descent(key) = path {
+//!             self.0.push(key.clone());
+//!         }
+//!         walk_path(self, path)
+//!     }
+//! }
+//!
+//! let path = JsonPathInst::from_str("$..book[?(@.price < 10)].title").unwrap();
+//! let mut fields = Fields::default();
+//! fields.visit_path(path.path());
+//! assert_eq!(fields.0, vec!["book", "price", "title"]);
+//! ```
+use crate::parser::model::{FilterExpression, JsonPath, JsonPathIndex, Operand};
+
+/// The traversal of the path by reference
+pub trait Visitor {
+    fn visit_path(&mut self, path: &JsonPath) {
+        walk_path(self, path)
+    }
+    fn visit_index(&mut self, index: &JsonPathIndex) {
+        walk_index(self, index)
+    }
+    fn visit_filter(&mut self, expr: &FilterExpression) {
+        walk_filter(self, expr)
+    }
+    fn visit_operand(&mut self, op: &Operand) {
+        walk_operand(self, op)
+    }
+}
+
+/// visits the nested elements of the path
+pub fn walk_path<V: Visitor + ?Sized>(v: &mut V, path: &JsonPath) {
+    match path {
+        JsonPath::Chain(elems) => elems.iter().for_each(|e| v.visit_path(e)),
+        JsonPath::Current(tail) => v.visit_path(tail),
+        JsonPath::Index(index) | JsonPath::DescentIndex(index) => v.visit_index(index),
+        JsonPath::Root
+        | JsonPath::Field(_)
+        | JsonPath::FieldGlob(_)
+        | JsonPath::Descent(_)
+        | JsonPath::DescentGlob(_)
+        | JsonPath::DescentW
+        | JsonPath::Wildcard
+        | JsonPath::Empty
+        | JsonPath::Fn(_)
+        | JsonPath::Type(_) => (),
+    }
+}
+
+/// visits the nested elements of the index
+pub fn walk_index<V: Visitor + ?Sized>(v: &mut V, index: &JsonPathIndex) {
+    match index {
+        JsonPathIndex::Union(elems) => elems.iter().for_each(|e| v.visit_path(e)),
+        JsonPathIndex::Filter(expr) => v.visit_filter(expr),
+        JsonPathIndex::Single(_)
+        | JsonPathIndex::UnionIndex(_)
+        | JsonPathIndex::UnionKeys(_)
+        | JsonPathIndex::Slice(..) => (),
+    }
+}
+
+/// visits the nested elements of the filter
+pub fn walk_filter<V: Visitor + ?Sized>(v: &mut V, expr: &FilterExpression) {
+    match expr {
+        FilterExpression::Atom(left, _, right) => {
+            v.visit_operand(left);
+            v.visit_operand(right);
+        }
+        FilterExpression::And(left, right)
+        | FilterExpression::Or(left, right)
+        | FilterExpression::Xor(left, right) => {
+            v.visit_filter(left);
+            v.visit_filter(right);
+        }
+        FilterExpression::Not(exp) => v.visit_filter(exp),
+    }
+}
+
+/// visits the path of the dynamic operand
+pub fn walk_operand<V: Visitor + ?Sized>(v: &mut V, op: &Operand) {
+    match op {
+        Operand::Static(_)
+        | Operand::Param(_)
+        | Operand::Context(_)
+        | Operand::Index
+        | Operand::Property
+        | Operand::Path => (),
+        Operand::Dynamic(jp) => v.visit_path(jp),
+    }
+}
+
+/// The traversal of the path changing the elements in place
+pub trait VisitorMut {
+    fn visit_path_mut(&mut self, path: &mut JsonPath) {
+        walk_path_mut(self, path)
+    }
+    fn visit_index_mut(&mut self, index: &mut JsonPathIndex) {
+        walk_index_mut(self, index)
+    }
+    fn visit_filter_mut(&mut self, expr: &mut FilterExpression) {
+        walk_filter_mut(self, expr)
+    }
+    fn visit_operand_mut(&mut self, op: &mut Operand) {
+        walk_operand_mut(self, op)
+    }
+}
+
+/// visits the nested elements of the path
+pub fn walk_path_mut<V: VisitorMut + ?Sized>(v: &mut V, path: &mut JsonPath) {
+    match path {
+        JsonPath::Chain(elems) => elems.iter_mut().for_each(|e| v.visit_path_mut(e)),
+        JsonPath::Current(tail) => v.visit_path_mut(tail),
+        JsonPath::Index(index) | JsonPath::DescentIndex(index) => v.visit_index_mut(index),
+        JsonPath::Root
+        | JsonPath::Field(_)
+        | JsonPath::FieldGlob(_)
+        | JsonPath::Descent(_)
+        | JsonPath::DescentGlob(_)
+        | JsonPath::DescentW
+        | JsonPath::Wildcard
+        | JsonPath::Empty
+        | JsonPath::Fn(_)
+        | JsonPath::Type(_) => (),
+    }
+}
+
+/// visits the nested elements of the index
+pub fn walk_index_mut<V: VisitorMut + ?Sized>(v: &mut V, index: &mut JsonPathIndex) {
+    match index {
+        JsonPathIndex::Union(elems) => elems.iter_mut().for_each(|e| v.visit_path_mut(e)),
+        JsonPathIndex::Filter(expr) => v.visit_filter_mut(expr),
+        JsonPathIndex::Single(_)
+        | JsonPathIndex::UnionIndex(_)
+        | JsonPathIndex::UnionKeys(_)
+        | JsonPathIndex::Slice(..) => (),
+    }
+}
+
+/// visits the nested elements of the filter
+pub fn walk_filter_mut<V: VisitorMut + ?Sized>(v: &mut V, expr: &mut FilterExpression) {
+    match expr {
+        FilterExpression::Atom(left, _, right) => {
+            v.visit_operand_mut(left);
+            v.visit_operand_mut(right);
+        }
+        FilterExpression::And(left, right)
+        | FilterExpression::Or(left, right)
+        | FilterExpression::Xor(left, right) => {
+            v.visit_filter_mut(left);
+            v.visit_filter_mut(right);
+        }
+        FilterExpression::Not(exp) => v.visit_filter_mut(exp),
+    }
+}
+
+/// visits the path of the dynamic operand
+pub fn walk_operand_mut<V: VisitorMut + ?Sized>(v: &mut V, op: &mut Operand) {
+    match op {
+        Operand::Static(_)
+        | Operand::Param(_)
+        | Operand::Context(_)
+        | Operand::Index
+        | Operand::Property
+        | Operand::Path => (),
+        Operand::Dynamic(jp) => v.visit_path_mut(jp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::model::{JsonPath, JsonPathIndex, Operand};
+    use crate::parser::parser::parse_json_path;
+    use crate::parser::visitor::{walk_operand, walk_path_mut, Visitor, VisitorMut};
+    use serde_json::Value;
+
+    struct StripFilters;
+
+    impl VisitorMut for StripFilters {
+        fn visit_path_mut(&mut self, path: &mut JsonPath) {
+            if let JsonPath::Chain(elems) = path {
+                elems.retain(|e| !matches!(e, JsonPath::Index(JsonPathIndex::Filter(_))));
+            }
+            walk_path_mut(self, path)
+        }
+    }
+
+    #[derive(Default)]
+    struct Constants(Vec<Value>);
+
+    impl Visitor for Constants {
+        fn visit_operand(&mut self, op: &Operand) {
+            if let Operand::Static(v) = op {
+                self.0.push(v.clone())
+            }
+            walk_operand(self, op)
+        }
+    }
+
+    #[test]
+    fn visitor_mut_test() {
+        let mut path = parse_json_path("$.a[?(@.b > 1)].c[?(@.d[?(@.e)])][0,2]").unwrap();
+        StripFilters.visit_path_mut(&mut path);
+        assert_eq!(path.to_string(), "$['a']['c'][0,2]");
+    }
+
+    #[test]
+    fn visitor_test() {
+        let path =
+            parse_json_path("$.a[?(@.b > 1 && (@.c == 'x' || !@.d[?(@.e in [1,2])]))]").unwrap();
+        let mut constants = Constants::default();
+        constants.visit_path(&path);
+        assert_eq!(
+            constants.0,
+            vec![Value::from(1), Value::from("x"), Value::from(vec![1, 2])]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use crate::path::JsonLike;
+use serde::de::{Error, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Number, Value};
+use std::borrow::Cow;
+use std::fmt::Formatter;
+
+/// The json document borrowing the strings and the keys from the text it has been deserialized from.
+/// Only the strings containing the escape sequences are copied.
+/// The fields are kept in the document order.
+///
+/// The matches found in it keep the borrows, therefore extracting the strings does not clone them.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_values, BorrowedValue, JsonPathInst};
+/// use std::borrow::Cow;
+/// use std::str::FromStr;
+///
+/// let text = r#"{"user":{"name":"alice"}}"#;
+/// let doc: BorrowedValue = serde_json::from_str(text).unwrap();
+/// let path = JsonPathInst::from_str("$.user.name").unwrap();
+///
+/// let names: Vec<Cow<str>> = find_values(&path, &doc)
+///     .into_iter()
+///     .filter_map(BorrowedValue::as_cow)
+///     .collect();
+/// assert!(matches!(names[0], Cow::Borrowed("alice")));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum BorrowedValue<'a> {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Vec<BorrowedValue<'a>>),
+    Object(Vec<(Cow<'a, str>, BorrowedValue<'a>)>),
+}
+
+impl<'a> BorrowedValue<'a> {
+    /// the string keeping the borrow of the text the document has been deserialized from
+    pub fn as_cow(&self) -> Option<Cow<'a, str>> {
+        match self {
+            BorrowedValue::String(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+
+    /// converts the value into [`Value`] copying the strings
+    pub fn to_value(&self) -> Value {
+        match self {
+            BorrowedValue::Null => Value::Null,
+            BorrowedValue::Bool(b) => Value::Bool(*b),
+            BorrowedValue::Number(n) => Value::Number(n.clone()),
+            BorrowedValue::String(s) => Value::String(s.to_string()),
+            BorrowedValue::Array(elems) => Value::Array(elems.iter().map(Self::to_value).collect()),
+            BorrowedValue::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> JsonLike for BorrowedValue<'a> {
+    type Entries<'b>
+        = std::iter::Map<
+        std::slice::Iter<'b, (Cow<'a, str>, BorrowedValue<'a>)>,
+        fn(&'b (Cow<'a, str>, BorrowedValue<'a>)) -> (&'b str, &'b BorrowedValue<'a>),
+    >
+    where
+        Self: 'b;
+
+    fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            BorrowedValue::Array(elems) => Some(elems),
+            _ => None,
+        }
+    }
+
+    fn entries(&self) -> Option<Self::Entries<'_>> {
+        match self {
+            BorrowedValue::Object(fields) => Some(fields.iter().map(|(k, v)| (k.as_ref(), v))),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            BorrowedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            BorrowedValue::Number(n) => n.as_f64(),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            BorrowedValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self, BorrowedValue::Null)
+    }
+
+    fn is_integer(&self) -> bool {
+        matches!(self, BorrowedValue::Number(n) if n.is_i64() || n.is_u64())
+    }
+
+    fn from_json(value: Value) -> Self {
+        match value {
+            Value::Null => BorrowedValue::Null,
+            Value::Bool(b) => BorrowedValue::Bool(b),
+            Value::Number(n) => BorrowedValue::Number(n),
+            Value::String(s) => BorrowedValue::String(Cow::Owned(s)),
+            Value::Array(elems) => {
+                BorrowedValue::Array(elems.into_iter().map(Self::from_json).collect())
+            }
+            Value::Object(fields) => BorrowedValue::Object(
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (Cow::Owned(k), Self::from_json(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for BorrowedValue<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(BorrowedVisitor)
+    }
+}
+
+struct BorrowedVisitor;
+
+impl<'de> Visitor<'de> for BorrowedVisitor {
+    type Value = BorrowedValue<'de>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a json value")
+    }
+
+    fn visit_bool<E: Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Bool(v))
+    }
+
+    fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Number(v.into()))
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Number(v.into()))
+    }
+
+    fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Number::from_f64(v).map_or(BorrowedValue::Null, BorrowedValue::Number))
+    }
+
+    fn visit_borrowed_str<E: Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::String(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::String(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E: Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::String(Cow::Owned(v)))
+    }
+
+    fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Null)
+    }
+
+    fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+        Ok(BorrowedValue::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        BorrowedValue::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut elems = vec![];
+        while let Some(el) = seq.next_element()? {
+            elems.push(el);
+        }
+        Ok(BorrowedValue::Array(elems))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut fields = vec![];
+        while let Some(Key(key)) = map.next_key()? {
+            fields.push((key, map.next_value()?));
+        }
+        Ok(BorrowedValue::Object(fields))
+    }
+}
+
+/// The key of the object borrowed from the text if it has no escape sequences
+struct Key<'a>(Cow<'a, str>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for Key<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match BorrowedValue::deserialize(deserializer)? {
+            BorrowedValue::String(key) => Ok(Key(key)),
+            _ => Err(D::Error::custom("the key of the object is not a string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::borrowed::BorrowedValue;
+    use crate::{find_values, JsonPathInst};
+    use serde_json::json;
+    use std::borrow::Cow;
+    use std::str::FromStr;
+
+    #[test]
+    fn borrowed_test() {
+        let text = r#"{"b":{"name":"x","esc":"a\"b"},"a":[1,2.5,true,null],"n":{"name":"y"}}"#;
+        let doc: BorrowedValue = serde_json::from_str(text).unwrap();
+        let find = |path: &str| {
+            let path = JsonPathInst::from_str(path).unwrap();
+            find_values(&path, &doc)
+                .into_iter()
+                .map(BorrowedValue::to_value)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(find("$..name"), vec![json!("x"), json!("y")]);
+        assert_eq!(find("$.a[?(@ > 1)]"), vec![json!(2.5)]);
+        assert_eq!(find("$.a.length()"), Vec::<serde_json::Value>::new());
+        assert_eq!(find("$.*[?(@.name == 'y')].name"), vec![json!("y")]);
+        assert_eq!(find("$.*"), {
+            let v: serde_json::Value = serde_json::from_str(text).unwrap();
+            vec![v["b"].clone(), v["a"].clone(), v["n"].clone()]
+        });
+        assert_eq!(
+            doc.to_value(),
+            serde_json::from_str::<serde_json::Value>(text).unwrap()
+        );
+    }
+
+    #[test]
+    fn zero_copy_test() {
+        let text = r#"{"name":"x","esc":"a\"b"}"#;
+        let doc: BorrowedValue = serde_json::from_str(text).unwrap();
+        let path = JsonPathInst::from_str("$.*").unwrap();
+        let found: Vec<Cow<str>> = find_values(&path, &doc)
+            .into_iter()
+            .filter_map(BorrowedValue::as_cow)
+            .collect();
+        assert!(matches!(found[0], Cow::Borrowed("x")));
+        assert!(matches!(&found[1], Cow::Owned(s) if s == "a\"b"));
+    }
+}
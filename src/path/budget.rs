@@ -0,0 +1,273 @@
+use std::cell::{Cell, RefCell};
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The flag stopping the evaluations sharing it from another thread,
+/// set by [`crate::EvalOptions::with_cancellation`].
+/// The clones share the same flag.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_options, CancellationToken, EvalOptions, JsonPathError, JsonPathInst};
+/// use serde_json::json;
+/// use std::str::FromStr;
+///
+/// let token = CancellationToken::new();
+/// let options = EvalOptions::default().with_cancellation(token.clone());
+/// let path = JsonPathInst::from_str("$..*").unwrap();
+///
+/// // usually called by another thread
+/// token.cancel();
+/// assert_eq!(
+///     find_with_options(&path, &json!({"a":[1,2]}), &options),
+///     Err(JsonPathError::Cancelled)
+/// );
+/// ```
+#[derive(Clone)]
+pub struct CancellationToken(Arc<dyn Flag>);
+
+/// The flag is kept behind the trait like the regexes since its inner mutability
+/// makes the path unsuitable for the keys of the hash sets.
+trait Flag: Send + Sync {
+    fn set(&self);
+    fn get(&self) -> bool;
+}
+
+impl Flag for AtomicBool {
+    fn set(&self) {
+        self.store(true, Ordering::Relaxed)
+    }
+
+    fn get(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// stops the evaluations checking the token as soon as they visit the next node
+    pub fn cancel(&self) {
+        self.0.set()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CancellationToken")
+            .field(&self.is_cancelled())
+            .finish()
+    }
+}
+
+/// The tokens are equal only if they share the same flag.
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(Arc::as_ptr(&self.0), Arc::as_ptr(&other.0))
+    }
+}
+
+impl Eq for CancellationToken {}
+
+/// The limits of the current evaluation
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Budget {
+    pub(crate) max_nodes: Option<usize>,
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) token: Option<CancellationToken>,
+}
+
+/// The reason the evaluation has stopped before visiting all the nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stop {
+    Nodes,
+    Deadline,
+    Cancelled,
+}
+
+/// the number of the nodes visited between the checks of the deadline since reading the clock is not free
+const CLOCK_EVERY: usize = 1024;
+
+thread_local! {
+    /// the number of the nodes the current evaluation is still allowed to visit
+    static REMAINING: Cell<Option<usize>> = const { Cell::new(None) };
+    static STOPPED: Cell<Option<Stop>> = const { Cell::new(None) };
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+    /// the number of the nodes visited since the last check of the deadline
+    static TICKS: Cell<usize> = const { Cell::new(0) };
+    static TOKEN: RefCell<Option<CancellationToken>> = const { RefCell::new(None) };
+}
+
+/// runs the evaluation within the budget and returns its result along with the reason it has stopped if it has.
+/// The evaluation stops visiting the nodes as soon as the budget is exhausted,
+/// therefore the result is incomplete in that case.
+pub(crate) fn with_budget<T>(budget: Budget, eval: impl FnOnce() -> T) -> (T, Option<Stop>) {
+    let _outer = Outer {
+        remaining: REMAINING.replace(budget.max_nodes),
+        stopped: STOPPED.replace(None),
+        deadline: DEADLINE.replace(budget.deadline),
+        ticks: TICKS.replace(0),
+        token: TOKEN.replace(budget.token),
+    };
+    let res = eval();
+    (res, STOPPED.get())
+}
+
+/// The budget of the outer evaluation put back when the inner one ends,
+/// also by the panic caught like the one of the ffi calls.
+struct Outer {
+    remaining: Option<usize>,
+    stopped: Option<Stop>,
+    deadline: Option<Instant>,
+    ticks: usize,
+    token: Option<CancellationToken>,
+}
+
+impl Drop for Outer {
+    fn drop(&mut self) {
+        REMAINING.set(self.remaining);
+        STOPPED.set(self.stopped);
+        DEADLINE.set(self.deadline);
+        TICKS.set(self.ticks);
+        TOKEN.set(self.token.take());
+    }
+}
+
+/// checks if the current evaluation has been cancelled or has run out of time
+fn interrupted() -> Option<Stop> {
+    if let Some(stop @ (Stop::Deadline | Stop::Cancelled)) = STOPPED.get() {
+        return Some(stop);
+    }
+    if TOKEN.with_borrow(|t| t.as_ref().is_some_and(CancellationToken::is_cancelled)) {
+        return Some(Stop::Cancelled);
+    }
+    let deadline = DEADLINE.get()?;
+    let ticks = TICKS.get() + 1;
+    if ticks < CLOCK_EVERY {
+        TICKS.set(ticks);
+        None
+    } else {
+        TICKS.set(0);
+        (Instant::now() >= deadline).then_some(Stop::Deadline)
+    }
+}
+
+/// takes the given number of the nodes from the budget of the current evaluation.
+/// Returns false if the budget is exhausted, the evaluation has been cancelled or has run out of time
+/// and the evaluation should stop.
+pub(crate) fn spend(nodes: usize) -> bool {
+    if let Some(stop) = interrupted() {
+        STOPPED.set(Some(stop));
+        return false;
+    }
+    match REMAINING.get() {
+        None => true,
+        Some(remaining) if remaining >= nodes => {
+            REMAINING.set(Some(remaining - nodes));
+            true
+        }
+        Some(_) => {
+            REMAINING.set(Some(0));
+            STOPPED.set(Some(Stop::Nodes));
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::budget::{spend, with_budget, Budget, CancellationToken, Stop, CLOCK_EVERY};
+    use std::panic::AssertUnwindSafe;
+    use std::time::Instant;
+
+    fn nodes(max_nodes: Option<usize>) -> Budget {
+        Budget {
+            max_nodes,
+            ..Budget::default()
+        }
+    }
+
+    #[test]
+    fn budget_test() {
+        let (spent, stopped) = with_budget(nodes(Some(3)), || [spend(2), spend(1), spend(1)]);
+        assert_eq!(spent, [true, true, false]);
+        assert_eq!(stopped, Some(Stop::Nodes));
+
+        let ((inner, inner_stopped), stopped) = with_budget(nodes(Some(1)), || {
+            let inner = with_budget(nodes(None), || spend(100));
+            (inner.0, inner.1)
+        });
+        assert!(inner && inner_stopped.is_none() && stopped.is_none());
+        assert!(spend(usize::MAX));
+    }
+
+    #[test]
+    fn panic_test() {
+        let (spent, stopped) = with_budget(nodes(Some(5)), || {
+            let caught = std::panic::catch_unwind(|| {
+                with_budget(nodes(Some(1)), || {
+                    spend(2);
+                    panic!("the evaluation fails")
+                })
+            });
+            assert!(caught.is_err());
+            [spend(5), spend(1)]
+        });
+        assert_eq!(spent, [true, false]);
+        assert_eq!(stopped, Some(Stop::Nodes));
+        assert!(spend(usize::MAX));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let budget = Budget {
+            deadline: Some(Instant::now()),
+            token: Some(token),
+            ..Budget::default()
+        };
+        let caught = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            with_budget(budget, || panic!("cancelled"))
+        }));
+        assert!(caught.is_err());
+        assert!((0..CLOCK_EVERY).all(|_| spend(1)));
+    }
+
+    #[test]
+    fn interrupt_test() {
+        let token = CancellationToken::new();
+        let budget = Budget {
+            token: Some(token.clone()),
+            ..Budget::default()
+        };
+        let (spent, stopped) = with_budget(budget, || {
+            let before = spend(1);
+            token.cancel();
+            [before, spend(1)]
+        });
+        assert_eq!(spent, [true, false]);
+        assert_eq!(stopped, Some(Stop::Cancelled));
+
+        let budget = Budget {
+            deadline: Some(Instant::now()),
+            ..Budget::default()
+        };
+        let (spent, stopped) =
+            with_budget(budget, || (0..CLOCK_EVERY).filter(|_| spend(1)).count());
+        assert_eq!(spent, CLOCK_EVERY - 1);
+        assert_eq!(stopped, Some(Stop::Deadline));
+        assert!(spend(1));
+    }
+}
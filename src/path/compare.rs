@@ -0,0 +1,446 @@
+use crate::path::{JsonLike, Rules};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// The view of the compared element given to the [`Comparator`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scalar<'a> {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(&'a str),
+    Array,
+    Object,
+}
+
+impl<'a> Scalar<'a> {
+    pub(crate) fn of<T: JsonLike>(v: &'a T) -> Self {
+        if v.is_null() {
+            Scalar::Null
+        } else if let Some(b) = v.as_bool() {
+            Scalar::Bool(b)
+        } else if let Some(n) = v.as_f64() {
+            Scalar::Number(n)
+        } else if let Some(s) = v.as_str() {
+            Scalar::String(s)
+        } else if v.as_array().is_some() {
+            Scalar::Array
+        } else {
+            Scalar::Object
+        }
+    }
+}
+
+/// The hooks deciding how the filters compare the single elements with `==`, `!=`, `<`, `<=`, `>` and `>=`
+/// and look them up with `in`, `nin`, `anyOf`, `noneOf` and `subsetOf`, set by [`crate::EvalOptions::with_comparator`].
+/// Returning `None` leaves the decision to the default rules:
+/// the elements are equal if they are the same json and only the numbers are ordered.
+/// The arrays and the objects are compared by the default rules only.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_options, Comparator, EvalOptions, JsonPathInst, Scalar};
+/// use serde_json::json;
+/// use std::str::FromStr;
+/// use std::sync::Arc;
+///
+/// /// the numbers closer than the tolerance are equal
+/// #[derive(Debug)]
+/// struct Tolerance(f64);
+///
+/// impl Comparator for Tolerance {
+///     fn eq(&self, left: Scalar, right: Scalar) -> Option<bool> {
+///         match (left, right) {
+///             (Scalar::Number(l), Scalar::Number(r)) => Some((l - r).abs() < self.0),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let data = json!([{"v":0.1},{"v":0.3}]);
+/// let path = JsonPathInst::from_str("$[?(@.v == 0.30000001)].v").unwrap();
+/// let options = EvalOptions::default().with_comparator(Arc::new(Tolerance(1e-6)));
+/// let found = find_with_options(&path, &data, &options).unwrap();
+/// assert_eq!(*found[0].value, json!(0.3));
+/// ```
+pub trait Comparator: Debug + Send + Sync {
+    /// decides if the elements are equal
+    fn eq(&self, _left: Scalar, _right: Scalar) -> Option<bool> {
+        None
+    }
+    /// decides if the left element is less than the right one
+    fn less(&self, _left: Scalar, _right: Scalar) -> Option<bool> {
+        None
+    }
+}
+
+/// The transformation applied to both strings before comparing them, see [`StringComparator::with_transform`]
+pub type StringTransform = Arc<dyn Fn(&str) -> String + Send + Sync>;
+/// The order of the strings, see [`StringComparator::with_order`]
+pub type StringOrder = Arc<dyn Fn(&str, &str) -> Ordering + Send + Sync>;
+
+/// The [`Comparator`] comparing the strings after transforming them and ordering them by the given order.
+/// The library has no unicode tables and does not normalize or collate the strings itself:
+/// the transform and the order are the plain functions given by the user,
+/// e.g. the canonical equality of `"e\u{301}"` and `"é"` needs the NFC of the crate `unicode-normalization`
+/// (`|s: &str| s.nfc().collect::<String>()`) and the locale aware order needs the crate like `icu_collator`.
+/// The other elements are compared by the default rules.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_options, EvalOptions, JsonPathInst, StringComparator};
+/// use serde_json::json;
+/// use std::str::FromStr;
+/// use std::sync::Arc;
+///
+/// let comparator = StringComparator::new()
+///     .with_transform(|s: &str| s.trim().to_string())
+///     .with_order(|l: &str, r: &str| l.to_lowercase().cmp(&r.to_lowercase()));
+/// let options = EvalOptions::default().with_comparator(Arc::new(comparator));
+///
+/// let data = json!([{"name":" Jose "},{"name":"ana"},{"name":"Bruno"}]);
+/// let path = JsonPathInst::from_str("$[?(@.name == 'jose')].name").unwrap();
+/// assert_eq!(find_with_options(&path, &data, &options).unwrap().len(), 1);
+///
+/// let path = JsonPathInst::from_str("$[?(@.name < 'b')].name").unwrap();
+/// let found = find_with_options(&path, &data, &options).unwrap();
+/// assert_eq!(*found[0].value, json!("ana"));
+/// ```
+#[derive(Clone, Default)]
+pub struct StringComparator {
+    transform: Option<StringTransform>,
+    order: Option<StringOrder>,
+}
+
+impl StringComparator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// transforms both strings before comparing them, e.g. trims or folds them
+    pub fn with_transform(
+        mut self,
+        transform: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+
+    /// compares the transformed strings by the order, it also orders them for `<`, `<=`, `>` and `>=`
+    pub fn with_order(
+        mut self,
+        order: impl Fn(&str, &str) -> Ordering + Send + Sync + 'static,
+    ) -> Self {
+        self.order = Some(Arc::new(order));
+        self
+    }
+
+    fn compare(&self, left: &str, right: &str) -> Option<Ordering> {
+        let order = self.order.as_ref();
+        match &self.transform {
+            Some(t) => {
+                let (left, right) = (t(left), t(right));
+                order.map_or(Some(left.cmp(&right)), |o| Some(o(&left, &right)))
+            }
+            None => order.map(|o| o(left, right)),
+        }
+    }
+}
+
+impl Debug for StringComparator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StringComparator")
+            .field("transform", &self.transform.is_some())
+            .field("order", &self.order.is_some())
+            .finish()
+    }
+}
+
+impl Comparator for StringComparator {
+    fn eq(&self, left: Scalar, right: Scalar) -> Option<bool> {
+        match (left, right) {
+            (Scalar::String(l), Scalar::String(r)) => {
+                Some(self.compare(l, r).map_or(l == r, Ordering::is_eq))
+            }
+            _ => None,
+        }
+    }
+
+    fn less(&self, left: Scalar, right: Scalar) -> Option<bool> {
+        match (left, right) {
+            (Scalar::String(l), Scalar::String(r)) if self.order.is_some() => {
+                self.compare(l, r).map(Ordering::is_lt)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The rules converting the scalars of the different types before comparing them in the filters,
+/// set by [`crate::EvalOptions::with_coercion`].
+/// The arrays and the objects are never converted and the [`Comparator`] is asked first.
+///
+/// | filter          | `Strict` | `Lenient` | `JsLike` |
+/// |-----------------|----------|-----------|----------|
+/// | `"10" == 10`    | false    | true      | true     |
+/// | `"2" < 10`      | false    | true      | true     |
+/// | `true == 1`     | false    | false     | true     |
+/// | `"b" > "a"`     | false    | false     | true     |
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_options, Coercion, EvalOptions, JsonPathInst};
+/// use serde_json::json;
+/// use std::str::FromStr;
+///
+/// let data = json!([{"qty":"12"},{"qty":3},{"qty":"n/a"}]);
+/// let path = JsonPathInst::from_str("$[?(@.qty > 10)]").unwrap();
+/// let options = EvalOptions::default().with_coercion(Coercion::Lenient);
+/// let found = find_with_options(&path, &data, &options).unwrap();
+/// assert_eq!(*found[0].value, json!({"qty":"12"}));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Coercion {
+    /// the elements of the different types are never equal and only the numbers are ordered
+    #[default]
+    Strict,
+    /// the strings holding the numbers like `"10"` or `" 2.5 "` are compared with the numbers as the numbers
+    Lenient,
+    /// the elements are compared like the `==` and the `<` of javascript:
+    /// the strings and the booleans are compared with the numbers as the numbers (`true` is `1`, `""` is `0`),
+    /// `null` equals only `null` but is `0` in `<`, and the strings are ordered by their utf-16 code units
+    JsLike,
+}
+
+impl Comparator for Coercion {
+    fn eq(&self, left: Scalar, right: Scalar) -> Option<bool> {
+        match (self, left, right) {
+            (Coercion::Strict, _, _) => None,
+            (Coercion::Lenient, Scalar::Number(n), Scalar::String(s))
+            | (Coercion::Lenient, Scalar::String(s), Scalar::Number(n)) => {
+                Some(numeric(s) == Some(n))
+            }
+            (Coercion::Lenient, _, _) => None,
+            (Coercion::JsLike, Scalar::Null, _) | (Coercion::JsLike, _, Scalar::Null) => None,
+            (Coercion::JsLike, Scalar::String(_), Scalar::String(_)) => None,
+            (Coercion::JsLike, l, r) => Some(js_number(l) == js_number(r)),
+        }
+    }
+
+    fn less(&self, left: Scalar, right: Scalar) -> Option<bool> {
+        match (self, left, right) {
+            (Coercion::Strict, _, _) => None,
+            (Coercion::Lenient, Scalar::Number(l), Scalar::String(r)) => {
+                Some(numeric(r).is_some_and(|r| l < r))
+            }
+            (Coercion::Lenient, Scalar::String(l), Scalar::Number(r)) => {
+                Some(numeric(l).is_some_and(|l| l < r))
+            }
+            (Coercion::Lenient, _, _) => None,
+            (Coercion::JsLike, Scalar::String(l), Scalar::String(r)) => {
+                Some(l.encode_utf16().lt(r.encode_utf16()))
+            }
+            (Coercion::JsLike, l, r) => Some(js_number(l) < js_number(r)),
+        }
+    }
+}
+
+/// the number held by the string like `" 2.5 "` or `"1e3"`
+fn numeric(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if s.is_empty() || !s.chars().all(|c| "0123456789+-.eE".contains(c)) {
+        None
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// the number javascript converts the scalar into, `NaN` is never equal nor less than anything
+fn js_number(v: Scalar) -> f64 {
+    match v {
+        Scalar::Null => 0.0,
+        Scalar::Bool(b) => f64::from(u8::from(b)),
+        Scalar::Number(n) => n,
+        Scalar::String(s) => {
+            let s = s.trim();
+            let radix = |prefix: [&str; 2], radix: u32| {
+                prefix
+                    .iter()
+                    .find_map(|p| s.strip_prefix(p))
+                    .map(|digits| u64::from_str_radix(digits, radix).map_or(f64::NAN, |n| n as f64))
+            };
+            match s {
+                "" => 0.0,
+                "Infinity" | "+Infinity" => f64::INFINITY,
+                "-Infinity" => f64::NEG_INFINITY,
+                _ => radix(["0x", "0X"], 16)
+                    .or_else(|| radix(["0o", "0O"], 8))
+                    .or_else(|| radix(["0b", "0B"], 2))
+                    .unwrap_or_else(|| numeric(s).unwrap_or(f64::NAN)),
+            }
+        }
+        Scalar::Array | Scalar::Object => f64::NAN,
+    }
+}
+
+/// asks the comparator and then the coercion of the rules if the elements are scalars
+fn ask<T: JsonLike>(
+    left: &T,
+    right: &T,
+    rules: &Rules,
+    hook: impl Fn(&dyn Comparator, Scalar, Scalar) -> Option<bool>,
+) -> Option<bool> {
+    if rules.comparator.is_none() && rules.coercion == Coercion::Strict {
+        return None;
+    }
+    match (Scalar::of(left), Scalar::of(right)) {
+        (Scalar::Array | Scalar::Object, _) | (_, Scalar::Array | Scalar::Object) => None,
+        (l, r) => rules
+            .comparator
+            .as_deref()
+            .and_then(|c| hook(c, l, r))
+            .or_else(|| hook(&rules.coercion, l, r)),
+    }
+}
+
+/// compares the elements with the comparator of the rules falling back to the json equality
+pub(crate) fn eq_el<T: JsonLike>(left: &T, right: &T, rules: &Rules) -> bool {
+    ask(left, right, rules, |c, l, r| c.eq(l, r)).unwrap_or_else(|| deep_eq(left, right))
+}
+
+/// checks if the comparator or the coercion of the rules decides whether the elements are equal
+pub(crate) fn decides_eq<T: JsonLike>(left: &T, right: &T, rules: &Rules) -> bool {
+    ask(left, right, rules, |c, l, r| c.eq(l, r)).is_some()
+}
+
+/// checks if the comparator or the coercion of the rules decides whether the left element is less
+pub(crate) fn decides_less<T: JsonLike>(left: &T, right: &T, rules: &Rules) -> bool {
+    ask(left, right, rules, |c, l, r| c.less(l, r)).is_some()
+}
+
+/// compares the subtrees structurally:
+/// the arrays element by element in order and the objects by the keys regardless of their order.
+/// The numbers are compared by their values like `1 == 1.0`, the integers are compared exactly.
+fn deep_eq<T: JsonLike>(left: &T, right: &T) -> bool {
+    if let (Some(l), Some(r)) = (left.as_array(), right.as_array()) {
+        l.len() == r.len() && l.iter().zip(r).all(|(l, r)| deep_eq(l, r))
+    } else if let (Some(l), Some(r)) = (left.entries(), right.entries()) {
+        let l: Vec<_> = l.collect();
+        l.len() == r.count()
+            && l.into_iter()
+                .all(|(k, l)| right.get(k).is_some_and(|r| deep_eq(l, r)))
+    } else if let (Some(l), Some(r)) = (left.as_f64(), right.as_f64()) {
+        if left.is_integer() && right.is_integer() {
+            left == right
+        } else {
+            l == r
+        }
+    } else {
+        left == right
+    }
+}
+
+/// compares the elements with the comparator of the rules falling back to comparing the numbers
+pub(crate) fn less_el<T: JsonLike>(left: &T, right: &T, rules: &Rules) -> bool {
+    ask(left, right, rules, |c, l, r| c.less(l, r)).unwrap_or_else(|| {
+        match (left.as_f64(), right.as_f64()) {
+            (Some(v1), Some(v2)) => v1 < v2,
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::compare::{eq_el, less_el, Coercion, Comparator, Scalar, StringComparator};
+    use crate::path::Rules;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn rules(comparator: Option<Arc<dyn Comparator>>, coercion: Coercion) -> Rules {
+        Rules {
+            comparator,
+            coercion,
+            ..Rules::default()
+        }
+    }
+
+    #[derive(Debug)]
+    struct CaseInsensitive;
+
+    impl Comparator for CaseInsensitive {
+        fn eq(&self, left: Scalar, right: Scalar) -> Option<bool> {
+            match (left, right) {
+                (Scalar::String(l), Scalar::String(r)) => Some(l.eq_ignore_ascii_case(r)),
+                _ => None,
+            }
+        }
+        fn less(&self, left: Scalar, right: Scalar) -> Option<bool> {
+            match (left, right) {
+                (Scalar::String(l), Scalar::String(r)) => Some(l < r),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn comparator_test() {
+        let (a, b) = (json!("abc"), json!("ABC"));
+        let default = Rules::default();
+        assert!(!eq_el(&a, &b, &default));
+        assert!(!less_el(&b, &a, &default));
+        let case = rules(Some(Arc::new(CaseInsensitive)), Coercion::Strict);
+        assert!(eq_el(&a, &b, &case));
+        assert!(less_el(&b, &a, &case));
+        assert!(less_el(&json!(1), &json!(2), &case));
+        assert!(!eq_el(&json!(["abc"]), &json!(["ABC"]), &case));
+    }
+
+    #[test]
+    fn string_comparator_test() {
+        let trimmed = StringComparator::new().with_transform(|s| s.trim().to_string());
+        let trimmed = rules(Some(Arc::new(trimmed)), Coercion::Strict);
+        assert!(eq_el(&json!(" abc"), &json!("abc "), &trimmed));
+        assert!(!less_el(&json!("a"), &json!("b"), &trimmed));
+        assert!(!eq_el(&json!("ab c"), &json!("abc"), &trimmed));
+        let (composed, decomposed) = (json!("caf\u{e9}"), json!("cafe\u{301}"));
+        assert!(!eq_el(&composed, &decomposed, &trimmed));
+        let ordered =
+            StringComparator::new().with_order(|l, r| l.to_lowercase().cmp(&r.to_lowercase()));
+        let ordered = rules(Some(Arc::new(ordered)), Coercion::Strict);
+        assert!(eq_el(&json!("Abc"), &json!("aBC"), &ordered));
+        assert!(less_el(&json!("a"), &json!("B"), &ordered));
+        assert!(!eq_el(&json!(" abc"), &json!("abc"), &ordered));
+        assert!(!eq_el(&json!("1"), &json!(1), &ordered));
+    }
+
+    #[test]
+    fn coercion_test() {
+        let check = |coercion: Coercion| {
+            let rules = rules(None, coercion);
+            [
+                eq_el(&json!("10"), &json!(10), &rules),
+                less_el(&json!("2"), &json!(10), &rules),
+                eq_el(&json!(true), &json!(1), &rules),
+                less_el(&json!("a"), &json!("b"), &rules),
+            ]
+        };
+        assert_eq!(check(Coercion::Strict), [false, false, false, false]);
+        assert_eq!(check(Coercion::Lenient), [true, true, false, false]);
+        assert_eq!(check(Coercion::JsLike), [true, true, true, true]);
+
+        let js = rules(None, Coercion::JsLike);
+        assert!(eq_el(&json!("0x1f"), &json!(31), &js));
+        assert!(eq_el(&json!(""), &json!(false), &js));
+        assert!(!eq_el(&json!(null), &json!(0), &js));
+        assert!(less_el(&json!(null), &json!(1), &js));
+        assert!(!less_el(&json!("2"), &json!("10"), &js));
+        assert!(!eq_el(&json!("x"), &json!("x "), &js));
+        assert!(!less_el(&json!("abc"), &json!(1), &js));
+        let lenient = rules(None, Coercion::Lenient);
+        assert!(eq_el(&json!(" 2.5 "), &json!(2.5), &lenient));
+        assert!(!eq_el(&json!("inf"), &json!(f64::MAX), &lenient));
+        assert!(!eq_el(&json!("1"), &json!(true), &lenient));
+    }
+}
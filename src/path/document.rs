@@ -0,0 +1,237 @@
+use crate::parser::model::JsonPath;
+use crate::path::top::Chain;
+use crate::path::{compiled_instance, Path};
+use crate::JsonPathValue::{NoValue, Slice};
+use crate::{
+    distinct_nodes, document_order, jsp_idx, jsp_obj, JsPathStr, JsonPathInst, JsonPathValue,
+    ResultOrder,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The statistics of the indexed document
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentStats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// the depth of the deepest element, the root has the depth 0
+    pub max_depth: usize,
+}
+
+/// The index of the document built by a single traversal.
+/// It keeps the locations of the fields by their keys
+/// and resolves the queries starting with the descent like `$..name` without traversing the document.
+/// The other queries are processed as usual.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{DocumentIndex, JsonPathInst, JsonPathValue};
+/// use serde_json::json;
+/// # use std::str::FromStr;
+///
+/// let data = json!({"a":{"name":"x"},"b":[{"name":"y","v":1}]});
+/// let index = DocumentIndex::new(&data);
+/// let path = JsonPathInst::from_str("$..name").unwrap();
+///
+/// assert_eq!(
+///     index.find_slice(&path),
+///     vec![
+///         JsonPathValue::Slice(&json!("x"), "$.['a'].['name']".to_string()),
+///         JsonPathValue::Slice(&json!("y"), "$.['b'][0].['name']".to_string()),
+///     ]
+/// );
+/// assert_eq!(index.stats().objects, 3);
+/// ```
+#[derive(Debug)]
+pub struct DocumentIndex<'a> {
+    root: &'a Value,
+    keys: HashMap<&'a str, Vec<(&'a Value, JsPathStr)>>,
+    stats: DocumentStats,
+}
+
+impl<'a> DocumentIndex<'a> {
+    /// traverses the document once in the depth-first order collecting the fields and the statistics
+    pub fn new(root: &'a Value) -> Self {
+        let mut keys: HashMap<&'a str, Vec<(&'a Value, JsPathStr)>> = HashMap::new();
+        let mut stats = DocumentStats::default();
+        let mut stack = vec![(root, String::from("$"), 0)];
+
+        while let Some((v, pref, depth)) = stack.pop() {
+            stats.max_depth = stats.max_depth.max(depth);
+            match v {
+                Value::Object(elems) => {
+                    stats.objects += 1;
+                    for (k, el) in elems.iter() {
+                        keys.entry(k.as_str())
+                            .or_default()
+                            .push((el, jsp_obj(&pref, k)));
+                    }
+                    for (k, el) in elems.iter().rev() {
+                        stack.push((el, jsp_obj(&pref, k), depth + 1));
+                    }
+                }
+                Value::Array(elems) => {
+                    stats.arrays += 1;
+                    for (i, el) in elems.iter().enumerate().rev() {
+                        stack.push((el, jsp_idx(&pref, i), depth + 1));
+                    }
+                }
+                Value::String(_) => stats.strings += 1,
+                Value::Number(_) => stats.numbers += 1,
+                Value::Bool(_) => stats.booleans += 1,
+                Value::Null => stats.nulls += 1,
+            }
+        }
+
+        DocumentIndex { root, keys, stats }
+    }
+
+    /// returns the statistics of the document
+    pub fn stats(&self) -> &DocumentStats {
+        &self.stats
+    }
+
+    /// returns all the fields with the given key in the depth-first order along with their paths
+    pub fn locations(&self, key: &str) -> &[(&'a Value, JsPathStr)] {
+        self.keys.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// finds the elements like [`crate::find_slice`] does,
+    /// the descent at the beginning of the path is resolved by the index.
+    pub fn find_slice(&self, path: &'a JsonPathInst) -> Vec<JsonPathValue<'a, Value>> {
+        let res = match &path.inner {
+            JsonPath::Chain(elems) => match elems.as_slice() {
+                // the functions like `length()` take all the found elements at once,
+                // so they are not evaluated location by location
+                [JsonPath::Root, JsonPath::Descent(key), rest @ ..]
+                    if !rest.iter().any(|p| matches!(p, JsonPath::Fn(_))) =>
+                {
+                    let ctx = path.ctx(&path.options);
+                    let tail = Chain::new(
+                        rest.iter()
+                            .map(|p| compiled_instance(p, self.root, &ctx))
+                            .collect(),
+                        false,
+                    );
+                    self.locations(key)
+                        .iter()
+                        .flat_map(|(v, p)| tail.find(Slice(v, p.clone())))
+                        .filter(|v| v.has_value())
+                        .collect()
+                }
+                _ => self.find_as_usual(path),
+            },
+            _ => self.find_as_usual(path),
+        };
+        let res = match path.options.order {
+            ResultOrder::Selector => res,
+            ResultOrder::Document => document_order(self.root, res),
+        };
+        let res = if path.options.distinct_nodes {
+            distinct_nodes(res)
+        } else {
+            res
+        };
+        if res.is_empty() {
+            vec![NoValue]
+        } else {
+            res
+        }
+    }
+
+    fn find_as_usual(&self, path: &'a JsonPathInst) -> Vec<JsonPathValue<'a, Value>> {
+        path.evaluate(self.root, JsonPathValue::from_root(self.root))
+            .into_iter()
+            .filter(|v| v.has_value())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::document::{DocumentIndex, DocumentStats};
+    use crate::{find_slice, Coercion, EvalOptions, JsonPathInst, StringLength};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn index_find_test() {
+        let json = json!({
+            "store": {
+                "book": [
+                    {"title": "a", "price": 8, "meta": {"price": 1}},
+                    {"title": "b", "price": 12}
+                ],
+                "bicycle": {"price": 19, "tags": [{"price": null}]}
+            },
+            "price": 3,
+            "limit": 10
+        });
+        let index = DocumentIndex::new(&json);
+        for query in [
+            "$..price",
+            "$..book[?(@.price < $.limit)].title",
+            "$..book[1]",
+            "$..tags.length()",
+            "$..book[*].length()",
+            "$..absent",
+            "$..price_*",
+            "$.store..price",
+            "$.store.book[*].title",
+        ] {
+            let path = JsonPathInst::from_str(query).expect("the path is correct");
+            assert_eq!(index.find_slice(&path), find_slice(&path, &json), "{query}");
+        }
+    }
+
+    #[test]
+    fn index_options_test() {
+        let json = json!({"a": {"name": "10"}, "b": {"name": "e\u{301}", "len": 2}});
+        let index = DocumentIndex::new(&json);
+        let options = [
+            EvalOptions::default().with_coercion(Coercion::Lenient),
+            EvalOptions::default().with_string_length(StringLength::Bytes),
+            EvalOptions::default().with_goessner(true),
+        ];
+        for query in ["$..name[?(@ > 5)]", "$..name.length()", "$..b[?(@.len)]"] {
+            for options in options.iter() {
+                let path = JsonPathInst::from_str(query)
+                    .expect("the path is correct")
+                    .with_options(options.clone());
+                assert_eq!(
+                    index.find_slice(&path),
+                    find_slice(&path, &json),
+                    "{query} {options:?}"
+                );
+            }
+        }
+        let path = JsonPathInst::from_str("$..name[?(@ > 5)]")
+            .expect("the path is correct")
+            .with_options(options[0].clone());
+        assert_eq!(index.find_slice(&path).len(), 1);
+    }
+
+    #[test]
+    fn index_stats_test() {
+        let json = json!({"a": [1, "b", null, {"c": true}], "d": 2.5});
+        let index = DocumentIndex::new(&json);
+        assert_eq!(
+            index.stats(),
+            &DocumentStats {
+                objects: 2,
+                arrays: 1,
+                strings: 1,
+                numbers: 2,
+                booleans: 1,
+                nulls: 1,
+                max_depth: 3,
+            }
+        );
+        assert_eq!(index.locations("c").len(), 1);
+        assert!(index.locations("e").is_empty());
+    }
+}
@@ -1,26 +1,38 @@
-use crate::jsp_idx;
-use crate::parser::model::{FilterExpression, FilterSign, JsonPath};
+use crate::parser::model::{FilterExpression, FilterSign, JsonPath, JsonPathIndex, Operand};
 use crate::path::json::*;
-use crate::path::top::ObjectField;
-use crate::path::{json_path_instance, process_operand, JsonPathValue, Path, PathInstance};
-use crate::JsonPathValue::{NoValue, Slice};
-use serde_json::value::Value::Array;
+use crate::path::strict::check_atom;
+use crate::path::top::{deep_flatten, EmptyPath, ObjectField};
+use crate::path::{
+    compiled_instance, process_index, process_operand, truthy, Ctx, JsonLike, JsonPathValue,
+    Location, OperandPath, Path, PathInstance, RegexMatcher, Rules, Step, Track, NOT_COMPILED,
+};
+use crate::JsonPathValue::{NewValue, NoValue, Slice};
 use serde_json::Value;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// borrows the values of the operand
+fn refs<'b, T: JsonLike>(values: &'b [Cow<'_, T>]) -> Vec<&'b T> {
+    values.iter().map(|v| v.as_ref()).collect()
+}
 
 /// process the slice like [start:end:step]
 #[derive(Debug)]
-pub(crate) struct ArraySlice {
+pub(crate) struct ArraySlice<T> {
     start_index: i32,
     end_index: i32,
     step: usize,
+    _t: PhantomData<T>,
 }
 
-impl ArraySlice {
-    pub(crate) fn new(start_index: i32, end_index: i32, step: usize) -> ArraySlice {
+impl<T> ArraySlice<T> {
+    pub(crate) fn new(start_index: i32, end_index: i32, step: usize) -> ArraySlice<T> {
         ArraySlice {
             start_index,
             end_index,
             step,
+            _t: PhantomData,
         }
     }
 
@@ -52,9 +64,9 @@ impl ArraySlice {
         }
     }
 
-    fn process<'a, T>(&self, elements: &'a [T]) -> Vec<(&'a T, usize)> {
+    fn process<'a, E>(&self, elements: &'a [E]) -> Vec<(&'a E, usize)> {
         let len = elements.len() as i32;
-        let mut filtered_elems: Vec<(&'a T, usize)> = vec![];
+        let mut filtered_elems: Vec<(&'a E, usize)> = vec![];
         match (self.start(len), self.end(len)) {
             (Some(start_idx), Some(end_idx)) => {
                 let end_idx = if end_idx == 0 {
@@ -74,63 +86,137 @@ impl ArraySlice {
     }
 }
 
-impl<'a> Path<'a> for ArraySlice {
-    type Data = Value;
+impl<T: JsonLike> ArraySlice<T> {
+    /// the slice of a string is taken by chars and generates a new value,
+    /// the empty one if the slice takes no chars
+    fn process_str<'a, P>(&self, str: &str) -> Vec<JsonPathValue<'a, T, P>> {
+        let chars: Vec<char> = str.chars().collect();
+        let sub: String = self.process(&chars).into_iter().map(|(c, _)| *c).collect();
+        vec![NewValue(T::from_json(Value::String(sub)))]
+    }
+}
 
-    fn find(&self, input: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for ArraySlice<T> {
+    type Data = T;
+
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         input.flat_map_slice(|data, pref| {
+            if let Some(str) = data.as_str() {
+                return self.process_str(str);
+            }
             data.as_array()
                 .map(|elems| self.process(elems))
                 .and_then(|v| {
                     if v.is_empty() {
                         None
                     } else {
-                        let v = v.into_iter().map(|(e, i)| (e, jsp_idx(&pref, i))).collect();
+                        let v = v.into_iter().map(|(e, i)| (e, pref.index(i))).collect();
                         Some(JsonPathValue::map_vec(v))
                     }
                 })
                 .unwrap_or_else(|| vec![NoValue])
         })
     }
+
+    fn selects_from(&self, input: &JsonPathValue<'a, Self::Data, P>) -> bool {
+        matches!(input, Slice(data, _) if data.as_array().is_some() || data.as_str().is_some())
+    }
 }
 
 /// process the simple index like [index]
-pub(crate) struct ArrayIndex {
+pub(crate) struct ArrayIndex<T> {
     index: usize,
+    /// the index is counted from the end like `-1`
+    from_end: bool,
+    _t: PhantomData<T>,
 }
 
-impl ArrayIndex {
+impl<T> ArrayIndex<T> {
     pub(crate) fn new(index: usize) -> Self {
-        ArrayIndex { index }
+        ArrayIndex {
+            index,
+            from_end: false,
+            _t: PhantomData,
+        }
+    }
+
+    /// the index like `-1` for the last element
+    pub(crate) fn from_end(index: usize) -> Self {
+        ArrayIndex {
+            index,
+            from_end: true,
+            _t: PhantomData,
+        }
+    }
+
+    fn position(&self, len: usize) -> Option<usize> {
+        if self.from_end {
+            len.checked_sub(self.index)
+        } else {
+            Some(self.index)
+        }
     }
 }
 
-impl<'a> Path<'a> for ArrayIndex {
-    type Data = Value;
+/// the index of the array, the negative one is counted from the end
+/// and the one that does not fit into any array finds nothing
+pub(crate) fn array_index<'a, T: JsonLike + 'a, P: Track + 'a>(
+    index: &Value,
+) -> PathInstance<'a, T, P> {
+    match (index.as_u64(), index.as_i64()) {
+        (Some(idx), _) => Box::new(ArrayIndex::new(idx as usize)),
+        (None, Some(idx)) => Box::new(ArrayIndex::from_end(idx.unsigned_abs() as usize)),
+        _ => Box::new(EmptyPath::new()),
+    }
+}
 
-    fn find(&self, input: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
-        input.flat_map_slice(|data, pref| {
-            data.as_array()
-                .and_then(|elems| elems.get(self.index))
-                .map(|e| vec![JsonPathValue::new_slice(e, jsp_idx(&pref, self.index))])
-                .unwrap_or_else(|| vec![NoValue])
-        })
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for ArrayIndex<T> {
+    type Data = T;
+
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        let mut res = vec![];
+        self.find_into(input, &mut res);
+        res
+    }
+
+    fn find_into(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        let res = match input {
+            Slice(data, pref) => data
+                .as_array()
+                .and_then(|elems| {
+                    let idx = self.position(elems.len())?;
+                    elems.get(idx).map(|e| Slice(e, pref.index(idx)))
+                })
+                .unwrap_or(NoValue),
+            _ => NoValue,
+        };
+        out.push(res)
     }
 }
 
 /// process @ element
-pub(crate) struct Current<'a> {
-    tail: Option<PathInstance<'a>>,
+pub(crate) struct Current<'a, T, P = String> {
+    tail: Option<PathInstance<'a, T, P>>,
 }
 
-impl<'a> Current<'a> {
-    pub(crate) fn from(jp: &'a JsonPath, root: &'a Value) -> Self {
+impl<'a, T: JsonLike, P: Track + 'a> Current<'a, T, P> {
+    pub(crate) fn from(jp: &'a JsonPath, root: &'a T, ctx: &Ctx<'a>) -> Self {
         match jp {
             JsonPath::Empty => Current::none(),
-            tail => Current::new(json_path_instance(tail, root)),
+            tail => Current::new(compiled_instance(tail, root, ctx)),
         }
     }
-    pub(crate) fn new(tail: PathInstance<'a>) -> Self {
+    pub(crate) fn new(tail: PathInstance<'a, T, P>) -> Self {
         Current { tail: Some(tail) }
     }
     pub(crate) fn none() -> Self {
@@ -138,34 +224,48 @@ impl<'a> Current<'a> {
     }
 }
 
-impl<'a> Path<'a> for Current<'a> {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for Current<'a, T, P> {
+    type Data = T;
 
-    fn find(&self, input: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         self.tail
             .as_ref()
             .map(|p| p.find(input.clone()))
             .unwrap_or_else(|| vec![input])
     }
+
+    fn find_into(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        match self.tail.as_ref() {
+            Some(p) => p.find_into(input, out),
+            None => out.push(input),
+        }
+    }
 }
 
 /// the list of indexes like [1,2,3]
-pub(crate) struct UnionIndex<'a> {
-    indexes: Vec<PathInstance<'a>>,
+pub(crate) struct UnionIndex<'a, T, P = String> {
+    indexes: Vec<PathInstance<'a, T, P>>,
 }
 
-impl<'a> UnionIndex<'a> {
+impl<'a, T: JsonLike + 'a, P: Track + 'a> UnionIndex<'a, T, P> {
     pub fn from_indexes(elems: &'a [Value]) -> Self {
-        let mut indexes: Vec<PathInstance<'a>> = vec![];
+        let mut indexes: Vec<PathInstance<'a, T, P>> = vec![];
 
         for idx in elems.iter() {
-            indexes.push(Box::new(ArrayIndex::new(idx.as_u64().unwrap() as usize)))
+            indexes.push(array_index(idx))
         }
 
         UnionIndex::new(indexes)
     }
     pub fn from_keys(elems: &'a [String]) -> Self {
-        let mut indexes: Vec<PathInstance<'a>> = vec![];
+        let mut indexes: Vec<PathInstance<'a, T, P>> = vec![];
 
         for key in elems.iter() {
             indexes.push(Box::new(ObjectField::new(key)))
@@ -173,171 +273,342 @@ impl<'a> UnionIndex<'a> {
 
         UnionIndex::new(indexes)
     }
+    pub fn from_mixed(elems: &'a [JsonPath], root: &'a T, ctx: &Ctx<'a>) -> Self {
+        UnionIndex::new(
+            elems
+                .iter()
+                .map(|e| compiled_instance(e, root, ctx))
+                .collect(),
+        )
+    }
 
-    pub fn new(indexes: Vec<PathInstance<'a>>) -> Self {
+    pub fn new(indexes: Vec<PathInstance<'a, T, P>>) -> Self {
         UnionIndex { indexes }
     }
 }
 
-impl<'a> Path<'a> for UnionIndex<'a> {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for UnionIndex<'a, T, P> {
+    type Data = T;
 
-    fn find(&self, input: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         self.indexes
             .iter()
             .flat_map(|e| e.find(input.clone()))
             .collect()
     }
+
+    fn find_into(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        for e in self.indexes.iter() {
+            e.find_into(input.clone(), out)
+        }
+    }
 }
 
 /// process filter element like [?(op sign op)]
-pub enum FilterPath<'a> {
+///
+/// The logical operators short-circuit: the right side of `&&` is not evaluated when the left side is false
+/// and the right side of `||` is not evaluated when the left side is true.
+/// Therefore, a cheap guard placed before an expensive regex or a nested path saves the evaluation of the latter.
+pub enum FilterPath<'a, T: JsonLike = Value> {
     Filter {
-        left: PathInstance<'a>,
-        right: PathInstance<'a>,
+        left: OperandPath<'a, T>,
+        right: OperandPath<'a, T>,
         op: &'a FilterSign,
+        /// the prepared regex if the right side is a static regex
+        regex: Option<RegexMatcher>,
     },
     Or {
-        left: PathInstance<'a>,
-        right: PathInstance<'a>,
+        left: Box<FilterPath<'a, T>>,
+        right: Box<FilterPath<'a, T>>,
     },
     And {
-        left: PathInstance<'a>,
-        right: PathInstance<'a>,
+        left: Box<FilterPath<'a, T>>,
+        right: Box<FilterPath<'a, T>>,
+    },
+    /// both sides are always evaluated
+    Xor {
+        left: Box<FilterPath<'a, T>>,
+        right: Box<FilterPath<'a, T>>,
     },
     Not {
-        exp: PathInstance<'a>,
+        exp: Box<FilterPath<'a, T>>,
     },
 }
 
-impl<'a> FilterPath<'a> {
-    pub(crate) fn new(expr: &'a FilterExpression, root: &'a Value) -> Self {
+impl<'a, T: JsonLike> FilterPath<'a, T> {
+    pub(crate) fn new(expr: &'a FilterExpression, root: &'a T, ctx: &Ctx<'a>) -> Self {
         match expr {
             FilterExpression::Atom(left, op, right) => FilterPath::Filter {
-                left: process_operand(left, root),
-                right: process_operand(right, root),
+                left: process_operand(left, root, ctx),
+                right: process_operand(right, root, ctx),
                 op,
+                regex: match (op, right) {
+                    (FilterSign::Regex, Operand::Static(Value::String(pattern))) => {
+                        ctx.compiled.regex(pattern)
+                    }
+                    _ => None,
+                },
             },
             FilterExpression::And(l, r) => FilterPath::And {
-                left: Box::new(FilterPath::new(l, root)),
-                right: Box::new(FilterPath::new(r, root)),
+                left: Box::new(FilterPath::new(l, root, ctx)),
+                right: Box::new(FilterPath::new(r, root, ctx)),
             },
             FilterExpression::Or(l, r) => FilterPath::Or {
-                left: Box::new(FilterPath::new(l, root)),
-                right: Box::new(FilterPath::new(r, root)),
+                left: Box::new(FilterPath::new(l, root, ctx)),
+                right: Box::new(FilterPath::new(r, root, ctx)),
+            },
+            FilterExpression::Xor(l, r) => FilterPath::Xor {
+                left: Box::new(FilterPath::new(l, root, ctx)),
+                right: Box::new(FilterPath::new(r, root, ctx)),
             },
             FilterExpression::Not(exp) => FilterPath::Not {
-                exp: Box::new(FilterPath::new(exp, root)),
+                exp: Box::new(FilterPath::new(exp, root, ctx)),
             },
         }
     }
     fn compound(
-        one: &'a FilterSign,
-        two: &'a FilterSign,
-        left: Vec<JsonPathValue<Value>>,
-        right: Vec<JsonPathValue<Value>>,
+        one: &FilterSign,
+        two: &FilterSign,
+        left: Vec<&T>,
+        right: Vec<&T>,
+        rules: &Rules,
     ) -> bool {
-        FilterPath::process_atom(one, left.clone(), right.clone())
-            || FilterPath::process_atom(two, left, right)
+        FilterPath::process_atom(one, left.clone(), right.clone(), rules)
+            || FilterPath::process_atom(two, left, right, rules)
     }
-    fn process_atom(
-        op: &'a FilterSign,
-        left: Vec<JsonPathValue<Value>>,
-        right: Vec<JsonPathValue<Value>>,
-    ) -> bool {
+    fn process_atom(op: &FilterSign, left: Vec<&T>, right: Vec<&T>, rules: &Rules) -> bool {
         match op {
-            FilterSign::Equal => eq(
-                JsonPathValue::vec_as_data(left),
-                JsonPathValue::vec_as_data(right),
-            ),
-            FilterSign::Unequal => !FilterPath::process_atom(&FilterSign::Equal, left, right),
-            FilterSign::Less => less(
-                JsonPathValue::vec_as_data(left),
-                JsonPathValue::vec_as_data(right),
-            ),
+            FilterSign::Equal => eq(left, right, rules),
+            FilterSign::Unequal => {
+                !FilterPath::process_atom(&FilterSign::Equal, left, right, rules)
+            }
+            FilterSign::Less => less(left, right, rules),
             FilterSign::LeOrEq => {
-                FilterPath::compound(&FilterSign::Less, &FilterSign::Equal, left, right)
+                FilterPath::compound(&FilterSign::Less, &FilterSign::Equal, left, right, rules)
             }
-            FilterSign::Greater => less(
-                JsonPathValue::vec_as_data(right),
-                JsonPathValue::vec_as_data(left),
-            ),
+            FilterSign::Greater => less(right, left, rules),
             FilterSign::GrOrEq => {
-                FilterPath::compound(&FilterSign::Greater, &FilterSign::Equal, left, right)
+                FilterPath::compound(&FilterSign::Greater, &FilterSign::Equal, left, right, rules)
             }
-            FilterSign::Regex => regex(
-                JsonPathValue::vec_as_data(left),
-                JsonPathValue::vec_as_data(right),
-            ),
-            FilterSign::In => inside(
-                JsonPathValue::vec_as_data(left),
-                JsonPathValue::vec_as_data(right),
-            ),
-            FilterSign::Nin => !FilterPath::process_atom(&FilterSign::In, left, right),
-            FilterSign::NoneOf => !FilterPath::process_atom(&FilterSign::AnyOf, left, right),
-            FilterSign::AnyOf => any_of(
-                JsonPathValue::vec_as_data(left),
-                JsonPathValue::vec_as_data(right),
-            ),
-            FilterSign::SubSetOf => sub_set_of(
-                JsonPathValue::vec_as_data(left),
-                JsonPathValue::vec_as_data(right),
-            ),
-            FilterSign::Exists => !JsonPathValue::vec_as_data(left).is_empty(),
-            FilterSign::Size => size(
-                JsonPathValue::vec_as_data(left),
-                JsonPathValue::vec_as_data(right),
-            ),
+            FilterSign::Regex => regex(left, right),
+            FilterSign::In => inside(left, right, rules),
+            FilterSign::Nin => !FilterPath::process_atom(&FilterSign::In, left, right, rules),
+            FilterSign::NoneOf => !FilterPath::process_atom(&FilterSign::AnyOf, left, right, rules),
+            FilterSign::AnyOf => any_of(left, right, rules),
+            FilterSign::SubSetOf => sub_set_of(left, right, rules),
+            FilterSign::Exists => !left.is_empty(),
+            FilterSign::Size => size(left, right, rules),
+            FilterSign::Schema => schema(left, right),
         }
     }
 
-    fn process(&self, curr_el: &'a Value) -> bool {
-        let pref = String::new();
+    /// checks the element at the given location
+    fn process<P: Track>(&self, curr_el: &'a T, loc: Location<'_, P>, rules: &Rules) -> bool {
         match self {
-            FilterPath::Filter { left, right, op } => FilterPath::process_atom(
+            FilterPath::Filter {
+                left,
                 op,
-                left.find(Slice(curr_el, pref.clone())),
-                right.find(Slice(curr_el, pref)),
-            ),
-            FilterPath::Or { left, right } => {
-                if !JsonPathValue::vec_as_data(left.find(Slice(curr_el, pref.clone()))).is_empty() {
-                    true
+                regex: Some(regex),
+                ..
+            } => {
+                let left = left.values(curr_el, loc);
+                check_atom(op, &refs(&left), &[], loc, rules);
+                regex_match(refs(&left), regex.as_ref())
+            }
+            FilterPath::Filter {
+                left,
+                op: FilterSign::Exists,
+                ..
+            } => {
+                let found = left.values(curr_el, loc);
+                if rules.goessner {
+                    refs(&found).into_iter().any(truthy)
                 } else {
-                    !JsonPathValue::vec_as_data(right.find(Slice(curr_el, pref))).is_empty()
+                    !found.is_empty()
                 }
             }
+            FilterPath::Filter {
+                left, right, op, ..
+            } => {
+                let (left, right) = (left.values(curr_el, loc), right.values(curr_el, loc));
+                let (left, right) = (refs(&left), refs(&right));
+                check_atom(op, &left, &right, loc, rules);
+                FilterPath::process_atom(op, left, right, rules)
+            }
+            FilterPath::Or { left, right } => {
+                left.holds(curr_el, loc, rules) || right.holds(curr_el, loc, rules)
+            }
             FilterPath::And { left, right } => {
-                if JsonPathValue::vec_as_data(left.find(Slice(curr_el, pref.clone()))).is_empty() {
-                    false
-                } else {
-                    !JsonPathValue::vec_as_data(right.find(Slice(curr_el, pref))).is_empty()
-                }
+                left.holds(curr_el, loc, rules) && right.holds(curr_el, loc, rules)
+            }
+            FilterPath::Xor { left, right } => {
+                left.holds(curr_el, loc, rules) != right.holds(curr_el, loc, rules)
+            }
+            FilterPath::Not { exp } => !exp.holds(curr_el, loc, rules),
+        }
+    }
+
+    /// checks the nested expression of the logical operator.
+    /// The nested expression is applied to the elements of the array like the filter itself,
+    /// it holds if any of them fits and the rest of them are not checked.
+    fn holds<P: Track>(&self, curr_el: &'a T, loc: Location<'_, P>, rules: &Rules) -> bool {
+        match curr_el.as_array() {
+            Some(elems) => {
+                let pref = loc.tracked();
+                elems
+                    .iter()
+                    .enumerate()
+                    .any(|(i, el)| self.process(el, Location::new(&pref, Step::Index(i)), rules))
             }
-            FilterPath::Not { exp } => {
-                JsonPathValue::vec_as_data(exp.find(Slice(curr_el, pref))).is_empty()
+            None => self.process(curr_el, loc, rules),
+        }
+    }
+}
+
+impl FilterPath<'_> {
+    /// evaluates the comparison of two constants since it does not depend on the element
+    pub(crate) fn eval_static(expr: &FilterExpression) -> Option<bool> {
+        match expr {
+            FilterExpression::Atom(Operand::Static(_), _, Operand::Static(_)) => {
+                let ctx = Ctx::new(&NOT_COMPILED, Rules::default());
+                Some(FilterPath::new(expr, &Value::Null, &ctx).process(
+                    &Value::Null,
+                    Location::new(&(), Step::Whole),
+                    &ctx.rules,
+                ))
             }
+            _ => None,
         }
     }
 }
 
-impl<'a> Path<'a> for FilterPath<'a> {
-    type Data = Value;
+/// The filter [?(<expression>)] checking the expression by the rules of the evaluation
+pub(crate) struct Filter<'a, T: JsonLike> {
+    expr: FilterPath<'a, T>,
+    rules: Rc<Rules>,
+}
+
+impl<'a, T: JsonLike> Filter<'a, T> {
+    pub(crate) fn new(expr: &'a FilterExpression, root: &'a T, ctx: &Ctx<'a>) -> Self {
+        Filter::from_path(FilterPath::new(expr, root, ctx), ctx.rules.clone())
+    }
+
+    pub(crate) fn from_path(expr: FilterPath<'a, T>, rules: Rc<Rules>) -> Self {
+        Filter { expr, rules }
+    }
+
+    /// checks the element at the given location
+    fn process<P: Track>(&self, curr_el: &'a T, loc: Location<'_, P>) -> bool {
+        self.expr.process(curr_el, loc, &self.rules)
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for Filter<'a, T> {
+    type Data = T;
 
-    fn find(&self, input: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         input.flat_map_slice(|data, pref| {
             let mut res = vec![];
-            match data {
-                Array(elems) => {
+            match data.as_array() {
+                Some(elems) => {
                     for (i, el) in elems.iter().enumerate() {
-                        if self.process(el) {
-                            res.push(Slice(el, jsp_idx(&pref, i)))
+                        if self.process(el, Location::new(&pref, Step::Index(i))) {
+                            res.push(Slice(el, pref.index(i)))
                         }
                     }
                 }
-                el => {
-                    if self.process(el) {
-                        res.push(Slice(el, pref))
+                None => match data.entries() {
+                    // goessner filters the fields of the objects like the elements of the arrays
+                    Some(entries) if self.rules.goessner => {
+                        for (key, el) in entries {
+                            if self.process(el, Location::new(&pref, Step::Key(key))) {
+                                res.push(Slice(el, pref.key(key)))
+                            }
+                        }
+                    }
+                    _ => {
+                        if self.process(data, Location::new(&pref, Step::Whole)) {
+                            res.push(Slice(data, pref))
+                        }
+                    }
+                },
+            }
+            if res.is_empty() {
+                vec![NoValue]
+            } else {
+                res
+            }
+        })
+    }
+
+    fn selects_from(&self, input: &JsonPathValue<'a, Self::Data, P>) -> bool {
+        input.has_value()
+    }
+}
+
+/// process the descent with the index like ..[?(<expression>)] or ..[1]
+/// The filter is applied to the children of every descendant including the incoming element.
+pub(crate) enum DescentIndex<'a, T: JsonLike, P = String> {
+    Filter(Filter<'a, T>),
+    Index(PathInstance<'a, T, P>),
+}
+
+impl<'a, T: JsonLike, P: Track + 'a> DescentIndex<'a, T, P> {
+    /// the descent visits every element of the document, so it reports no warnings of the strict mode
+    pub(crate) fn new(index: &'a JsonPathIndex, root: &'a T, ctx: &Ctx<'a>) -> Self {
+        let ctx = ctx.unchecked();
+        match index {
+            JsonPathIndex::Filter(fe) => DescentIndex::Filter(Filter::new(fe, root, &ctx)),
+            idx => DescentIndex::Index(process_index(idx, root, &ctx)),
+        }
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for DescentIndex<'a, T, P> {
+    type Data = T;
+
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        input.flat_map_slice(|data, pref| {
+            let mut nodes = vec![(data, pref.clone())];
+            nodes.append(&mut deep_flatten(data, pref));
+
+            let mut res = vec![];
+            for (node, pref) in nodes {
+                match self {
+                    DescentIndex::Filter(filter) => {
+                        if let Some(elems) = node.as_array() {
+                            for (i, el) in elems.iter().enumerate() {
+                                if filter.process(el, Location::new(&pref, Step::Index(i))) {
+                                    res.push(Slice(el, pref.index(i)))
+                                }
+                            }
+                        } else if let Some(elems) = node.entries() {
+                            for (key, el) in elems {
+                                if filter.process(el, Location::new(&pref, Step::Key(key))) {
+                                    res.push(Slice(el, pref.key(key)))
+                                }
+                            }
+                        }
                     }
+                    DescentIndex::Index(index) => res.extend(
+                        index
+                            .find(Slice(node, pref))
+                            .into_iter()
+                            .filter(|v| v.has_value()),
+                    ),
                 }
             }
             if res.is_empty() {
@@ -347,23 +618,34 @@ impl<'a> Path<'a> for FilterPath<'a> {
             }
         })
     }
+
+    fn selects_from(&self, input: &JsonPathValue<'a, Self::Data, P>) -> bool {
+        match self {
+            DescentIndex::Filter(filter) => filter.selects_from(input),
+            DescentIndex::Index(index) => index.selects_from(input),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::parser::model::{FilterExpression, FilterSign, JsonPath, JsonPathIndex, Operand};
-    use crate::path::index::{ArrayIndex, ArraySlice};
+    use crate::path::index::{ArrayIndex, ArraySlice, Filter, FilterPath};
     use crate::path::JsonPathValue;
-    use crate::path::{json_path_instance, Path};
+    use crate::path::{
+        json_path_instance, process_operand, Ctx, Path, RegexMatcher, Rules, NOT_COMPILED,
+    };
     use crate::JsonPathValue::NoValue;
     use crate::{chain, filter, idx, jp_v, op, path};
-    use serde_json::json;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     #[test]
     fn array_slice_end_start_test() {
         let array = [0, 1, 2, 3, 4, 5];
         let len = array.len() as i32;
-        let mut slice = ArraySlice::new(0, 0, 0);
+        let mut slice: ArraySlice<Value> = ArraySlice::new(0, 0, 0);
 
         assert_eq!(slice.start(len).unwrap(), 0);
         slice.start_index = 1;
@@ -437,6 +719,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn slice_str_test() {
+        let isbn = json!("0-553-21311-3");
+
+        let slice = ArraySlice::new(0, 5, 1);
+        assert_eq!(slice.find(jp_v!(&isbn)), vec![jp_v!(json!("0-553"))]);
+
+        let slice = ArraySlice::new(-1, 0, 1);
+        assert_eq!(slice.find(jp_v!(&isbn)), vec![jp_v!(json!("3"))]);
+
+        let slice = ArraySlice::new(0, 5, 2);
+        assert_eq!(slice.find(jp_v!(&isbn)), vec![jp_v!(json!("053"))]);
+
+        let unicode = json!("héllo");
+        let slice = ArraySlice::new(1, 3, 1);
+        assert_eq!(slice.find(jp_v!(&unicode)), vec![jp_v!(json!("él"))]);
+
+        let slice = ArraySlice::new(20, 0, 1);
+        assert_eq!(slice.find(jp_v!(&isbn)), vec![jp_v!(json!(""))]);
+    }
+
     #[test]
     fn index_test() {
         let array = json!([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
@@ -591,6 +894,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn filter_regex_test() {
         let json = json!({
@@ -849,4 +1153,42 @@ mod tests {
             vec![NoValue]
         )
     }
+
+    #[test]
+    fn short_circuit_test() {
+        let json = json!([
+            {"kind":"text","value":"abc"},
+            {"kind":"number","value":5},
+            {"kind":"text","value":"xyz"},
+        ]);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let regex: RegexMatcher = Arc::new(move |v: &str| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            v.starts_with('a')
+        });
+
+        let guard = filter!(op!(path!(@,path!("kind"))), "==", op!("text"));
+        let value = op!(path!(@,path!("value")));
+        let pattern = op!("^a");
+        let sign = FilterSign::Regex;
+        let ctx = Ctx::new(&NOT_COMPILED, Rules::default());
+        let expr = FilterPath::And {
+            left: Box::new(FilterPath::new(&guard, &json, &ctx)),
+            right: Box::new(FilterPath::Filter {
+                left: process_operand(&value, &json, &ctx),
+                right: process_operand(&pattern, &json, &ctx),
+                op: &sign,
+                regex: Some(regex),
+            }),
+        };
+        let filter = Filter::from_path(expr, ctx.rules.clone());
+
+        let abc = json!({"kind":"text","value":"abc"});
+        assert_eq!(
+            filter.find(JsonPathValue::from_root(&json)),
+            jp_v![&abc;"$[0]",]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
 }
@@ -1,29 +1,39 @@
-use regex::Regex;
-use serde_json::Value;
+use crate::path::compare::{eq_el, less_el};
+use crate::path::{length_of, matcher, JsonLike, Rules};
 
 /// compare sizes of json elements
 /// The method expects to get a number on the right side and array or string or object on the left
-/// where the number of characters, elements or fields will be compared respectively.
-pub fn size(left: Vec<&Value>, right: Vec<&Value>) -> bool {
-    if let Some(Value::Number(n)) = right.first() {
-        if let Some(sz) = n.as_f64() {
-            for el in left.iter() {
-                match el {
-                    Value::String(v) if v.len() == sz as usize => true,
-                    Value::Array(elems) if elems.len() == sz as usize => true,
-                    Value::Object(fields) if fields.len() == sz as usize => true,
-                    _ => return false,
-                };
+/// where the number of characters, elements or fields will be compared respectively,
+/// the characters are counted as the [`crate::StringLength`] of the rules tells.
+pub fn size<T: JsonLike>(left: Vec<&T>, right: Vec<&T>, rules: &Rules) -> bool {
+    if let Some(sz) = right.first().and_then(|n| n.as_f64()) {
+        for el in left.iter() {
+            let Some(len) = length_of(*el, &rules.string_length) else {
+                return false;
+            };
+            if len != sz as usize {
+                return false;
             }
-            return true;
         }
+        return true;
     }
     false
 }
 
+/// the elements of the set on the right side of the set operators:
+/// the elements of the single array (like the literal list or the path to the array)
+/// or all the elements found by the path yielding several of them (like `$.refs[*]` or `$..id`)
+fn right_set<'a, T: JsonLike>(right: &[&'a T]) -> Option<Vec<&'a T>> {
+    match right {
+        [] => None,
+        [one] => one.as_array().map(|elems| elems.iter().collect()),
+        many => Some(many.to_vec()),
+    }
+}
+
 /// ensure the array on the left side is a subset of the array on the right side.
 //todo change the naive impl to sets
-pub fn sub_set_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
+pub fn sub_set_of<T: JsonLike>(left: Vec<&T>, right: Vec<&T>, rules: &Rules) -> bool {
     if left.is_empty() {
         return true;
     }
@@ -32,7 +42,7 @@ pub fn sub_set_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
     }
 
     if let Some(elems) = left.first().and_then(|e| e.as_array()) {
-        if let Some(Value::Array(right_elems)) = right.first() {
+        if let Some(right_elems) = right_set(&right) {
             if right_elems.is_empty() {
                 return false;
             }
@@ -41,7 +51,7 @@ pub fn sub_set_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
                 let mut res = false;
 
                 for r in right_elems.iter() {
-                    if el.eq(r) {
+                    if eq_el(el, *r, rules) {
                         res = true
                     }
                 }
@@ -57,7 +67,7 @@ pub fn sub_set_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
 
 /// ensure at least one element in the array  on the left side belongs to the array on the right side.
 //todo change the naive impl to sets
-pub fn any_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
+pub fn any_of<T: JsonLike>(left: Vec<&T>, right: Vec<&T>, rules: &Rules) -> bool {
     if left.is_empty() {
         return true;
     }
@@ -65,7 +75,7 @@ pub fn any_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
         return false;
     }
 
-    if let Some(Value::Array(elems)) = right.first() {
+    if let Some(elems) = right_set(&right) {
         if elems.is_empty() {
             return false;
         }
@@ -74,14 +84,14 @@ pub fn any_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
             if let Some(left_elems) = el.as_array() {
                 for l in left_elems.iter() {
                     for r in elems.iter() {
-                        if l.eq(r) {
+                        if eq_el(l, *r, rules) {
                             return true;
                         }
                     }
                 }
             } else {
                 for r in elems.iter() {
-                    if el.eq(&r) {
+                    if eq_el(*el, *r, rules) {
                         return true;
                     }
                 }
@@ -93,84 +103,94 @@ pub fn any_of(left: Vec<&Value>, right: Vec<&Value>) -> bool {
 }
 
 /// ensure that the element on the left sides mathes the regex on the right side
-pub fn regex(left: Vec<&Value>, right: Vec<&Value>) -> bool {
+pub fn regex<T: JsonLike>(left: Vec<&T>, right: Vec<&T>) -> bool {
     if left.is_empty() || right.is_empty() {
         return false;
     }
 
-    match right.first() {
-        Some(Value::String(str)) => {
-            if let Ok(regex) = Regex::new(str) {
-                for el in left.iter() {
-                    if let Some(v) = el.as_str() {
-                        if regex.is_match(v) {
-                            return true;
-                        }
-                    }
-                }
-            }
-            false
-        }
+    match right.first().and_then(|r| r.as_str()) {
+        Some(str) => match matcher(str) {
+            Some(is_match) => regex_match(left, |v| is_match(v)),
+            None => false,
+        },
         _ => false,
     }
 }
 
+/// ensure that the element on the left sides mathes the prepared regex
+pub fn regex_match<T: JsonLike>(left: Vec<&T>, is_match: impl Fn(&str) -> bool) -> bool {
+    left.iter().filter_map(|el| el.as_str()).any(is_match)
+}
+
+/// ensure that the element on the left side conforms to the registered schema named on the right side.
+/// The unknown schema matches nothing.
+#[cfg(feature = "jsonschema")]
+pub fn schema<T: JsonLike>(left: Vec<&T>, right: Vec<&T>) -> bool {
+    match right.first().and_then(|r| r.as_str()) {
+        Some(name) => match crate::schema::registered_schema(name) {
+            Some(schema) => left
+                .iter()
+                .any(|el| crate::schema::is_valid(&el.to_json(), &schema)),
+            None => false,
+        },
+        None => false,
+    }
+}
+
+/// the schema predicate is not parsed without the feature `jsonschema`
+#[cfg(not(feature = "jsonschema"))]
+pub fn schema<T: JsonLike>(_left: Vec<&T>, _right: Vec<&T>) -> bool {
+    false
+}
+
 /// ensure that the element on the left side belongs to the array on the right side.
-pub fn inside(left: Vec<&Value>, right: Vec<&Value>) -> bool {
+pub fn inside<T: JsonLike>(left: Vec<&T>, right: Vec<&T>, rules: &Rules) -> bool {
     if left.is_empty() {
         return false;
     }
 
-    match right.first() {
-        Some(Value::Array(elems)) => {
-            for el in left.iter() {
-                if elems.contains(el) {
-                    return true;
-                }
+    if let Some(elems) = right_set(&right) {
+        for el in left.iter() {
+            if elems.iter().any(|e| eq_el(*el, *e, rules)) {
+                return true;
             }
-            false
         }
-        Some(Value::Object(elems)) => {
-            for el in left.iter() {
-                for r in elems.values() {
-                    if el.eq(&r) {
-                        return true;
-                    }
-                }
+        false
+    } else if let Some(elems) = right.first().and_then(|r| r.entries()) {
+        let elems: Vec<&T> = elems.map(|(_, v)| v).collect();
+        for el in left.iter() {
+            if elems.iter().any(|e| eq_el(*el, *e, rules)) {
+                return true;
             }
-            false
         }
-        _ => false,
+        false
+    } else {
+        false
     }
 }
 
 /// ensure the number on the left side is less the number on the right side
-pub fn less(left: Vec<&Value>, right: Vec<&Value>) -> bool {
+pub fn less<T: JsonLike>(left: Vec<&T>, right: Vec<&T>, rules: &Rules) -> bool {
     if left.len() == 1 && right.len() == 1 {
-        match (left.first(), right.first()) {
-            (Some(Value::Number(l)), Some(Value::Number(r))) => l
-                .as_f64()
-                .and_then(|v1| r.as_f64().map(|v2| v1 < v2))
-                .unwrap_or(false),
-            _ => false,
-        }
+        less_el(left[0], right[0], rules)
     } else {
         false
     }
 }
 
 /// compare elements
-pub fn eq(left: Vec<&Value>, right: Vec<&Value>) -> bool {
+pub fn eq<T: JsonLike>(left: Vec<&T>, right: Vec<&T>, rules: &Rules) -> bool {
     if left.len() != right.len() {
         false
     } else {
-        left.iter().zip(right).map(|(a, b)| a.eq(&b)).all(|a| a)
+        left.iter().zip(right).all(|(a, b)| eq_el(*a, b, rules))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::path::json::{any_of, eq, less, regex, size, sub_set_of};
+    use crate::path::json::{any_of, eq, inside, less, regex, size, sub_set_of};
+    use crate::path::Rules;
     use serde_json::{json, Value};
 
     #[test]
@@ -195,20 +215,39 @@ mod tests {
         let right2 = json!([1, 2, 3]);
         let right3 = json!({"value":[42],"value2":[42]});
 
-        assert!(eq(vec![&left], vec![&right]));
+        assert!(eq(vec![&left], vec![&right], &Rules::default()));
 
-        assert!(!eq(vec![], vec![&right]));
-        assert!(!eq(vec![&right], vec![]));
+        assert!(!eq(vec![], vec![&right], &Rules::default()));
+        assert!(!eq(vec![&right], vec![], &Rules::default()));
 
         assert!(eq(
             vec![&left, &left1, &left2, &left3],
-            vec![&right, &right1, &right2, &right3]
+            vec![&right, &right1, &right2, &right3],
+            &Rules::default()
         ));
 
         assert!(!eq(
             vec![&left1, &left, &left2, &left3],
-            vec![&right, &right1, &right2, &right3]
+            vec![&right, &right1, &right2, &right3],
+            &Rules::default()
         ));
+
+        let (int, float) = (json!([1, {"a": 2}]), json!([1.0, {"a": 2.0}]));
+        assert!(eq(vec![&int], vec![&float], &Rules::default()));
+        assert!(!eq(vec![&json!(1)], vec![&json!(1.5)], &Rules::default()));
+    }
+
+    #[test]
+    fn set_of_found_elements_test() {
+        let (a, b, c) = (json!("a"), json!("b"), json!("c"));
+        let left = json!(["a", "b"]);
+        assert!(sub_set_of(vec![&left], vec![&a, &b, &c], &Rules::default()));
+        assert!(!sub_set_of(vec![&left], vec![&a, &c], &Rules::default()));
+        assert!(any_of(vec![&left], vec![&b, &c], &Rules::default()));
+        assert!(!any_of(vec![&left], vec![&c, &c], &Rules::default()));
+        assert!(inside(vec![&a], vec![&c, &a], &Rules::default()));
+        assert!(!inside(vec![&a], vec![&a], &Rules::default()));
+        assert!(!inside(vec![&a], vec![], &Rules::default()));
     }
 
     #[test]
@@ -216,25 +255,36 @@ mod tests {
         let left = json!(10);
         let right = json!(11);
 
-        assert!(less(vec![&left], vec![&right]));
-        assert!(!less(vec![&right], vec![&left]));
+        assert!(less(vec![&left], vec![&right], &Rules::default()));
+        assert!(!less(vec![&right], vec![&left], &Rules::default()));
 
         let left = json!(-10);
         let right = json!(-11);
 
-        assert!(!less(vec![&left], vec![&right]));
-        assert!(less(vec![&right], vec![&left]));
+        assert!(!less(vec![&left], vec![&right], &Rules::default()));
+        assert!(less(vec![&right], vec![&left], &Rules::default()));
 
         let left = json!(-10.0);
         let right = json!(-11.0);
 
-        assert!(!less(vec![&left], vec![&right]));
-        assert!(less(vec![&right], vec![&left]));
+        assert!(!less(vec![&left], vec![&right], &Rules::default()));
+        assert!(less(vec![&right], vec![&left], &Rules::default()));
 
-        assert!(!less(vec![], vec![&right]));
-        assert!(!less(vec![&right, &right], vec![&left]));
+        assert!(!less(vec![], vec![&right], &Rules::default()));
+        assert!(!less(vec![&right, &right], vec![&left], &Rules::default()));
     }
 
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn glob_pattern_test() {
+        let left = json!("abc.json");
+        assert!(regex(vec![&left], vec![&json!("*.json")]));
+        assert!(regex(vec![&left], vec![&json!("a*")]));
+        assert!(!regex(vec![&left], vec![&json!("b*")]));
+        assert!(!regex(vec![&left], vec![&json!("^a.*")]));
+    }
+
+    #[cfg(feature = "regex")]
     #[test]
     fn regex_test() {
         let right = json!("[a-zA-Z]+[0-9]#[0-9]+");
@@ -251,14 +301,18 @@ mod tests {
     fn any_of_test() {
         let right = json!([1, 2, 3, 4, 5, 6]);
         let left = json!([1, 100, 101]);
-        assert!(any_of(vec![&left], vec![&right]));
+        assert!(any_of(vec![&left], vec![&right], &Rules::default()));
 
         let left = json!([11, 100, 101]);
-        assert!(!any_of(vec![&left], vec![&right]));
+        assert!(!any_of(vec![&left], vec![&right], &Rules::default()));
 
         let left1 = json!(1);
         let left2 = json!(11);
-        assert!(any_of(vec![&left1, &left2], vec![&right]));
+        assert!(any_of(
+            vec![&left1, &left2],
+            vec![&right],
+            &Rules::default()
+        ));
     }
 
     #[test]
@@ -274,11 +328,13 @@ mod tests {
                 left2.clone(),
                 left3.clone()
             ])],
-            vec![&right]
+            vec![&right],
+            &Rules::default()
         ));
         assert!(!sub_set_of(
             vec![&Value::Array(vec![left1, left2, left3, left40])],
-            vec![&right]
+            vec![&right],
+            &Rules::default()
         ));
     }
 
@@ -288,8 +344,8 @@ mod tests {
         let left2 = json!([1, 2, 3]);
         let left3 = json!([1, 2, 3, 4]);
         let right = json!(3);
-        assert!(size(vec![&left1], vec![&right]));
-        assert!(size(vec![&left2], vec![&right]));
-        assert!(!size(vec![&left3], vec![&right]));
+        assert!(size(vec![&left1], vec![&right], &Rules::default()));
+        assert!(size(vec![&left2], vec![&right], &Rules::default()));
+        assert!(!size(vec![&left3], vec![&right], &Rules::default()));
     }
 }
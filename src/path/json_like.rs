@@ -0,0 +1,345 @@
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::fmt::Debug;
+
+/// The tree of the json-like values the path is evaluated on.
+///
+/// The evaluation needs only the access to the fields and the elements and the scalar values,
+/// therefore implementing the trait for the other document types (like yaml or custom trees)
+/// allows querying them without converting into [`Value`].
+/// The constants of the query and the generated values (like the length) are converted from [`Value`].
+///
+/// The fields are visited in the order of [`JsonLike::entries`], therefore the matches derived from the objects,
+/// including the ones found by the wildcards and the descent, come back in the document order
+/// if the entries keep the order of the text.
+/// The union of the keys `['b','a']` yields the fields in the order of the query,
+/// see [`crate::ResultOrder`] for the ordering of the other selectors and the document order.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_values, JsonLike, JsonPathInst};
+/// use serde_json::Value;
+/// use std::str::FromStr;
+///
+/// #[derive(Debug, Clone, PartialEq, Default)]
+/// enum Tree {
+///     #[default]
+///     Leaf,
+///     Num(f64),
+///     Node(Vec<(String, Tree)>),
+/// }
+///
+/// impl JsonLike for Tree {
+///     type Entries<'a> = std::iter::Map<
+///         std::slice::Iter<'a, (String, Tree)>,
+///         fn(&'a (String, Tree)) -> (&'a str, &'a Tree),
+///     >;
+///
+///     fn as_array(&self) -> Option<&[Self]> {
+///         None
+///     }
+///     fn entries(&self) -> Option<Self::Entries<'_>> {
+///         match self {
+///             Tree::Node(fields) => Some(fields.iter().map(|(k, v)| (k.as_str(), v))),
+///             _ => None,
+///         }
+///     }
+///     fn as_str(&self) -> Option<&str> {
+///         None
+///     }
+///     fn as_f64(&self) -> Option<f64> {
+///         match self {
+///             Tree::Num(n) => Some(*n),
+///             _ => None,
+///         }
+///     }
+///     fn as_bool(&self) -> Option<bool> {
+///         None
+///     }
+///     fn is_null(&self) -> bool {
+///         *self == Tree::Leaf
+///     }
+///     fn is_integer(&self) -> bool {
+///         false
+///     }
+///     fn from_json(value: Value) -> Self {
+///         value.as_f64().map(Tree::Num).unwrap_or(Tree::Leaf)
+///     }
+/// }
+///
+/// let tree = Tree::Node(vec![
+///     ("a".to_string(), Tree::Node(vec![("v".to_string(), Tree::Num(1.0))])),
+///     ("b".to_string(), Tree::Node(vec![("v".to_string(), Tree::Num(5.0))])),
+/// ]);
+/// let path = JsonPathInst::from_str("$.*[?(@.v > 2)].v").unwrap();
+/// assert_eq!(find_values(&path, &tree), vec![&Tree::Num(5.0)]);
+/// ```
+pub trait JsonLike: Clone + PartialEq + Debug + Default {
+    /// the iterator over the fields of the object along with their keys
+    type Entries<'a>: DoubleEndedIterator<Item = (&'a str, &'a Self)>
+    where
+        Self: 'a;
+
+    /// the elements if the value is an array
+    fn as_array(&self) -> Option<&[Self]>;
+    /// the fields in their order if the value is an object
+    fn entries(&self) -> Option<Self::Entries<'_>>;
+    /// the field of the object
+    fn get(&self, key: &str) -> Option<&Self> {
+        self.entries()?.find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+    fn as_str(&self) -> Option<&str>;
+    /// the number if the value is a number
+    fn as_f64(&self) -> Option<f64>;
+    fn as_bool(&self) -> Option<bool>;
+    fn is_null(&self) -> bool;
+    /// checks if the value is a number without the fractional part
+    fn is_integer(&self) -> bool;
+    /// converts the constant of the query or the generated value
+    fn from_json(value: Value) -> Self;
+    /// converts the constant of the query, the conversion happens once per evaluation
+    fn constant(value: &Value) -> Cow<'_, Self> {
+        Cow::Owned(Self::from_json(value.clone()))
+    }
+    /// converts the value back into json for the checks working on [`Value`] like the schema predicate
+    fn to_json(&self) -> Cow<'_, Value> {
+        let value = if let Some(elems) = self.as_array() {
+            Value::Array(elems.iter().map(|e| e.to_json().into_owned()).collect())
+        } else if let Some(entries) = self.entries() {
+            Value::Object(
+                entries
+                    .map(|(k, v)| (k.to_string(), v.to_json().into_owned()))
+                    .collect(),
+            )
+        } else if let Some(s) = self.as_str() {
+            Value::from(s)
+        } else if let Some(n) = self.as_f64() {
+            if self.is_integer() && n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+                Value::from(n as i64)
+            } else {
+                Value::from(n)
+            }
+        } else if let Some(b) = self.as_bool() {
+            Value::Bool(b)
+        } else {
+            Value::Null
+        };
+        Cow::Owned(value)
+    }
+}
+
+impl JsonLike for Value {
+    type Entries<'a> = std::iter::Map<
+        serde_json::map::Iter<'a>,
+        fn((&'a String, &'a Value)) -> (&'a str, &'a Value),
+    >;
+
+    fn as_array(&self) -> Option<&[Self]> {
+        Value::as_array(self).map(Vec::as_slice)
+    }
+
+    fn entries(&self) -> Option<Self::Entries<'_>> {
+        Value::as_object(self).map(|fields| fields.iter().map(entry as fn(_) -> _))
+    }
+
+    fn get(&self, key: &str) -> Option<&Self> {
+        Value::as_object(self).and_then(|fields| Map::get(fields, key))
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        Value::as_str(self)
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        Value::as_f64(self)
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        Value::as_bool(self)
+    }
+
+    fn is_null(&self) -> bool {
+        Value::is_null(self)
+    }
+
+    fn is_integer(&self) -> bool {
+        self.is_i64() || self.is_u64()
+    }
+
+    fn from_json(value: Value) -> Self {
+        value
+    }
+
+    fn constant(value: &Value) -> Cow<'_, Self> {
+        Cow::Borrowed(value)
+    }
+
+    fn to_json(&self) -> Cow<'_, Value> {
+        Cow::Borrowed(self)
+    }
+}
+
+fn entry<'a>((k, v): (&'a String, &'a Value)) -> (&'a str, &'a Value) {
+    (k.as_str(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::JsonLike;
+    use crate::{find_values, JsonPathInst, ResultOrder};
+    use serde_json::{json, Value};
+    use std::str::FromStr;
+
+    /// the document keeping the fields in the order of the text like `preserve_order`
+    #[derive(Debug, Clone, PartialEq, Default)]
+    enum Doc {
+        #[default]
+        Null,
+        Num(f64),
+        Arr(Vec<Doc>),
+        Obj(Vec<(String, Doc)>),
+    }
+
+    impl JsonLike for Doc {
+        type Entries<'a> = std::iter::Map<
+            std::slice::Iter<'a, (String, Doc)>,
+            fn(&'a (String, Doc)) -> (&'a str, &'a Doc),
+        >;
+
+        fn as_array(&self) -> Option<&[Self]> {
+            match self {
+                Doc::Arr(elems) => Some(elems),
+                _ => None,
+            }
+        }
+        fn entries(&self) -> Option<Self::Entries<'_>> {
+            match self {
+                Doc::Obj(fields) => Some(fields.iter().map(|(k, v)| (k.as_str(), v))),
+                _ => None,
+            }
+        }
+        fn as_str(&self) -> Option<&str> {
+            None
+        }
+        fn as_f64(&self) -> Option<f64> {
+            match self {
+                Doc::Num(n) => Some(*n),
+                _ => None,
+            }
+        }
+        fn as_bool(&self) -> Option<bool> {
+            None
+        }
+        fn is_null(&self) -> bool {
+            *self == Doc::Null
+        }
+        fn is_integer(&self) -> bool {
+            false
+        }
+        fn from_json(value: Value) -> Self {
+            value.as_f64().map(Doc::Num).unwrap_or_default()
+        }
+    }
+
+    fn obj(fields: Vec<(&str, Doc)>) -> Doc {
+        Doc::Obj(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    fn numbers(path: &str, doc: &Doc) -> Vec<f64> {
+        let path = JsonPathInst::from_str(path).unwrap();
+        find_values(&path, doc)
+            .into_iter()
+            .filter_map(|v| v.as_f64())
+            .collect()
+    }
+
+    #[test]
+    fn document_order_test() {
+        let doc = obj(vec![
+            ("z", obj(vec![("v", Doc::Num(1.0)), ("b", Doc::Num(2.0))])),
+            (
+                "m",
+                Doc::Arr(vec![obj(vec![("v", Doc::Num(3.0))]), Doc::Num(4.0)]),
+            ),
+            ("a", obj(vec![("v", Doc::Num(5.0))])),
+        ]);
+        assert_eq!(numbers("$.z.*", &doc), vec![1.0, 2.0]);
+        assert_eq!(numbers("$..*", &doc), vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(numbers("$..v", &doc), vec![1.0, 3.0, 5.0]);
+        assert_eq!(numbers("$.*.v", &doc), vec![1.0, 5.0]);
+        // the descent filter selects the children of every descendant in the document order
+        assert_eq!(numbers("$..[?(@.v > 2)].v", &doc), vec![5.0, 3.0]);
+        assert_eq!(numbers("$['a','z'].v", &doc), vec![5.0, 1.0]);
+        assert_eq!(numbers("$.z['b','v']", &doc), vec![2.0, 1.0]);
+    }
+
+    #[test]
+    fn result_order_test() {
+        let doc = obj(vec![
+            ("z", obj(vec![("v", Doc::Num(1.0)), ("b", Doc::Num(2.0))])),
+            (
+                "m",
+                Doc::Arr(vec![obj(vec![("v", Doc::Num(3.0))]), Doc::Num(4.0)]),
+            ),
+            ("a", obj(vec![("v", Doc::Num(5.0))])),
+        ]);
+        let numbers = |path: &str| -> Vec<f64> {
+            let path = JsonPathInst::from_str(path)
+                .unwrap()
+                .with_order(ResultOrder::Document);
+            find_values(&path, &doc)
+                .into_iter()
+                .filter_map(|v| v.as_f64())
+                .collect()
+        };
+        assert_eq!(numbers("$..[?(@.v > 2)].v"), vec![3.0, 5.0]);
+        assert_eq!(numbers("$['a','z'].v"), vec![1.0, 5.0]);
+        assert_eq!(numbers("$.z['b','v']"), vec![1.0, 2.0]);
+        assert_eq!(numbers("$.m[1,0].v"), vec![3.0]);
+        assert_eq!(numbers("$..*"), vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn result_order_value_test() {
+        let data = json!({"b":[{"x":3},{"x":1}],"a":{"x":2}});
+        let path = JsonPathInst::from_str("$['b','a']..x").unwrap();
+        assert_eq!(
+            find_values(&path, &data),
+            vec![&json!(3), &json!(1), &json!(2)]
+        );
+        let path = path.with_order(ResultOrder::Document);
+        assert_eq!(
+            find_values(&path, &data),
+            vec![&json!(2), &json!(3), &json!(1)]
+        );
+
+        let path = JsonPathInst::from_str("$[1,0]")
+            .unwrap()
+            .with_order(ResultOrder::Document);
+        assert_eq!(
+            crate::find_map(&path, &json!([[1], [2]]))
+                .into_iter()
+                .map(|m| m.path.unwrap())
+                .collect::<Vec<_>>(),
+            vec!["$[0]", "$[1]"]
+        );
+    }
+
+    #[test]
+    fn value_order_test() {
+        let data = json!({"z":{"v":1},"a":{"v":2}});
+        let path = JsonPathInst::from_str("$..v").unwrap();
+        let expected: Vec<&Value> = data
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|v| &v["v"])
+            .collect();
+        assert_eq!(find_values(&path, &data), expected);
+    }
+}
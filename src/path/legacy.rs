@@ -0,0 +1,32 @@
+use crate::path::JsonLike;
+
+/// the element is not `null`, `false`, `0` or the empty string, like the condition of javascript
+pub(crate) fn truthy<T: JsonLike>(v: &T) -> bool {
+    if v.is_null() {
+        false
+    } else if let Some(b) = v.as_bool() {
+        b
+    } else if let Some(n) = v.as_f64() {
+        n != 0.0 && !n.is_nan()
+    } else if let Some(s) = v.as_str() {
+        !s.is_empty()
+    } else {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::legacy::truthy;
+    use serde_json::json;
+
+    #[test]
+    fn truthy_test() {
+        for v in [json!(null), json!(false), json!(0), json!(-0.0), json!("")] {
+            assert!(!truthy(&v), "{v}");
+        }
+        for v in [json!(true), json!(1), json!("0"), json!([]), json!({})] {
+            assert!(truthy(&v), "{v}");
+        }
+    }
+}
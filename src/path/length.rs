@@ -0,0 +1,103 @@
+use crate::path::JsonLike;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// How `length()` and `size` count the strings, set by [`crate::EvalOptions::with_string_length`].
+/// The arrays are counted by the elements and the objects by the fields.
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::{find_with_options, EvalOptions, JsonPathInst, StringLength};
+/// use serde_json::json;
+/// use std::str::FromStr;
+///
+/// let data = json!({"name":"héllo"});
+/// let path = JsonPathInst::from_str("$.name.length()").unwrap();
+/// let count = |len: StringLength| {
+///     let options = EvalOptions::default().with_string_length(len);
+///     find_with_options(&path, &data, &options).unwrap()[0].value.clone()
+/// };
+/// assert_eq!(*count(StringLength::Chars), json!(5));
+/// assert_eq!(*count(StringLength::Bytes), json!(6));
+/// ```
+#[derive(Clone, Default)]
+pub enum StringLength {
+    /// the bytes of the utf-8 encoding
+    Bytes,
+    /// the unicode scalar values like the `length()` of RFC 9535
+    #[default]
+    Chars,
+    /// the given function like counting the graphemes with `unicode-segmentation`,
+    /// the library has no unicode tables for the graphemes itself
+    Custom(Arc<dyn Fn(&str) -> usize + Send + Sync>),
+}
+
+impl StringLength {
+    fn count(&self, s: &str) -> usize {
+        match self {
+            StringLength::Bytes => s.len(),
+            StringLength::Chars => s.chars().count(),
+            StringLength::Custom(count) => count(s),
+        }
+    }
+}
+
+impl Debug for StringLength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StringLength::Bytes => f.write_str("Bytes"),
+            StringLength::Chars => f.write_str("Chars"),
+            StringLength::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// The custom countings are equal only if they are the same one.
+impl PartialEq for StringLength {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (StringLength::Custom(a), StringLength::Custom(b)) => {
+                std::ptr::addr_eq(Arc::as_ptr(a), Arc::as_ptr(b))
+            }
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+impl Eq for StringLength {}
+
+/// the number of the elements of the array, the fields of the object or the length of the string
+/// counted as the given string length tells
+pub(crate) fn length_of<T: JsonLike>(v: &T, len: &StringLength) -> Option<usize> {
+    if let Some(s) = v.as_str() {
+        Some(len.count(s))
+    } else if let Some(elems) = v.as_array() {
+        Some(elems.len())
+    } else {
+        v.entries().map(Iterator::count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::length::{length_of, StringLength};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn length_test() {
+        let chars = StringLength::Chars;
+        assert_eq!(length_of(&json!([1, 2]), &chars), Some(2));
+        assert_eq!(length_of(&json!({"a":1}), &chars), Some(1));
+        assert_eq!(length_of(&json!("añ"), &chars), Some(2));
+        assert_eq!(length_of(&json!(12), &chars), None);
+        assert_eq!(length_of(&json!(null), &chars), None);
+        assert_eq!(length_of(&json!("añ"), &StringLength::Bytes), Some(3));
+        let words = StringLength::Custom(Arc::new(|s| s.split_whitespace().count()));
+        assert_eq!(length_of(&json!("a b  c"), &words), Some(3));
+        assert_eq!(length_of(&json!(["a b"]), &words), Some(1));
+        assert_eq!(words, words.clone());
+        assert_ne!(words, StringLength::Custom(Arc::new(|s| s.len())));
+        assert_ne!(StringLength::Bytes, StringLength::Chars);
+    }
+}
@@ -1,75 +1,468 @@
-use crate::JsonPathValue;
+use crate::parser::model::{FilterExpression, FilterSign};
+use crate::parser::visitor::{walk_filter, walk_path, Visitor};
+use crate::JsonPathValue::{NewValue, NoValue, Slice};
+use crate::{JsonPathValue, PathSegment};
 use serde_json::Value;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::parser::model::{Function, JsonPath, JsonPathIndex, Operand};
-use crate::path::index::{ArrayIndex, ArraySlice, Current, FilterPath, UnionIndex};
+use crate::path::index::{array_index, ArraySlice, Current, DescentIndex, Filter, UnionIndex};
 use crate::path::top::*;
 
+/// The module defines the document borrowing the strings from the text
+mod borrowed;
+/// The module keeps the number of the nodes the evaluation is allowed to visit and stops the cancelled evaluation
+mod budget;
+/// The module lets the user decide how the filters compare the elements
+mod compare;
+/// The module is in charge of the prebuilt index of the document
+mod document;
 /// The module is in charge of processing [[JsonPathIndex]] elements
 mod index;
 /// The module is a helper module providing the set of helping funcitons to process a json elements
 mod json;
+/// The module defines the trait of the documents the path is evaluated on
+mod json_like;
+/// The module keeps the quirks of the original javascript implementation of Goessner
+mod legacy;
+/// The module counts the elements of the arrays, the fields of the objects and the strings
+mod length;
+/// The module rewrites the expensive patterns of the path before the evaluation
+mod optimize;
+/// The module is in charge of the strict evaluation reporting the type mismatches
+mod strict;
 /// The module is responsible for processing of the [[JsonPath]] elements
 mod top;
+/// The module keeps track of the locations of the found elements
+mod track;
+
+pub use borrowed::BorrowedValue;
+pub use budget::CancellationToken;
+pub(crate) use budget::{spend, with_budget, Budget, Stop};
+pub(crate) use compare::eq_el;
+pub use compare::{Coercion, Comparator, Scalar, StringComparator, StringOrder, StringTransform};
+pub use document::{DocumentIndex, DocumentStats};
+pub use json_like::JsonLike;
+pub(crate) use legacy::truthy;
+pub(crate) use length::length_of;
+pub use length::StringLength;
+pub(crate) use optimize::optimize;
+pub(crate) use strict::CheckedIndex;
+pub use strict::{EvalWarning, EvalWarningKind};
+pub(crate) use top::{exceeds_depth, glob_parts, Chain};
+pub use track::{PathNode, Track};
 
 /// The trait defining the behaviour of processing every separated element.
 /// type Data usually stands for json [[Value]]
 /// The trait also requires to have a root json to process.
 /// It needs in case if in the filter there will be a pointer to the absolute path
-pub trait Path<'a> {
+/// The locations of the found elements are kept as the [`Track`] tells, the rendered paths by default.
+pub trait Path<'a, P: Track = String> {
     type Data;
     /// when every element needs to handle independently
-    fn find(&self, input: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         vec![input]
     }
+    /// when every element needs to handle independently and the result is pushed into the given buffer.
+    /// The elements producing at most one result override it to avoid allocating the intermediate vectors.
+    fn find_into(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        out.extend(self.find(input))
+    }
     /// when the whole output needs to handle
     fn flat_find(
         &self,
-        input: Vec<JsonPathValue<'a, Self::Data>>,
+        input: Vec<JsonPathValue<'a, Self::Data, P>>,
         _is_search_length: bool,
-    ) -> Vec<JsonPathValue<'a, Self::Data>> {
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         input.into_iter().flat_map(|d| self.find(d)).collect()
     }
     /// defines when we need to invoke `find` or `flat_find`
     fn needs_all(&self) -> bool {
         false
     }
+    /// checks if the element selects its results from the given input like the filter or the slice does,
+    /// so finding nothing means the selection is empty rather than the path does not fit the data
+    fn selects_from(&self, _input: &JsonPathValue<'a, Self::Data, P>) -> bool {
+        false
+    }
 }
 
 /// The basic type for instances.
-pub type PathInstance<'a> = Box<dyn Path<'a, Data = Value> + 'a>;
+pub type PathInstance<'a, T = Value, P = String> = Box<dyn Path<'a, P, Data = T> + 'a>;
+
+/// The prepared regex checking if the text matches it.
+/// The regex is kept behind the function since the regex has the inner mutability
+/// that makes the path unsuitable for the keys of the hash sets.
+pub(crate) type RegexMatcher = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// The prepared regex giving the captured group of the text if it matches
+pub(crate) type RegexCapture = Arc<dyn Fn(&str, usize) -> Option<String> + Send + Sync>;
+
+#[cfg(feature = "regex")]
+pub(crate) fn matcher(pattern: &str) -> Option<RegexMatcher> {
+    regex::Regex::new(pattern)
+        .ok()
+        .map(|regex| Arc::new(move |text: &str| regex.is_match(text)) as RegexMatcher)
+}
+
+/// Without the regex engine the pattern is the glob like the keys of the fields,
+/// matching the whole text where `*` stands for any sequence of chars.
+#[cfg(not(feature = "regex"))]
+pub(crate) fn matcher(pattern: &str) -> Option<RegexMatcher> {
+    let pattern = pattern.to_string();
+    Some(Arc::new(move |text: &str| glob_match(&pattern, text)))
+}
+
+#[cfg(feature = "regex")]
+pub(crate) fn capture(pattern: &str) -> Option<RegexCapture> {
+    regex::Regex::new(pattern).ok().map(|regex| {
+        Arc::new(move |text: &str, group: usize| {
+            let found = regex.captures(text)?.get(group)?;
+            Some(found.as_str().to_string())
+        }) as RegexCapture
+    })
+}
+
+/// the globs capture nothing
+#[cfg(not(feature = "regex"))]
+pub(crate) fn capture(_pattern: &str) -> Option<RegexCapture> {
+    None
+}
+
+/// the reason the pattern of `~=` or `extract` can not be compiled
+#[cfg(feature = "regex")]
+pub(crate) fn pattern_error(pattern: &str) -> Option<String> {
+    regex::Regex::new(pattern).err().map(|e| e.to_string())
+}
+
+/// every glob is correct
+#[cfg(not(feature = "regex"))]
+pub(crate) fn pattern_error(_pattern: &str) -> Option<String> {
+    None
+}
+
+/// The parts of the path prepared once and reused by every evaluation,
+/// like the regexes of the filters.
+#[derive(Clone, Default)]
+pub(crate) struct Compiled {
+    regexes: BTreeMap<String, RegexMatcher>,
+    captures: BTreeMap<String, RegexCapture>,
+}
+
+/// The compiled form without the prepared parts, every part is prepared during the construction.
+pub(crate) static NOT_COMPILED: Compiled = Compiled {
+    regexes: BTreeMap::new(),
+    captures: BTreeMap::new(),
+};
+
+impl Compiled {
+    pub(crate) fn new(path: &JsonPath) -> Self {
+        #[derive(Default)]
+        struct Regexes(
+            BTreeMap<String, RegexMatcher>,
+            BTreeMap<String, RegexCapture>,
+        );
+        impl Visitor for Regexes {
+            fn visit_path(&mut self, path: &JsonPath) {
+                if let JsonPath::Fn(Function::Extract(p, _)) = path {
+                    if let Some(c) = capture(p) {
+                        self.1.insert(p.clone(), c);
+                    }
+                }
+                walk_path(self, path)
+            }
+            fn visit_filter(&mut self, expr: &FilterExpression) {
+                if let FilterExpression::Atom(
+                    _,
+                    FilterSign::Regex,
+                    Operand::Static(Value::String(p)),
+                ) = expr
+                {
+                    if let Some(m) = matcher(p) {
+                        self.0.insert(p.clone(), m);
+                    }
+                }
+                walk_filter(self, expr)
+            }
+        }
+        let mut regexes = Regexes::default();
+        regexes.visit_path(path);
+        Compiled {
+            regexes: regexes.0,
+            captures: regexes.1,
+        }
+    }
+
+    /// returns the prepared regex or compiles it if it has not been prepared
+    pub(crate) fn regex(&self, pattern: &str) -> Option<RegexMatcher> {
+        self.regexes
+            .get(pattern)
+            .cloned()
+            .or_else(|| matcher(pattern))
+    }
+
+    /// returns the prepared regex of `extract` or compiles it if it has not been prepared
+    pub(crate) fn capture(&self, pattern: &str) -> Option<RegexCapture> {
+        self.captures
+            .get(pattern)
+            .cloned()
+            .or_else(|| capture(pattern))
+    }
+}
+
+impl Debug for Compiled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Compiled")
+            .field("regexes", &self.regexes.keys().collect::<Vec<_>>())
+            .field("captures", &self.captures.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// The rules of the evaluation given by [`crate::EvalOptions`]:
+/// how the filters compare the elements, how the strings are counted and the quirks of Goessner.
+/// Every node of the path depending on them keeps them, so the evaluations with the different options
+/// do not interfere with each other.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Rules {
+    pub(crate) comparator: Option<Arc<dyn Comparator>>,
+    pub(crate) coercion: Coercion,
+    pub(crate) string_length: StringLength,
+    /// the filters are applied to the fields of the objects
+    /// and check the truthiness of the elements like the javascript of Goessner
+    pub(crate) goessner: bool,
+    /// collects the type mismatches met by the evaluation for the strict mode
+    pub(crate) warnings: Option<Rc<RefCell<Vec<EvalWarning>>>>,
+}
+
+/// What the nodes of the path are built with: the prepared parts of the path and the rules of the evaluation
+pub(crate) struct Ctx<'a> {
+    pub(crate) compiled: &'a Compiled,
+    pub(crate) rules: Rc<Rules>,
+}
+
+impl<'a> Ctx<'a> {
+    pub(crate) fn new(compiled: &'a Compiled, rules: Rules) -> Self {
+        Ctx {
+            compiled,
+            rules: Rc::new(rules),
+        }
+    }
+
+    /// the same rules without collecting the warnings, for the parts of the path that are not its steps
+    /// like the operands of the filters and the descent visiting every element of the document
+    pub(crate) fn unchecked(&self) -> Ctx<'a> {
+        let rules = match self.rules.warnings {
+            Some(_) => Rc::new(Rules {
+                warnings: None,
+                ..Rules::clone(&self.rules)
+            }),
+            None => self.rules.clone(),
+        };
+        Ctx {
+            compiled: self.compiled,
+            rules,
+        }
+    }
+}
 
 /// The major method to process the top part of json part
-pub fn json_path_instance<'a>(json_path: &'a JsonPath, root: &'a Value) -> PathInstance<'a> {
+pub fn json_path_instance<'a, T: JsonLike + 'a>(
+    json_path: &'a JsonPath,
+    root: &'a T,
+) -> PathInstance<'a, T> {
+    compiled_instance(json_path, root, &Ctx::new(&NOT_COMPILED, Rules::default()))
+}
+
+/// The method processes the path reusing the prepared parts and following the given rules
+pub(crate) fn compiled_instance<'a, T: JsonLike + 'a, P: Track + 'a>(
+    json_path: &'a JsonPath,
+    root: &'a T,
+    ctx: &Ctx<'a>,
+) -> PathInstance<'a, T, P> {
     match json_path {
         JsonPath::Root => Box::new(RootPointer::new(root)),
         JsonPath::Field(key) => Box::new(ObjectField::new(key)),
-        JsonPath::Chain(chain) => Box::new(Chain::from(chain, root)),
-        JsonPath::Wildcard => Box::new(Wildcard {}),
+        JsonPath::FieldGlob(pattern) => Box::new(ObjectField::glob(pattern)),
+        JsonPath::Chain(chain) => Box::new(Chain::from(chain, root, ctx)),
+        JsonPath::Wildcard => Box::new(Wildcard::new()),
         JsonPath::Descent(key) => Box::new(DescentObject::new(key)),
-        JsonPath::DescentW => Box::new(DescentWildcard),
-        JsonPath::Current(value) => Box::new(Current::from(value, root)),
-        JsonPath::Index(index) => process_index(index, root),
-        JsonPath::Empty => Box::new(IdentityPath {}),
-        JsonPath::Fn(Function::Length) => Box::new(FnPath::Size),
+        JsonPath::DescentGlob(pattern) => Box::new(DescentObject::glob(pattern)),
+        JsonPath::DescentW => Box::new(DescentWildcard::new()),
+        JsonPath::DescentIndex(index) => Box::new(DescentIndex::new(index, root, ctx)),
+        JsonPath::Current(value) => Box::new(Current::from(value, root, ctx)),
+        JsonPath::Index(index) => process_index(index, root, ctx),
+        JsonPath::Empty => Box::new(IdentityPath::new()),
+        JsonPath::Fn(Function::Length) => Box::new(FnPath::size(ctx.rules.string_length.clone())),
+        JsonPath::Fn(Function::Aggregate(a)) => Box::new(AggregatePath::new(*a)),
+        JsonPath::Fn(Function::Keys) => Box::new(KeysPath::new()),
+        JsonPath::Fn(Function::Extract(re, group)) => {
+            Box::new(ExtractPath::new(ctx.compiled.capture(re), *group))
+        }
+        JsonPath::Fn(Function::Default(v)) => Box::new(DefaultPath::new(v)),
+        JsonPath::Type(tp) => Box::new(TypeSelector::new(tp)),
     }
 }
 
 /// The method processes the indexes(all expressions indie [])
-fn process_index<'a>(json_path_index: &'a JsonPathIndex, root: &'a Value) -> PathInstance<'a> {
-    match json_path_index {
-        JsonPathIndex::Single(index) => Box::new(ArrayIndex::new(index.as_u64().unwrap() as usize)),
+fn process_index<'a, T: JsonLike + 'a, P: Track + 'a>(
+    json_path_index: &'a JsonPathIndex,
+    root: &'a T,
+    ctx: &Ctx<'a>,
+) -> PathInstance<'a, T, P> {
+    let index: PathInstance<'a, T, P> = match json_path_index {
+        JsonPathIndex::Single(index) => array_index(index),
         JsonPathIndex::Slice(s, e, step) => Box::new(ArraySlice::new(*s, *e, *step)),
         JsonPathIndex::UnionKeys(elems) => Box::new(UnionIndex::from_keys(elems)),
         JsonPathIndex::UnionIndex(elems) => Box::new(UnionIndex::from_indexes(elems)),
-        JsonPathIndex::Filter(fe) => Box::new(FilterPath::new(fe, root)),
+        JsonPathIndex::Union(elems) => Box::new(UnionIndex::from_mixed(elems, root, ctx)),
+        JsonPathIndex::Filter(fe) => return Box::new(Filter::new(fe, root, ctx)),
+    };
+    match (&ctx.rules.warnings, json_path_index) {
+        (
+            Some(warnings),
+            JsonPathIndex::Single(_) | JsonPathIndex::UnionIndex(_) | JsonPathIndex::Slice(..),
+        ) => Box::new(CheckedIndex::new(json_path_index, index, warnings.clone())),
+        _ => index,
+    }
+}
+
+/// Where the element checked by the filter is, gives `@index`, `@property` and `@path`
+#[derive(Debug)]
+pub(crate) struct Location<'p, P> {
+    /// the location of the filtered value
+    pub(crate) pref: &'p P,
+    pub(crate) step: Step<'p>,
+}
+
+impl<P> Clone for Location<'_, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for Location<'_, P> {}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Step<'p> {
+    /// the element of the filtered array at the position
+    Index(usize),
+    /// the field of the filtered object
+    Key(&'p str),
+    /// the filtered value itself like the object filtered as a whole
+    Whole,
+}
+
+impl<'p, P: Track> Location<'p, P> {
+    pub(crate) fn new(pref: &'p P, step: Step<'p>) -> Self {
+        Location { pref, step }
+    }
+
+    /// the position in the filtered array
+    fn index(&self) -> Option<usize> {
+        match self.step {
+            Step::Index(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// the key of the field or the position of the element,
+    /// the value filtered as a whole takes it from the last step of its path
+    fn property(&self) -> Option<Value> {
+        match self.step {
+            Step::Index(i) => Some(Value::from(i)),
+            Step::Key(key) => Some(Value::from(key)),
+            Step::Whole => match self.pref.last()? {
+                PathSegment::Key(key) => Some(Value::from(key)),
+                PathSegment::Index(i) => Some(Value::from(i)),
+            },
+        }
+    }
+
+    /// the location of the element itself
+    pub(crate) fn tracked(&self) -> P {
+        match self.step {
+            Step::Index(i) => self.pref.index(i),
+            Step::Key(key) => self.pref.key(key),
+            Step::Whole => self.pref.clone(),
+        }
+    }
+
+    /// the path of the element, `None` if the paths are not tracked like inside the operands
+    pub(crate) fn path(&self) -> Option<String> {
+        self.tracked().rendered()
+    }
+}
+
+/// The operand of the filter expression
+pub(crate) enum OperandPath<'a, T: JsonLike> {
+    /// the constant converted into the type of the document
+    Static(Cow<'a, T>),
+    /// the path evaluated from the current element, the paths of its elements are not tracked
+    Dynamic(PathInstance<'a, T, ()>),
+    /// the unbound parameter or context that has no values like the absent field
+    Unbound,
+    /// the metadata of the current element like `@index`, `@property` or `@path`
+    Meta(&'a Operand),
+}
+
+impl<'a, T: JsonLike> OperandPath<'a, T> {
+    /// the values of the operand for the current element
+    /// including the generated ones like the result of `length()`
+    pub(crate) fn values<'b, P: Track>(
+        &'b self,
+        curr_el: &'a T,
+        loc: Location<'_, P>,
+    ) -> Vec<Cow<'b, T>>
+    where
+        'a: 'b,
+    {
+        match self {
+            OperandPath::Static(v) => vec![Cow::Borrowed(v.as_ref())],
+            OperandPath::Dynamic(p) => p
+                .find(Slice(curr_el, ()))
+                .into_iter()
+                .filter_map(|v| match v {
+                    Slice(el, _) => Some(Cow::Borrowed(el)),
+                    NewValue(el) => Some(Cow::Owned(el)),
+                    NoValue => None,
+                })
+                .collect(),
+            OperandPath::Unbound => vec![],
+            OperandPath::Meta(op) => match op {
+                Operand::Index => loc.index().map(Value::from),
+                Operand::Property => loc.property(),
+                _ => loc.path().map(Value::from),
+            }
+            .map(|v| Cow::Owned(T::from_json(v)))
+            .into_iter()
+            .collect(),
+        }
     }
 }
 
 /// The method processes the operand inside the filter expressions
-fn process_operand<'a>(op: &'a Operand, root: &'a Value) -> PathInstance<'a> {
+fn process_operand<'a, T: JsonLike + 'a>(
+    op: &'a Operand,
+    root: &'a T,
+    ctx: &Ctx<'a>,
+) -> OperandPath<'a, T> {
     match op {
-        Operand::Static(v) => json_path_instance(&JsonPath::Root, v),
-        Operand::Dynamic(jp) => json_path_instance(jp, root),
+        Operand::Static(v) => OperandPath::Static(T::constant(v)),
+        Operand::Dynamic(jp) => OperandPath::Dynamic(compiled_instance(jp, root, &ctx.unchecked())),
+        Operand::Param(_) | Operand::Context(_) => OperandPath::Unbound,
+        Operand::Index | Operand::Property | Operand::Path => OperandPath::Meta(op),
     }
 }
@@ -0,0 +1,129 @@
+use crate::parser::model::{FilterExpression, JsonPath, JsonPathIndex};
+use crate::parser::visitor::{walk_filter_mut, walk_path_mut, VisitorMut};
+use crate::path::index::FilterPath;
+
+/// rewrites the expensive patterns of the path in place and returns the descriptions of the applied rewrites.
+///
+/// - the comparisons of two constants are evaluated and the logical operators depending on them are simplified,
+///   e.g. `@.a > 1 && 1 == 1` turns into `@.a > 1`. The result stays the same.
+/// - the wildcard descent followed by the filter `..*[?(...)]` turns into the descent filter `..[?(...)]`.
+///   The former visits the elements of every array twice: as the elements of the array and as the descendants.
+///   The rewritten query yields every matched element once and orders them by their parents.
+pub(crate) fn optimize(path: &mut JsonPath) -> Vec<String> {
+    let mut optimizer = Optimizer::default();
+    optimizer.visit_path_mut(path);
+    optimizer.rewrites
+}
+
+#[derive(Default)]
+struct Optimizer {
+    rewrites: Vec<String>,
+}
+
+impl VisitorMut for Optimizer {
+    fn visit_path_mut(&mut self, path: &mut JsonPath) {
+        if let JsonPath::Chain(elems) = path {
+            let mut i = 0;
+            while i + 1 < elems.len() {
+                if let [JsonPath::DescentW, JsonPath::Index(JsonPathIndex::Filter(_))] =
+                    &elems[i..i + 2]
+                {
+                    if let JsonPath::Index(index) = elems.remove(i + 1) {
+                        let before = format!("..*{index}");
+                        elems[i] = JsonPath::DescentIndex(index);
+                        self.rewrites.push(format!("`{before}` => `{}`", elems[i]));
+                    }
+                }
+                i += 1;
+            }
+        }
+        walk_path_mut(self, path)
+    }
+
+    fn visit_filter_mut(&mut self, expr: &mut FilterExpression) {
+        walk_filter_mut(self, expr);
+        let folded = match expr {
+            FilterExpression::And(left, right) => match (constant(left), constant(right)) {
+                (Some(false), _) | (_, Some(true)) => Some(left.as_ref().clone()),
+                (_, Some(false)) | (Some(true), _) => Some(right.as_ref().clone()),
+                _ => None,
+            },
+            FilterExpression::Or(left, right) => match (constant(left), constant(right)) {
+                (Some(true), _) | (_, Some(false)) => Some(left.as_ref().clone()),
+                (_, Some(true)) | (Some(false), _) => Some(right.as_ref().clone()),
+                _ => None,
+            },
+            FilterExpression::Xor(left, right) => match (constant(left), constant(right)) {
+                (_, Some(false)) => Some(left.as_ref().clone()),
+                (Some(false), _) => Some(right.as_ref().clone()),
+                (_, Some(true)) => Some(FilterExpression::Not(left.clone())),
+                (Some(true), _) => Some(FilterExpression::Not(right.clone())),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(folded) = folded {
+            self.rewrites.push(format!("`{expr}` => `{folded}`"));
+            *expr = folded;
+        }
+    }
+}
+
+/// the value of the expression if it does not depend on the element
+fn constant(expr: &FilterExpression) -> Option<bool> {
+    match expr {
+        FilterExpression::Atom(..) => FilterPath::eval_static(expr),
+        FilterExpression::Not(exp) => constant(exp).map(|v| !v),
+        FilterExpression::And(left, right) => Some(constant(left)? && constant(right)?),
+        FilterExpression::Or(left, right) => Some(constant(left)? || constant(right)?),
+        FilterExpression::Xor(left, right) => Some(constant(left)? != constant(right)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parser::parse_json_path;
+    use crate::path::optimize::optimize;
+
+    fn optimized(path: &str) -> (String, Vec<String>) {
+        let mut path = parse_json_path(path).expect("the path is correct");
+        let rewrites = optimize(&mut path);
+        (path.to_string(), rewrites)
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn fold_test() {
+        assert_eq!(
+            optimized("$.a[?(@.b > 1 && 1 == 1)]"),
+            (
+                "$['a'][?(@['b'] > 1)]".to_string(),
+                vec!["`@['b'] > 1 && 1 == 1` => `@['b'] > 1`".to_string()]
+            )
+        );
+        assert_eq!(
+            optimized("$.a[?(@.b || 'x' ~= 'x.*' || @.c)]").0,
+            "$['a'][?('x' ~= 'x.*')]"
+        );
+        assert_eq!(
+            optimized("$.a[?(@.b && !(1 > 2) && (2 < 1 || @.c))]").0,
+            "$['a'][?(@['b'] && @['c'])]"
+        );
+        assert_eq!(
+            optimized("$.a[?(@.b > 1 && @.c)]"),
+            ("$['a'][?(@['b'] > 1 && @['c'])]".to_string(), vec![])
+        );
+    }
+
+    #[test]
+    fn descent_wildcard_test() {
+        assert_eq!(
+            optimized("$..*[?(@.isbn)].title"),
+            (
+                "$..[?(@['isbn'])]['title']".to_string(),
+                vec!["`..*[?(@['isbn'])]` => `..[?(@['isbn'])]`".to_string()]
+            )
+        );
+        assert!(optimized("$..*.title").1.is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+use crate::parser::model::{FilterSign, JsonPathIndex};
+use crate::path::compare::{decides_eq, decides_less};
+use crate::path::{JsonLike, Location, Path, PathInstance, Rules, Scalar, Track};
+use crate::JsonPathValue;
+use crate::JsonPathValue::{NewValue, NoValue, Slice};
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use thiserror::Error;
+
+/// The problem found by the strict evaluation.
+/// The regular evaluation treats these cases as the absence of the match.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum EvalWarningKind {
+    #[error("the index `{index}` is applied to {found} but the array is expected")]
+    IndexNonArray { index: String, found: &'static str },
+    #[error("the operator `{sign}` compares {left} with {right}")]
+    TypeMismatch {
+        sign: FilterSign,
+        left: &'static str,
+        right: &'static str,
+    },
+    #[error("the regex is applied to {found} but the string is expected")]
+    RegexNonString { found: &'static str },
+}
+
+/// The warning of the strict evaluation along with the path of the element where it happened
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvalWarning {
+    pub path: String,
+    pub kind: EvalWarningKind,
+}
+
+impl Display for EvalWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl std::error::Error for EvalWarning {}
+
+/// The index, the union of the indexes or the slice reporting the elements it is applied to
+/// that are not arrays (or strings for the slice) before processing them
+pub(crate) struct CheckedIndex<'a, T, P = String> {
+    index: &'a JsonPathIndex,
+    inner: PathInstance<'a, T, P>,
+    warnings: Rc<RefCell<Vec<EvalWarning>>>,
+}
+
+impl<'a, T: JsonLike, P: Track> CheckedIndex<'a, T, P> {
+    pub(crate) fn new(
+        index: &'a JsonPathIndex,
+        inner: PathInstance<'a, T, P>,
+        warnings: Rc<RefCell<Vec<EvalWarning>>>,
+    ) -> Self {
+        CheckedIndex {
+            index,
+            inner,
+            warnings,
+        }
+    }
+
+    fn check(&self, input: &JsonPathValue<'a, T, P>) {
+        let (data, path) = match input {
+            Slice(data, pref) => (*data, pref.rendered().unwrap_or_default()),
+            NewValue(data) => (data, String::from("$")),
+            NoValue => return,
+        };
+        let applicable = data.as_array().is_some()
+            || (data.as_str().is_some() && matches!(self.index, JsonPathIndex::Slice(..)));
+        if !applicable {
+            self.warnings.borrow_mut().push(EvalWarning {
+                path,
+                kind: EvalWarningKind::IndexNonArray {
+                    index: self.index.to_string(),
+                    found: type_name(Scalar::of(data)),
+                },
+            })
+        }
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for CheckedIndex<'a, T, P> {
+    type Data = T;
+
+    fn find(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        self.check(&input);
+        self.inner.find(input)
+    }
+
+    fn find_into(
+        &self,
+        input: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        self.check(&input);
+        self.inner.find_into(input, out)
+    }
+
+    fn selects_from(&self, input: &JsonPathValue<'a, Self::Data, P>) -> bool {
+        self.inner.selects_from(input)
+    }
+}
+
+/// reports the elements of the different types compared by the filter at the given location
+/// unless the comparator or the coercion of the rules decides how to compare them
+pub(crate) fn check_atom<T: JsonLike>(
+    sign: &FilterSign,
+    left: &[&T],
+    right: &[&T],
+    loc: Location<'_, impl Track>,
+    rules: &Rules,
+) {
+    let Some(warnings) = &rules.warnings else {
+        return;
+    };
+    let kind = match (sign, left, right) {
+        (FilterSign::Regex, [l], _) if l.as_str().is_none() => {
+            Some(EvalWarningKind::RegexNonString {
+                found: type_name(Scalar::of(*l)),
+            })
+        }
+        (
+            FilterSign::Less | FilterSign::Greater | FilterSign::LeOrEq | FilterSign::GrOrEq,
+            [l],
+            [r],
+        ) => {
+            let (l, r) = (Scalar::of(*l), Scalar::of(*r));
+            let comparable = matches!(
+                (l, r),
+                (Scalar::Number(_), Scalar::Number(_)) | (Scalar::String(_), Scalar::String(_))
+            );
+            (!comparable).then(|| mismatch(sign, l, r))
+        }
+        (FilterSign::Equal | FilterSign::Unequal, [l], [r]) => {
+            let (l, r) = (Scalar::of(*l), Scalar::of(*r));
+            let comparable = l == Scalar::Null
+                || r == Scalar::Null
+                || std::mem::discriminant(&l) == std::mem::discriminant(&r);
+            (!comparable).then(|| mismatch(sign, l, r))
+        }
+        _ => None,
+    };
+    let decided = |l: &T, r: &T| match sign {
+        FilterSign::Equal | FilterSign::Unequal => decides_eq(l, r, rules),
+        FilterSign::Greater | FilterSign::GrOrEq => decides_less(r, l, rules),
+        _ => decides_less(l, r, rules),
+    };
+    match kind {
+        Some(EvalWarningKind::TypeMismatch { .. }) if decided(left[0], right[0]) => (),
+        Some(kind) => warnings.borrow_mut().push(EvalWarning {
+            path: loc.path().unwrap_or_default(),
+            kind,
+        }),
+        None => (),
+    }
+}
+
+fn mismatch(sign: &FilterSign, left: Scalar, right: Scalar) -> EvalWarningKind {
+    EvalWarningKind::TypeMismatch {
+        sign: sign.clone(),
+        left: type_name(left),
+        right: type_name(right),
+    }
+}
+
+fn type_name(data: Scalar) -> &'static str {
+    match data {
+        Scalar::Null => "null",
+        Scalar::Bool(_) => "boolean",
+        Scalar::Number(_) => "number",
+        Scalar::String(_) => "string",
+        Scalar::Array => "array",
+        Scalar::Object => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::model::FilterSign;
+    use crate::path::strict::{EvalWarning, EvalWarningKind};
+    use crate::{Coercion, EvalOptions, JsonPathInst};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn warnings(path: &str, json: serde_json::Value) -> Vec<EvalWarning> {
+        let path = JsonPathInst::from_str(path).expect("the path is correct");
+        path.warnings(&json)
+    }
+
+    #[test]
+    fn strict_mismatch_test() {
+        let json = json!({"orders":[{"id":"1","total":"12"},{"id":2,"total":5}]});
+        assert_eq!(
+            warnings("$.orders[?(@.total > 10)]", json.clone()),
+            vec![EvalWarning {
+                path: "$.['orders'][0]".to_string(),
+                kind: EvalWarningKind::TypeMismatch {
+                    sign: FilterSign::Greater,
+                    left: "string",
+                    right: "number"
+                }
+            }]
+        );
+        assert_eq!(
+            warnings("$.orders[?(@.id == 1 || @.id ~= '2')]", json),
+            vec![
+                EvalWarning {
+                    path: "$.['orders'][0]".to_string(),
+                    kind: EvalWarningKind::TypeMismatch {
+                        sign: FilterSign::Equal,
+                        left: "string",
+                        right: "number"
+                    }
+                },
+                EvalWarning {
+                    path: "$.['orders'][1]".to_string(),
+                    kind: EvalWarningKind::RegexNonString { found: "number" }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_index_test() {
+        let json = json!({"name":"abc","items":{"a":1}});
+        assert_eq!(
+            warnings("$.items[0]", json.clone()),
+            vec![EvalWarning {
+                path: "$.['items']".to_string(),
+                kind: EvalWarningKind::IndexNonArray {
+                    index: "[0]".to_string(),
+                    found: "object"
+                }
+            }]
+        );
+        assert!(warnings("$.name[0:2]", json.clone()).is_empty());
+        assert_eq!(
+            warnings("$.name[1]", json)[0].to_string(),
+            "$.['name']: the index `[1]` is applied to string but the array is expected"
+        );
+    }
+
+    #[test]
+    fn strict_coercion_test() {
+        let json = json!({"orders":[{"total":"12"},{"total":5}]});
+        let path =
+            JsonPathInst::from_str("$.orders[?(@.total > 10)]").expect("the path is correct");
+        let strict = EvalOptions::default().with_strict(true);
+        assert!(path
+            .clone()
+            .with_options(strict.clone())
+            .query(&json)
+            .is_err());
+
+        let lenient = path.with_options(strict.with_coercion(Coercion::Lenient));
+        assert!(lenient.warnings(&json).is_empty());
+        assert_eq!(lenient.query(&json).map(|m| m.len()), Ok(1));
+    }
+}
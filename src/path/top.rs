@@ -1,35 +1,48 @@
 use crate::parser::model::*;
-use crate::path::{json_path_instance, JsonPathValue, Path, PathInstance};
+use crate::path::{
+    compiled_instance, length_of, spend, Ctx, JsonLike, JsonPathValue, Path, PathInstance,
+    RegexCapture, StringLength, Track,
+};
 use crate::JsonPathValue::{NewValue, NoValue, Slice};
-use crate::{jsp_idx, jsp_obj, JsPathStr};
 use serde_json::value::Value::{Array, Object};
 use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::marker::PhantomData;
 
 /// to process the element [*]
-pub(crate) struct Wildcard {}
+pub(crate) struct Wildcard<T> {
+    _t: PhantomData<T>,
+}
 
-impl<'a> Path<'a> for Wildcard {
-    type Data = Value;
+impl<T> Wildcard<T> {
+    pub(crate) fn new() -> Self {
+        Wildcard { _t: PhantomData }
+    }
+}
 
-    fn find(&self, data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
-        data.flat_map_slice(|data, pref| {
-            let res = match data {
-                Array(elems) => {
-                    let mut res = vec![];
-                    for (idx, el) in elems.iter().enumerate() {
-                        res.push(Slice(el, jsp_idx(&pref, idx)));
-                    }
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for Wildcard<T> {
+    type Data = T;
 
-                    res
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        data.flat_map_slice(|data, pref| {
+            let res = if let Some(elems) = data.as_array() {
+                let mut res = vec![];
+                for (idx, el) in elems.iter().enumerate() {
+                    res.push(Slice(el, pref.index(idx)));
                 }
-                Object(elems) => {
-                    let mut res = vec![];
-                    for (key, el) in elems.into_iter() {
-                        res.push(Slice(el, jsp_obj(&pref, key)));
-                    }
-                    res
+
+                res
+            } else if let Some(elems) = data.entries() {
+                let mut res = vec![];
+                for (key, el) in elems {
+                    res.push(Slice(el, pref.key(key)));
                 }
-                _ => vec![],
+                res
+            } else {
+                vec![]
             };
             if res.is_empty() {
                 vec![NoValue]
@@ -41,22 +54,53 @@ impl<'a> Path<'a> for Wildcard {
 }
 
 /// empty path. Returns incoming data.
-pub(crate) struct IdentityPath {}
+pub(crate) struct IdentityPath<T> {
+    _t: PhantomData<T>,
+}
 
-impl<'a> Path<'a> for IdentityPath {
-    type Data = Value;
+impl<T> IdentityPath<T> {
+    pub(crate) fn new() -> Self {
+        IdentityPath { _t: PhantomData }
+    }
+}
 
-    fn find(&self, data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for IdentityPath<T> {
+    type Data = T;
+
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         vec![data]
     }
+
+    fn find_into(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        out.push(data)
+    }
+}
+
+/// finds nothing, e.g. the index that does not fit into any array
+pub(crate) struct EmptyPath<T> {
+    _t: PhantomData<T>,
 }
 
-pub(crate) struct EmptyPath {}
+impl<T> EmptyPath<T> {
+    pub(crate) fn new() -> Self {
+        EmptyPath { _t: PhantomData }
+    }
+}
 
-impl<'a> Path<'a> for EmptyPath {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for EmptyPath<T> {
+    type Data = T;
 
-    fn find(&self, _data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    fn find(
+        &self,
+        _data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         vec![]
     }
 }
@@ -72,49 +116,82 @@ impl<'a, T> RootPointer<'a, T> {
     }
 }
 
-impl<'a> Path<'a> for RootPointer<'a, Value> {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for RootPointer<'a, T> {
+    type Data = T;
 
-    fn find(&self, _data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
-        vec![JsonPathValue::from_root(self.root)]
+    fn find(
+        &self,
+        _data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        vec![Slice(self.root, P::root())]
+    }
+
+    fn find_into(
+        &self,
+        _data: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        out.push(Slice(self.root, P::root()))
     }
 }
 
 /// process object fields like ['key'] or .key
-pub(crate) struct ObjectField<'a> {
+pub(crate) struct ObjectField<'a, T> {
     key: &'a str,
+    /// the key containing `*` like `.cpu_*` is the glob pattern
+    glob: bool,
+    _t: PhantomData<T>,
 }
 
-impl<'a> ObjectField<'a> {
-    pub(crate) fn new(key: &'a str) -> ObjectField<'a> {
-        ObjectField { key }
+impl<'a, T> ObjectField<'a, T> {
+    pub(crate) fn new(key: &'a str) -> ObjectField<'a, T> {
+        ObjectField {
+            key,
+            glob: false,
+            _t: PhantomData,
+        }
+    }
+
+    pub(crate) fn glob(pattern: &'a str) -> ObjectField<'a, T> {
+        ObjectField {
+            key: pattern,
+            glob: true,
+            _t: PhantomData,
+        }
     }
 }
 
-impl<'a> Clone for ObjectField<'a> {
+impl<'a, T> Clone for ObjectField<'a, T> {
     fn clone(&self) -> Self {
-        ObjectField::new(self.key)
+        ObjectField {
+            key: self.key,
+            glob: self.glob,
+            _t: PhantomData,
+        }
     }
 }
 
-impl<'a> Path<'a> for FnPath {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for FnPath<T> {
+    type Data = T;
 
     fn flat_find(
         &self,
-        input: Vec<JsonPathValue<'a, Self::Data>>,
+        input: Vec<JsonPathValue<'a, Self::Data, P>>,
         is_search_length: bool,
-    ) -> Vec<JsonPathValue<'a, Self::Data>> {
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         // todo rewrite
         if JsonPathValue::only_no_value(&input) {
             return vec![NoValue];
         }
         let res = if is_search_length {
-            NewValue(json!(input.iter().filter(|v| v.has_value()).count()))
+            NewValue(T::from_json(json!(input
+                .iter()
+                .filter(|v| v.has_value())
+                .count())))
         } else {
-            let take_len = |v: &Value| match v {
-                Array(elems) => NewValue(json!(elems.len())),
-                _ => NoValue,
+            let take_len = |v: &T| match length_of(v, &self.string_length) {
+                Some(len) => NewValue(T::from_json(json!(len))),
+                None => NoValue,
             };
 
             match input.first() {
@@ -134,103 +211,441 @@ impl<'a> Path<'a> for FnPath {
     }
 }
 
-pub(crate) enum FnPath {
-    Size,
+/// the function `length()` counting the strings as the given string length tells
+pub(crate) struct FnPath<T> {
+    string_length: StringLength,
+    _t: PhantomData<T>,
+}
+
+impl<T> FnPath<T> {
+    pub(crate) fn size(string_length: StringLength) -> Self {
+        FnPath {
+            string_length,
+            _t: PhantomData,
+        }
+    }
+}
+
+/// the aggregations of the numbers like `min()`
+pub(crate) struct AggregatePath<T> {
+    aggregate: Aggregate,
+    _t: PhantomData<T>,
+}
+
+impl<T> AggregatePath<T> {
+    pub(crate) fn new(aggregate: Aggregate) -> Self {
+        AggregatePath {
+            aggregate,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for AggregatePath<T> {
+    type Data = T;
+
+    fn flat_find(
+        &self,
+        input: Vec<JsonPathValue<'a, Self::Data, P>>,
+        _is_search_length: bool,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        let found: Vec<&T> = input
+            .iter()
+            .filter_map(|v| match v {
+                Slice(v, _) => Some(*v),
+                NewValue(v) => Some(v),
+                NoValue => None,
+            })
+            .collect();
+        // the only array gives its elements
+        let numbers: Vec<f64> = match found.as_slice() {
+            [single] => match single.as_array() {
+                Some(elems) => elems.iter().filter_map(JsonLike::as_f64).collect(),
+                None => single.as_f64().into_iter().collect(),
+            },
+            found => found.iter().filter_map(|v| v.as_f64()).collect(),
+        };
+        if numbers.is_empty() {
+            return vec![NoValue];
+        }
+        let count = numbers.len() as f64;
+        let sum: f64 = numbers.iter().sum();
+        let res = match self.aggregate {
+            Aggregate::Min => numbers.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregate::Max => numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregate::Avg => sum / count,
+            Aggregate::Stddev => {
+                let avg = sum / count;
+                (numbers.iter().map(|n| (n - avg) * (n - avg)).sum::<f64>() / count).sqrt()
+            }
+            Aggregate::Sum => sum,
+        };
+        vec![NewValue(T::from_json(json!(res)))]
+    }
+
+    fn needs_all(&self) -> bool {
+        true
+    }
+}
+
+/// the function `keys()` giving the keys of the objects
+pub(crate) struct KeysPath<T> {
+    _t: PhantomData<T>,
+}
+
+impl<T> KeysPath<T> {
+    pub(crate) fn new() -> Self {
+        KeysPath { _t: PhantomData }
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for KeysPath<T> {
+    type Data = T;
+
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        let keys = match &data {
+            Slice(v, _) => v
+                .entries()
+                .map(|e| e.map(|(k, _)| Value::from(k)).collect()),
+            NewValue(v) => v
+                .entries()
+                .map(|e| e.map(|(k, _)| Value::from(k)).collect()),
+            NoValue => None,
+        };
+        match keys {
+            Some(keys) => vec![NewValue(T::from_json(Value::Array(keys)))],
+            None => vec![NoValue],
+        }
+    }
+}
+
+/// the function `extract('regex', group)` giving the captured group of the strings
+pub(crate) struct ExtractPath<T> {
+    capture: Option<RegexCapture>,
+    group: usize,
+    _t: PhantomData<T>,
+}
+
+impl<T> ExtractPath<T> {
+    pub(crate) fn new(capture: Option<RegexCapture>, group: usize) -> Self {
+        ExtractPath {
+            capture,
+            group,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for ExtractPath<T> {
+    type Data = T;
+
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        let text = match &data {
+            Slice(v, _) => v.as_str(),
+            NewValue(v) => v.as_str(),
+            NoValue => None,
+        };
+        let captured = text
+            .zip(self.capture.as_ref())
+            .and_then(|(text, capture)| capture(text, self.group));
+        match captured {
+            Some(c) => vec![NewValue(T::from_json(Value::from(c)))],
+            None => vec![NoValue],
+        }
+    }
+}
+
+/// the fallback like `?? 30` replacing the empty result of the preceding path
+pub(crate) struct DefaultPath<'a, T: JsonLike> {
+    value: Cow<'a, T>,
+}
+
+impl<'a, T: JsonLike> DefaultPath<'a, T> {
+    pub(crate) fn new(value: &'a Value) -> Self {
+        DefaultPath {
+            value: T::constant(value),
+        }
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for DefaultPath<'a, T> {
+    type Data = T;
+
+    fn flat_find(
+        &self,
+        input: Vec<JsonPathValue<'a, Self::Data, P>>,
+        _is_search_length: bool,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        if input.iter().any(JsonPathValue::has_value) {
+            input
+        } else {
+            vec![NewValue(self.value.as_ref().clone())]
+        }
+    }
+
+    fn needs_all(&self) -> bool {
+        true
+    }
+}
+
+/// process the type selectors like @string()
+pub(crate) struct TypeSelector<'a, T> {
+    tp: &'a JsonType,
+    _t: PhantomData<T>,
+}
+
+impl<'a, T: JsonLike> TypeSelector<'a, T> {
+    pub(crate) fn new(tp: &'a JsonType) -> Self {
+        TypeSelector {
+            tp,
+            _t: PhantomData,
+        }
+    }
+
+    fn matches(&self, v: &T) -> bool {
+        let is_array = v.as_array().is_some();
+        let is_object = v.entries().is_some();
+        match self.tp {
+            JsonType::Null => v.is_null(),
+            JsonType::Boolean => v.as_bool().is_some(),
+            JsonType::Number => v.as_f64().is_some(),
+            JsonType::Integer => v.is_integer(),
+            JsonType::String => v.as_str().is_some(),
+            JsonType::Array => is_array,
+            JsonType::Object => is_object,
+            JsonType::Scalar => !is_array && !is_object,
+        }
+    }
 }
 
-impl<'a> Path<'a> for ObjectField<'a> {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for TypeSelector<'a, T> {
+    type Data = T;
 
-    fn find(&self, data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
-        let take_field = |v: &'a Value| match v {
-            Object(fields) => fields.get(self.key),
-            _ => None,
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        let is_matched = match &data {
+            Slice(v, _) => self.matches(v),
+            NewValue(v) => self.matches(v),
+            NoValue => false,
         };
+        if is_matched {
+            vec![data]
+        } else {
+            vec![NoValue]
+        }
+    }
+}
+
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for ObjectField<'a, T> {
+    type Data = T;
+
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        let mut res = vec![];
+        self.find_into(data, &mut res);
+        res
+    }
+
+    fn find_into(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+        out: &mut Vec<JsonPathValue<'a, Self::Data, P>>,
+    ) {
+        let take_field = |v: &'a T| v.get(self.key);
+
+        if self.glob {
+            return out.extend(self.find_by_pattern(data));
+        }
 
         let res = match data {
             Slice(js, p) => take_field(js)
-                .map(|v| JsonPathValue::new_slice(v, jsp_obj(&p, self.key)))
+                .map(|v| Slice(v, p.key(self.key)))
                 .unwrap_or_else(|| NoValue),
             _ => NoValue,
         };
-        vec![res]
+        out.push(res)
     }
 }
+
+impl<'a, T: JsonLike> ObjectField<'a, T> {
+    /// the glob pattern matches all the suitable fields
+    fn find_by_pattern<P: Track>(
+        &self,
+        data: JsonPathValue<'a, T, P>,
+    ) -> Vec<JsonPathValue<'a, T, P>> {
+        let res: Vec<JsonPathValue<'a, T, P>> = match data {
+            Slice(v, p) => match v.entries() {
+                Some(fields) => fields
+                    .filter(|(k, _)| glob_match(self.key, k))
+                    .map(|(k, v)| Slice(v, p.key(k)))
+                    .collect(),
+                None => vec![],
+            },
+            _ => vec![],
+        };
+        if res.is_empty() {
+            vec![NoValue]
+        } else {
+            res
+        }
+    }
+}
+
+/// splits the glob pattern into the chars and the wildcards (`None`),
+/// `\*` and `\\` stand for the star and the backslash themselves
+pub(crate) fn glob_parts(pattern: &str) -> Vec<Option<char>> {
+    let mut parts = vec![];
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        parts.push(match c {
+            '*' => None,
+            '\\' => Some(chars.next_if(|n| matches!(n, '*' | '\\')).unwrap_or(c)),
+            _ => Some(c),
+        });
+    }
+    parts
+}
+
+/// matches the key against the pattern where `*` stands for any sequence of chars
+pub(crate) fn glob_match(pattern: &str, key: &str) -> bool {
+    let pattern = glob_parts(pattern);
+    let key: Vec<char> = key.chars().collect();
+    let (mut p, mut k) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while k < key.len() {
+        if p < pattern.len() && pattern[p].is_none() {
+            backtrack = Some((p, k));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == Some(key[k]) {
+            p += 1;
+            k += 1;
+        } else if let Some((star_p, star_k)) = backtrack {
+            p = star_p + 1;
+            k = star_k + 1;
+            backtrack = Some((star_p, star_k + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(Option::is_none)
+}
 /// the top method of the processing ..*
-pub(crate) struct DescentWildcard;
+pub(crate) struct DescentWildcard<T> {
+    _t: PhantomData<T>,
+}
+
+impl<T> DescentWildcard<T> {
+    pub(crate) fn new() -> Self {
+        DescentWildcard { _t: PhantomData }
+    }
+}
 
-impl<'a> Path<'a> for DescentWildcard {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for DescentWildcard<T> {
+    type Data = T;
 
-    fn find(&self, data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         data.map_slice(deep_flatten)
     }
 }
 
-// todo rewrite to tail rec
-fn deep_flatten(data: &Value, pref: JsPathStr) -> Vec<(&Value, JsPathStr)> {
+/// collects all the descendants of the element in the depth-first order.
+/// The traversal uses the explicit stack, therefore the deep documents do not overflow the call stack.
+pub(crate) fn deep_flatten<T: JsonLike, P: Track>(data: &T, pref: P) -> Vec<(&T, P)> {
     let mut acc = vec![];
-    match data {
-        Object(elems) => {
-            for (f, v) in elems.into_iter() {
-                let pref = jsp_obj(&pref, f);
-                acc.push((v, pref.clone()));
-                acc.append(&mut deep_flatten(v, pref));
-            }
+    let mut stack = vec![];
+    push_children(data, &pref, &mut stack);
+    while let Some((v, pref)) = stack.pop() {
+        if !spend(1) {
+            break;
         }
-        Array(elems) => {
-            for (i, v) in elems.iter().enumerate() {
-                let pref = jsp_idx(&pref, i);
-                acc.push((v, pref.clone()));
-                acc.append(&mut deep_flatten(v, pref));
-            }
-        }
-        _ => (),
+        push_children(v, &pref, &mut stack);
+        acc.push((v, pref));
     }
     acc
 }
 
-// todo rewrite to tail rec
-fn deep_path_by_key<'a>(
-    data: &'a Value,
-    key: ObjectField<'a>,
-    pref: JsPathStr,
-) -> Vec<(&'a Value, JsPathStr)> {
-    let mut result: Vec<(&'a Value, JsPathStr)> =
-        JsonPathValue::vec_as_pair(key.find(JsonPathValue::new_slice(data, pref.clone())));
-    match data {
-        Object(elems) => {
-            let mut next_levels: Vec<(&'a Value, JsPathStr)> = elems
-                .into_iter()
-                .flat_map(|(k, v)| deep_path_by_key(v, key.clone(), jsp_obj(&pref, k)))
-                .collect();
-            result.append(&mut next_levels);
-            result
+/// pushes the children in the reversed order to pop them in the direct order
+fn push_children<'a, T: JsonLike, P: Track>(data: &'a T, pref: &P, stack: &mut Vec<(&'a T, P)>) {
+    if let Some(elems) = data.as_array() {
+        for (i, v) in elems.iter().enumerate().rev() {
+            stack.push((v, pref.index(i)));
         }
-        Array(elems) => {
-            let mut next_levels: Vec<(&'a Value, JsPathStr)> = elems
-                .iter()
-                .enumerate()
-                .flat_map(|(i, v)| deep_path_by_key(v, key.clone(), jsp_idx(&pref, i)))
-                .collect();
-            result.append(&mut next_levels);
-            result
+    } else if let Some(elems) = data.entries() {
+        for (f, v) in elems.rev() {
+            stack.push((v, pref.key(f)));
         }
-        _ => result,
     }
 }
 
+/// checks if the element has the descendants deeper than the given depth.
+/// The direct children of the element have the depth 1.
+pub(crate) fn exceeds_depth(data: &Value, max_depth: usize) -> bool {
+    let mut stack = vec![(data, 0)];
+    while let Some((v, depth)) = stack.pop() {
+        let children: Box<dyn Iterator<Item = &Value>> = match v {
+            Object(elems) => Box::new(elems.values()),
+            Array(elems) => Box::new(elems.iter()),
+            _ => continue,
+        };
+        for child in children {
+            if depth + 1 > max_depth {
+                return true;
+            }
+            stack.push((child, depth + 1));
+        }
+    }
+    false
+}
+
+fn deep_path_by_key<'a, T: JsonLike, P: Track>(
+    data: &'a T,
+    key: ObjectField<'a, T>,
+    pref: P,
+) -> Vec<(&'a T, P)> {
+    let mut nodes = vec![(data, pref.clone())];
+    nodes.append(&mut deep_flatten(data, pref));
+    nodes
+        .into_iter()
+        .flat_map(|(v, pref)| JsonPathValue::vec_as_pair(key.find(Slice(v, pref))))
+        .collect()
+}
+
 /// processes decent object like ..
-pub(crate) struct DescentObject<'a> {
+pub(crate) struct DescentObject<'a, T> {
     key: &'a str,
+    /// the key containing `*` like `..cpu_*` is the glob pattern
+    glob: bool,
+    _t: PhantomData<T>,
 }
 
-impl<'a> Path<'a> for DescentObject<'a> {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for DescentObject<'a, T> {
+    type Data = T;
 
-    fn find(&self, data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
         data.flat_map_slice(|data, pref| {
-            let res_col = deep_path_by_key(data, ObjectField::new(self.key), pref.clone());
+            let field = if self.glob {
+                ObjectField::glob(self.key)
+            } else {
+                ObjectField::new(self.key)
+            };
+            let res_col = deep_path_by_key(data, field, pref.clone());
             if res_col.is_empty() {
                 vec![NoValue]
             } else {
@@ -240,28 +655,58 @@ impl<'a> Path<'a> for DescentObject<'a> {
     }
 }
 
-impl<'a> DescentObject<'a> {
+impl<'a, T> DescentObject<'a, T> {
     pub fn new(key: &'a str) -> Self {
-        DescentObject { key }
+        DescentObject {
+            key,
+            glob: false,
+            _t: PhantomData,
+        }
+    }
+
+    pub fn glob(pattern: &'a str) -> Self {
+        DescentObject {
+            key: pattern,
+            glob: true,
+            _t: PhantomData,
+        }
     }
 }
 
 /// the top method of the processing representing the chain of other operators
-pub(crate) struct Chain<'a> {
-    chain: Vec<PathInstance<'a>>,
+pub(crate) struct Chain<'a, T = Value, P = String> {
+    chain: Vec<PathInstance<'a, T, P>>,
     is_search_length: bool,
 }
 
-impl<'a> Chain<'a> {
-    pub fn new(chain: Vec<PathInstance<'a>>, is_search_length: bool) -> Self {
+impl<'a, T: JsonLike, P: Track + 'a> Chain<'a, T, P> {
+    pub fn new(chain: Vec<PathInstance<'a, T, P>>, is_search_length: bool) -> Self {
         Chain {
             chain,
             is_search_length,
         }
     }
-    pub fn from(chain: &'a [JsonPath], root: &'a Value) -> Self {
+    pub fn from(chain: &'a [JsonPath], root: &'a T, ctx: &Ctx<'a>) -> Self {
+        Chain::new(
+            chain
+                .iter()
+                .map(|p| compiled_instance(p, root, ctx))
+                .collect(),
+            Chain::is_search_length(chain),
+        )
+    }
+}
+
+impl Chain<'_> {
+    /// checks if the length function of the chain counts the found elements
+    pub fn is_search_length(chain: &[JsonPath]) -> bool {
+        // the fallback does not change what the length counts
+        let chain = match chain.split_last() {
+            Some((JsonPath::Fn(Function::Default(_)), init)) => init,
+            _ => chain,
+        };
         let chain_len = chain.len();
-        let is_search_length = if chain_len > 2 {
+        if chain_len > 2 {
             let mut res = false;
             // if the result of the slice expected to be a slice, union or filter -
             // length should return length of resulted array
@@ -275,6 +720,8 @@ impl<'a> Chain<'a> {
                             (
                                 JsonPath::Index(JsonPathIndex::UnionIndex(_))
                                 | JsonPath::Index(JsonPathIndex::UnionKeys(_))
+                                | JsonPath::Index(JsonPathIndex::Union(_))
+                                | JsonPath::DescentIndex(_)
                                 | JsonPath::Index(JsonPathIndex::Slice(_, _, _))
                                 | JsonPath::Index(JsonPathIndex::Filter(_))
                                 | JsonPath::Wildcard,
@@ -296,37 +743,78 @@ impl<'a> Chain<'a> {
             res
         } else {
             false
-        };
-
-        Chain::new(
-            chain.iter().map(|p| json_path_instance(p, root)).collect(),
-            is_search_length,
-        )
+        }
     }
 }
 
-impl<'a> Path<'a> for Chain<'a> {
-    type Data = Value;
+impl<'a, T: JsonLike, P: Track> Chain<'a, T, P> {
+    /// finds the elements like `find` and tells if they have run out at the step selecting them,
+    /// like the filter or the slice, that is applicable to the elements given to it but selects none of them.
+    /// Otherwise, the step the elements have run out at does not fit the data, like the absent field.
+    pub(crate) fn find_selecting(
+        &self,
+        data: JsonPathValue<'a, T, P>,
+    ) -> (Vec<JsonPathValue<'a, T, P>>, bool) {
+        let mut selecting = None;
+        let res = self.run(data, Some(&mut selecting));
+        (res, selecting.unwrap_or(false))
+    }
 
-    fn find(&self, data: JsonPathValue<'a, Self::Data>) -> Vec<JsonPathValue<'a, Self::Data>> {
+    /// goes through the steps, the step the elements have run out at is checked if the selection is asked for
+    fn run(
+        &self,
+        data: JsonPathValue<'a, T, P>,
+        mut selecting: Option<&mut Option<bool>>,
+    ) -> Vec<JsonPathValue<'a, T, P>> {
         let mut res = vec![data];
+        // the buffers are swapped on every step to reuse the allocated memory
+        let mut next = vec![];
 
         for inst in self.chain.iter() {
+            let selects = match selecting.as_deref() {
+                Some(None) => res.iter().any(|v| inst.selects_from(v)),
+                _ => false,
+            };
             if inst.needs_all() {
                 res = inst.flat_find(res, self.is_search_length)
             } else {
-                res = res.into_iter().flat_map(|d| inst.find(d)).collect()
+                for d in res.drain(..) {
+                    inst.find_into(d, &mut next)
+                }
+                std::mem::swap(&mut res, &mut next);
+            }
+            if let Some(selecting @ None) = selecting.as_deref_mut() {
+                if !res.iter().any(|v| v.has_value()) {
+                    *selecting = Some(selects);
+                }
+            }
+            if !spend(res.len()) {
+                break;
             }
         }
         res
     }
 }
 
+impl<'a, T: JsonLike, P: Track> Path<'a, P> for Chain<'a, T, P> {
+    type Data = T;
+
+    fn find(
+        &self,
+        data: JsonPathValue<'a, Self::Data, P>,
+    ) -> Vec<JsonPathValue<'a, Self::Data, P>> {
+        self.run(data, None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parser::model::{JsonPath, JsonPathIndex};
-    use crate::path::top::{deep_flatten, json_path_instance, Function, ObjectField, RootPointer};
-    use crate::path::{JsonPathValue, Path};
+    use crate::path::top::{
+        deep_flatten, exceeds_depth, glob_match, Function, ObjectField, RootPointer,
+    };
+    use crate::path::{json_path_instance, JsonPathValue, Path};
+    use crate::tests::Nested;
     use crate::JsonPathValue::NoValue;
     use crate::{chain, function, idx, jp_v, path};
     use serde_json::json;
@@ -350,6 +838,22 @@ mod tests {
         assert_eq!(field.find(res_income), vec![NoValue]);
     }
 
+    #[test]
+    fn glob_match_test() {
+        assert!(glob_match("cpu_*", "cpu_user"));
+        assert!(glob_match("cpu_*", "cpu_"));
+        assert!(glob_match("*_total", "requests_total"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("error*", "error*"));
+        // the escaped star and backslash stand for themselves
+        assert!(glob_match(r"a\**", "a*b"));
+        assert!(!glob_match(r"a\**", "ab"));
+        assert!(glob_match(r"a\\*", r"a\b"));
+        assert!(glob_match(r"a\b*", r"a\bc"));
+        assert!(!glob_match("cpu_*", "mem_user"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+    }
+
     #[test]
     fn root_test() {
         let res_income = json!({"product": {"key":42}});
@@ -453,6 +957,17 @@ mod tests {
         let expected_res = jp_v!(&one;"$[0].['a']",);
         assert_eq!(path_inst.find(jp_v!(&json)), expected_res)
     }
+    #[test]
+    fn deep_nesting_test() {
+        let nested = Nested::new(json!(1), 100_000);
+        let value = &nested.0;
+        assert_eq!(deep_flatten(value, ()).len(), 100_000);
+        assert!(exceeds_depth(value, 99_999));
+        assert!(!exceeds_depth(value, 100_000));
+        assert!(!exceeds_depth(&json!({"a":[1]}), 2));
+        assert!(exceeds_depth(&json!({"a":[1]}), 1));
+    }
+
     #[test]
     fn deep_path_test() {
         let value = json!([1]);
@@ -0,0 +1,292 @@
+use crate::{jsp_idx, jsp_obj, to_path, PathSegment};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// The location of the found element the evaluation keeps track of.
+/// Every step of the path derives the locations of the elements it finds from the location of its input.
+///
+/// - [`String`] renders the path of the element like `$.['store'].['book'][0]` at every step.
+/// - [`PathNode`] links the location to the location of its parent,
+///   so the found elements share the locations of their common ancestors and the paths are rendered on demand.
+/// - `()` does not track the paths at all, the evaluation does not spend the time building them.
+pub trait Track: Clone + Debug {
+    /// the location of the root of the document
+    fn root() -> Self;
+    /// the location of the element of the array at the given position
+    fn index(&self, idx: usize) -> Self;
+    /// the location of the field of the object
+    fn key(&self, key: &str) -> Self;
+    /// the path of the location like `$.['store'].['book'][0]`, `None` if the paths are not tracked
+    fn rendered(&self) -> Option<String>;
+    /// the last step of the location, `None` for the root or if the paths are not tracked
+    fn last(&self) -> Option<PathSegment>;
+    /// the steps from the root to the location, `None` if the paths are not tracked
+    fn to_segments(&self) -> Option<Vec<PathSegment>>;
+}
+
+impl Track for String {
+    fn root() -> Self {
+        String::from("$")
+    }
+
+    fn index(&self, idx: usize) -> Self {
+        jsp_idx(self, idx)
+    }
+
+    fn key(&self, key: &str) -> Self {
+        jsp_obj(self, key)
+    }
+
+    fn rendered(&self) -> Option<String> {
+        Some(self.clone())
+    }
+
+    fn last(&self) -> Option<PathSegment> {
+        if let Some(key) = self.strip_suffix("']") {
+            key.rfind(".['")
+                .map(|i| PathSegment::Key(key[i + 3..].to_string()))
+        } else {
+            let idx = self.strip_suffix(']')?;
+            idx[idx.rfind('[')? + 1..]
+                .parse::<usize>()
+                .ok()
+                .map(PathSegment::Index)
+        }
+    }
+
+    /// the rendered path is split back into the steps,
+    /// `None` if the string is not a path generated by the library
+    fn to_segments(&self) -> Option<Vec<PathSegment>> {
+        let mut segments = parse_segments_rev(self.strip_prefix('$').unwrap_or(self))?;
+        segments.reverse();
+        Some(segments)
+    }
+}
+
+/// parses the segments in the reversed order trying every possible end of the key,
+/// therefore the keys containing `']` are recognized too
+fn parse_segments_rev(path: &str) -> Option<Vec<PathSegment>> {
+    if path.is_empty() {
+        Some(vec![])
+    } else if let Some(tail) = path.strip_prefix(".['") {
+        tail.match_indices("']").find_map(|(end, _)| {
+            parse_segments_rev(&tail[end + 2..]).map(|mut segments| {
+                segments.push(PathSegment::Key(tail[..end].to_string()));
+                segments
+            })
+        })
+    } else if let Some(tail) = path.strip_prefix('[') {
+        let end = tail.find(']')?;
+        let idx = tail[..end].parse().ok()?;
+        parse_segments_rev(&tail[end + 1..]).map(|mut segments| {
+            segments.push(PathSegment::Index(idx));
+            segments
+        })
+    } else {
+        None
+    }
+}
+
+/// The paths are not tracked
+impl Track for () {
+    fn root() -> Self {}
+
+    fn index(&self, _idx: usize) -> Self {}
+
+    fn key(&self, _key: &str) -> Self {}
+
+    fn rendered(&self) -> Option<String> {
+        None
+    }
+
+    fn last(&self) -> Option<PathSegment> {
+        None
+    }
+
+    fn to_segments(&self) -> Option<Vec<PathSegment>> {
+        None
+    }
+}
+
+/// The location of the found element linked to the location of its parent.
+/// The siblings share the location of the parent instead of copying its path,
+/// the path is rendered only when it is asked for by [`Display`] or [`PathNode::segments`].
+#[derive(Clone, Default)]
+pub struct PathNode(Option<Arc<(PathNode, PathSegment)>>);
+
+impl PathNode {
+    fn child(&self, segment: PathSegment) -> Self {
+        PathNode(Some(Arc::new((self.clone(), segment))))
+    }
+
+    /// the location of the parent, `None` for the root
+    pub fn parent(&self) -> Option<&PathNode> {
+        self.0.as_ref().map(|node| &node.0)
+    }
+
+    /// the last step of the location, `None` for the root
+    pub fn segment(&self) -> Option<&PathSegment> {
+        self.0.as_ref().map(|node| &node.1)
+    }
+
+    /// the steps from the root to the location
+    pub fn segments(&self) -> Vec<PathSegment> {
+        let mut segments = vec![];
+        let mut node = self;
+        while let Some(segment) = node.segment() {
+            segments.push(segment.clone());
+            node = node
+                .parent()
+                .expect("the node with the segment has the parent");
+        }
+        segments.reverse();
+        segments
+    }
+
+    /// the location of the given one as if its root were this location
+    pub(crate) fn join(&self, other: &PathNode) -> PathNode {
+        other
+            .segments()
+            .into_iter()
+            .fold(self.clone(), |node, segment| node.child(segment))
+    }
+
+    /// the steps from the location up to the root
+    fn ancestors(&self) -> impl Iterator<Item = &PathSegment> {
+        let mut node = Some(self);
+        std::iter::from_fn(move || {
+            let segment = node?.segment();
+            node = node?.parent();
+            segment
+        })
+    }
+}
+
+impl Track for PathNode {
+    fn root() -> Self {
+        PathNode(None)
+    }
+
+    fn index(&self, idx: usize) -> Self {
+        self.child(PathSegment::Index(idx))
+    }
+
+    fn key(&self, key: &str) -> Self {
+        self.child(PathSegment::Key(key.to_string()))
+    }
+
+    fn rendered(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+
+    fn last(&self) -> Option<PathSegment> {
+        self.segment().cloned()
+    }
+
+    fn to_segments(&self) -> Option<Vec<PathSegment>> {
+        Some(self.segments())
+    }
+}
+
+/// renders the path like `$.['store'].['book'][0]`
+impl Display for PathNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&to_path(&self.segments()))
+    }
+}
+
+impl Debug for PathNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PathNode").field(&self.to_string()).finish()
+    }
+}
+
+/// The locations are compared by their steps, the shared parents are not compared twice.
+impl PartialEq for PathNode {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut left, mut right) = (self, other);
+        loop {
+            match (&left.0, &right.0) {
+                (None, None) => return true,
+                (Some(l), Some(r)) if Arc::ptr_eq(l, r) => return true,
+                (Some(l), Some(r)) if l.1 == r.1 => (left, right) = (&l.0, &r.0),
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl Eq for PathNode {}
+
+impl Hash for PathNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ancestors().for_each(|segment| segment.hash(state))
+    }
+}
+
+/// The long chains of the deep documents are released one by one instead of recursively.
+impl Drop for PathNode {
+    fn drop(&mut self) {
+        let mut next = self.0.take();
+        while let Some(node) = next {
+            next = match Arc::try_unwrap(node) {
+                Ok((mut parent, _)) => parent.0.take(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::{PathNode, Track};
+    use crate::PathSegment;
+
+    #[test]
+    fn string_track_test() {
+        let path = String::root().key("store").index(1);
+        assert_eq!(path, "$.['store'][1]");
+        assert_eq!(path.last(), Some(PathSegment::Index(1)));
+        assert_eq!(
+            path.key("a b").last(),
+            Some(PathSegment::Key("a b".to_string()))
+        );
+        assert_eq!(String::root().last(), None);
+        assert_eq!(
+            "$.['a']'][0]".to_string().to_segments(),
+            Some(vec![
+                PathSegment::Key("a']".to_string()),
+                PathSegment::Index(0)
+            ])
+        );
+        assert_eq!("$.abc".to_string().to_segments(), None);
+    }
+
+    #[test]
+    fn path_node_test() {
+        let book = PathNode::root().key("store").key("book");
+        let first = book.index(0);
+        let second = book.index(1);
+        assert_eq!(first.to_string(), "$.['store'].['book'][0]");
+        assert_eq!(
+            second.segments(),
+            vec![
+                PathSegment::Key("store".to_string()),
+                PathSegment::Key("book".to_string()),
+                PathSegment::Index(1)
+            ]
+        );
+        assert_eq!(first.parent(), second.parent());
+        assert_ne!(first, second);
+        assert_eq!(first, PathNode::root().key("store").key("book").index(0));
+        assert_eq!(PathNode::root().to_string(), "$");
+        assert_eq!(book.join(&PathNode::root().index(1)), second);
+
+        let mut deep = PathNode::root();
+        for i in 0..200_000 {
+            deep = deep.index(i % 2);
+        }
+        assert_eq!(deep.last(), Some(PathSegment::Index(1)));
+    }
+}
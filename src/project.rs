@@ -0,0 +1,123 @@
+//! The projection of every element found by the query into a new object.
+//!
+//! The fields of the object are found by the subqueries relative to the element,
+//! like selecting the columns of every row.
+//!
+//! ```
+//! use jsonpath_rust::Projection;
+//! use serde_json::json;
+//!
+//! let data = json!({"store":{"book":[
+//!     {"title":"Sayings of the Century","price":8.95},
+//!     {"title":"Moby Dick","price":8.99,"tags":["sea","whale"]}
+//! ]}});
+//! let projection = Projection::new("$..book[*]")
+//!     .unwrap()
+//!     .field("t", "@.title")
+//!     .unwrap()
+//!     .field("p", "@.price")
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     projection.apply(&data),
+//!     vec![
+//!         json!({"t":"Sayings of the Century","p":8.95}),
+//!         json!({"t":"Moby Dick","p":8.99}),
+//!     ]
+//! );
+//! ```
+use crate::JsonPathInst;
+use serde_json::{Map, Value};
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// The base query along with the subqueries building the fields of the objects
+#[derive(Debug, Clone)]
+pub struct Projection {
+    base: JsonPathInst,
+    fields: Vec<(String, JsonPathInst)>,
+}
+
+impl Projection {
+    /// starts the projection of the elements found by the base query
+    pub fn new(base: &str) -> Result<Self, String> {
+        Ok(Projection {
+            base: JsonPathInst::from_str(base)?,
+            fields: vec![],
+        })
+    }
+
+    /// adds the field found by the subquery evaluated on every element found by the base query.
+    /// The subquery starts from the element either with `@` or with `$`.
+    /// The singular subquery (see [`JsonPathInst::is_singular`]) gives the element itself or `null` if it is absent,
+    /// the other ones give the array of the found elements.
+    pub fn field(mut self, name: &str, subquery: &str) -> Result<Self, String> {
+        self.fields
+            .push((name.to_string(), JsonPathInst::from_str(subquery)?));
+        Ok(self)
+    }
+
+    /// builds the object for every element found by the base query in the order they are found
+    pub fn apply(&self, json: &Value) -> Vec<Value> {
+        self.base
+            .find_slice(json)
+            .iter()
+            .map(|row| {
+                let fields: Map<String, Value> = self
+                    .fields
+                    .iter()
+                    .map(|(name, sub)| (name.clone(), project(sub, row)))
+                    .collect();
+                Value::Object(fields)
+            })
+            .collect()
+    }
+}
+
+fn project(subquery: &JsonPathInst, row: &Value) -> Value {
+    let mut found = subquery
+        .find_slice(row)
+        .into_iter()
+        .map(|v| v.deref().clone());
+    if subquery.is_singular() {
+        found.next().unwrap_or(Value::Null)
+    } else {
+        Value::Array(found.collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Projection;
+    use serde_json::json;
+
+    #[test]
+    fn projection_test() {
+        let data = json!({"orders":[
+            {"id":1,"items":[{"sku":"a"},{"sku":"b"}],"customer":{"name":"x"}},
+            {"id":2,"items":[]}
+        ]});
+        let projection = Projection::new("$.orders[*]")
+            .unwrap()
+            .field("id", "$.id")
+            .unwrap()
+            .field("skus", "@.items[*].sku")
+            .unwrap()
+            .field("customer", "@.customer.name")
+            .unwrap()
+            .field("count", "@.items.length()")
+            .unwrap();
+        assert_eq!(
+            projection.apply(&data),
+            vec![
+                json!({"id":1,"skus":["a","b"],"customer":"x","count":2}),
+                json!({"id":2,"skus":[],"customer":null,"count":0}),
+            ]
+        );
+        assert!(Projection::new("$.orders[*]")
+            .unwrap()
+            .field("id", "@.[")
+            .is_err());
+        assert!(Projection::new("$.x[*]").unwrap().apply(&data).is_empty());
+    }
+}
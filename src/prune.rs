@@ -0,0 +1,355 @@
+//! The split of the document into the branches found by the query and the rest of it.
+//!
+//! ```
+//! use jsonpath_rust::{partition, JsonPathInst};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let data = json!({"user":{"name":"ann","password":"x"},"items":[{"id":1,"token":"t"},{"id":2}]});
+//! let path = JsonPathInst::from_str("$..['password','token']").unwrap();
+//!
+//! let (restricted, public) = partition(&path, &data);
+//! assert_eq!(restricted, json!({"user":{"password":"x"},"items":[{"token":"t"}]}));
+//! assert_eq!(public, json!({"user":{"name":"ann"},"items":[{"id":1},{"id":2}]}));
+//! ```
+use crate::{find_nodes, JsonPathInst, PathSegment};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// The found paths merged into the tree
+#[derive(Debug, Default)]
+struct Found {
+    /// the element is found as a whole
+    whole: bool,
+    children: HashMap<PathSegment, Found>,
+}
+
+impl Found {
+    fn new(path: &JsonPathInst, json: &Value) -> Self {
+        Found::union(&[path], json)
+    }
+
+    /// merges the elements found by all the queries, the element found several times is kept once
+    fn union(paths: &[&JsonPathInst], json: &Value) -> Self {
+        let mut found = Found::default();
+        for path in paths {
+            for (_, node) in find_nodes(path, json) {
+                found.insert(node.segments());
+            }
+        }
+        found
+    }
+
+    fn insert(&mut self, segments: Vec<PathSegment>) {
+        let node = segments
+            .into_iter()
+            .fold(self, |node, s| node.children.entry(s).or_default());
+        node.whole = true;
+    }
+
+    /// splits the element into the found branches and the rest of it, the absent side is `None`
+    fn split(&self, json: &Value) -> (Option<Value>, Option<Value>) {
+        if self.whole {
+            return (Some(json.clone()), None);
+        }
+        if self.children.is_empty() {
+            return (None, Some(json.clone()));
+        }
+        match json {
+            Value::Object(fields) => {
+                let (mut found, mut rest) = (Map::new(), Map::new());
+                for (k, v) in fields {
+                    let (f, r) = self.child(PathSegment::Key(k.clone()), v);
+                    found.extend(f.map(|f| (k.clone(), f)));
+                    rest.extend(r.map(|r| (k.clone(), r)));
+                }
+                (Some(Value::Object(found)), Some(Value::Object(rest)))
+            }
+            Value::Array(elems) => {
+                let (mut found, mut rest) = (vec![], vec![]);
+                for (i, v) in elems.iter().enumerate() {
+                    let (f, r) = self.child(PathSegment::Index(i), v);
+                    found.extend(f);
+                    rest.extend(r);
+                }
+                (Some(Value::Array(found)), Some(Value::Array(rest)))
+            }
+            _ => (None, Some(json.clone())),
+        }
+    }
+
+    /// keeps only the found branches of the element like the first side of [`Found::split`]
+    fn keep(&self, json: &Value) -> Option<Value> {
+        if self.whole {
+            return Some(json.clone());
+        }
+        if self.children.is_empty() {
+            return None;
+        }
+        match json {
+            Value::Object(fields) => Some(Value::Object(
+                fields
+                    .iter()
+                    .filter_map(|(k, v)| {
+                        let child = self.children.get(&PathSegment::Key(k.clone()))?;
+                        Some((k.clone(), child.keep(v)?))
+                    })
+                    .collect(),
+            )),
+            Value::Array(elems) => Some(Value::Array(
+                elems
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| self.children.get(&PathSegment::Index(i))?.keep(v))
+                    .collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// removes the found branches of the element like the second side of [`Found::split`]
+    fn remove(&self, json: &Value) -> Option<Value> {
+        if self.whole {
+            return None;
+        }
+        let rest = |segment: PathSegment, v: &Value| match self.children.get(&segment) {
+            Some(child) => child.remove(v),
+            None => Some(v.clone()),
+        };
+        match json {
+            Value::Object(fields) if !self.children.is_empty() => Some(Value::Object(
+                fields
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.clone(), rest(PathSegment::Key(k.clone()), v)?)))
+                    .collect(),
+            )),
+            Value::Array(elems) if !self.children.is_empty() => Some(Value::Array(
+                elems
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| rest(PathSegment::Index(i), v))
+                    .collect(),
+            )),
+            _ => Some(json.clone()),
+        }
+    }
+
+    fn child(&self, segment: PathSegment, json: &Value) -> (Option<Value>, Option<Value>) {
+        match self.children.get(&segment) {
+            Some(child) => child.split(json),
+            None => (None, Some(json.clone())),
+        }
+    }
+}
+
+/// the container of the same type without the elements or `null` for the scalars
+fn empty_like(json: &Value) -> Value {
+    match json {
+        Value::Object(_) => Value::Object(Map::new()),
+        Value::Array(_) => Value::Array(vec![]),
+        _ => Value::Null,
+    }
+}
+
+/// splits the document in one pass into the found branches and the rest of it.
+///
+/// The first document keeps the found elements along with their ancestors
+/// and the second one keeps everything else, both preserve the nesting of the original document.
+/// The arrays keep the elements in their order without the gaps left by the elements going to the other side,
+/// the containers emptied by the query stay in the rest of the document.
+/// If nothing is found the first document is the empty container like the root (`null` for the scalar root),
+/// if the root itself is found the rest is `null`.
+/// The generated elements (like the length) have no path and are skipped.
+pub fn partition(path: &JsonPathInst, json: &Value) -> (Value, Value) {
+    let (found, rest) = Found::new(path, json).split(json);
+    (
+        found.unwrap_or_else(|| empty_like(json)),
+        rest.unwrap_or(Value::Null),
+    )
+}
+
+/// copies the document pruned to the found elements along with their ancestors,
+/// like the first document of [`partition`] without building the rest of it.
+///
+/// ```
+/// use jsonpath_rust::{select_subtree, JsonPathInst};
+/// use serde_json::json;
+/// use std::str::FromStr;
+///
+/// let data = json!({"store":{"book":[{"title":"a","price":8},{"title":"b"}],"bicycle":{"price":19}}});
+/// let path = JsonPathInst::from_str("$.store.book[*].title").unwrap();
+///
+/// assert_eq!(
+///     select_subtree(&path, &data),
+///     json!({"store":{"book":[{"title":"a"},{"title":"b"}]}})
+/// );
+/// ```
+pub fn select_subtree(path: &JsonPathInst, json: &Value) -> Value {
+    Found::new(path, json)
+        .keep(json)
+        .unwrap_or_else(|| empty_like(json))
+}
+
+/// copies the document pruned to the elements found by any of the queries along with their ancestors,
+/// like [`select_subtree`] for the union of the queries.
+/// The element found several times or inside another found element is kept once
+/// and the elements of the arrays keep their order in the document regardless of the order of the queries.
+///
+/// ```
+/// use jsonpath_rust::{select_subtrees, JsonPathInst};
+/// use serde_json::json;
+/// use std::str::FromStr;
+///
+/// let data = json!({"items":[{"id":1,"name":"a","secret":1},{"id":2,"name":"b"}],"total":2});
+/// let ids = JsonPathInst::from_str("$.items[*].id").unwrap();
+/// let first = JsonPathInst::from_str("$.items[0].name").unwrap();
+/// let total = JsonPathInst::from_str("$.total").unwrap();
+///
+/// assert_eq!(
+///     select_subtrees(&[&ids, &first, &total], &data),
+///     json!({"items":[{"id":1,"name":"a"},{"id":2}],"total":2})
+/// );
+/// ```
+pub fn select_subtrees(paths: &[&JsonPathInst], json: &Value) -> Value {
+    Found::union(paths, json)
+        .keep(json)
+        .unwrap_or_else(|| empty_like(json))
+}
+
+/// copies the document without the found elements, the complement of [`select_subtree`]
+/// like the second document of [`partition`] without building the first one.
+/// If the root itself is found the result is `null`.
+///
+/// ```
+/// use jsonpath_rust::{except, JsonPathInst};
+/// use serde_json::json;
+/// use std::str::FromStr;
+///
+/// let data = json!({"user":{"name":"ann","password":"x"},"sessions":[{"id":1,"token":"t"}]});
+/// let path = JsonPathInst::from_str("$..['password','token']").unwrap();
+///
+/// assert_eq!(
+///     except(&path, &data),
+///     json!({"user":{"name":"ann"},"sessions":[{"id":1}]})
+/// );
+/// ```
+pub fn except(path: &JsonPathInst, json: &Value) -> Value {
+    Found::new(path, json).remove(json).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{except, partition, select_subtree, select_subtrees, JsonPathInst};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn split(path: &str, json: &serde_json::Value) -> (serde_json::Value, serde_json::Value) {
+        let path = JsonPathInst::from_str(path).expect("the path is correct");
+        partition(&path, json)
+    }
+
+    #[test]
+    fn partition_test() {
+        let json = json!({"a":{"b":[1,{"c":2,"d":3},4],"e":"x"},"f":[]});
+        assert_eq!(
+            split("$.a.b[1].c", &json),
+            (
+                json!({"a":{"b":[{"c":2}]}}),
+                json!({"a":{"b":[1,{"d":3},4],"e":"x"},"f":[]})
+            )
+        );
+        assert_eq!(
+            split("$.a.b[0,2]", &json),
+            (
+                json!({"a":{"b":[1,4]}}),
+                json!({"a":{"b":[{"c":2,"d":3}],"e":"x"},"f":[]})
+            )
+        );
+        // the found element containing another found one goes as a whole
+        assert_eq!(split("$..*", &json), (json.clone(), json!({})));
+        assert_eq!(
+            split("$.a.*", &json),
+            (
+                json!({"a":{"b":[1,{"c":2,"d":3},4],"e":"x"}}),
+                json!({"a":{},"f":[]})
+            )
+        );
+        assert_eq!(split("$.x", &json), (json!({}), json.clone()));
+        assert_eq!(split("$", &json), (json.clone(), json!(null)));
+        assert_eq!(split("$.a.b.length()", &json), (json!({}), json.clone()));
+    }
+
+    #[test]
+    fn select_subtree_test() {
+        let json = json!({"a":{"b":[1,{"c":2,"d":3},4],"e":"x"},"f":[]});
+        for q in [
+            "$.a.b[1].c",
+            "$.a.b[0,2]",
+            "$..*",
+            "$.a.*",
+            "$.x",
+            "$",
+            "$..[?(@ > 1)]",
+        ] {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            assert_eq!(
+                select_subtree(&path, &json),
+                partition(&path, &json).0,
+                "{q}"
+            );
+        }
+        let path = JsonPathInst::from_str("$..d").expect("the path is correct");
+        assert_eq!(select_subtree(&path, &json), json!({"a":{"b":[{"d":3}]}}));
+    }
+
+    #[test]
+    fn except_test() {
+        let json = json!({"a":{"b":[1,{"c":2,"d":3},4],"e":"x"},"f":[]});
+        for q in [
+            "$.a.b[1].c",
+            "$.a.b[0,2]",
+            "$..*",
+            "$.a.*",
+            "$.x",
+            "$",
+            "$..[?(@ > 1)]",
+        ] {
+            let path = JsonPathInst::from_str(q).expect("the path is correct");
+            assert_eq!(except(&path, &json), partition(&path, &json).1, "{q}");
+        }
+        let path = JsonPathInst::from_str("$..['c','e']").expect("the path is correct");
+        assert_eq!(
+            except(&path, &json),
+            json!({"a":{"b":[1,{"d":3},4]},"f":[]})
+        );
+    }
+
+    #[test]
+    fn select_subtrees_test() {
+        let json = json!({"a":[{"b":1,"c":2},{"b":3},{"c":4}],"d":{"e":5}});
+        let paths = |qs: &[&str]| -> Vec<JsonPathInst> {
+            qs.iter()
+                .map(|q| JsonPathInst::from_str(q).expect("the path is correct"))
+                .collect()
+        };
+        let select = |qs: &[&str]| {
+            let paths = paths(qs);
+            select_subtrees(&paths.iter().collect::<Vec<_>>(), &json)
+        };
+        assert_eq!(
+            select(&["$.a[2].c", "$.a[0].b", "$.a[0].c"]),
+            json!({"a":[{"b":1,"c":2},{"c":4}]})
+        );
+        assert_eq!(
+            select(&["$.a[*].b", "$.a[1]", "$..e"]),
+            json!({"a":[{"b":1},{"b":3}],"d":{"e":5}})
+        );
+        assert_eq!(
+            select(&["$.a[0]", "$.a[0].b", "$.a[0]"]),
+            json!({"a":[{"b":1,"c":2}]})
+        );
+        assert_eq!(select(&["$.x"]), json!({}));
+        assert_eq!(select(&[]), json!({}));
+        assert_eq!(select(&["$.d", "$"]), json);
+    }
+}
@@ -0,0 +1,105 @@
+//! The lazy evaluation over the raw json text.
+//!
+//! The leading fields and indexes of the query like `$.metadata.id` or `$.items[0]` are resolved
+//! by skimming the text of the document: only the containers on the way are scanned
+//! and the skipped branches are never turned into [`Value`].
+//! The rest of the query is evaluated on the parsed subtree.
+//!
+//! ```
+//! use jsonpath_rust::JsonPathInst;
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let raw = r#"{"metadata":{"id":7,"labels":{"a":"b"}},"payload":[1,2,3]}"#;
+//! let path = JsonPathInst::from_str("$.metadata.id").unwrap();
+//! assert_eq!(path.find_raw(raw).unwrap(), vec![json!(7)]);
+//! ```
+use crate::extract::{LazyQuery, LazyStep};
+use crate::JsonPathInst;
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::collections::HashMap;
+
+impl JsonPathInst {
+    /// finds the elements in the raw json text parsing only the branches the query needs.
+    /// The leading fields and indexes of the query are resolved without parsing the skipped branches,
+    /// the subtree they lead to is parsed and the rest of the query is evaluated on it.
+    /// The queries referring to the root `$` inside the filters after the leading part parse the whole document.
+    ///
+    /// Returns the error if the text is not a valid json.
+    pub fn find_raw(&self, json: &str) -> Result<Vec<Value>, serde_json::Error> {
+        let raw: &RawValue = serde_json::from_str(json)?;
+        self.find_raw_value(raw)
+    }
+
+    /// finds the elements in the raw json value parsing only the branches the query needs,
+    /// see [`JsonPathInst::find_raw`].
+    pub fn find_raw_value(&self, json: &RawValue) -> Result<Vec<Value>, serde_json::Error> {
+        let query = LazyQuery::new(self.path());
+        let mut current = json;
+        for step in query.steps.iter() {
+            match select(step, current)? {
+                Some(next) => current = next,
+                None => return Ok(vec![]),
+            }
+        }
+        Ok(query.eval_rest(&serde_json::from_str(current.get())?))
+    }
+}
+
+/// the raw value of the field or the element, the containers of the other type yield nothing
+fn select<'r>(
+    step: &LazyStep,
+    raw: &'r RawValue,
+) -> Result<Option<&'r RawValue>, serde_json::Error> {
+    let text = raw.get().trim_start();
+    match step {
+        LazyStep::Field(key) if text.starts_with('{') => {
+            let mut fields: HashMap<String, &RawValue> = serde_json::from_str(text)?;
+            Ok(fields.remove(*key))
+        }
+        LazyStep::Index(idx) if text.starts_with('[') => {
+            let elems: Vec<&RawValue> = serde_json::from_str(text)?;
+            Ok(elems.get(*idx).copied())
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonPathInst;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn find_raw(path: &str, raw: &str) -> Vec<serde_json::Value> {
+        JsonPathInst::from_str(path).unwrap().find_raw(raw).unwrap()
+    }
+
+    #[test]
+    fn lazy_test() {
+        let raw = r#"{"meta":{"id":1,"tags":["a","b"]},"items":[{"v":1},{"v":5}]}"#;
+        assert_eq!(find_raw("$.meta.id", raw), vec![json!(1)]);
+        assert_eq!(find_raw("$.meta.tags[1]", raw), vec![json!("b")]);
+        assert_eq!(find_raw("$.items[?(@.v > 2)].v", raw), vec![json!(5)]);
+        assert_eq!(find_raw("$.meta.tags.length()", raw), vec![json!(2)]);
+        assert_eq!(
+            find_raw("$.items[?(@.v == $.meta.id)]", raw),
+            vec![json!({"v":1})]
+        );
+        assert!(find_raw("$.meta.absent", raw).is_empty());
+        assert!(find_raw("$.items.id", raw).is_empty());
+        assert!(find_raw("$.meta[0]", raw).is_empty());
+        assert_eq!(find_raw("$", "[1]"), vec![json!([1])]);
+    }
+
+    #[test]
+    fn skipped_branches_test() {
+        let raw = r#"{"skipped":[1,2,{"x":"A"}],"meta":{"id":"a\"b"}}"#;
+        assert_eq!(find_raw("$.meta.id", raw), vec![json!("a\"b")]);
+        assert!(JsonPathInst::from_str("$.a")
+            .unwrap()
+            .find_raw("{\"a\":")
+            .is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! The registry of the compiled queries kept under their names.
+//!
+//! The services usually define their queries in the configuration and evaluate them by the names.
+//! The registry is shared between the threads, the definitions are reloaded at once
+//! while the evaluations started before keep using the queries they have taken.
+//!
+//! ```
+//! use jsonpath_rust::QueryRegistry;
+//! use serde_json::json;
+//!
+//! let registry = QueryRegistry::new();
+//! registry
+//!     .reload_json(&json!({"titles": "$..title", "cheap": "$..book[?(@.price < 10)].title"}))
+//!     .unwrap();
+//!
+//! let data = json!({"book":[{"title":"a","price":5},{"title":"b","price":15}]});
+//! assert_eq!(registry.find("cheap", &data), Some(json!(["a"])));
+//! assert_eq!(registry.find("absent", &data), None);
+//! ```
+use crate::{find, JsonPathInst};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The thread-safe registry of the compiled queries keyed by their names.
+#[derive(Debug, Default)]
+pub struct QueryRegistry {
+    queries: RwLock<HashMap<String, Arc<JsonPathInst>>>,
+}
+
+impl QueryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// compiles the query and keeps it under the name replacing the previous one
+    pub fn register(&self, name: &str, query: &str) -> Result<(), String> {
+        let path = Arc::new(compile(name, query)?);
+        self.write().insert(name.to_string(), path);
+        Ok(())
+    }
+
+    /// removes the query returning it if it has been registered
+    pub fn remove(&self, name: &str) -> Option<Arc<JsonPathInst>> {
+        self.write().remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<JsonPathInst>> {
+        self.read().get(name).cloned()
+    }
+
+    /// the names of the registered queries in the alphabetical order
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// replaces all the queries with the given definitions of the names and the queries.
+    /// The definitions are compiled before the replacement,
+    /// therefore the registry stays as it is if any of them fails to parse.
+    pub fn reload<N, Q>(&self, definitions: impl IntoIterator<Item = (N, Q)>) -> Result<(), String>
+    where
+        N: AsRef<str>,
+        Q: AsRef<str>,
+    {
+        let queries = definitions
+            .into_iter()
+            .map(|(name, query)| {
+                let name = name.as_ref();
+                Ok((name.to_string(), Arc::new(compile(name, query.as_ref())?)))
+            })
+            .collect::<Result<HashMap<_, _>, String>>()?;
+        *self.write() = queries;
+        Ok(())
+    }
+
+    /// replaces all the queries with the ones defined by the json object like `{"titles": "$..title"}`,
+    /// see [`QueryRegistry::reload`]
+    pub fn reload_json(&self, definitions: &Value) -> Result<(), String> {
+        let definitions = definitions
+            .as_object()
+            .ok_or("the definitions of the queries are not an object")?
+            .iter()
+            .map(|(name, query)| match query.as_str() {
+                Some(query) => Ok((name.as_str(), query)),
+                None => Err(format!("the query `{name}` is not a string")),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        self.reload(definitions)
+    }
+
+    /// evaluates the query by its name like [`find`] does, `None` if there is no such query
+    pub fn find(&self, name: &str, json: &Value) -> Option<Value> {
+        // the lock is released before the evaluation so the reloads are not blocked by it
+        let path = self.get(name)?;
+        Some(find(&path, json))
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<String, Arc<JsonPathInst>>> {
+        // the map stays consistent even if a thread panicked while holding the lock
+        self.queries.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<String, Arc<JsonPathInst>>> {
+        self.queries.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn compile(name: &str, query: &str) -> Result<JsonPathInst, String> {
+    JsonPathInst::from_str(query).map_err(|e| format!("the query `{name}` is incorrect: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::QueryRegistry;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn registry_test() {
+        let registry = QueryRegistry::new();
+        assert!(registry.is_empty());
+        registry.register("ids", "$[*].id").unwrap();
+        registry.register("names", "$[*].name").unwrap();
+        assert_eq!(registry.names(), vec!["ids", "names"]);
+
+        let data = json!([{"id":1,"name":"a"},{"id":2}]);
+        assert_eq!(registry.find("ids", &data), Some(json!([1, 2])));
+        let ids = registry.get("ids").unwrap();
+
+        assert!(registry.register("broken", "$.[").is_err());
+        assert!(registry
+            .reload([("ids", "$[0].id"), ("broken", "$.[")])
+            .unwrap_err()
+            .starts_with("the query `broken` is incorrect"));
+        assert_eq!(registry.len(), 2);
+
+        registry.reload([("ids", "$[0].id")]).unwrap();
+        assert_eq!(registry.names(), vec!["ids"]);
+        assert_eq!(registry.find("ids", &data), Some(json!([1])));
+        assert_eq!(ids.find_slice(&data).len(), 2);
+
+        assert_eq!(
+            registry.reload_json(&json!({"ids": 1})),
+            Err("the query `ids` is not a string".to_string())
+        );
+        assert!(registry.reload_json(&json!(["$.a"])).is_err());
+        assert!(registry.remove("ids").is_some());
+        assert_eq!(registry.find("ids", &data), None);
+    }
+
+    #[test]
+    fn shared_registry_test() {
+        let registry = Arc::new(QueryRegistry::new());
+        registry.register("q", "$.a").unwrap();
+        let readers: Vec<_> = (0..4)
+            .map(|i| {
+                let registry = registry.clone();
+                std::thread::spawn(move || {
+                    let found = registry.find("q", &json!({"a": i, "b": i}));
+                    registry.register(&format!("q{i}"), "$.b").unwrap();
+                    found
+                })
+            })
+            .collect();
+        for (i, reader) in readers.into_iter().enumerate() {
+            assert_eq!(reader.join().unwrap(), Some(json!([i])));
+        }
+        assert_eq!(registry.len(), 5);
+    }
+}
@@ -0,0 +1,1084 @@
+//! The validation of the matched elements against the json schema.
+//!
+//! The validator supports the keywords describing the structure of the data:
+//! `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! `minItems`, `maxItems`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`,
+//! `minLength`, `maxLength`, `pattern`, `allOf`, `anyOf`, `oneOf`, `not`
+//! and `$ref` pointing inside the schema like `#/$defs/item`.
+//! The reference leading back to itself without going into the value, like `{"$ref":"#"}`, is the error.
+//! The other keywords are ignored.
+//!
+//! ```
+//! use jsonpath_rust::schema::validate_at;
+//! use jsonpath_rust::JsonPathInst;
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let data = json!({"users":[{"name":"a","age":3},{"name":1}]});
+//! let schema = json!({"type":"object","required":["name","age"],"properties":{"name":{"type":"string"}}});
+//! let path = JsonPathInst::from_str("$.users[*]").unwrap();
+//!
+//! assert_eq!(
+//!     validate_at(&path, &data, &schema),
+//!     vec![(
+//!         "$.['users'][1]".to_string(),
+//!         vec![
+//!             "the required field `age` is absent".to_string(),
+//!             "/name: the type is not string".to_string()
+//!         ]
+//!     )]
+//! );
+//! ```
+//!
+//! The schemas registered by [`register_schema`] are available in the filters
+//! by the predicate `schema(@, 'name')` holding if the element conforms to the schema.
+//! The unknown schema matches nothing.
+//!
+//! ```
+//! use jsonpath_rust::schema::register_schema;
+//! use jsonpath_rust::{find, JsonPathInst};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! register_schema("event_v2", json!({"required":["id","version"],"properties":{"version":{"const":2}}}));
+//! let data = json!({"log":{"events":[{"id":1,"version":1},{"id":2,"version":2}]}});
+//! let path = JsonPathInst::from_str("$..events[?(schema(@, 'event_v2'))].id").unwrap();
+//!
+//! assert_eq!(find(&path, &data), json!([2]));
+//! ```
+use crate::parser::model::{FilterExpression, FilterSign, JsonPath, JsonPathIndex, Operand};
+use crate::{Coercion, JsonPathError, JsonPathInst};
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use thiserror::Error;
+
+/// the schemas available to the predicate `schema(@, 'name')` by their names
+static SCHEMAS: OnceLock<RwLock<HashMap<String, Arc<Value>>>> = OnceLock::new();
+
+fn schemas() -> &'static RwLock<HashMap<String, Arc<Value>>> {
+    SCHEMAS.get_or_init(Default::default)
+}
+
+/// validates every element found by the query against the schema.
+/// Returns the paths of the invalid elements along with their errors.
+/// The generated elements (like the length) have the empty path.
+pub fn validate_at(
+    path: &JsonPathInst,
+    json: &Value,
+    schema: &Value,
+) -> Vec<(String, Vec<String>)> {
+    let found = match path.query(json) {
+        Ok(found) => found,
+        Err(JsonPathError::Inapplicable) => vec![],
+        Err(e) => return vec![(String::new(), vec![e.to_string()])],
+    };
+    found
+        .into_iter()
+        .filter_map(|m| {
+            let errors = validate(&m.value, schema);
+            (!errors.is_empty()).then(|| (m.path.unwrap_or_default(), errors))
+        })
+        .collect()
+}
+
+/// validates the value against the schema and returns the errors,
+/// every error starts with the json pointer of the invalid part if it is not the value itself.
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = vec![];
+    Validator {
+        root: schema,
+        following: RefCell::new(vec![]),
+    }
+    .validate(value, schema, "", &mut errors);
+    errors
+}
+
+/// checks if the value conforms to the schema
+pub fn is_valid(value: &Value, schema: &Value) -> bool {
+    validate(value, schema).is_empty()
+}
+
+/// keeps the schema under the name for the predicate `schema(@, 'name')` replacing the previous one.
+/// The registered schemas are shared by all the queries of the process.
+pub fn register_schema(name: &str, schema: Value) {
+    schemas()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.to_string(), Arc::new(schema));
+}
+
+/// removes the schema returning it if it has been registered
+pub fn unregister_schema(name: &str) -> Option<Arc<Value>> {
+    schemas()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(name)
+}
+
+pub(crate) fn registered_schema(name: &str) -> Option<Arc<Value>> {
+    schemas()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .cloned()
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    /// the references being followed with the pointers of the values they are applied to,
+    /// the same pair met again is the cycle never reaching a keyword
+    following: RefCell<Vec<(String, String)>>,
+}
+
+impl Validator<'_> {
+    fn validate(&self, value: &Value, schema: &Value, ptr: &str, errors: &mut Vec<String>) {
+        let keywords = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return error(errors, ptr, "no value is allowed".to_string()),
+            Value::Object(keywords) => keywords,
+            _ => return,
+        };
+
+        if let Some(reference) = keywords.get("$ref").and_then(Value::as_str) {
+            let step = (reference.to_string(), ptr.to_string());
+            match self.resolve(reference) {
+                _ if self.following.borrow().contains(&step) => error(
+                    errors,
+                    ptr,
+                    format!("the reference `{reference}` refers to itself"),
+                ),
+                Some(schema) => {
+                    self.following.borrow_mut().push(step);
+                    self.validate(value, schema, ptr, errors);
+                    self.following.borrow_mut().pop();
+                }
+                None => error(
+                    errors,
+                    ptr,
+                    format!("the reference `{reference}` is not found"),
+                ),
+            }
+        }
+        if let Some(types) = keywords.get("type") {
+            let fits = match types {
+                Value::String(tp) => has_type(value, tp),
+                Value::Array(tps) => tps
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .any(|tp| has_type(value, tp)),
+                _ => true,
+            };
+            if !fits {
+                error(
+                    errors,
+                    ptr,
+                    format!("the type is not {}", types_text(types)),
+                );
+            }
+        }
+        if let Some(Value::Array(options)) = keywords.get("enum") {
+            if !options.contains(value) {
+                error(errors, ptr, "the value is not one of the enum".to_string());
+            }
+        }
+        if let Some(expected) = keywords.get("const") {
+            if expected != value {
+                error(errors, ptr, format!("the value is not {expected}"));
+            }
+        }
+        match value {
+            Value::Object(fields) => self.validate_object(fields, keywords, ptr, errors),
+            Value::Array(elems) => self.validate_array(elems, keywords, ptr, errors),
+            Value::String(s) => validate_string(s, keywords, ptr, errors),
+            Value::Number(n) => {
+                validate_number(n.as_f64().unwrap_or(f64::NAN), keywords, ptr, errors)
+            }
+            Value::Bool(_) | Value::Null => {}
+        }
+        self.validate_combinators(value, keywords, ptr, errors);
+    }
+
+    fn validate_object(
+        &self,
+        fields: &Map<String, Value>,
+        keywords: &Map<String, Value>,
+        ptr: &str,
+        errors: &mut Vec<String>,
+    ) {
+        if let Some(Value::Array(required)) = keywords.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(key) {
+                    error(errors, ptr, format!("the required field `{key}` is absent"));
+                }
+            }
+        }
+        let properties = keywords.get("properties").and_then(Value::as_object);
+        for (key, v) in fields {
+            let field_ptr = format!("{ptr}/{}", key.replace('~', "~0").replace('/', "~1"));
+            match (
+                properties.and_then(|p| p.get(key)),
+                keywords.get("additionalProperties"),
+            ) {
+                (Some(schema), _) | (None, Some(schema)) => {
+                    self.validate(v, schema, &field_ptr, errors)
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn validate_array(
+        &self,
+        elems: &[Value],
+        keywords: &Map<String, Value>,
+        ptr: &str,
+        errors: &mut Vec<String>,
+    ) {
+        if let Some(min) = keywords.get("minItems").and_then(Value::as_u64) {
+            if (elems.len() as u64) < min {
+                error(
+                    errors,
+                    ptr,
+                    format!("the array has less than {min} elements"),
+                );
+            }
+        }
+        if let Some(max) = keywords.get("maxItems").and_then(Value::as_u64) {
+            if (elems.len() as u64) > max {
+                error(
+                    errors,
+                    ptr,
+                    format!("the array has more than {max} elements"),
+                );
+            }
+        }
+        if let Some(schema) = keywords.get("items") {
+            for (i, el) in elems.iter().enumerate() {
+                self.validate(el, schema, &format!("{ptr}/{i}"), errors);
+            }
+        }
+    }
+
+    fn validate_combinators(
+        &self,
+        value: &Value,
+        keywords: &Map<String, Value>,
+        ptr: &str,
+        errors: &mut Vec<String>,
+    ) {
+        let valid = |schema: &Value| {
+            let mut errors = vec![];
+            self.validate(value, schema, ptr, &mut errors);
+            errors.is_empty()
+        };
+        if let Some(Value::Array(schemas)) = keywords.get("allOf") {
+            schemas
+                .iter()
+                .for_each(|s| self.validate(value, s, ptr, errors));
+        }
+        if let Some(Value::Array(schemas)) = keywords.get("anyOf") {
+            if !schemas.iter().any(valid) {
+                error(
+                    errors,
+                    ptr,
+                    "the value does not fit any of the schemas".to_string(),
+                );
+            }
+        }
+        if let Some(Value::Array(schemas)) = keywords.get("oneOf") {
+            if schemas.iter().filter(|s| valid(s)).count() != 1 {
+                error(
+                    errors,
+                    ptr,
+                    "the value does not fit exactly one of the schemas".to_string(),
+                );
+            }
+        }
+        if let Some(schema) = keywords.get("not") {
+            if valid(schema) {
+                error(errors, ptr, "the value fits the negated schema".to_string());
+            }
+        }
+    }
+
+    /// finds the part of the root schema by the reference like `#/$defs/item`
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.root.pointer(pointer))
+    }
+}
+
+/// The selector of the query that can never match the documents conforming to the schema,
+/// see [`validate_against_schema`]. Every issue carries the part of the query before the selector,
+/// the parts inside the filters start with `@`.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum PathIssue {
+    #[error("`{path}` has no member `{member}` by the schema")]
+    UnknownMember { path: String, member: String },
+    #[error("the index `{index}` is applied to `{path}` that is never an array by the schema")]
+    NotAnArray { path: String, index: String },
+    #[error("the filter `{filter}` compares `{operand}` with the value of the type it never has by the schema")]
+    IncompatibleTypes { filter: String, operand: String },
+}
+
+/// the schema allowing any value
+static ANY: Value = Value::Bool(true);
+
+/// checks the query against the schema of the documents without any document,
+/// reporting the selectors that can never match: the members the schema does not allow,
+/// the indexes applied to the values that are never arrays and the filters comparing
+/// the values with the constants of the types (or out of the `enum`) they can never have.
+/// Only the first dead selector of the chain is reported, since nothing reaches the rest of it.
+/// The types are compared only for the paths comparing the values strictly, see [`crate::Coercion`].
+///
+/// ## Example
+/// ```rust
+/// use jsonpath_rust::schema::{validate_against_schema, PathIssue};
+/// use jsonpath_rust::JsonPathInst;
+/// use serde_json::json;
+/// use std::str::FromStr;
+///
+/// let schema = json!({"type":"object","properties":{
+///     "users":{"type":"array","items":{
+///         "type":"object","additionalProperties":false,
+///         "properties":{"name":{"type":"string"},"age":{"type":"integer"}}
+///     }}
+/// }});
+///
+/// let path = JsonPathInst::from_str("$.users[*].nmae").unwrap();
+/// assert_eq!(
+///     validate_against_schema(&path, &schema),
+///     vec![PathIssue::UnknownMember {
+///         path: "$['users'][*]".to_string(),
+///         member: "nmae".to_string()
+///     }]
+/// );
+///
+/// let path = JsonPathInst::from_str("$.users[?(@.age == '42')].name").unwrap();
+/// assert!(matches!(
+///     validate_against_schema(&path, &schema)[0],
+///     PathIssue::IncompatibleTypes { .. }
+/// ));
+/// assert!(validate_against_schema(&JsonPathInst::from_str("$.users[0].age").unwrap(), &schema).is_empty());
+/// ```
+pub fn validate_against_schema(path: &JsonPathInst, schema: &Value) -> Vec<PathIssue> {
+    let options = path.options();
+    let mut checker = PathChecker {
+        root: schema,
+        typed: options.coercion == Coercion::Strict
+            && options.comparator.is_none()
+            && !options.goessner,
+        goessner: options.goessner,
+        issues: vec![],
+    };
+    checker.chain(path.path(), vec![schema], false);
+    checker.issues
+}
+
+/// walks the query over the schema keeping the schemas of the values every step may reach
+struct PathChecker<'s> {
+    root: &'s Value,
+    /// the filters compare the values of the same type only
+    typed: bool,
+    /// the filters are applied to the fields of the objects
+    goessner: bool,
+    issues: Vec<PathIssue>,
+}
+
+impl<'s> PathChecker<'s> {
+    /// the schemas reached by the chain, nothing if the chain can not be followed by the schema,
+    /// the relative chains of the filters are written starting with `@`
+    fn chain(
+        &mut self,
+        path: &JsonPath,
+        mut nodes: Vec<&'s Value>,
+        relative: bool,
+    ) -> Option<Vec<&'s Value>> {
+        let steps = match path {
+            JsonPath::Chain(elems) => elems.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        let mut prefix = vec![];
+        for step in steps {
+            if nodes.is_empty() {
+                break;
+            }
+            let text = if relative {
+                JsonPath::current(JsonPath::Chain(prefix.clone())).to_string()
+            } else {
+                JsonPath::Chain(prefix.clone()).to_string()
+            };
+            nodes = self.step(step, nodes, text)?;
+            prefix.push(step.clone());
+        }
+        Some(nodes)
+    }
+
+    fn step(
+        &mut self,
+        step: &JsonPath,
+        nodes: Vec<&'s Value>,
+        prefix: String,
+    ) -> Option<Vec<&'s Value>> {
+        match step {
+            JsonPath::Root => Some(vec![self.root]),
+            JsonPath::Empty => Some(nodes),
+            JsonPath::Field(key) => Some(self.member(&nodes, key, prefix)),
+            JsonPath::FieldGlob(_) | JsonPath::Wildcard => self.children(&nodes),
+            JsonPath::Descent(key) => {
+                let nodes = self.descendants(&nodes);
+                Some(self.member(&nodes, key, prefix))
+            }
+            JsonPath::DescentGlob(_) => {
+                let nodes = self.descendants(&nodes);
+                self.children(&nodes)
+            }
+            JsonPath::Index(index) => self.index(index, &nodes, prefix),
+            JsonPath::Current(tail) => self.chain(tail, nodes, true),
+            JsonPath::Chain(_) => self.chain(step, nodes, false),
+            JsonPath::DescentW
+            | JsonPath::DescentIndex(_)
+            | JsonPath::Fn(_)
+            | JsonPath::Type(_) => None,
+        }
+    }
+
+    fn member(&mut self, nodes: &[&'s Value], key: &str, prefix: String) -> Vec<&'s Value> {
+        let found: Vec<&'s Value> = nodes
+            .iter()
+            .flat_map(|n| self.expand(n))
+            .filter_map(|n| member_of(n, key))
+            .collect();
+        if found.is_empty() {
+            self.issues.push(PathIssue::UnknownMember {
+                path: prefix,
+                member: key.to_string(),
+            });
+        }
+        found
+    }
+
+    fn index(
+        &mut self,
+        index: &JsonPathIndex,
+        nodes: &[&'s Value],
+        prefix: String,
+    ) -> Option<Vec<&'s Value>> {
+        let expanded: Vec<&'s Value> = nodes.iter().flat_map(|n| self.expand(n)).collect();
+        match index {
+            JsonPathIndex::Single(_) | JsonPathIndex::UnionIndex(_) | JsonPathIndex::Slice(..) => {
+                let sliced = matches!(index, JsonPathIndex::Slice(..));
+                let applicable = expanded
+                    .iter()
+                    .any(|n| can_be(n, "array") || sliced && can_be(n, "string"));
+                if !applicable {
+                    self.issues.push(PathIssue::NotAnArray {
+                        path: prefix,
+                        index: index.to_string(),
+                    });
+                    return Some(vec![]);
+                }
+                Some(expanded.into_iter().flat_map(items_of).collect())
+            }
+            JsonPathIndex::UnionKeys(keys) => Some(
+                keys.iter()
+                    .flat_map(|key| self.member(nodes, key, prefix.clone()))
+                    .collect(),
+            ),
+            JsonPathIndex::Filter(_) if self.goessner => None,
+            JsonPathIndex::Filter(expr) => {
+                let mut selected = vec![];
+                for node in expanded {
+                    if can_be(node, "array") {
+                        selected.extend(items_of(node));
+                    }
+                    if can_be(node, "object") {
+                        selected.push(node);
+                    }
+                }
+                self.filter(expr, expr, &selected);
+                Some(selected)
+            }
+            JsonPathIndex::Union(_) => None,
+        }
+    }
+
+    fn filter(&mut self, whole: &FilterExpression, expr: &FilterExpression, nodes: &[&'s Value]) {
+        match expr {
+            FilterExpression::Atom(left, sign, right) => {
+                let left_nodes = self.operand(left, nodes);
+                let right_nodes = self.operand(right, nodes);
+                let compared = match (left, right, left_nodes, right_nodes) {
+                    (Operand::Dynamic(op), Operand::Static(v), Some(found), _) => {
+                        Some((op, v, found))
+                    }
+                    (Operand::Static(v), Operand::Dynamic(op), _, Some(found)) => {
+                        Some((op, v, found))
+                    }
+                    _ => None,
+                };
+                if let Some((op, v, found)) = compared {
+                    let comparable = |n: &&Value| match sign {
+                        FilterSign::Equal => admits(n, v),
+                        FilterSign::Less
+                        | FilterSign::Greater
+                        | FilterSign::LeOrEq
+                        | FilterSign::GrOrEq
+                            if v.is_number() =>
+                        {
+                            can_be(n, "number")
+                        }
+                        _ => true,
+                    };
+                    let expanded: Vec<&Value> = found.iter().flat_map(|n| self.expand(n)).collect();
+                    if self.typed && !expanded.is_empty() && !expanded.iter().any(comparable) {
+                        self.issues.push(PathIssue::IncompatibleTypes {
+                            filter: whole.to_string(),
+                            operand: op.to_string(),
+                        });
+                    }
+                }
+            }
+            FilterExpression::And(left, right)
+            | FilterExpression::Or(left, right)
+            | FilterExpression::Xor(left, right) => {
+                self.filter(whole, left, nodes);
+                self.filter(whole, right, nodes);
+            }
+            FilterExpression::Not(expr) => self.filter(whole, expr, nodes),
+        }
+    }
+
+    /// the schemas of the values of the operand, nothing for the constants
+    fn operand(&mut self, op: &Operand, nodes: &[&'s Value]) -> Option<Vec<&'s Value>> {
+        match op {
+            Operand::Dynamic(path) => self.chain(path, nodes.to_vec(), false),
+            _ => None,
+        }
+    }
+
+    /// the alternatives of the schema with the references and the combinators replaced by their schemas
+    fn expand(&self, schema: &'s Value) -> Vec<&'s Value> {
+        let mut expanded = vec![];
+        self.expand_into(schema, &mut vec![], &mut expanded);
+        if expanded.is_empty() {
+            // the schema referring only to itself like `{"$ref":"#"}` constrains nothing
+            expanded.push(&ANY);
+        }
+        expanded
+    }
+
+    /// every schema is expanded once, the reference to the visited one adds nothing new
+    /// since the alternatives are joined anyway
+    fn expand_into(
+        &self,
+        schema: &'s Value,
+        visited: &mut Vec<&'s Value>,
+        expanded: &mut Vec<&'s Value>,
+    ) {
+        if visited.iter().any(|v| std::ptr::eq(*v, schema)) {
+            return;
+        }
+        visited.push(schema);
+        let Value::Object(keywords) = schema else {
+            return expanded.push(schema);
+        };
+        let mut branches: Vec<&'s Value> = vec![];
+        if let Some(reference) = keywords.get("$ref").and_then(Value::as_str) {
+            // the unknown reference allows anything
+            branches.push(
+                reference
+                    .strip_prefix('#')
+                    .and_then(|pointer| self.root.pointer(pointer))
+                    .unwrap_or(&ANY),
+            );
+        }
+        for combinator in ["allOf", "anyOf", "oneOf"] {
+            if let Some(Value::Array(schemas)) = keywords.get(combinator) {
+                branches.extend(schemas);
+            }
+        }
+        if branches.is_empty() {
+            return expanded.push(schema);
+        }
+        for branch in branches {
+            self.expand_into(branch, visited, expanded);
+        }
+        let structural = [
+            "type",
+            "const",
+            "enum",
+            "properties",
+            "additionalProperties",
+            "items",
+        ];
+        if structural.iter().any(|k| keywords.contains_key(*k)) {
+            expanded.push(schema);
+        }
+    }
+
+    /// the schemas of the members and the elements
+    fn children(&self, nodes: &[&'s Value]) -> Option<Vec<&'s Value>> {
+        let mut children = vec![];
+        for node in nodes.iter().flat_map(|n| self.expand(n)) {
+            let Value::Object(keywords) = node else {
+                children.push(&ANY);
+                continue;
+            };
+            if can_be(node, "object") {
+                for key in ["properties", "patternProperties"] {
+                    if let Some(Value::Object(fields)) = keywords.get(key) {
+                        children.extend(fields.values());
+                    }
+                }
+                match keywords.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {}
+                    Some(schema) => children.push(schema),
+                    None => children.push(&ANY),
+                }
+            }
+            if can_be(node, "array") {
+                children.extend(items_of(node));
+            }
+        }
+        (!children.is_empty()).then_some(children)
+    }
+
+    /// the schemas of the values themselves and all their descendants
+    fn descendants(&self, nodes: &[&'s Value]) -> Vec<&'s Value> {
+        let mut found: Vec<&'s Value> = vec![];
+        let mut queue: Vec<&'s Value> = nodes.to_vec();
+        while let Some(node) = queue.pop() {
+            if found.iter().any(|f| std::ptr::eq(*f, node)) {
+                continue;
+            }
+            found.push(node);
+            if let Some(children) = self.children(&[node]) {
+                queue.extend(children);
+            }
+        }
+        found
+    }
+}
+
+/// the schema of the member of the object, nothing if the schema does not allow it
+fn member_of<'s>(schema: &'s Value, key: &str) -> Option<&'s Value> {
+    let Value::Object(keywords) = schema else {
+        return schema.as_bool().unwrap_or(true).then_some(&ANY);
+    };
+    if !can_be(schema, "object") {
+        return None;
+    }
+    if let Some(field) = keywords.get("properties").and_then(|p| p.get(key)) {
+        return Some(field);
+    }
+    if let Some(Value::Object(patterns)) = keywords.get("patternProperties") {
+        let matched = patterns
+            .iter()
+            .find(|(p, _)| Regex::new(p).is_ok_and(|re| re.is_match(key)));
+        if let Some((_, field)) = matched {
+            return Some(field);
+        }
+    }
+    match keywords.get("additionalProperties") {
+        Some(Value::Bool(false)) => None,
+        Some(schema) => Some(schema),
+        None => Some(&ANY),
+    }
+}
+
+/// the schemas of the elements of the array
+fn items_of(schema: &Value) -> Vec<&Value> {
+    let Value::Object(keywords) = schema else {
+        return vec![&ANY];
+    };
+    let mut items = vec![];
+    if let Some(Value::Array(prefix)) = keywords.get("prefixItems") {
+        items.extend(prefix);
+    }
+    match keywords.get("items") {
+        Some(Value::Array(tuple)) => {
+            items.extend(tuple);
+            items.push(keywords.get("additionalItems").unwrap_or(&ANY));
+        }
+        Some(schema) => items.push(schema),
+        None => items.push(&ANY),
+    }
+    items
+}
+
+/// checks if the schema allows the values of the type like `object`
+fn can_be(schema: &Value, tp: &str) -> bool {
+    let Value::Object(keywords) = schema else {
+        return schema.as_bool().unwrap_or(true);
+    };
+    if let Some(expected) = keywords.get("const") {
+        return has_type(expected, tp) || tp == "number" && has_type(expected, "integer");
+    }
+    if let Some(Value::Array(options)) = keywords.get("enum") {
+        return options.iter().any(|o| has_type(o, tp));
+    }
+    let fits = |t: &str| t == tp || t == "integer" && tp == "number";
+    match keywords.get("type") {
+        Some(Value::String(t)) => fits(t),
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).any(fits),
+        _ => true,
+    }
+}
+
+/// checks if the schema allows the value as far as its type, `const` and `enum` tell
+fn admits(schema: &Value, value: &Value) -> bool {
+    let Value::Object(keywords) = schema else {
+        return schema.as_bool().unwrap_or(true);
+    };
+    let same = |o: &Value| o == value || o.as_f64().is_some() && o.as_f64() == value.as_f64();
+    if let Some(expected) = keywords.get("const") {
+        return same(expected);
+    }
+    if let Some(Value::Array(options)) = keywords.get("enum") {
+        return options.iter().any(same);
+    }
+    match keywords.get("type") {
+        Some(Value::String(t)) => has_type(value, t),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|t| has_type(value, t)),
+        _ => true,
+    }
+}
+
+fn validate_string(s: &str, keywords: &Map<String, Value>, ptr: &str, errors: &mut Vec<String>) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = keywords.get("minLength").and_then(Value::as_u64) {
+        if len < min {
+            error(errors, ptr, format!("the string is shorter than {min}"));
+        }
+    }
+    if let Some(max) = keywords.get("maxLength").and_then(Value::as_u64) {
+        if len > max {
+            error(errors, ptr, format!("the string is longer than {max}"));
+        }
+    }
+    if let Some(pattern) = keywords.get("pattern").and_then(Value::as_str) {
+        match Regex::new(pattern) {
+            Ok(regex) if !regex.is_match(s) => error(
+                errors,
+                ptr,
+                format!("the string does not match `{pattern}`"),
+            ),
+            Ok(_) => {}
+            Err(_) => error(errors, ptr, format!("the pattern `{pattern}` is incorrect")),
+        }
+    }
+}
+
+fn validate_number(n: f64, keywords: &Map<String, Value>, ptr: &str, errors: &mut Vec<String>) {
+    let bound = |k: &str| keywords.get(k).and_then(Value::as_f64);
+    if let Some(min) = bound("minimum").filter(|min| n < *min) {
+        error(errors, ptr, format!("the number is less than {min}"));
+    }
+    if let Some(max) = bound("maximum").filter(|max| n > *max) {
+        error(errors, ptr, format!("the number is greater than {max}"));
+    }
+    if let Some(min) = bound("exclusiveMinimum").filter(|min| n <= *min) {
+        error(errors, ptr, format!("the number is not greater than {min}"));
+    }
+    if let Some(max) = bound("exclusiveMaximum").filter(|max| n >= *max) {
+        error(errors, ptr, format!("the number is not less than {max}"));
+    }
+}
+
+fn has_type(value: &Value, tp: &str) -> bool {
+    match tp {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
+    }
+}
+
+fn types_text(types: &Value) -> String {
+    match types {
+        Value::Array(tps) => tps
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        tp => tp.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+fn error(errors: &mut Vec<String>, ptr: &str, msg: String) {
+    if ptr.is_empty() {
+        errors.push(msg)
+    } else {
+        errors.push(format!("{ptr}: {msg}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{
+        is_valid, register_schema, unregister_schema, validate, validate_against_schema,
+        validate_at, PathIssue,
+    };
+    use crate::{find, find_values, Coercion, EvalOptions, JsonPathInst};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn validate_test() {
+        let schema = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "tags": {"type": "array", "items": {"$ref": "#/$defs/tag"}, "maxItems": 2},
+                "kind": {"enum": ["a", "b"]}
+            },
+            "additionalProperties": false,
+            "$defs": {"tag": {"type": "string", "pattern": "^[a-z]+$", "minLength": 2}}
+        });
+        assert!(validate(&json!({"id": 2, "tags": ["ab"], "kind": "a"}), &schema).is_empty());
+        assert_eq!(
+            validate(
+                &json!({"id": 0.5, "tags": ["a", "B1", "cc"], "kind": "c", "x/y": 1}),
+                &schema
+            ),
+            vec![
+                "/id: the type is not integer",
+                "/id: the number is less than 1",
+                "/kind: the value is not one of the enum",
+                "/tags: the array has more than 2 elements",
+                "/tags/0: the string is shorter than 2",
+                "/tags/1: the string does not match `^[a-z]+$`",
+                "/x~1y: no value is allowed",
+            ]
+        );
+        assert_eq!(
+            validate(&json!("x"), &schema),
+            vec!["the type is not object"]
+        );
+    }
+
+    #[test]
+    fn combinators_test() {
+        let schema = json!({"anyOf": [{"type": "string"}, {"type": ["integer", "null"]}], "not": {"const": 3}});
+        assert!(validate(&json!(null), &schema).is_empty());
+        assert_eq!(
+            validate(&json!(3), &schema),
+            vec!["the value fits the negated schema"]
+        );
+        assert_eq!(
+            validate(&json!(1.5), &schema),
+            vec!["the value does not fit any of the schemas"]
+        );
+        let schema = json!({"oneOf": [{"minimum": 1}, {"maximum": 5}]});
+        assert!(validate(&json!(7), &schema).is_empty());
+        assert!(!validate(&json!(3), &schema).is_empty());
+    }
+
+    #[test]
+    fn ref_cycle_test() {
+        assert_eq!(
+            validate(&json!(1), &json!({"$ref": "#"})),
+            vec!["the reference `#` refers to itself"]
+        );
+        let schema = json!({"$ref": "#/$defs/a", "$defs": {"a": {"$ref": "#/$defs/a"}}});
+        assert_eq!(
+            validate(&json!({"x": 1}), &schema),
+            vec!["the reference `#/$defs/a` refers to itself"]
+        );
+        assert!(!is_valid(&json!(null), &json!({"allOf": [{"$ref": "#"}]})));
+        // the reference applied to the nested values is not the cycle
+        let tree = json!({"type": "object", "properties": {"child": {"$ref": "#"}}});
+        assert!(is_valid(&json!({"child": {"child": {}}}), &tree));
+        assert_eq!(
+            validate(&json!({"child": {"child": {"child": 1}}}), &tree),
+            vec!["/child/child/child: the type is not object"]
+        );
+    }
+
+    #[test]
+    fn validate_at_test() {
+        let data = json!({"a": [1, "x", 2, null]});
+        let path = JsonPathInst::from_str("$.a[*]").unwrap();
+        assert_eq!(
+            validate_at(&path, &data, &json!({"type": "number"})),
+            vec![
+                (
+                    "$.['a'][1]".to_string(),
+                    vec!["the type is not number".to_string()]
+                ),
+                (
+                    "$.['a'][3]".to_string(),
+                    vec!["the type is not number".to_string()]
+                ),
+            ]
+        );
+        let path = JsonPathInst::from_str("$.b").unwrap();
+        assert!(validate_at(&path, &data, &json!(false)).is_empty());
+    }
+
+    #[test]
+    fn schema_predicate_test() {
+        register_schema("test_event", json!({"type":"object","required":["id"]}));
+        let data = json!({"a":{"events":[{"id":1},{"name":"x"},{"meta":{"id":3}}]}});
+        let path = JsonPathInst::from_str("$..events[?(schema(@, 'test_event'))]").unwrap();
+        assert_eq!(find(&path, &data), json!([{"id":1}]));
+        let path = JsonPathInst::from_str("$..events[?(!schema(@, 'test_event'))]").unwrap();
+        assert_eq!(find(&path, &data), json!([{"name":"x"}, {"meta":{"id":3}}]));
+        let path = JsonPathInst::from_str("$..events[?(schema(@.meta, 'test_event'))]").unwrap();
+        assert_eq!(find(&path, &data), json!([{"meta":{"id":3}}]));
+        let path = JsonPathInst::from_str("$..events[?(schema(@, 'absent'))]").unwrap();
+        assert!(find_values(&path, &data).is_empty());
+
+        assert!(unregister_schema("test_event").is_some());
+        let path = JsonPathInst::from_str("$..events[?(schema(@, 'test_event'))]").unwrap();
+        assert!(find_values(&path, &data).is_empty());
+        assert!(unregister_schema("test_event").is_none());
+    }
+
+    #[test]
+    fn validate_against_schema_test() {
+        let schema = json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "orders": {"type": "array", "items": {"$ref": "#/$defs/order"}},
+                "meta": {"type": "object"},
+                "code": {"type": "string"}
+            },
+            "$defs": {
+                "order": {
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "status": {"enum": ["new", "paid"]},
+                        "total": {"type": "number"},
+                        "payment": {"anyOf": [
+                            {"type": "object", "additionalProperties": false, "properties": {"card": {"type": "string"}}},
+                            {"type": "object", "additionalProperties": false, "properties": {"iban": {"type": "string"}}}
+                        ]}
+                    }
+                }
+            }
+        });
+        let issues = |q: &str| {
+            validate_against_schema(
+                &JsonPathInst::from_str(q).expect("the path is correct"),
+                &schema,
+            )
+        };
+        let unknown = |path: &str, member: &str| PathIssue::UnknownMember {
+            path: path.to_string(),
+            member: member.to_string(),
+        };
+
+        for ok in [
+            "$.orders[*].id",
+            "$.orders[0].payment.iban",
+            "$.orders[1:].payment.card",
+            "$.meta.anything.deeper",
+            "$.code[0:2]",
+            "$..card",
+            "$.orders[?(@.status == 'paid' && @.total > 10)].id",
+            "$.orders[?(@.id == 1.0)]",
+            "$.orders[?(@.total >= $.orders[0].total)]",
+            "$.*.length()",
+        ] {
+            assert_eq!(issues(ok), vec![], "{ok}");
+        }
+        assert_eq!(issues("$.order[*].id"), vec![unknown("$", "order")]);
+        assert_eq!(
+            issues("$.orders[*].payment.cash.amount"),
+            vec![unknown("$['orders'][*]['payment']", "cash")]
+        );
+        // `meta` allows any member
+        assert_eq!(issues("$..cash"), vec![]);
+        assert_eq!(
+            issues("$.orders[?(@.totl > 1)]"),
+            vec![unknown("@", "totl")]
+        );
+        assert_eq!(
+            issues("$.meta[0]"),
+            vec![PathIssue::NotAnArray {
+                path: "$['meta']".to_string(),
+                index: "[0]".to_string()
+            }]
+        );
+        assert_eq!(
+            issues("$.orders[?(@.status == 'payed')]"),
+            vec![PathIssue::IncompatibleTypes {
+                filter: "@['status'] == 'payed'".to_string(),
+                operand: "@['status']".to_string()
+            }]
+        );
+        assert_eq!(issues("$.orders[?(@.id == '1' || @.id > 'a')]").len(), 1);
+        assert_eq!(issues("$.code[?(@.x)]"), vec![]);
+        assert_eq!(issues("$.orders[?(1.5 == @.id)]").len(), 1);
+
+        let lenient = JsonPathInst::from_str("$.orders[?(@.id == '1')]")
+            .expect("the path is correct")
+            .with_options(EvalOptions::default().with_coercion(Coercion::Lenient));
+        assert_eq!(validate_against_schema(&lenient, &schema), vec![]);
+
+        let recursive = json!({"$ref": "#/$defs/node", "$defs": {"node": {
+            "type": "object", "additionalProperties": false,
+            "properties": {"value": {"type": "number"}, "next": {"$ref": "#/$defs/node"}}
+        }}});
+        let path = JsonPathInst::from_str("$.next.next.next.value").expect("the path is correct");
+        assert_eq!(validate_against_schema(&path, &recursive), vec![]);
+        let path = JsonPathInst::from_str("$..nxt").expect("the path is correct");
+        assert_eq!(validate_against_schema(&path, &recursive).len(), 1);
+    }
+
+    #[test]
+    fn against_ref_cycle_test() {
+        let path = JsonPathInst::from_str("$.a[0].b").expect("the path is correct");
+        assert_eq!(
+            validate_against_schema(&path, &json!({"$ref": "#"})),
+            vec![]
+        );
+        let schema =
+            json!({"$ref": "#/$defs/a", "$defs": {"a": {"allOf": [{"$ref": "#/$defs/a"}]}}});
+        assert_eq!(validate_against_schema(&path, &schema), vec![]);
+
+        // every definition refers to the next one twice, the schemas are expanded once
+        let mut defs = serde_json::Map::new();
+        for i in 0..64 {
+            let next = format!("#/$defs/d{}", i + 1);
+            defs.insert(
+                format!("d{i}"),
+                json!({"anyOf": [{"$ref": next}, {"$ref": next}]}),
+            );
+        }
+        defs.insert(
+            "d64".to_string(),
+            json!({"type": "object", "additionalProperties": false, "properties": {"x": {}}}),
+        );
+        let schema = json!({"$ref": "#/$defs/d0", "$defs": defs});
+        let path = JsonPathInst::from_str("$.x").expect("the path is correct");
+        assert_eq!(validate_against_schema(&path, &schema), vec![]);
+        let path = JsonPathInst::from_str("$.y").expect("the path is correct");
+        assert_eq!(
+            validate_against_schema(&path, &schema),
+            vec![PathIssue::UnknownMember {
+                path: "$".to_string(),
+                member: "y".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! The stable text of the found elements for the snapshot (golden file) tests.
+//!
+//! Every found element is printed on its own line as `path = value`, the path is normalized
+//! like `$['store']['book'][0]` and the value is the compact json with the keys of the objects sorted,
+//! the lines are sorted by the paths with the indexes compared as the numbers.
+//! Therefore the text depends neither on the order of the keys in the document nor on the order of the selectors.
+//! The generated elements (like the length) have no path and are printed after the rest with the query itself.
+//!
+//! ```
+//! use jsonpath_rust::{snapshot, JsonPathInst};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let json = json!({"b":{"y":2,"x":[1]},"a":"s"});
+//! let path = JsonPathInst::from_str("$['b','a']").unwrap();
+//!
+//! assert_eq!(snapshot(&path, &json), "$['a'] = \"s\"\n$['b'] = {\"x\":[1],\"y\":2}\n");
+//! ```
+use crate::{find_map, JsonLike, JsonPathInst, PathStyle};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// prints the found elements as the sorted lines `path = value`, see the [module](self) for the details
+pub fn snapshot(path: &JsonPathInst, json: &Value) -> String {
+    let mut found: Vec<_> = find_map(path, json)
+        .into_iter()
+        .map(|m| (m.to_segments(), canonical(&*m.value)))
+        .collect();
+    // the generated elements keep the order they are found in
+    found.sort_by(|(l, _), (r, _)| match (l, r) {
+        (Some(l), Some(r)) => l.cmp(r),
+        (l, r) => r.is_some().cmp(&l.is_some()),
+    });
+    let mut out = String::new();
+    for (segments, value) in found {
+        let path = match segments {
+            Some(segments) => PathStyle::Normalized.format(&segments),
+            None => path.canonicalize(),
+        };
+        let _ = writeln!(out, "{path} = {value}");
+    }
+    out
+}
+
+/// the compact json with the keys of the objects sorted
+fn canonical<T: JsonLike>(json: &T) -> String {
+    let mut out = String::new();
+    write_canonical(json, &mut out);
+    out
+}
+
+fn write_canonical<T: JsonLike>(json: &T, out: &mut String) {
+    if let Some(elems) = json.as_array() {
+        out.push('[');
+        for (i, el) in elems.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_canonical(el, out);
+        }
+        out.push(']');
+    } else if let Some(entries) = json.entries() {
+        let mut entries: Vec<_> = entries.collect();
+        entries.sort_by_key(|(k, _)| *k);
+        out.push('{');
+        for (i, (k, v)) in entries.into_iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str(&Value::from(k).to_string());
+            out.push(':');
+            write_canonical(v, out);
+        }
+        out.push('}');
+    } else {
+        out.push_str(&json.to_json().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{snapshot, JsonPathInst};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn snap(query: &str, json: &serde_json::Value) -> String {
+        snapshot(
+            &JsonPathInst::from_str(query).expect("the path is correct"),
+            json,
+        )
+    }
+
+    #[test]
+    fn snapshot_test() {
+        let json =
+            json!({"list":[0,1,2,3,4,5,6,7,8,9,10,11],"o":{"b":{"z":null,"a":[true]},"it's":"q"}});
+        assert_eq!(
+            snap("$.list[11,2,10]", &json),
+            "$['list'][2] = 2\n$['list'][10] = 10\n$['list'][11] = 11\n"
+        );
+        assert_eq!(
+            snap("$.o.*", &json),
+            "$['o']['b'] = {\"a\":[true],\"z\":null}\n$['o']['it\\'s'] = \"q\"\n"
+        );
+        assert_eq!(snap("$.missing", &json), "");
+        assert_eq!(
+            snap("$.o..a", &json),
+            snap("$..['a']", &json!({"o":{"b":{"a":[true],"z":null}}}))
+        );
+        assert_eq!(snap("$.list.length()", &json), "$['list'].length() = 12\n");
+    }
+}
@@ -0,0 +1,107 @@
+//! The bindings for javascript, built by `wasm-pack build --features wasm`.
+//!
+//! The web pages evaluate the queries by the same engine as the rust code does.
+//! The documents are passed as the json text and the results are returned as the json text of the arrays:
+//!
+//! ```js
+//! import { compile } from "jsonpath-rust";
+//!
+//! const query = compile("$..book[?(@.price < 10)].title");
+//! const titles = JSON.parse(query.find(text));
+//! const paths = JSON.parse(query.find_paths(text));
+//! ```
+use crate::{JsonPathError, JsonPathInst, Match};
+use serde_json::Value;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+/// The compiled query reused for every document
+#[wasm_bindgen]
+pub struct Query {
+    inner: JsonPathInst,
+}
+
+/// compiles the query, the error describes the wrong part of it
+#[wasm_bindgen]
+pub fn compile(query: &str) -> Result<Query, JsError> {
+    Query::new(query).map_err(|e| JsError::new(&e))
+}
+
+#[wasm_bindgen]
+impl Query {
+    /// the json array of the found values, empty if nothing is found
+    pub fn find(&self, json: &str) -> Result<String, JsError> {
+        self.find_values(json).map_err(|e| JsError::new(&e))
+    }
+
+    /// the json array of the paths of the found values like `$.['a'][0]`,
+    /// the generated values (like the length) have no path
+    pub fn find_paths(&self, json: &str) -> Result<String, JsError> {
+        self.find_value_paths(json).map_err(|e| JsError::new(&e))
+    }
+}
+
+impl Query {
+    fn new(query: &str) -> Result<Self, String> {
+        JsonPathInst::from_str(query).map(|inner| Query { inner })
+    }
+
+    fn find_values(&self, json: &str) -> Result<String, String> {
+        self.with_matches(json, |found| {
+            found.into_iter().map(|m| (*m.value).clone()).collect()
+        })
+    }
+
+    fn find_value_paths(&self, json: &str) -> Result<String, String> {
+        self.with_matches(json, |found| {
+            found
+                .into_iter()
+                .filter_map(|m| m.path)
+                .map(Value::from)
+                .collect()
+        })
+    }
+
+    fn with_matches(
+        &self,
+        json: &str,
+        collect: impl FnOnce(Vec<Match<'_>>) -> Vec<Value>,
+    ) -> Result<String, String> {
+        let doc: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let found = match self.inner.query(&doc) {
+            Ok(found) => found,
+            Err(JsonPathError::Inapplicable) => vec![],
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(Value::Array(collect(found)).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wasm::Query;
+
+    #[test]
+    fn query_test() {
+        let query = Query::new("$.a[?(@ > 1)]").expect("the query is correct");
+        assert_eq!(
+            query.find_values(r#"{"a":[1,2,3]}"#),
+            Ok("[2,3]".to_string())
+        );
+        assert_eq!(
+            query.find_value_paths(r#"{"a":[1,2,3]}"#),
+            Ok(r#"["$.['a'][1]","$.['a'][2]"]"#.to_string())
+        );
+        assert_eq!(query.find_values(r#"{"b":1}"#), Ok("[]".to_string()));
+        assert!(query.find_values("{").is_err());
+
+        let length = Query::new("$.a.length()").expect("the query is correct");
+        assert_eq!(length.find_values(r#"{"a":[1,2]}"#), Ok("[2]".to_string()));
+        assert_eq!(
+            length.find_value_paths(r#"{"a":[1,2]}"#),
+            Ok("[]".to_string())
+        );
+
+        assert!(Query::new("$.a[").is_err());
+    }
+}
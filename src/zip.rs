@@ -0,0 +1,147 @@
+//! The row-wise alignment of the elements found by several queries.
+//!
+//! ```
+//! use jsonpath_rust::{zip_queries, JsonPathInst};
+//! use serde_json::json;
+//! use std::str::FromStr;
+//!
+//! let data = json!({"store":{"book":[
+//!     {"title":"Sayings of the Century","price":8.95},
+//!     {"title":"Sword of Honour"},
+//!     {"title":"Moby Dick","price":8.99}
+//! ]}});
+//! let titles = JsonPathInst::from_str("$..book[*].title").unwrap();
+//! let prices = JsonPathInst::from_str("$..book[*].price").unwrap();
+//! let rows = zip_queries(&[&titles, &prices], &data);
+//!
+//! assert_eq!(rows.len(), 3);
+//! assert_eq!(rows[1].ancestor, "$.['store'].['book'][1]");
+//! assert_eq!(rows[1].cells, vec![vec![&json!("Sword of Honour")], vec![]]);
+//! assert_eq!(rows[2].cells, vec![vec![&json!("Moby Dick")], vec![&json!(8.99)]]);
+//! ```
+use crate::{find_map, to_path, JsonPathInst, JsonPtr, PathSegment};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The elements found by every query under the same ancestor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row<'a> {
+    /// the path of the ancestor shared by the elements of the row
+    pub ancestor: String,
+    /// the elements found by every query in the order of the queries
+    pub cells: Vec<Vec<&'a Value>>,
+}
+
+/// evaluates the queries and aligns the found elements by their ancestors.
+///
+/// The ancestors of the rows are at the depth of the deepest ancestor
+/// the elements found by the different queries share, like `$.['store'].['book'][1]` for the title and the price
+/// of the same book, and the rows come in the order of their first elements.
+/// The elements found by a single query are aligned by their parents.
+/// The generated elements (like length) and the elements not deeper than the ancestors are skipped.
+pub fn zip_queries<'a>(paths: &[&'a JsonPathInst], json: &'a Value) -> Vec<Row<'a>> {
+    let found: Vec<Vec<(Vec<PathSegment>, &'a Value)>> = paths
+        .iter()
+        .map(|path| {
+            find_map(path, json)
+                .into_iter()
+                .filter_map(|m| {
+                    let segments = m.to_segments()?;
+                    match m.value {
+                        JsonPtr::Slice(v) => Some((segments, v)),
+                        JsonPtr::NewValue(_) => None,
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let depth = shared_depth(&found);
+    let mut rows: Vec<Row<'a>> = vec![];
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (q, elems) in found.iter().enumerate() {
+        for (segments, v) in elems {
+            let len = match depth {
+                Some(depth) if segments.len() > depth => depth,
+                Some(_) => continue,
+                None if segments.is_empty() => continue,
+                None => segments.len() - 1,
+            };
+            let ancestor = to_path(&segments[..len]);
+            let i = *positions.entry(ancestor.clone()).or_insert_with(|| {
+                rows.push(Row {
+                    ancestor,
+                    cells: vec![vec![]; paths.len()],
+                });
+                rows.len() - 1
+            });
+            rows[i].cells[q].push(v);
+        }
+    }
+    rows
+}
+
+/// the length of the longest prefix shared by the paths found by the different queries.
+/// The longest one is shared by a pair of the neighbours in the sorted list of all the paths.
+fn shared_depth(found: &[Vec<(Vec<PathSegment>, &Value)>]) -> Option<usize> {
+    let mut all: Vec<(&[PathSegment], usize)> = found
+        .iter()
+        .enumerate()
+        .flat_map(|(q, elems)| elems.iter().map(move |(s, _)| (s.as_slice(), q)))
+        .collect();
+    all.sort();
+    all.windows(2)
+        .filter(|pair| pair[0].1 != pair[1].1)
+        .map(|pair| {
+            pair[0]
+                .0
+                .iter()
+                .zip(pair[1].0)
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{zip_queries, JsonPathInst};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn zip_test() {
+        let data = json!({"orders":[
+            {"id":1,"items":[{"sku":"a"},{"sku":"b"}]},
+            {"id":2,"items":[]},
+            {"items":[{"sku":"c"}]}
+        ]});
+        let ids = JsonPathInst::from_str("$.orders[*].id").unwrap();
+        let skus = JsonPathInst::from_str("$..sku").unwrap();
+        let rows = zip_queries(&[&ids, &skus], &data);
+        let rows: Vec<(&str, Vec<usize>)> = rows
+            .iter()
+            .map(|r| (r.ancestor.as_str(), r.cells.iter().map(Vec::len).collect()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("$.['orders'][0]", vec![1, 2]),
+                ("$.['orders'][1]", vec![1, 0]),
+                ("$.['orders'][2]", vec![0, 1]),
+            ]
+        );
+
+        let single = zip_queries(&[&skus], &data);
+        assert_eq!(single.len(), 3);
+        assert_eq!(single[0].ancestor, "$.['orders'][0].['items'][0]");
+
+        let one = json!({"book":{"title":"a","price":1}});
+        let titles = JsonPathInst::from_str("$..title").unwrap();
+        let prices = JsonPathInst::from_str("$..price").unwrap();
+        let rows = zip_queries(&[&titles, &prices], &one);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].cells, vec![vec![&json!("a")], vec![&json!(1)]]);
+        assert!(zip_queries(&[], &one).is_empty());
+    }
+}